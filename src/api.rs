@@ -0,0 +1,284 @@
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+
+use crate::core::filters::ConnectionFilter;
+use crate::core::monitor::ConnectionMonitor;
+use crate::core::snapshot::{ConnectionView, MetricsSnapshot};
+use crate::output::prometheus;
+
+/// Largest request body read, enough for any Grafana query.
+const MAX_BODY: u64 = 64 * 1024;
+
+/// Series the Grafana endpoints offer: the filtered active connection history, and the
+/// unfiltered minute and hour rollups of it.
+const GRAFANA_TARGETS: [&str; 4] = ["active_connections", "rollup_min", "rollup_avg", "rollup_max"];
+
+/// Read-only view of the monitor and the filter currently active in the TUI.
+#[derive(Clone)]
+pub struct ApiState {
+    pub monitor: Arc<Mutex<ConnectionMonitor>>,
+    pub filter: Arc<Mutex<ConnectionFilter>>,
+}
+
+impl ApiState {
+    /// The current filter and the locked monitor. The API only reads them, so a lock
+    /// poisoned by a panic elsewhere is still served rather than failing every request.
+    fn lock(&self) -> (ConnectionFilter, MutexGuard<'_, ConnectionMonitor>) {
+        let filter = self.filter.lock().unwrap_or_else(PoisonError::into_inner).clone();
+        let monitor = self.monitor.lock().unwrap_or_else(PoisonError::into_inner);
+        (filter, monitor)
+    }
+}
+
+/// Starts serving the API on a background thread, each client on its own thread so a
+/// stalled one does not hold up the others.
+pub fn spawn(listener: TcpListener, state: ApiState) {
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            match stream {
+                Ok(stream) => {
+                    let state = state.clone();
+                    thread::spawn(move || handle_connection(stream, &state).ok());
+                }
+                Err(_) => continue,
+            }
+        }
+    });
+}
+
+fn handle_connection(mut stream: TcpStream, state: &ApiState) -> io::Result<()> {
+    stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+    stream.set_write_timeout(Some(Duration::from_secs(5)))?;
+
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+
+    // Only the body length matters among the headers
+    let mut content_length = 0;
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 0 && header.trim() != "" {
+        if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().unwrap_or(0);
+            }
+        }
+        header.clear();
+    }
+    let mut body = String::new();
+    reader.take(content_length.min(MAX_BODY)).read_to_string(&mut body)?;
+
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or("");
+    let target = parts.next().unwrap_or("");
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+
+    // Only the Grafana endpoints take queries in a POST body
+    let routed = match method {
+        "GET" => Ok(route(path, query, state)),
+        "POST" if path.starts_with("/grafana") => Ok(grafana_route(path, &body, state)),
+        _ => Err(()),
+    };
+    let (status, content_type, body) = match routed {
+        Ok(Some((content_type, body))) => (200, content_type, body),
+        Ok(None) => (404, "application/json", json!({ "error": "not found" }).to_string()),
+        Err(()) => (405, "application/json", json!({ "error": "method not allowed" }).to_string()),
+    };
+
+    let reason = match status {
+        200 => "OK",
+        404 => "Not Found",
+        _ => "Method Not Allowed",
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status, reason, content_type, body.len(), body,
+    )?;
+    stream.flush()
+}
+
+fn route(path: &str, query: &str, state: &ApiState) -> Option<(&'static str, String)> {
+    let (filter, monitor) = state.lock();
+
+    let body = match path.trim_end_matches('/') {
+        "/api/filter" => json!({
+            "pids": filter.pids,
+            "process_names": filter.process_names,
+            "remote_hosts": filter.remote_hosts,
+            "remote_ports": filter.remote_ports,
+            "groups": filter.groups,
+            "query": filter.query.as_ref().map(|query| query.to_string()),
+            "ignore_case": filter.matching.ignore_case,
+            "fuzzy": filter.matching.fuzzy,
+            "description": filter.to_string(),
+        }),
+        "/api/summary" => to_json(monitor.get_summary(&filter)),
+        "/api/connections" => {
+            let connections: Vec<ConnectionView> = monitor.get_filtered_active_connections(&filter)
+                .into_iter()
+                .map(|conn| {
+                    let process = monitor.get_process(conn.pid).and_then(|p| p.name.clone());
+                    ConnectionView::new(conn, process)
+                })
+                .collect();
+            to_json(connections)
+        }
+        "/api/processes" => to_json(monitor.get_process_metrics(&filter)),
+        "/api/hosts" => to_json(monitor.get_host_metrics(&filter)),
+        "/api/process-hosts" => to_json(monitor.get_process_host_metrics(&filter)),
+        "/api/groups" => to_json(monitor.get_group_metrics(&filter)),
+        "/api/history" => {
+            let since = query_param(query, "since")
+                .and_then(|value| DateTime::parse_from_rfc3339(value).ok())
+                .map(|time| time.with_timezone(&Utc).into());
+            let samples: Vec<Value> = monitor.get_connection_history_filtered(&filter, since, None)
+                .into_iter()
+                .map(|(time, active)| {
+                    let time: DateTime<Utc> = time.into();
+                    json!({ "timestamp": time, "active_connections": active })
+                })
+                .collect();
+            Value::from(samples)
+        }
+        "/api/annotations" => to_json(monitor.get_annotations().collect::<Vec<_>>()),
+        "/api/rollups" => {
+            let rollups: Vec<Value> = monitor.metrics.active_history.rollups()
+                .map(|rollup| {
+                    let start: DateTime<Utc> = rollup.start.into();
+                    json!({
+                        "start": start,
+                        "seconds": rollup.width.as_secs(),
+                        "samples": rollup.samples,
+                        "min": rollup.min,
+                        "avg": rollup.average(),
+                        "max": rollup.max,
+                    })
+                })
+                .collect();
+            Value::from(rollups)
+        }
+        // Grafana's JSON datasource checks the connection with a plain GET
+        "/grafana" => json!({ "status": "ok" }),
+        "/metrics" => {
+            let snapshot = MetricsSnapshot::capture(&monitor, &filter);
+            return Some(("text/plain; version=0.0.4", prometheus::render(&snapshot)));
+        }
+        _ => return None,
+    };
+
+    Some(("application/json", body.to_string()))
+}
+
+/// A Grafana JSON datasource `/query` or `/annotations` request; only the fields used.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GrafanaRequest {
+    range: Option<GrafanaRange>,
+    #[serde(default)]
+    targets: Vec<GrafanaTarget>,
+    max_data_points: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GrafanaRange {
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GrafanaTarget {
+    target: Option<String>,
+}
+
+/// Endpoints of the Grafana JSON datasource protocol: `/search` lists the series, `/query`
+/// returns them as `[value, epoch ms]` datapoints and `/annotations` the graph annotations.
+fn grafana_route(path: &str, body: &str, state: &ApiState) -> Option<(&'static str, String)> {
+    let (filter, monitor) = state.lock();
+    let request: GrafanaRequest = serde_json::from_str(body).unwrap_or_default();
+    let (from, to) = match &request.range {
+        Some(range) => (Some(SystemTime::from(range.from)), Some(SystemTime::from(range.to))),
+        None => (None, None),
+    };
+    let in_range = |time: SystemTime| from.is_none_or(|from| time >= from) && to.is_none_or(|to| time <= to);
+
+    let body = match path.trim_end_matches('/') {
+        "/grafana/search" | "/grafana/metrics" => Value::from(GRAFANA_TARGETS.to_vec()),
+        "/grafana/query" => {
+            let series: Vec<Value> = request.targets.iter()
+                .filter_map(|target| target.target.as_deref())
+                .map(|target| {
+                    let points: Vec<(SystemTime, f64)> = match target {
+                        "active_connections" => monitor.get_connection_history_filtered(&filter, from, to)
+                            .into_iter()
+                            .map(|(time, active)| (time, active as f64))
+                            .collect(),
+                        _ => monitor.metrics.active_history.rollups()
+                            .filter(|rollup| in_range(rollup.start))
+                            .filter_map(|rollup| match target {
+                                "rollup_min" => Some((rollup.start, rollup.min as f64)),
+                                "rollup_avg" => Some((rollup.start, rollup.average())),
+                                "rollup_max" => Some((rollup.start, rollup.max as f64)),
+                                _ => None,
+                            })
+                            .collect(),
+                    };
+                    let datapoints: Vec<Value> = downsample(points, request.max_data_points)
+                        .into_iter()
+                        .map(|(time, value)| json!([value, epoch_millis(time)]))
+                        .collect();
+                    json!({ "target": target, "datapoints": datapoints })
+                })
+                .collect();
+            Value::from(series)
+        }
+        "/grafana/annotations" => {
+            let annotations: Vec<Value> = monitor.get_annotations()
+                .filter(|annotation| in_range(annotation.time.into()))
+                .map(|annotation| json!({
+                    "time": annotation.time.timestamp_millis(),
+                    "title": annotation.kind.as_str(),
+                    "text": annotation.text,
+                    "tags": [annotation.kind.as_str()],
+                }))
+                .collect();
+            Value::from(annotations)
+        }
+        _ => return None,
+    };
+    Some(("application/json", body.to_string()))
+}
+
+/// Keeps at most `max_points` points, the highest of each run of neighbours, so short
+/// spikes still show when Grafana asks for fewer points than were sampled.
+fn downsample(points: Vec<(SystemTime, f64)>, max_points: Option<usize>) -> Vec<(SystemTime, f64)> {
+    let Some(max_points) = max_points.filter(|&max| max > 0 && points.len() > max) else {
+        return points;
+    };
+    let bucket = points.len().div_ceil(max_points);
+    points.chunks(bucket)
+        .filter_map(|chunk| chunk.iter().copied().reduce(|peak, point| if point.1 > peak.1 { point } else { peak }))
+        .collect()
+}
+
+fn epoch_millis(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH).map_or(0, |since| since.as_millis() as u64)
+}
+
+fn to_json(value: impl Serialize) -> Value {
+    serde_json::to_value(value).unwrap_or(Value::Null)
+}
+
+fn query_param<'a>(query: &'a str, name: &str) -> Option<&'a str> {
+    query.split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| value)
+}
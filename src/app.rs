@@ -1,22 +1,76 @@
 use std::io;
+use std::net::TcpListener;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
-use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, MouseEvent, MouseEventKind};
-use crossterm::{execute, event::EnableMouseCapture, event::DisableMouseCapture};
+use chrono::{DateTime, Local, Utc};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use crossterm::{execute, event::EnableMouseCapture, event::DisableMouseCapture, event::EnableBracketedPaste, event::DisableBracketedPaste};
 use ratatui::{DefaultTerminal, Frame};
 
+use crate::api::{self, ApiState};
 use crate::core::monitor::ConnectionMonitor;
-use crate::core::filters::ConnectionFilter;
+use crate::core::snapshot::{FullSnapshot, MetricsSnapshot};
+use crate::config::{ColumnsConfig, Config};
+use crate::error::Error;
+use crate::core::filters::{ConnectionFilter, FilterExpr, MatchOptions, QueryField, QueryOp, SavedFilter};
+use crate::core::process;
+use crate::core::anomaly::AnomalyOptions;
+use crate::core::backlog::BacklogOptions;
+use crate::core::churn::ChurnOptions;
+use crate::core::retransmits::RetransmitOptions;
+use crate::core::limits::LimitOptions;
+use crate::core::rollup::RollupOptions;
+use crate::core::script::ScriptHooks;
+use crate::core::views::GROUP_VIEW;
+use crate::core::annotations::AnnotationKind;
+use crate::core::leaks::LeakOptions;
+use crate::core::pacing::{BurstOptions, PaceChange, SamplePacer, format_interval};
+use crate::core::groups::EndpointGroup;
+use crate::core::aliases::HostAliases;
+use crate::core::blocklist::{BlocklistFeed, BlocklistOptions};
+use crate::core::watchlist::{PinRule, Watchlist};
+use crate::output::EventDispatcher;
+use crate::output::profile_log::ProfileLog;
+use crate::remote::Aggregator;
+use crate::summary::{FiredAlert, session_summary};
 use crate::widgets::{
     HostTableWidget, 
     ProcessHostTableWidget,
     ProcessTableWidget,
     SummaryWidget,
     ActiveConnectionsGraphWidget,
-    FilterWidget
+    FilterWidget,
+    QueryPromptWidget,
+    TextPromptWidget,
+    ResetPromptWidget,
+    FilterPickerWidget,
+    ColumnPickerWidget,
+    HostDetailWidget,
+    GroupTableWidget,
+    ListenTableWidget,
+    ClientTableWidget,
+    EventLogWidget,
+    OverheadWidget,
+    CounterOverlayWidget,
+    TopBarsWidget,
+    HeatmapWidget,
+    FlowWidget,
+    PinnedGraphsWidget,
+    ToastsWidget,
 };
+use crate::widgets::filter_picker::QUICK_SLOTS;
+use crate::widgets::query_prompt::QueryInput;
+use crate::widgets::reset_prompt::ResetScope;
+use crate::widgets::{client_table, group_table, host_table, listen_table, process_host_table, process_table};
+use crate::widgets::process_host_table::TreeNode;
+use crate::widgets::{glyphs, theme};
+use crate::widgets::table::{Column, Highlight, format_secs};
+use crate::widgets::top_bars::TopBarsKind;
+use crate::widgets::pinned_graphs::{MAX_PINNED_GRAPHS, PinOutcome};
+use crate::widgets::toasts::{TOAST_DURATION, ToastKind};
 
-use ratatui::layout::{Layout, Direction, Constraint};
+use ratatui::layout::{Layout, Direction, Constraint, Position, Rect};
 use ratatui::widgets::Paragraph;
 use ratatui::style::{Style, Color};
 use ratatui::text::{Span, Line};
@@ -36,29 +90,173 @@ impl SortBy {
             SortBy::Max => "Max",
         }
     }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "total" => Some(SortBy::Total),
+            "active" => Some(SortBy::Active),
+            "max" => Some(SortBy::Max),
+            _ => None,
+        }
+    }
+}
+
+/// Where each part of the screen is drawn, shared by drawing and mouse handling.
+struct ScreenLayout {
+    graph: Rect,
+    summary: Rect,
+    /// Graph and summary of the pinned filter in split view.
+    compare: Option<(Rect, Rect)>,
+    pinned_graphs: Option<Rect>,
+    overhead: Option<Rect>,
+    process_host: Rect,
+    event_log: Option<Rect>,
+    host: Rect,
+    process: Rect,
+    group: Option<Rect>,
+    inbound: Option<Rect>,
+    /// Details and actions of the focused table's selected row.
+    selection_bar: Rect,
+    status_bar: Rect,
+}
+
+/// What the filter popup is currently editing.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FilterTarget {
+    Filter,
+    Highlight,
 }
 
+/// Colors assigned in turn to highlight rules added at runtime.
+const HIGHLIGHT_COLORS: [Color; 5] = [
+    Color::Magenta,
+    Color::Cyan,
+    Color::LightRed,
+    Color::LightGreen,
+    Color::LightBlue,
+];
+
+/// How long an error stays in the status bar after it last occurred.
+const ERROR_DURATION: Duration = Duration::from_secs(60);
+/// How often connections are sampled unless refreshes are too slow for it.
+const SAMPLE_INTERVAL: Duration = Duration::from_millis(250);
+/// Shortest time between two frames, so bursts of input are drawn once.
+const MIN_FRAME_INTERVAL: Duration = Duration::from_millis(33);
+
+/// Number of previous filters kept for undo.
+const MAX_FILTER_HISTORY: usize = 50;
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum FocusedTable {
     ProcessHost,
     Process,
     Host,
+    Group,
+    Listen,
+    Clients,
+}
+
+impl FocusedTable {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "process-host" => Some(FocusedTable::ProcessHost),
+            "process" => Some(FocusedTable::Process),
+            "host" => Some(FocusedTable::Host),
+            "group" => Some(FocusedTable::Group),
+            "listen" => Some(FocusedTable::Listen),
+            "clients" => Some(FocusedTable::Clients),
+            _ => None,
+        }
+    }
 }
 
 pub struct App {
     pub host_table_widget: HostTableWidget,
     pub process_host_table_widget: ProcessHostTableWidget,
     pub process_table_widget: ProcessTableWidget,
+    pub group_table_widget: GroupTableWidget,
+    pub listen_table_widget: ListenTableWidget,
+    pub client_table_widget: ClientTableWidget,
+    pub event_log_widget: EventLogWidget,
     pub summary_widget: SummaryWidget,
+    pub overhead_widget: OverheadWidget,
+    pub counter_overlay_widget: CounterOverlayWidget,
+    pub toasts_widget: ToastsWidget,
     pub active_connections_graph_widget: ActiveConnectionsGraphWidget,
+    pub top_bars_widget: TopBarsWidget,
+    pub compare_graph_widget: ActiveConnectionsGraphWidget,
+    pub compare_summary_widget: SummaryWidget,
+    pub pinned_graphs_widget: PinnedGraphsWidget,
     pub filter_widget: FilterWidget,
+    pub query_prompt_widget: QueryPromptWidget,
+    pub save_prompt_widget: TextPromptWidget,
+    pub annotation_prompt_widget: TextPromptWidget,
+    pub reset_prompt_widget: ResetPromptWidget,
+    pub filter_picker_widget: FilterPickerWidget,
+    pub column_picker_widget: ColumnPickerWidget,
+    pub host_detail_widget: HostDetailWidget,
+    pub heatmap_widget: HeatmapWidget,
+    pub flow_widget: FlowWidget,
     pub monitor: Arc<Mutex<ConnectionMonitor>>,
     pub current_filter: ConnectionFilter,
+    pub filter_history: Vec<ConnectionFilter>,
     pub exit: bool,
+    pub deadline: Option<Instant>,
     pub last_tick: Instant,
+    /// How often connections are sampled; frames are only drawn when something changed.
     pub tick_rate: Duration,
+    pub needs_redraw: bool,
+    pub last_draw: Option<Instant>,
+    /// When a time-limited message expires and the status bar must be redrawn.
+    pub redraw_at: Option<Instant>,
+    /// Monitor revision shown in the last frame.
+    pub drawn_revision: u64,
+    /// Second the clock and uptime were last drawn at.
+    pub drawn_second: i64,
+    /// Lengthens `tick_rate` while refreshes take longer than it, and shortens it while
+    /// filtered connections churn if adaptive sampling is on.
+    pub pacer: SamplePacer,
+    /// First event not yet counted towards churn.
+    pub churn_seq: u64,
     pub mouse_enabled: bool,
+    pub mouse_requested: bool,
     pub focused_table: FocusedTable,
+    pub show_groups: bool,
+    /// Inbound table shown next to the others, by listening port or by client, if any.
+    pub inbound_table: Option<FocusedTable>,
+    pub show_event_log: bool,
+    pub show_overhead: bool,
+    /// Bar chart shown in place of the connections graph, if any.
+    pub top_bars: Option<TopBarsKind>,
+    /// Filter pinned to the second row of the split view, if split.
+    pub compare_filter: Option<ConnectionFilter>,
+    pub show_counters: bool,
+    /// How long exited processes stay in the process tables, as configured.
+    pub exited_retention: Option<Duration>,
+    /// How long rows without open connections stay in the tables, as configured.
+    pub inactive_retention: Option<Duration>,
+    pub watchlist: Watchlist,
+    pub filter_target: FilterTarget,
+    pub config_highlights: Vec<Highlight>,
+    pub runtime_highlights: Vec<Highlight>,
+    pub events: EventDispatcher,
+    pub api_filter: Option<Arc<Mutex<ConnectionFilter>>>,
+    pub aggregator: Option<Aggregator>,
+    /// File and time of the snapshot being analyzed, when not monitoring live.
+    pub offline_source: Option<String>,
+    pub saved_filters: Vec<SavedFilter>,
+    pub config_path: Option<PathBuf>,
+    pub match_defaults: MatchOptions,
+    pub screen_area: Rect,
+    pub clipboard: Option<arboard::Clipboard>,
+    /// Process the last key asked to kill, killed if the next key asks again.
+    pub pending_kill: Option<u32>,
+    /// Most recent recoverable error, when it happened, and how many more came with it.
+    pub last_error: Option<(String, DateTime<Local>, usize)>,
+    pub started: DateTime<Utc>,
+    pub fired_alerts: Vec<FiredAlert>,
+    pub profile_log: Option<ProfileLog>,
+    pub blocklists: Option<BlocklistFeed>,
 }
 
 impl App {
@@ -66,40 +264,409 @@ impl App {
         let monitor = Arc::new(Mutex::new(ConnectionMonitor::new()));
         let current_filter = ConnectionFilter::default();
         
-        App {
+        let mut app = App {
             host_table_widget: HostTableWidget::new(Arc::clone(&monitor)),
             process_host_table_widget: ProcessHostTableWidget::new(Arc::clone(&monitor)),
             process_table_widget: ProcessTableWidget::new(Arc::clone(&monitor)),
+            group_table_widget: GroupTableWidget::new(Arc::clone(&monitor)),
+            listen_table_widget: ListenTableWidget::new(Arc::clone(&monitor)),
+            client_table_widget: ClientTableWidget::new(Arc::clone(&monitor)),
+            event_log_widget: EventLogWidget::new(Arc::clone(&monitor)),
             summary_widget: SummaryWidget::new(Arc::clone(&monitor)),
+            overhead_widget: OverheadWidget::new(Arc::clone(&monitor)),
+            counter_overlay_widget: CounterOverlayWidget::new(Arc::clone(&monitor)),
+            toasts_widget: ToastsWidget::new(),
             active_connections_graph_widget: ActiveConnectionsGraphWidget::new(Arc::clone(&monitor))
                 .with_max_points(300),
+            top_bars_widget: TopBarsWidget::new(Arc::clone(&monitor)),
+            compare_graph_widget: ActiveConnectionsGraphWidget::new(Arc::clone(&monitor))
+                .with_max_points(300),
+            compare_summary_widget: SummaryWidget::new(Arc::clone(&monitor)),
+            pinned_graphs_widget: PinnedGraphsWidget::new(Arc::clone(&monitor)),
             filter_widget: FilterWidget::new(),
+            query_prompt_widget: QueryPromptWidget::new(),
+            save_prompt_widget: TextPromptWidget::new(),
+            annotation_prompt_widget: TextPromptWidget::new(),
+            reset_prompt_widget: ResetPromptWidget::new(),
+            filter_picker_widget: FilterPickerWidget::new(),
+            column_picker_widget: ColumnPickerWidget::new(),
+            host_detail_widget: HostDetailWidget::new(Arc::clone(&monitor)),
+            heatmap_widget: HeatmapWidget::new(Arc::clone(&monitor)),
+            flow_widget: FlowWidget::new(Arc::clone(&monitor)),
             monitor,
             current_filter,
+            filter_history: Vec::new(),
             exit: false,
+            deadline: None,
             last_tick: Instant::now(),
-            tick_rate: Duration::from_millis(250),
+            tick_rate: SAMPLE_INTERVAL,
+            needs_redraw: true,
+            last_draw: None,
+            redraw_at: None,
+            drawn_revision: 0,
+            drawn_second: 0,
+            pacer: SamplePacer::new(SAMPLE_INTERVAL),
+            churn_seq: 0,
             mouse_enabled: false,
+            mouse_requested: true,
             focused_table: FocusedTable::ProcessHost,
+            show_groups: false,
+            inbound_table: None,
+            show_event_log: false,
+            show_overhead: false,
+            top_bars: None,
+            compare_filter: None,
+            show_counters: false,
+            exited_retention: None,
+            inactive_retention: None,
+            watchlist: Watchlist::default(),
+            filter_target: FilterTarget::Filter,
+            config_highlights: Vec::new(),
+            runtime_highlights: Vec::new(),
+            events: EventDispatcher::new(),
+            api_filter: None,
+            aggregator: None,
+            offline_source: None,
+            saved_filters: Vec::new(),
+            config_path: None,
+            match_defaults: MatchOptions::default(),
+            screen_area: Rect::default(),
+            clipboard: None,
+            pending_kill: None,
+            last_error: None,
+            started: Utc::now(),
+            fired_alerts: Vec::new(),
+            profile_log: None,
+            blocklists: None,
+        };
+        app.set_focused_table(FocusedTable::ProcessHost);
+        app
+    }
+    
+    pub fn with_aliases(self, aliases: HostAliases) -> Self {
+        if !aliases.is_empty() {
+            if let Ok(mut monitor) = self.monitor.lock() {
+                monitor.set_aliases(aliases);
+            }
+        }
+        self
+    }
+
+    pub fn with_name_ttl(self, ttl: Duration) -> Self {
+        if let Ok(mut monitor) = self.monitor.lock() {
+            monitor.set_name_ttl(ttl);
+        }
+        self
+    }
+
+    pub fn with_rollup_options(self, options: RollupOptions) -> Self {
+        if let Ok(mut monitor) = self.monitor.lock() {
+            monitor.set_rollup_options(options);
+        }
+        self
+    }
+
+    pub fn with_followed_processes(self, names: &[String]) -> Self {
+        if let Ok(mut monitor) = self.monitor.lock() {
+            monitor.set_followed_processes(names);
+        }
+        self
+    }
+
+    pub fn with_carry_restarts(self, carry: bool) -> Self {
+        if let Ok(mut monitor) = self.monitor.lock() {
+            monitor.set_carry_restarts(carry);
+        }
+        self
+    }
+
+    pub fn with_idle_threshold(self, threshold: Duration) -> Self {
+        if let Ok(mut monitor) = self.monitor.lock() {
+            monitor.set_idle_threshold(threshold);
+        }
+        self
+    }
+
+    pub fn with_exited_retention(mut self, retention: Option<Duration>) -> Self {
+        self.exited_retention = retention;
+        if let Ok(mut monitor) = self.monitor.lock() {
+            monitor.set_exited_retention(retention);
+        }
+        self
+    }
+
+    pub fn with_inactive_retention(mut self, retention: Option<Duration>) -> Self {
+        self.inactive_retention = retention;
+        if let Ok(mut monitor) = self.monitor.lock() {
+            monitor.set_inactive_retention(retention);
+        }
+        self
+    }
+
+    pub fn with_burst_sampling(mut self, burst: Option<BurstOptions>) -> Self {
+        self.pacer = SamplePacer::new(SAMPLE_INTERVAL).with_burst(burst);
+        self
+    }
+
+    pub fn with_conntrack(self, enabled: bool) -> Self {
+        if let Ok(mut monitor) = self.monitor.lock() {
+            monitor.set_conntrack(enabled);
+        }
+        self
+    }
+
+    pub fn with_proxy_detection(self, enabled: bool) -> Self {
+        if let Ok(mut monitor) = self.monitor.lock() {
+            monitor.set_proxy_detection(enabled);
+        }
+        self
+    }
+
+    pub fn with_script(self, script: Option<ScriptHooks>) -> Self {
+        if let Ok(mut monitor) = self.monitor.lock() {
+            monitor.set_script(script);
+        }
+        self
+    }
+
+    pub fn with_thread_attribution(self, enabled: bool) -> Self {
+        if let Ok(mut monitor) = self.monitor.lock() {
+            monitor.set_thread_attribution(enabled);
+        }
+        self
+    }
+
+    pub fn with_groups(mut self, groups: Vec<EndpointGroup>) -> Self {
+        self.show_groups = !groups.is_empty();
+        if let Ok(mut monitor) = self.monitor.lock() {
+            monitor.set_groups(groups);
+        }
+        self
+    }
+    
+    /// Shows the aggregation view named `view` in the group table, which is then shown even
+    /// without endpoint groups.
+    pub fn with_view(mut self, view: Option<String>) -> Self {
+        if let Some(view) = view {
+            self.set_view(view);
+        }
+        self
+    }
+
+    pub fn with_leak_options(self, options: Option<LeakOptions>) -> Self {
+        if let Ok(mut monitor) = self.monitor.lock() {
+            monitor.set_leak_options(options);
+        }
+        self
+    }
+
+    pub fn with_anomaly_options(self, options: Option<AnomalyOptions>) -> Self {
+        if let Ok(mut monitor) = self.monitor.lock() {
+            monitor.set_anomaly_options(options);
+        }
+        self
+    }
+
+    pub fn with_churn_options(self, options: Option<ChurnOptions>) -> Self {
+        if let Ok(mut monitor) = self.monitor.lock() {
+            monitor.set_churn_options(options);
+        }
+        self
+    }
+
+    pub fn with_backlog_options(self, options: Option<BacklogOptions>) -> Self {
+        if let Ok(mut monitor) = self.monitor.lock() {
+            monitor.set_backlog_options(options);
+        }
+        self
+    }
+
+    pub fn with_tcp_info(self, enabled: bool) -> Self {
+        if let Ok(mut monitor) = self.monitor.lock() {
+            monitor.set_tcp_info(enabled);
+        }
+        self
+    }
+
+    pub fn with_dns(self, enabled: bool) -> Self {
+        if let Ok(mut monitor) = self.monitor.lock() {
+            monitor.set_dns(enabled);
+        }
+        self
+    }
+
+    pub fn with_retransmit_options(self, options: Option<RetransmitOptions>) -> Self {
+        if let Ok(mut monitor) = self.monitor.lock() {
+            monitor.set_retransmit_options(options);
+        }
+        self
+    }
+
+    pub fn with_limit_options(self, options: Option<LimitOptions>) -> Self {
+        if let Ok(mut monitor) = self.monitor.lock() {
+            monitor.set_limit_options(options);
+        }
+        self
+    }
+
+    /// Starts loading the blocklists in the background; connections are checked once loaded.
+    pub fn with_blocklists(mut self, options: Option<BlocklistOptions>) -> Self {
+        if let Some(options) = options {
+            if let Ok(mut monitor) = self.monitor.lock() {
+                monitor.set_blocklist_alerts(options.alert);
+            }
+            self.blocklists = Some(BlocklistFeed::start(&options));
+        }
+        self
+    }
+
+    pub fn with_watchlist(mut self, watchlist: Watchlist) -> Self {
+        self.watchlist = watchlist;
+        self.apply_watchlist();
+        self
+    }
+    
+    pub fn with_highlights(mut self, highlights: Vec<Highlight>) -> Self {
+        self.config_highlights = highlights;
+        self.apply_highlights();
+        self
+    }
+    
+    pub fn with_columns(mut self, columns: &ColumnsConfig) -> Self {
+        if let Some(columns) = &columns.process_host {
+            self.process_host_table_widget.set_columns(columns);
+        }
+        if let Some(columns) = &columns.process {
+            self.process_table_widget.set_columns(columns);
+        }
+        if let Some(columns) = &columns.host {
+            self.host_table_widget.set_columns(columns);
+        }
+        if let Some(columns) = &columns.group {
+            self.group_table_widget.set_columns(columns);
+        }
+        if let Some(columns) = &columns.listen {
+            self.listen_table_widget.set_columns(columns);
+        }
+        if let Some(columns) = &columns.clients {
+            self.client_table_widget.set_columns(columns);
+        }
+        self
+    }
+
+    pub fn with_events(mut self, events: EventDispatcher) -> Self {
+        self.events = events;
+        self
+    }
+    
+    /// Matching used by cleared filters and new highlight rules.
+    pub fn with_match_defaults(mut self, matching: MatchOptions) -> Self {
+        self.match_defaults = matching;
+        self
+    }
+    
+    /// Saved filters for the picker and F1-F5; new ones are appended to the config at `config_path`.
+    pub fn with_saved_filters(mut self, saved_filters: Vec<SavedFilter>, config_path: Option<PathBuf>) -> Self {
+        self.saved_filters = saved_filters;
+        self.config_path = config_path;
+        self
+    }
+    
+    /// Initial sort order, instead of by total.
+    pub fn with_sort_by(mut self, sort_by: Option<SortBy>) -> Self {
+        if let Some(sort_by) = sort_by {
+            self.set_sort_by(sort_by);
         }
+        self
+    }
+
+    /// Focuses `table` on startup; the group table only when groups are configured. Focusing
+    /// an inbound table shows it.
+    pub fn with_focus(mut self, table: Option<FocusedTable>) -> Self {
+        if let Some(table) = table.filter(|table| *table != FocusedTable::Group || self.show_groups) {
+            if matches!(table, FocusedTable::Listen | FocusedTable::Clients) {
+                self.inbound_table = Some(table);
+            }
+            self.set_focused_table(table);
+        }
+        self
+    }
+
+    /// Number of samples the active connections graph keeps, instead of 300.
+    pub fn with_max_points(mut self, points: Option<usize>) -> Self {
+        if let Some(points) = points {
+            self.active_connections_graph_widget.set_max_points(points);
+            self.compare_graph_widget.set_max_points(points);
+            self.pinned_graphs_widget.set_max_points(points);
+        }
+        self
+    }
+
+    /// Whether to capture the mouse on startup; `M` still toggles it.
+    pub fn with_mouse(mut self, enabled: bool) -> Self {
+        self.mouse_requested = enabled;
+        self
+    }
+
+    /// Quits on its own once `duration` has passed; one too long to reach never does.
+    pub fn with_duration(mut self, duration: Option<Duration>) -> Self {
+        self.deadline = duration.and_then(|duration| Instant::now().checked_add(duration));
+        self
+    }
+
+    /// Serves live data over HTTP on `listener` while the TUI runs.
+    pub fn with_api(mut self, listener: TcpListener) -> Self {
+        let filter = Arc::new(Mutex::new(self.current_filter.clone()));
+        api::spawn(listener, ApiState {
+            monitor: Arc::clone(&self.monitor),
+            filter: Arc::clone(&filter),
+        });
+        self.api_filter = Some(filter);
+        self
+    }
+    
+    /// Explores a saved snapshot instead of polling live connections.
+    pub fn with_offline(mut self, snapshot: FullSnapshot, path: &Path) -> Self {
+        let recorded: DateTime<Local> = snapshot.metrics.timestamp.into();
+        self.offline_source = Some(format!("{} ({})", path.display(), recorded.format("%Y-%m-%d %H:%M:%S")));
+        self.started = snapshot.started.unwrap_or(snapshot.metrics.timestamp);
+        if let Ok(mut monitor) = self.monitor.lock() {
+            monitor.load_offline(snapshot);
+        }
+        self
+    }
+
+    /// Shows connections reported by remote agents instead of this machine's.
+    pub fn with_aggregator(mut self, aggregator: Aggregator) -> Self {
+        // Drop the local sample taken when the monitor was created
+        self.reset_monitor();
+        self.aggregator = Some(aggregator);
+        self
     }
     
+    /// Logs tcpcount's own overhead after every refresh.
+    pub fn with_profile_log(mut self, profile_log: Option<ProfileLog>) -> Self {
+        self.profile_log = profile_log;
+        self
+    }
+
     pub fn with_filter(mut self, filter: ConnectionFilter) -> Self {
-        self.current_filter = filter.clone();
-        self.apply_filter(filter);
+        self.set_active_filter(filter);
         self
     }
 
     pub fn run(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
-        if let Ok(()) = execute!(
-            std::io::stdout(),
-            EnableMouseCapture
-        ) {
+        if self.mouse_requested && execute!(std::io::stdout(), EnableMouseCapture).is_ok() {
             self.mouse_enabled = true;
         }
+        let paste_enabled = execute!(std::io::stdout(), EnableBracketedPaste).is_ok();
 
         let result = self.run_loop(terminal);
 
+        if paste_enabled {
+            let _ = execute!(std::io::stdout(), DisableBracketedPaste);
+        }
+
         if self.mouse_enabled {
             let _ = execute!(
                 std::io::stdout(),
@@ -112,12 +679,23 @@ impl App {
 
     fn run_loop(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
         while !self.exit {
-            let timeout = self.tick_rate
-                .checked_sub(self.last_tick.elapsed())
-                .unwrap_or_else(|| Duration::from_secs(0));
+            let mut wake = self.last_tick + self.tick_rate;
+            if self.needs_redraw {
+                wake = wake.min(self.last_draw.map_or_else(Instant::now, |at| at + MIN_FRAME_INTERVAL));
+            }
+            if let Some(at) = self.redraw_at {
+                wake = wake.min(at);
+            }
             
-            if crossterm::event::poll(timeout)? {
-                self.handle_events()?;
+            if crossterm::event::poll(wake.saturating_duration_since(Instant::now()))? {
+                // Handle everything already queued so a burst of keys or scrolling costs one frame
+                loop {
+                    self.handle_events()?;
+                    self.needs_redraw = true;
+                    if self.exit || !crossterm::event::poll(Duration::ZERO)? {
+                        break;
+                    }
+                }
             }
             
             if self.last_tick.elapsed() >= self.tick_rate {
@@ -125,74 +703,381 @@ impl App {
                 self.last_tick = Instant::now();
             }
             
-            terminal.draw(|frame| self.draw(frame))?;
+            if self.redraw_at.is_some_and(|at| Instant::now() >= at) {
+                self.redraw_at = None;
+                self.needs_redraw = true;
+            }
+            
+            let frame_due = self.last_draw.is_none_or(|at| at.elapsed() >= MIN_FRAME_INTERVAL);
+            if self.needs_redraw && frame_due && !self.exit {
+                let frame = terminal.draw(|frame| self.draw(frame))?;
+                self.screen_area = frame.area;
+                self.needs_redraw = false;
+                self.last_draw = Some(Instant::now());
+            }
         }
         Ok(())
     }
 
     fn tick(&mut self) {
-        self.update_monitor();
-        self.active_connections_graph_widget.update();
+        if self.deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            self.exit();
+        }
+        if self.offline_source.is_none() {
+            let started = Instant::now();
+            self.update_monitor();
+            let refresh = started.elapsed();
+            let churn = self.count_churn();
+            let changes = [self.pacer.record(refresh), self.pacer.record_churn(churn, Instant::now())];
+            for change in changes.into_iter().flatten() {
+                let kind = match change {
+                    PaceChange::Slower { .. } => ToastKind::Alert,
+                    PaceChange::Faster { .. } | PaceChange::Steady { .. } | PaceChange::Burst { .. } => ToastKind::Info,
+                };
+                self.show_toast(kind, change.to_string());
+            }
+            self.tick_rate = self.pacer.interval();
+        }
+        self.dispatch_events();
+        if self.active_connections_graph_widget.update() {
+            self.needs_redraw = true;
+        }
+        if self.compare_filter.is_some() && self.compare_graph_widget.update() {
+            self.needs_redraw = true;
+        }
+        if self.pinned_graphs_widget.update() {
+            self.needs_redraw = true;
+        }
+        let revision = self.monitor.lock().map_or(self.drawn_revision, |monitor| monitor.revision());
+        if revision != self.drawn_revision {
+            self.drawn_revision = revision;
+            self.needs_redraw = true;
+        }
+        // The clock, uptime and sample count move on even when no connection changed
+        let second = Utc::now().timestamp();
+        if self.offline_source.is_none() && second != self.drawn_second {
+            self.drawn_second = second;
+            self.needs_redraw = true;
+        }
+    }
+
+    fn dispatch_events(&mut self) {
+        let result = match self.monitor.lock() {
+            Ok(monitor) => self.events.dispatch(&monitor),
+            Err(_) => return,
+        };
+        if let Err(e) = result {
+            self.record_errors(vec![e]);
+        }
     }
 
     fn update_monitor(&mut self) {
+        let mut alerts = Vec::new();
+        let mut errors = Vec::new();
         if let Ok(mut monitor) = self.monitor.lock() {
-            monitor.refresh().ok();
+            if let Some((lists, load_errors)) = self.blocklists.as_ref().and_then(BlocklistFeed::poll) {
+                monitor.set_blocklists(lists);
+                errors.extend(load_errors);
+            }
+            match self.aggregator.as_mut() {
+                Some(aggregator) => monitor.apply_remote_samples(aggregator.samples()),
+                None => {
+                    if let Err(e) = monitor.refresh() {
+                        errors.push(e);
+                    }
+                }
+            }
+            errors.extend(monitor.take_errors());
+            if let Some(profile) = self.profile_log.as_mut() {
+                if let Err(e) = profile.write(monitor.overhead()) {
+                    errors.push(e);
+                }
+            }
+            alerts.extend(monitor.take_leak_alerts().iter().map(ToString::to_string));
+            alerts.extend(monitor.take_churn_alerts().iter().map(ToString::to_string));
+            alerts.extend(monitor.take_limit_alerts().iter().map(ToString::to_string));
+            alerts.extend(monitor.take_backlog_alerts().iter().map(ToString::to_string));
+            alerts.extend(monitor.take_retransmit_alerts().iter().map(ToString::to_string));
+            alerts.extend(monitor.take_blocklist_alerts().iter().map(ToString::to_string));
+            alerts.extend(monitor.take_script_alerts());
+            for alert in &alerts {
+                monitor.annotate(AnnotationKind::Alert, alert.clone());
+            }
+            // Restarts annotate themselves
+            alerts.extend(monitor.take_restart_alerts().iter().map(ToString::to_string));
+        }
+        self.record_errors(errors);
+        for alert in &alerts {
+            self.show_toast(ToastKind::Alert, alert.clone());
+        }
+        let time = Local::now();
+        self.fired_alerts.extend(alerts.into_iter().map(|message| FiredAlert { time, message }));
+    }
+
+    /// Opens and closes of filtered connections since the last call, if adaptive sampling is on.
+    fn count_churn(&mut self) -> usize {
+        if !self.pacer.has_burst() {
+            return 0;
+        }
+        let Ok(monitor) = self.monitor.lock() else {
+            return 0;
+        };
+        let churn = monitor.count_filtered_events_since(&self.current_filter, self.churn_seq);
+        self.churn_seq = monitor.next_event_seq();
+        churn
+    }
+
+    /// Report of the session so far, for `--summary-on-exit`.
+    pub fn session_summary(&self) -> String {
+        match self.monitor.lock() {
+            Ok(monitor) => session_summary(&monitor, &self.current_filter, self.started, &self.fired_alerts),
+            Err(_) => String::new(),
+        }
+    }
+
+    /// The metrics of the session so far under the current filter.
+    pub fn metrics_snapshot(&self) -> Option<MetricsSnapshot> {
+        self.monitor.lock().ok().map(|monitor| MetricsSnapshot::capture(&monitor, &self.current_filter))
+    }
+
+    /// Starts the graph over without touching totals, so nothing needs confirming.
+    fn clear_graph(&mut self) {
+        self.active_connections_graph_widget.clear();
+        self.compare_graph_widget.clear();
+        self.pinned_graphs_widget.clear();
+        self.show_status_message("Graph cleared".to_string());
+    }
+
+    fn confirm_reset(&mut self) {
+        if self.offline_source.is_some() {
+            self.show_status_message("Offline data can't be reset".to_string());
+            return;
         }
+        self.reset_prompt_widget.show();
     }
 
     fn reset_monitor(&mut self) {
+        if self.offline_source.is_some() {
+            self.show_status_message("Offline data can't be reset".to_string());
+            return;
+        }
         if let Ok(mut monitor) = self.monitor.lock() {
             monitor.reset();
         }
     }
 
-    fn draw(&self, frame: &mut Frame) {
+    fn screen_layout(&self, area: Rect) -> ScreenLayout {
+        let mut summary_height = 9;
+        if self.monitor.lock().is_ok_and(|monitor| monitor.limit_options().is_some()) {
+            // Room for the system-wide gauge
+            summary_height += 1;
+        }
+        let mut top_height = if self.compare_filter.is_some() { summary_height * 2 } else { summary_height };
+        if !self.pinned_graphs_widget.is_empty() {
+            top_height += self.pinned_graphs_widget.len() as u16 + 2;
+        }
         let main_chunks = Layout::default()
             .direction(Direction::Vertical)
             .constraints([
-                Constraint::Length(7),   // First row: Graph + Summary
+                Constraint::Length(top_height), // First row: Graph + Summary, twice when split
                 Constraint::Percentage(38), // Second row: Process-Host Table
                 Constraint::Percentage(38), // Third row: Host Table + Process Table
-                Constraint::Length(1),   // Fourth row: Status bar
+                Constraint::Length(1),   // Fourth row: Selected row
+                Constraint::Length(1),   // Fifth row: Status bar
             ])
             .margin(1)
-            .split(frame.area());
+            .split(area);
             
-        let top_chunks = Layout::default()
-            .direction(Direction::Horizontal)
-            .constraints([
+        let top_constraints = if self.show_overhead {
+            vec![
+                Constraint::Percentage(55), // Graph
+                Constraint::Percentage(22), // Summary count
+                Constraint::Percentage(23), // Overhead
+            ]
+        } else {
+            vec![
                 Constraint::Percentage(75), // Graph (75% of width)
                 Constraint::Percentage(25), // Summary count (25% of width)
-            ])
-            .split(main_chunks[0]);
-            
-        let bottom_chunks = Layout::default()
+            ]
+        };
+        let (graph_rows, pinned_graphs) = if self.pinned_graphs_widget.is_empty() {
+            (main_chunks[0], None)
+        } else {
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(0), Constraint::Length(self.pinned_graphs_widget.len() as u16 + 2)])
+                .split(main_chunks[0]);
+            (chunks[0], Some(chunks[1]))
+        };
+        let (top_row, compare_row) = if self.compare_filter.is_some() {
+            let halves = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+                .split(graph_rows);
+            (halves[0], Some(halves[1]))
+        } else {
+            (graph_rows, None)
+        };
+        let top_chunks = Layout::default()
             .direction(Direction::Horizontal)
-            .constraints([
+            .constraints(top_constraints)
+            .split(top_row);
+        let compare = compare_row.map(|row| {
+            let chunks = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Percentage(75), Constraint::Percentage(25)])
+                .split(row);
+            (chunks[0], chunks[1])
+        });
+            
+        let bottom_constraints = match (self.show_groups, self.inbound_table.is_some()) {
+            (true, true) => vec![
+                Constraint::Percentage(30), // Host Table
+                Constraint::Percentage(25), // Process Table
+                Constraint::Percentage(20), // Group Table
+                Constraint::Percentage(25), // Inbound Table
+            ],
+            (true, false) => vec![
+                Constraint::Percentage(40), // Host Table
+                Constraint::Percentage(35), // Process Table
+                Constraint::Percentage(25), // Group Table
+            ],
+            (false, true) => vec![
+                Constraint::Percentage(35), // Host Table
+                Constraint::Percentage(30), // Process Table
+                Constraint::Percentage(35), // Inbound Table
+            ],
+            (false, false) => vec![
                 Constraint::Percentage(50), // Host Table
                 Constraint::Percentage(50), // Process Table
-            ])
-            .split(main_chunks[2]);
+            ],
+        };
+        
+        let bottom_chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(bottom_constraints)
+            .split(main_chunks[2]);
         
-        frame.render_widget(&self.active_connections_graph_widget, top_chunks[0]);
-        frame.render_widget(&self.summary_widget, top_chunks[1]);
+        let (process_host, event_log) = if self.show_event_log {
+            let middle_chunks = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([
+                    Constraint::Percentage(60), // Process-Host Table
+                    Constraint::Percentage(40), // Event Log
+                ])
+                .split(main_chunks[1]);
+            (middle_chunks[0], Some(middle_chunks[1]))
+        } else {
+            (main_chunks[1], None)
+        };
+        
+        ScreenLayout {
+            graph: top_chunks[0],
+            summary: top_chunks[1],
+            compare,
+            pinned_graphs,
+            overhead: self.show_overhead.then(|| top_chunks[2]),
+            process_host,
+            event_log,
+            host: bottom_chunks[0],
+            process: bottom_chunks[1],
+            group: self.show_groups.then(|| bottom_chunks[2]),
+            inbound: self.inbound_table.map(|_| bottom_chunks[bottom_chunks.len() - 1]),
+            selection_bar: main_chunks[3],
+            status_bar: main_chunks[4],
+        }
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        let layout = self.screen_layout(frame.area());
         
-        frame.render_widget(&self.process_host_table_widget, main_chunks[1]);
+        if self.top_bars.is_some() {
+            frame.render_widget(&self.top_bars_widget, layout.graph);
+        } else {
+            frame.render_widget(&self.active_connections_graph_widget, layout.graph);
+        }
+        frame.render_widget(&self.summary_widget, layout.summary);
+        if let Some((graph, summary)) = layout.compare {
+            frame.render_widget(&self.compare_graph_widget, graph);
+            frame.render_widget(&self.compare_summary_widget, summary);
+        }
+        if let Some(area) = layout.pinned_graphs {
+            frame.render_widget(&self.pinned_graphs_widget, area);
+        }
+        if let Some(area) = layout.overhead {
+            frame.render_widget(&self.overhead_widget, area);
+        }
+        frame.render_widget(&self.process_host_table_widget, layout.process_host);
+        if let Some(area) = layout.event_log {
+            frame.render_widget(&self.event_log_widget, area);
+        }
         
-        frame.render_widget(&self.host_table_widget, bottom_chunks[0]);
-        frame.render_widget(&self.process_table_widget, bottom_chunks[1]);
+        frame.render_widget(&self.host_table_widget, layout.host);
+        frame.render_widget(&self.process_table_widget, layout.process);
+        if let Some(area) = layout.group {
+            frame.render_widget(&self.group_table_widget, area);
+        }
+        if let Some(area) = layout.inbound {
+            if self.inbound_table == Some(FocusedTable::Clients) {
+                frame.render_widget(&self.client_table_widget, area);
+            } else {
+                frame.render_widget(&self.listen_table_widget, area);
+            }
+        }
         
         let mut status_text = Vec::new();
         
         let filter_str = if self.current_filter.is_empty() {
             "No filters active".to_string()
         } else {
-            format!("Filter: {}", self.current_filter.to_string())
+            format!("Filter: {}", self.current_filter)
         };
         
         status_text.push(Span::styled(filter_str, Style::default().fg(Color::Yellow)));
         
+        if let Some((message, time, more)) = &self.last_error {
+            if (Local::now() - *time).to_std().is_ok_and(|age| age < ERROR_DURATION) {
+                let mut text = format!("{} {} {}", glyphs::glyph("✗", "x"), time.format("%H:%M:%S"), message);
+                if *more > 0 {
+                    text.push_str(&format!(" (+{} more)", more));
+                }
+                status_text.push(Span::raw(" | "));
+                status_text.push(Span::styled(text, Style::default().fg(Color::LightRed)));
+            }
+        }
+
+        if self.pacer.is_backed_off() {
+            status_text.push(Span::raw(" | "));
+            status_text.push(Span::styled(
+                format!("Slow refresh: sampling every {}", format_interval(self.tick_rate)),
+                Style::default().fg(Color::Yellow),
+            ));
+        } else if self.pacer.is_bursting() {
+            status_text.push(Span::raw(" | "));
+            status_text.push(Span::styled(
+                format!("Churn: sampling every {}", format_interval(self.tick_rate)),
+                Style::default().fg(Color::Cyan),
+            ));
+        }
+
+        if let Some(source) = &self.offline_source {
+            status_text.push(Span::raw(" | "));
+            status_text.push(Span::styled(format!("Offline: {}", source), Style::default().fg(Color::Magenta)));
+        }
+
+        if let Some(aggregator) = &self.aggregator {
+            status_text.push(Span::raw(" | "));
+            status_text.push(Span::styled(
+                format!("Agents: {}", aggregator.agent_count()),
+                Style::default().fg(Color::Magenta),
+            ));
+        }
+        
+        if !self.mouse_enabled {
+            status_text.push(Span::raw(" | "));
+            status_text.push(Span::styled("Mouse off", Style::default().fg(Color::DarkGray)));
+        }
+        
         // Add spacer
         status_text.push(Span::raw(" | "));
 
@@ -201,26 +1086,71 @@ impl App {
             FocusedTable::ProcessHost => "Focus: Process-Host",
             FocusedTable::Process => "Focus: Process",
             FocusedTable::Host => "Focus: Host",
+            FocusedTable::Group => "Focus: Group",
+            FocusedTable::Listen => "Focus: Listening Ports",
+            FocusedTable::Clients => "Focus: Clients",
         };
         status_text.push(Span::styled(focused_table_str, Style::default().fg(Color::Cyan)));
         status_text.push(Span::raw(" | "));
         
         // Add key bindings
-        status_text.push(Span::styled("1-3", Style::default().fg(Color::Green)));
+        let switch_keys = match (self.show_groups, self.inbound_table.is_some()) {
+            (true, true) => "1-5",
+            (true, false) => "1-4",
+            (false, true) => "1-3,5",
+            (false, false) => "1-3",
+        };
+        status_text.push(Span::styled(switch_keys, Style::default().fg(Color::Green)));
         status_text.push(Span::raw(": Switch Table "));
 
-        status_text.push(Span::styled("↑↓", Style::default().fg(Color::Green)));
-        status_text.push(Span::raw(": Scroll "));
+        status_text.push(Span::styled(glyphs::glyph("↑↓", "Up/Down"), Style::default().fg(Color::Green)));
+        status_text.push(Span::raw(": Select "));
 
         status_text.push(Span::styled("f", Style::default().fg(Color::Green)));
         status_text.push(Span::raw(": Filter "));
         
+        status_text.push(Span::styled("/", Style::default().fg(Color::Green)));
+        status_text.push(Span::raw(": Query "));
+        
+        status_text.push(Span::styled("s/l", Style::default().fg(Color::Green)));
+        status_text.push(Span::raw(": Save/Load "));
+        
         status_text.push(Span::styled("c", Style::default().fg(Color::Green)));
         status_text.push(Span::raw(": Clear "));
         
+        status_text.push(Span::styled("u", Style::default().fg(Color::Green)));
+        status_text.push(Span::raw(": Undo "));
+        
         status_text.push(Span::styled("r", Style::default().fg(Color::Green)));
         status_text.push(Span::raw(": Reset "));
 
+        status_text.push(Span::styled("h", Style::default().fg(Color::Green)));
+        status_text.push(Span::raw(": Highlight "));
+
+        status_text.push(Span::styled("e", Style::default().fg(Color::Green)));
+        status_text.push(Span::raw(": Events "));
+
+        status_text.push(Span::styled("o", Style::default().fg(Color::Green)));
+        status_text.push(Span::raw(": Overhead "));
+
+        status_text.push(Span::styled("b", Style::default().fg(Color::Green)));
+        status_text.push(Span::raw(": Bars "));
+
+        status_text.push(Span::styled("V", Style::default().fg(Color::Green)));
+        status_text.push(Span::raw(": Split "));
+
+        status_text.push(Span::styled("p", Style::default().fg(Color::Green)));
+        status_text.push(Span::raw(": Pin "));
+
+        status_text.push(Span::styled("y/Y", Style::default().fg(Color::Green)));
+        status_text.push(Span::raw(": Copy Row/Filter "));
+
+        status_text.push(Span::styled("C", Style::default().fg(Color::Green)));
+        status_text.push(Span::raw(": Columns "));
+
+        status_text.push(Span::styled("S", Style::default().fg(Color::Green)));
+        status_text.push(Span::raw(": Snapshot "));
+
         status_text.push(Span::styled("t/a/m", Style::default().fg(Color::Green)));
         status_text.push(Span::raw(": Sort "));
         
@@ -228,11 +1158,57 @@ impl App {
         status_text.push(Span::raw(": Quit"));
         
         let status_bar = Paragraph::new(Line::from(status_text));
-        frame.render_widget(status_bar, main_chunks[3]);
+        frame.render_widget(status_bar, layout.status_bar);
+
+        if let Some((details, actions)) = self.selection_context() {
+            let mut selection_text = vec![Span::styled(glyphs::glyph("▸ ", "> "), Style::default().fg(Color::Cyan))];
+            for (key, action) in actions {
+                selection_text.push(Span::styled(key, Style::default().fg(Color::Green)));
+                selection_text.push(Span::raw(format!(": {} ", action)));
+            }
+            selection_text.push(Span::raw("| "));
+            selection_text.push(Span::styled(details, Style::default().fg(Color::White)));
+            frame.render_widget(Paragraph::new(Line::from(selection_text)), layout.selection_bar);
+        }
         
+        let toast_area = Rect { height: layout.selection_bar.y - frame.area().y, ..frame.area() };
+        frame.render_widget(&self.toasts_widget, toast_area);
+
+        if self.show_counters {
+            frame.render_widget(&self.counter_overlay_widget, frame.area());
+        }
         if self.filter_widget.is_active() {
             frame.render_widget(&self.filter_widget, frame.area());
         }
+        if self.query_prompt_widget.is_active() {
+            frame.render_widget(&self.query_prompt_widget, frame.area());
+        }
+        if self.save_prompt_widget.is_active() {
+            frame.render_widget(&self.save_prompt_widget, frame.area());
+        }
+        if self.annotation_prompt_widget.is_active() {
+            frame.render_widget(&self.annotation_prompt_widget, frame.area());
+        }
+        if self.reset_prompt_widget.is_active() {
+            frame.render_widget(&self.reset_prompt_widget, frame.area());
+        }
+        if self.filter_picker_widget.is_active() {
+            frame.render_widget(&self.filter_picker_widget, frame.area());
+        }
+        if self.column_picker_widget.is_active() {
+            frame.render_widget(&self.column_picker_widget, frame.area());
+        }
+        if self.host_detail_widget.is_active() {
+            frame.render_widget(&self.host_detail_widget, frame.area());
+        }
+        if self.heatmap_widget.is_active() {
+            frame.render_widget(&self.heatmap_widget, frame.area());
+        }
+        if self.flow_widget.is_active() {
+            frame.render_widget(&self.flow_widget, frame.area());
+        }
+
+        theme::apply(frame.buffer_mut());
     }
 
     fn handle_events(&mut self) -> io::Result<()> {
@@ -243,6 +1219,10 @@ impl App {
             Event::Mouse(mouse_event) => {
                 self.handle_mouse_event(mouse_event)
             }
+            Event::Paste(text) => {
+                self.filter_widget.handle_paste(&text);
+                self.query_prompt_widget.handle_paste(&text);
+            }
             _ => {}
         };
         Ok(())
@@ -251,22 +1231,151 @@ impl App {
     fn handle_key_event(&mut self, key_event: KeyEvent) {
         if self.filter_widget.is_active() {
             if let Some(new_filter) = self.filter_widget.handle_key_event(key_event) {
-                self.apply_filter(new_filter);
+                match self.filter_target {
+                    FilterTarget::Filter => self.apply_filter(new_filter),
+                    FilterTarget::Highlight => self.add_highlight(new_filter),
+                }
+            }
+            return;
+        }
+        
+        if self.query_prompt_widget.is_active() {
+            if let Some(input) = self.query_prompt_widget.handle_key_event(key_event) {
+                let mut filter = self.current_filter.clone();
+                filter.query = match input {
+                    QueryInput::Set(query) => Some(query),
+                    QueryInput::Cleared => None,
+                };
+                self.apply_filter(filter);
+            }
+            return;
+        }
+        
+        if self.save_prompt_widget.is_active() {
+            if let Some(name) = self.save_prompt_widget.handle_key_event(key_event) {
+                if let Err(e) = self.save_current_filter(name) {
+                    self.save_prompt_widget.show_error(e);
+                }
+            }
+            return;
+        }
+
+        if self.annotation_prompt_widget.is_active() {
+            if let Some(text) = self.annotation_prompt_widget.handle_key_event(key_event) {
+                self.annotate(AnnotationKind::Manual, text);
+            }
+            return;
+        }
+        
+        if self.reset_prompt_widget.is_active() {
+            match self.reset_prompt_widget.handle_key_event(key_event) {
+                Some(ResetScope::All) => self.reset_monitor(),
+                Some(ResetScope::Graph) => self.clear_graph(),
+                Some(ResetScope::Closed) => {
+                    if let Ok(mut monitor) = self.monitor.lock() {
+                        monitor.clear_closed();
+                    }
+                    self.show_status_message("Closed connections cleared".to_string());
+                }
+                None => {}
+            }
+            return;
+        }
+
+        if self.filter_picker_widget.is_active() {
+            if let Some(idx) = self.filter_picker_widget.handle_key_event(key_event) {
+                self.apply_saved_filter(idx);
+            }
+            return;
+        }
+
+        if self.column_picker_widget.is_active() {
+            if let Some(columns) = self.column_picker_widget.handle_key_event(key_event) {
+                self.set_focused_columns(&columns);
             }
             return;
         }
+
+        if self.host_detail_widget.is_active() {
+            self.host_detail_widget.handle_key_event(key_event);
+            return;
+        }
+
+        if self.heatmap_widget.is_active() {
+            self.heatmap_widget.handle_key_event(key_event);
+            return;
+        }
+
+        if self.flow_widget.is_active() {
+            self.flow_widget.handle_key_event(key_event);
+            return;
+        }
         
+        let pending_kill = self.pending_kill.take();
         match key_event.code {
             KeyCode::Char('q') => self.exit(),
-            KeyCode::Char('r') => self.reset_monitor(),
+            KeyCode::Char('r') => self.confirm_reset(),
+            KeyCode::Char('G') => self.clear_graph(),
             KeyCode::Char('c') => self.clear_all_filters(),
+            KeyCode::Char('z') if key_event.modifiers.contains(KeyModifiers::CONTROL) => self.undo_filter(),
+            KeyCode::Char('u') => self.undo_filter(),
             KeyCode::Char('f') => self.enter_filter_mode(),
+            KeyCode::Char('/') => self.query_prompt_widget.show(self.current_filter.query.as_ref()),
+            KeyCode::Char('s') => self.save_prompt_widget.show("Save Filter As"),
+            KeyCode::Char('A') => self.annotation_prompt_widget.show("Annotate Graph"),
+            KeyCode::Char('l') => self.filter_picker_widget.show(&self.saved_filters),
+            KeyCode::Char('C') => self.show_column_picker(),
+            KeyCode::Char('S') => self.write_snapshot(),
+            KeyCode::F(n) if (1..=QUICK_SLOTS as u8).contains(&n) => self.apply_saved_filter(n as usize - 1),
+            KeyCode::Char('h') => self.enter_highlight_mode(),
+            KeyCode::Char('H') => self.clear_highlights(),
             KeyCode::Char('t') => self.set_sort_by(SortBy::Total),
             KeyCode::Char('a') => self.set_sort_by(SortBy::Active),
             KeyCode::Char('m') => self.set_sort_by(SortBy::Max),
-            KeyCode::Char('1') => self.focused_table = FocusedTable::ProcessHost,
-            KeyCode::Char('2') => self.focused_table = FocusedTable::Host,
-            KeyCode::Char('3') => self.focused_table = FocusedTable::Process,
+            KeyCode::Char('p') => self.toggle_pin_selected(),
+            KeyCode::Enter if self.focused_table == FocusedTable::Host => self.show_host_detail(),
+            KeyCode::Enter => self.filter_selected(true),
+            KeyCode::Char('=') => self.filter_selected(false),
+            KeyCode::Char('k') => self.kill_selected(pending_kill),
+            KeyCode::Char('x') => self.exclude_selected(),
+            KeyCode::Char('T') => self.toggle_process_host_tree(),
+            KeyCode::Char(' ') if self.focused_table == FocusedTable::ProcessHost => self.process_host_table_widget.toggle_selected(None),
+            KeyCode::Right if self.focused_table == FocusedTable::ProcessHost => self.process_host_table_widget.toggle_selected(Some(true)),
+            KeyCode::Left if self.focused_table == FocusedTable::ProcessHost => self.process_host_table_widget.toggle_selected(Some(false)),
+            KeyCode::Char('e') => {
+                self.show_event_log = !self.show_event_log;
+                self.event_log_widget.scroll_to_bottom();
+            }
+            KeyCode::Char('o') => self.show_overhead = !self.show_overhead,
+            KeyCode::Char('b') => self.cycle_top_bars(),
+            KeyCode::Char('w') => self.heatmap_widget.show(),
+            KeyCode::Char('F') => self.flow_widget.show(self.current_filter.clone()),
+            KeyCode::Char('V') => self.toggle_compare(),
+            KeyCode::Char('v') => self.cycle_view(),
+            KeyCode::Char('P') => self.toggle_pinned_graph(),
+            KeyCode::Char('D') => self.show_counters = !self.show_counters,
+            KeyCode::Char('X') => self.toggle_exited_processes(),
+            KeyCode::Char('I') => self.toggle_inactive_rows(),
+            KeyCode::Char('M') => self.toggle_mouse_capture(),
+            KeyCode::Char('y') => self.copy_selected_row(),
+            KeyCode::Char('Y') => self.copy_to_clipboard(self.current_filter.shareable()),
+            KeyCode::Char('1') => self.set_focused_table(FocusedTable::ProcessHost),
+            KeyCode::Char('2') => self.set_focused_table(FocusedTable::Host),
+            KeyCode::Char('3') => self.set_focused_table(FocusedTable::Process),
+            KeyCode::Char('4') if self.show_groups => self.set_focused_table(FocusedTable::Group),
+            KeyCode::Char('5') => {
+                if let Some(table) = self.inbound_table {
+                    self.set_focused_table(table);
+                }
+            }
+            KeyCode::Char('i') => self.cycle_inbound_table(),
+            // With Shift, the navigation keys scroll the event log instead of the table
+            KeyCode::Up if self.is_event_log_key(key_event) => self.event_log_widget.scroll_up(1),
+            KeyCode::Down if self.is_event_log_key(key_event) => self.event_log_widget.scroll_down(1),
+            KeyCode::PageUp if self.is_event_log_key(key_event) => self.event_log_widget.scroll_up(10),
+            KeyCode::PageDown if self.is_event_log_key(key_event) => self.event_log_widget.scroll_down(10),
+            KeyCode::Home if self.is_event_log_key(key_event) => self.event_log_widget.scroll_to_top(),
+            KeyCode::End if self.is_event_log_key(key_event) => self.event_log_widget.scroll_to_bottom(),
             KeyCode::Up => self.scroll_focused_table_up(1),
             KeyCode::Down => self.scroll_focused_table_down(1),
             KeyCode::PageUp => self.scroll_focused_table_up(10),
@@ -277,6 +1386,21 @@ impl App {
         }
     }
 
+    fn is_event_log_key(&self, key_event: KeyEvent) -> bool {
+        self.show_event_log && key_event.modifiers.contains(KeyModifiers::SHIFT)
+    }
+
+    /// Releases the mouse so the terminal's own text selection works, or captures it again.
+    fn toggle_mouse_capture(&mut self) {
+        if self.mouse_enabled {
+            if execute!(std::io::stdout(), DisableMouseCapture).is_ok() {
+                self.mouse_enabled = false;
+            }
+        } else if execute!(std::io::stdout(), EnableMouseCapture).is_ok() {
+            self.mouse_enabled = true;
+        }
+    }
+
     fn handle_mouse_event(&mut self, mouse_event: MouseEvent) {
         if !self.mouse_enabled {
             return;
@@ -289,111 +1413,806 @@ impl App {
             MouseEventKind::ScrollDown => {
                 self.scroll_focused_table_down(3);
             }
+            MouseEventKind::Down(MouseButton::Left) => {
+                self.handle_click(mouse_event.column, mouse_event.row);
+            }
             _ => {}
         }
     }
 
+    /// Focuses the table under the click, then sorts by a clicked header or selects a clicked row.
+    fn handle_click(&mut self, x: u16, y: u16) {
+        if self.filter_widget.is_active() || self.query_prompt_widget.is_active() ||
+            self.save_prompt_widget.is_active() || self.filter_picker_widget.is_active() ||
+            self.reset_prompt_widget.is_active() || self.annotation_prompt_widget.is_active() ||
+            self.column_picker_widget.is_active() || self.host_detail_widget.is_active() ||
+            self.heatmap_widget.is_active() || self.flow_widget.is_active()
+        {
+            return;
+        }
+
+        let layout = self.screen_layout(self.screen_area);
+        let mut tables = vec![
+            (FocusedTable::ProcessHost, layout.process_host),
+            (FocusedTable::Host, layout.host),
+            (FocusedTable::Process, layout.process),
+        ];
+        if let Some(area) = layout.group {
+            tables.push((FocusedTable::Group, area));
+        }
+        if let (Some(area), Some(table)) = (layout.inbound, self.inbound_table) {
+            tables.push((table, area));
+        }
+
+        let Some((table, area)) = tables.into_iter().find(|(_, area)| area.contains(Position { x, y })) else {
+            return;
+        };
+        self.set_focused_table(table);
+        let total_rows = self.focused_table_len();
+        match table {
+            FocusedTable::ProcessHost => self.process_host_table_widget.handle_click(area, x, y, total_rows),
+            FocusedTable::Process => self.process_table_widget.handle_click(area, x, y, total_rows),
+            FocusedTable::Host => self.host_table_widget.handle_click(area, x, y, total_rows),
+            FocusedTable::Group => self.group_table_widget.handle_click(area, x, y, total_rows),
+            FocusedTable::Listen => self.listen_table_widget.handle_click(area, x, y, total_rows),
+            FocusedTable::Clients => self.client_table_widget.handle_click(area, x, y, total_rows),
+        }
+    }
+
     fn scroll_focused_table_up(&mut self, amount: usize) {
         match self.focused_table {
             FocusedTable::ProcessHost => self.process_host_table_widget.scroll_up(amount),
             FocusedTable::Process => self.process_table_widget.scroll_up(amount),
             FocusedTable::Host => self.host_table_widget.scroll_up(amount),
+            FocusedTable::Group => self.group_table_widget.scroll_up(amount),
+            FocusedTable::Listen => self.listen_table_widget.scroll_up(amount),
+            FocusedTable::Clients => self.client_table_widget.scroll_up(amount),
         }
     }
 
     fn scroll_focused_table_down(&mut self, amount: usize) {
+        let total_rows = self.focused_table_len();
+        match self.focused_table {
+            FocusedTable::ProcessHost => self.process_host_table_widget.scroll_down(amount, total_rows),
+            FocusedTable::Process => self.process_table_widget.scroll_down(amount, total_rows),
+            FocusedTable::Host => self.host_table_widget.scroll_down(amount, total_rows),
+            FocusedTable::Group => self.group_table_widget.scroll_down(amount, total_rows),
+            FocusedTable::Listen => self.listen_table_widget.scroll_down(amount, total_rows),
+            FocusedTable::Clients => self.client_table_widget.scroll_down(amount, total_rows),
+        }
+    }
+
+    fn scroll_focused_table_to_top(&mut self) {
         match self.focused_table {
+            FocusedTable::ProcessHost => self.process_host_table_widget.scroll_to_top(),
+            FocusedTable::Process => self.process_table_widget.scroll_to_top(),
+            FocusedTable::Host => self.host_table_widget.scroll_to_top(),
+            FocusedTable::Group => self.group_table_widget.scroll_to_top(),
+            FocusedTable::Listen => self.listen_table_widget.scroll_to_top(),
+            FocusedTable::Clients => self.client_table_widget.scroll_to_top(),
+        }
+    }
+
+    fn scroll_focused_table_to_bottom(&mut self) {
+        let total_rows = self.focused_table_len();
+        match self.focused_table {
+            FocusedTable::ProcessHost => self.process_host_table_widget.scroll_to_bottom(total_rows),
+            FocusedTable::Process => self.process_table_widget.scroll_to_bottom(total_rows),
+            FocusedTable::Host => self.host_table_widget.scroll_to_bottom(total_rows),
+            FocusedTable::Group => self.group_table_widget.scroll_to_bottom(total_rows),
+            FocusedTable::Listen => self.listen_table_widget.scroll_to_bottom(total_rows),
+            FocusedTable::Clients => self.client_table_widget.scroll_to_bottom(total_rows),
+        }
+    }
+
+    fn focused_table_len(&self) -> usize {
+        let monitor = match self.monitor.lock() {
+            Ok(monitor) => monitor,
+            Err(_) => return 0,
+        };
+
+        match self.focused_table {
+            FocusedTable::ProcessHost => self.process_host_table_widget.sorted_metrics(&monitor).len(),
+            FocusedTable::Process => monitor.get_process_metrics(&self.current_filter).len(),
+            FocusedTable::Host => monitor.get_host_metrics(&self.current_filter).len(),
+            FocusedTable::Group => monitor.get_view_metrics(self.group_table_widget.view(), &self.current_filter).len(),
+            FocusedTable::Listen => monitor.get_listen_port_metrics(&self.current_filter).len(),
+            FocusedTable::Clients => monitor.get_client_metrics(&self.current_filter).len(),
+        }
+    }
+
+    fn set_focused_table(&mut self, table: FocusedTable) {
+        self.focused_table = table;
+        self.process_host_table_widget.set_focused(table == FocusedTable::ProcessHost);
+        self.process_table_widget.set_focused(table == FocusedTable::Process);
+        self.host_table_widget.set_focused(table == FocusedTable::Host);
+        self.group_table_widget.set_focused(table == FocusedTable::Group);
+        self.listen_table_widget.set_focused(table == FocusedTable::Listen);
+        self.client_table_widget.set_focused(table == FocusedTable::Clients);
+    }
+
+    fn set_view(&mut self, view: String) {
+        let has_groups = self.monitor.lock().is_ok_and(|monitor| !monitor.get_groups().is_empty());
+        self.show_groups = has_groups || view != GROUP_VIEW;
+        self.group_table_widget.set_view(view);
+        if !self.show_groups && self.focused_table == FocusedTable::Group {
+            self.set_focused_table(FocusedTable::Host);
+        }
+    }
+
+    /// Switches the group table to the next aggregation view. The endpoint group view is
+    /// hidden again when no groups are configured.
+    fn cycle_view(&mut self) {
+        let Ok(monitor) = self.monitor.lock() else {
+            return;
+        };
+        let names = monitor.view_names();
+        let next = names.iter()
+            .position(|name| name == self.group_table_widget.view())
+            .map_or(0, |idx| (idx + 1) % names.len());
+        let Some(view) = names.get(next).cloned() else {
+            return;
+        };
+        let column = monitor.view_column(&view).unwrap_or_default().to_string();
+        drop(monitor);
+
+        self.set_view(view);
+        self.show_status_message(format!("Connections by {}", column));
+    }
+
+    /// Cycles the inbound table between by listening port, by client and hidden, keeping
+    /// focus on it while it is shown.
+    fn cycle_inbound_table(&mut self) {
+        let focused = self.inbound_table == Some(self.focused_table);
+        self.inbound_table = match self.inbound_table {
+            None => Some(FocusedTable::Listen),
+            Some(FocusedTable::Listen) => Some(FocusedTable::Clients),
+            Some(_) => None,
+        };
+        if focused {
+            self.set_focused_table(self.inbound_table.unwrap_or(FocusedTable::Host));
+        }
+    }
+
+    /// Splits the top of the screen, pinning the current filter to the second row while
+    /// the first keeps following filter changes, or goes back to a single view.
+    fn toggle_compare(&mut self) {
+        if self.compare_filter.take().is_some() {
+            self.active_connections_graph_widget.set_label(None);
+            self.summary_widget.set_title(None);
+            return;
+        }
+        let filter = self.current_filter.clone();
+        self.compare_graph_widget.set_label(Some(format!("B: {}", filter_label(&filter))));
+        self.compare_summary_widget.set_title(Some("B".to_string()));
+        self.compare_graph_widget.set_filter(filter.clone());
+        self.compare_summary_widget.set_filter(filter.clone());
+        self.compare_filter = Some(filter);
+        self.update_compare_labels();
+        self.show_status_message("Split view: B is pinned, filters now change A".to_string());
+    }
+
+    /// Adds a sparkline for the current filter under the graph, or removes it.
+    fn toggle_pinned_graph(&mut self) {
+        if self.current_filter.is_empty() {
+            self.show_status_message("Set a filter to pin its graph".to_string());
+            return;
+        }
+        let message = match self.pinned_graphs_widget.toggle(self.current_filter.clone()) {
+            PinOutcome::Pinned => format!("Pinned graph for {}", self.current_filter),
+            PinOutcome::Unpinned => format!("Unpinned graph for {}", self.current_filter),
+            PinOutcome::Full => format!("At most {} graphs can be pinned; unpin one first", MAX_PINNED_GRAPHS),
+        };
+        self.show_status_message(message);
+    }
+
+    /// Names the live side of the split view after the current filter.
+    fn update_compare_labels(&mut self) {
+        if self.compare_filter.is_some() {
+            self.active_connections_graph_widget.set_label(Some(format!("A: {}", filter_label(&self.current_filter))));
+            self.summary_widget.set_title(Some("A".to_string()));
+        }
+    }
+
+    /// Swaps the connections graph for bars of the top hosts, then processes, then back.
+    fn cycle_top_bars(&mut self) {
+        self.top_bars = match self.top_bars {
+            None => Some(TopBarsKind::Hosts),
+            Some(TopBarsKind::Hosts) => Some(TopBarsKind::Processes),
+            Some(TopBarsKind::Processes) => None,
+        };
+        if let Some(kind) = self.top_bars {
+            self.top_bars_widget.set_kind(kind);
+        }
+    }
+
+    /// Hides exited processes from the process tables right away, or goes back to the
+    /// configured retention.
+    fn toggle_exited_processes(&mut self) {
+        let Ok(mut monitor) = self.monitor.lock() else {
+            return;
+        };
+        let hidden = monitor.exited_retention() == Some(Duration::ZERO);
+        let retention = if hidden {
+            self.exited_retention.filter(|retention| !retention.is_zero())
+        } else {
+            Some(Duration::ZERO)
+        };
+        monitor.set_exited_retention(retention);
+        drop(monitor);
+        let message = match retention {
+            Some(retention) if retention.is_zero() => "Exited processes hidden".to_string(),
+            Some(retention) => format!("Exited processes hidden after {}", format_secs(retention.as_secs() as i64)),
+            None => "Exited processes shown".to_string(),
+        };
+        self.show_status_message(message);
+    }
+
+    /// Shows the rows hidden for inactivity, or hides them again.
+    fn toggle_inactive_rows(&mut self) {
+        let Some(configured) = self.inactive_retention else {
+            self.show_status_message("Set hide_inactive_after under [rows] to hide inactive rows".to_string());
+            return;
+        };
+        let Ok(mut monitor) = self.monitor.lock() else {
+            return;
+        };
+        let retention = if monitor.inactive_retention().is_some() { None } else { Some(configured) };
+        monitor.set_inactive_retention(retention);
+        drop(monitor);
+        let message = match retention {
+            Some(retention) => format!("Rows inactive for {} hidden", format_secs(retention.as_secs() as i64)),
+            None => "Inactive rows shown".to_string(),
+        };
+        self.show_status_message(message);
+    }
+
+    fn toggle_pin_selected(&mut self) {
+        let rule = match self.focused_table {
             FocusedTable::ProcessHost => {
-                if let Ok(monitor) = self.monitor.lock() {
-                    let metrics = monitor.get_process_host_metrics(&self.current_filter);
-                    let total_rows = metrics.len();
-                    let visible_rows = 15; // Approximate
-                    self.process_host_table_widget.scroll_down(amount, total_rows, visible_rows);
+                self.process_host_table_widget.selected_metrics().map(|metrics| {
+                    let name = self.monitor.lock().ok()
+                        .and_then(|monitor| monitor.get_process(metrics.pid).and_then(|p| p.name.clone()))
+                        .unwrap_or_else(|| "Unknown".to_string());
+                    PinRule::for_process_host(name, metrics.host, metrics.port)
+                })
+            }
+            FocusedTable::Process => {
+                self.process_table_widget.selected_metrics()
+                    .map(|metrics| PinRule::for_process(metrics.name))
+            }
+            FocusedTable::Host => {
+                self.host_table_widget.selected_metrics()
+                    .map(|metrics| PinRule::for_host(metrics.host, metrics.port))
+            }
+            FocusedTable::Group | FocusedTable::Listen | FocusedTable::Clients => None,
+        };
+
+        if let Some(rule) = rule {
+            self.watchlist.toggle(rule);
+            self.apply_watchlist();
+        }
+    }
+
+    /// Groups the Process-Host table by process, or lists its pairs again.
+    fn toggle_process_host_tree(&mut self) {
+        let tree = !self.process_host_table_widget.is_tree();
+        self.process_host_table_widget.set_tree(tree);
+        self.show_status_message(if tree {
+            format!("Process-Host tree: Space expands a process, {} collapses", glyphs::glyph("←", "Left"))
+        } else {
+            "Process-Host table: one row per pair".to_string()
+        });
+    }
+
+    /// Full details of the focused table's selected row, which its columns may cut short,
+    /// and the keys that act on it.
+    fn selection_context(&self) -> Option<(String, Vec<(&'static str, &'static str)>)> {
+        let mut actions = Vec::new();
+        let details = match self.focused_table {
+            FocusedTable::ProcessHost => {
+                let row = self.process_host_table_widget.selected_row()?;
+                let metrics = row.metrics;
+                if self.can_kill() && metrics.is_alive {
+                    actions.push(("k", "Kill"));
+                }
+                if let TreeNode::Process { hosts, expanded } = row.node {
+                    actions.push(("Space", if expanded { "Collapse" } else { "Expand" }));
+                    actions.push(("Enter", "Filter PID"));
+                    actions.push(("y", "Copy"));
+                    actions.push(("x", "Exclude"));
+                    return Some((format!("PID {} {} {} {} hosts", metrics.pid, self.process_label(metrics.pid, &metrics.process_name),
+                        glyphs::glyph("→", "->"), hosts), actions));
                 }
+                actions.push(("p", "Pin"));
+                actions.push(("Enter", "Filter Row"));
+                actions.push(("=", "Filter PID+Host"));
+                format!("PID {} {} {} {}:{}", metrics.pid, self.process_label(metrics.pid, &metrics.process_name),
+                    glyphs::glyph("→", "->"), metrics.host, metrics.port)
             }
             FocusedTable::Process => {
-                if let Ok(monitor) = self.monitor.lock() {
-                    let metrics = monitor.get_process_metrics(&self.current_filter);
-                    let total_rows = metrics.len();
-                    let visible_rows = 15; // Approximate
-                    self.process_table_widget.scroll_down(amount, total_rows, visible_rows);
+                let metrics = self.process_table_widget.selected_metrics()?;
+                if self.can_kill() && metrics.is_alive {
+                    actions.push(("k", "Kill"));
                 }
+                actions.push(("p", "Pin"));
+                actions.push(("Enter", "Filter PID"));
+                actions.push(("=", "Filter Process"));
+                format!("PID {} {}", metrics.pid, self.process_label(metrics.pid, &metrics.name))
             }
             FocusedTable::Host => {
-                if let Ok(monitor) = self.monitor.lock() {
-                    let metrics = monitor.get_host_metrics(&self.current_filter);
-                    let total_rows = metrics.len();
-                    let visible_rows = 15; // Approximate
-                    self.host_table_widget.scroll_down(amount, total_rows, visible_rows);
+                let metrics = self.host_table_widget.selected_metrics()?;
+                actions.push(("Enter", "Details"));
+                actions.push(("p", "Pin"));
+                actions.push(("=", "Filter Host"));
+                let mut details = format!("{}:{} ({})", metrics.host, metrics.port, metrics.scope);
+                if let Some(list) = &metrics.blocklist {
+                    details.push_str(&format!(", on blocklist {}", list));
+                }
+                details
+            }
+            FocusedTable::Group => {
+                let metrics = self.group_table_widget.selected_metrics()?;
+                let view = self.group_table_widget.view();
+                let column = self.monitor.lock().ok()
+                    .and_then(|monitor| monitor.view_column(view).map(str::to_string))
+                    .unwrap_or_else(|| view.to_string());
+                format!("{}: {}", column, metrics.group)
+            }
+            FocusedTable::Listen => {
+                let metrics = self.listen_table_widget.selected_metrics()?;
+                format!("Port {}: {}", metrics.port, metrics.processes.join(", "))
+            }
+            FocusedTable::Clients => {
+                let metrics = self.client_table_widget.selected_metrics()?;
+                let ports: Vec<String> = metrics.ports.iter().map(u16::to_string).collect();
+                match &metrics.hostname {
+                    Some(hostname) => format!("{} ({}) {} ports {}", hostname, metrics.addr, glyphs::glyph("→", "->"), ports.join(", ")),
+                    None => format!("{} {} ports {}", metrics.addr, glyphs::glyph("→", "->"), ports.join(", ")),
                 }
             }
+        };
+        let term = self.selected_term();
+        if term.is_some() && matches!(self.focused_table, FocusedTable::Group | FocusedTable::Listen | FocusedTable::Clients) {
+            actions.push(("Enter", "Filter"));
+        }
+        actions.push(("y", "Copy"));
+        if term.is_some() {
+            actions.push(("x", "Exclude"));
         }
+        Some((details, actions))
     }
 
-    fn scroll_focused_table_to_top(&mut self) {
-        match self.focused_table {
-            FocusedTable::ProcessHost => self.process_host_table_widget.scroll_to_top(),
-            FocusedTable::Process => self.process_table_widget.scroll_to_top(),
-            FocusedTable::Host => self.host_table_widget.scroll_to_top(),
+    /// A process's name followed by its executable path, where that is known.
+    fn process_label(&self, pid: u32, name: &str) -> String {
+        let exe = self.monitor.lock().ok()
+            .and_then(|monitor| monitor.get_process(pid).and_then(|process| process.exe.clone()));
+        match exe {
+            Some(exe) => format!("{} ({})", name, exe),
+            None => name.to_string(),
         }
     }
 
-    fn scroll_focused_table_to_bottom(&mut self) {
+    /// Whether the processes shown run on this machine, so they can be signalled.
+    fn can_kill(&self) -> bool {
+        self.offline_source.is_none() && self.aggregator.is_none()
+    }
+
+    /// Sends SIGTERM to the selected row's process once `k` is pressed twice in a row.
+    fn kill_selected(&mut self, pending: Option<u32>) {
+        let selected = match self.focused_table {
+            FocusedTable::ProcessHost => self.process_host_table_widget.selected_row()
+                .filter(|row| row.metrics.is_alive)
+                .map(|row| (row.metrics.pid, row.metrics.process_name)),
+            FocusedTable::Process => self.process_table_widget.selected_metrics()
+                .filter(|metrics| metrics.is_alive)
+                .map(|metrics| (metrics.pid, metrics.name)),
+            _ => None,
+        };
+        let Some((pid, name)) = selected.filter(|_| self.can_kill()) else {
+            self.show_status_message("No running process selected".to_string());
+            return;
+        };
+
+        if pending != Some(pid) {
+            self.pending_kill = Some(pid);
+            self.show_status_message(format!("Press k again to kill PID {} ({})", pid, name));
+            return;
+        }
+        let started_at = self.monitor.lock().ok()
+            .and_then(|monitor| monitor.get_process(pid).and_then(|process| process.started_at));
+        match process::terminate(pid, &name, started_at) {
+            Ok(()) => self.show_status_message(format!("Sent SIGTERM to PID {} ({})", pid, name)),
+            Err(e) => self.show_toast(ToastKind::Error, format!("Kill failed: {}", e)),
+        }
+    }
+
+    /// A query term matching everything the selected row stands for.
+    fn selected_term(&self) -> Option<FilterExpr> {
+        let (field, value) = match self.focused_table {
+            FocusedTable::ProcessHost => {
+                let row = self.process_host_table_widget.selected_row()?;
+                match row.node {
+                    TreeNode::Process { .. } => (QueryField::Process, row.metrics.process_name),
+                    _ => (QueryField::Host, row.metrics.host),
+                }
+            }
+            FocusedTable::Process => (QueryField::Process, self.process_table_widget.selected_metrics()?.name),
+            FocusedTable::Host => (QueryField::Host, self.host_table_widget.selected_metrics()?.host),
+            FocusedTable::Group => {
+                let field = match self.group_table_widget.view() {
+                    GROUP_VIEW => QueryField::Group,
+                    "scope" => QueryField::Scope,
+                    "tunnel" => QueryField::Tunnel,
+                    _ => return None,
+                };
+                let key = self.group_table_widget.selected_metrics()?.group;
+                // Direct connections have no tunnel name; `tunnel=no` matches them
+                if field == QueryField::Tunnel && key == "direct" {
+                    (field, "no".to_string())
+                } else {
+                    (field, key)
+                }
+            }
+            FocusedTable::Clients => (QueryField::Net, self.client_table_widget.selected_metrics()?.addr.to_string()),
+            FocusedTable::Listen => (QueryField::LocalPort, self.listen_table_widget.selected_metrics()?.port.to_string()),
+        };
+        Some(FilterExpr::Compare { field, op: QueryOp::Eq, value })
+    }
+
+    /// Ands `not <row>` onto the query, undoable like any filter change.
+    fn exclude_selected(&mut self) {
+        let Some(term) = self.selected_term() else {
+            self.show_status_message("Nothing to exclude for this row".to_string());
+            return;
+        };
+        let message = format!("Excluded {} (u to undo)", term);
+        let mut filter = self.current_filter.clone();
+        filter.and_query(FilterExpr::Not(Box::new(term)));
+        self.apply_filter(filter);
+        self.show_status_message(message);
+    }
+
+    /// The current filter narrowed to the selected row: to all of its key when `whole`,
+    /// else to the one attribute that names it, i.e. the host, the process name, or the
+    /// PID and host of a process-host row.
+    fn selected_row_filter(&self, whole: bool) -> Option<ConnectionFilter> {
+        // Exact terms, so filtering on `10.0.0.1` or `node` leaves out `10.0.0.12` and `node-exporter`
+        let term = |field, value: String| FilterExpr::Compare { field, op: QueryOp::Eq, value };
+        let mut terms = Vec::new();
         match self.focused_table {
             FocusedTable::ProcessHost => {
-                if let Ok(monitor) = self.monitor.lock() {
-                    let metrics = monitor.get_process_host_metrics(&self.current_filter);
-                    let total_rows = metrics.len();
-                    let visible_rows = 15; // Approximate
-                    self.process_host_table_widget.scroll_to_bottom(total_rows, visible_rows);
+                let row = self.process_host_table_widget.selected_row()?;
+                let metrics = row.metrics;
+                terms.push(term(QueryField::Pid, metrics.pid.to_string()));
+                if !matches!(row.node, TreeNode::Process { .. }) {
+                    terms.push(term(QueryField::Host, metrics.host));
+                    if whole {
+                        terms.push(term(QueryField::Port, metrics.port.to_string()));
+                    }
                 }
             }
             FocusedTable::Process => {
-                if let Ok(monitor) = self.monitor.lock() {
-                    let metrics = monitor.get_process_metrics(&self.current_filter);
-                    let total_rows = metrics.len();
-                    let visible_rows = 15; // Approximate
-                    self.process_table_widget.scroll_to_bottom(total_rows, visible_rows);
+                let metrics = self.process_table_widget.selected_metrics()?;
+                if whole {
+                    terms.push(term(QueryField::Pid, metrics.pid.to_string()));
+                } else {
+                    terms.push(term(QueryField::Process, metrics.name));
                 }
             }
             FocusedTable::Host => {
-                if let Ok(monitor) = self.monitor.lock() {
-                    let metrics = monitor.get_host_metrics(&self.current_filter);
-                    let total_rows = metrics.len();
-                    let visible_rows = 15; // Approximate
-                    self.host_table_widget.scroll_to_bottom(total_rows, visible_rows);
+                let metrics = self.host_table_widget.selected_metrics()?;
+                terms.push(term(QueryField::Host, metrics.host));
+                if whole {
+                    terms.push(term(QueryField::Port, metrics.port.to_string()));
                 }
             }
+            FocusedTable::Group | FocusedTable::Listen | FocusedTable::Clients => terms.push(self.selected_term()?),
+        }
+
+        let mut filter = self.current_filter.clone();
+        for term in terms {
+            filter.and_query(term);
         }
+        Some(filter)
+    }
+
+    /// Filters to the selected row, as a whole or by one attribute, without the filter popup.
+    fn filter_selected(&mut self, whole: bool) {
+        match self.selected_row_filter(whole) {
+            Some(filter) => {
+                self.apply_filter(filter);
+                self.show_status_message(format!("Filter: {} (u to undo)", self.current_filter));
+            }
+            None => self.show_status_message("Nothing to filter on for this row".to_string()),
+        }
+    }
+
+    /// Opens the detail view for the selected host row.
+    fn show_host_detail(&mut self) {
+        if let Some(metrics) = self.host_table_widget.selected_metrics() {
+            self.host_detail_widget.show(metrics.host, metrics.port, self.current_filter.clone());
+        }
+    }
+
+    /// Opens the column picker for the focused table.
+    fn show_column_picker(&mut self) {
+        let (title, available, shown) = match self.focused_table {
+            FocusedTable::ProcessHost => ("Process-Host", &process_host_table::AVAILABLE_COLUMNS[..], self.process_host_table_widget.columns()),
+            FocusedTable::Process => ("Process", &process_table::AVAILABLE_COLUMNS[..], self.process_table_widget.columns()),
+            FocusedTable::Host => ("Host", &host_table::AVAILABLE_COLUMNS[..], self.host_table_widget.columns()),
+            FocusedTable::Group => ("Group", &group_table::AVAILABLE_COLUMNS[..], self.group_table_widget.columns()),
+            FocusedTable::Listen => ("Listening Port", &listen_table::AVAILABLE_COLUMNS[..], self.listen_table_widget.columns()),
+            FocusedTable::Clients => ("Client", &client_table::AVAILABLE_COLUMNS[..], self.client_table_widget.columns()),
+        };
+        self.column_picker_widget.show(title, available, shown);
+    }
+
+    fn set_focused_columns(&mut self, columns: &[Column]) {
+        match self.focused_table {
+            FocusedTable::ProcessHost => self.process_host_table_widget.set_columns(columns),
+            FocusedTable::Process => self.process_table_widget.set_columns(columns),
+            FocusedTable::Host => self.host_table_widget.set_columns(columns),
+            FocusedTable::Group => self.group_table_widget.set_columns(columns),
+            FocusedTable::Listen => self.listen_table_widget.set_columns(columns),
+            FocusedTable::Clients => self.client_table_widget.set_columns(columns),
+        }
+    }
+
+    /// Copies the visible columns of the focused table's selected row, tab-separated.
+    fn copy_selected_row(&mut self) {
+        let cells = match self.focused_table {
+            FocusedTable::ProcessHost => self.process_host_table_widget.selected_cells(),
+            FocusedTable::Process => self.process_table_widget.selected_cells(),
+            FocusedTable::Host => self.host_table_widget.selected_cells(),
+            FocusedTable::Group => self.group_table_widget.selected_cells(),
+            FocusedTable::Listen => self.listen_table_widget.selected_cells(),
+            FocusedTable::Clients => self.client_table_widget.selected_cells(),
+        };
+        let row = cells.map(|cells| cells.join("\t"));
+
+        match row {
+            Some(row) => self.copy_to_clipboard(row),
+            None => self.show_status_message("No row selected".to_string()),
+        }
+    }
+
+    /// Dumps metrics and active connections to a timestamped JSON file in the working directory.
+    fn write_snapshot(&mut self) {
+        let snapshot = match self.monitor.lock() {
+            Ok(monitor) => FullSnapshot::capture(&monitor, &self.current_filter, self.started),
+            Err(_) => return,
+        };
+        match std::env::current_dir().and_then(|dir| snapshot.write_to(&dir)) {
+            Ok(path) => self.show_status_message(format!("Snapshot written to {}", path.display())),
+            Err(e) => self.show_toast(ToastKind::Error, format!("Snapshot failed: {}", e)),
+        }
+    }
+
+    fn copy_to_clipboard(&mut self, text: String) {
+        // Kept open for the whole session: on X11 the copied text is only served while it exists
+        if self.clipboard.is_none() {
+            match arboard::Clipboard::new() {
+                Ok(clipboard) => self.clipboard = Some(clipboard),
+                Err(e) => {
+                    self.show_toast(ToastKind::Error, format!("Clipboard unavailable: {}", e));
+                    return;
+                }
+            }
+        }
+
+        let Some(clipboard) = self.clipboard.as_mut() else {
+            return;
+        };
+        match clipboard.set_text(text.clone()) {
+            Ok(()) => self.show_status_message(format!("Copied: {}", text.replace('\t', " "))),
+            Err(e) => self.show_toast(ToastKind::Error, format!("Copy failed: {}", e)),
+        }
+    }
+
+    fn show_status_message(&mut self, message: String) {
+        self.show_toast(ToastKind::Info, message);
+    }
+
+    fn show_toast(&mut self, kind: ToastKind, message: String) {
+        self.toasts_widget.push(kind, message);
+        self.needs_redraw = true;
+        self.schedule_redraw(TOAST_DURATION);
+    }
+
+    /// Redraws once `after` has passed, for status bar content that expires.
+    fn schedule_redraw(&mut self, after: Duration) {
+        let at = Instant::now() + after;
+        self.redraw_at = Some(self.redraw_at.map_or(at, |scheduled| scheduled.min(at)));
+    }
+
+    /// Shows the last of `errors` in the status bar error area, counting repeats once, and
+    /// as a toast when it differs from the one shown.
+    fn record_errors(&mut self, errors: Vec<Error>) {
+        let mut messages: Vec<String> = Vec::new();
+        for message in errors.iter().map(ToString::to_string) {
+            if !messages.contains(&message) {
+                messages.push(message);
+            }
+        }
+        if let Some(message) = messages.pop() {
+            let now = Local::now();
+            if self.last_error.as_ref().is_none_or(|(shown, _, _)| *shown != message) {
+                self.toasts_widget.push(ToastKind::Error, message.clone());
+                self.schedule_redraw(TOAST_DURATION);
+            }
+            // A lasting error recurs every tick; only redraw when the text shown changes
+            let shown = self.last_error.as_ref().map(|(message, time, more)| (message, time.format("%H:%M:%S").to_string(), *more));
+            if shown != Some((&message, now.format("%H:%M:%S").to_string(), messages.len())) {
+                self.needs_redraw = true;
+            }
+            self.last_error = Some((message, now, messages.len()));
+            self.schedule_redraw(ERROR_DURATION);
+        }
+    }
+
+    fn apply_watchlist(&mut self) {
+        self.host_table_widget.set_watchlist(self.watchlist.clone());
+        self.process_host_table_widget.set_watchlist(self.watchlist.clone());
+        self.process_table_widget.set_watchlist(self.watchlist.clone());
     }
     
     fn clear_all_filters(&mut self) {
-        let filter = ConnectionFilter::default();
-        self.current_filter = filter.clone();
-        self.apply_filter(filter);
+        self.apply_filter(ConnectionFilter::default().with_matching(self.match_defaults));
     }
     
     fn enter_filter_mode(&mut self) {
-        self.filter_widget.show(&self.current_filter);
+        self.filter_target = FilterTarget::Filter;
+        self.load_filter_completions();
+        self.filter_widget.show(&self.current_filter, "Filter Connections");
+    }
+    
+    /// Offers the process names and hosts the monitor currently knows as filter completions.
+    fn load_filter_completions(&mut self) {
+        if let Ok(monitor) = self.monitor.lock() {
+            let processes = monitor.get_processes().iter()
+                .filter_map(|process| process.name.clone())
+                .collect();
+            let hosts = monitor.get_host_metrics(&ConnectionFilter::default()).into_iter()
+                .map(|metrics| metrics.host)
+                .collect();
+            self.filter_widget.set_completions(processes, hosts);
+        }
+    }
+    
+    fn save_current_filter(&mut self, name: String) -> Result<(), String> {
+        if self.current_filter.is_empty() {
+            return Err("No filter is active".to_string());
+        }
+        if self.saved_filters.iter().any(|saved| saved.name == name) {
+            return Err(format!("A filter named '{}' already exists", name));
+        }
+        
+        let saved = SavedFilter { name, filter: self.current_filter.clone() };
+        if let Some(path) = &self.config_path {
+            Config::append_saved_filter(path, &saved)
+                .map_err(|e| format!("Failed to save to '{}': {}", path.display(), e))?;
+        }
+        self.saved_filters.push(saved);
+        Ok(())
     }
     
+    fn apply_saved_filter(&mut self, idx: usize) {
+        if let Some(saved) = self.saved_filters.get(idx) {
+            self.apply_filter(saved.filter.clone());
+        }
+    }
+    
+    fn enter_highlight_mode(&mut self) {
+        self.filter_target = FilterTarget::Highlight;
+        self.load_filter_completions();
+        self.filter_widget.show(&ConnectionFilter::default().with_matching(self.match_defaults), "Highlight Rows");
+    }
+    
+    fn add_highlight(&mut self, filter: ConnectionFilter) {
+        if filter.is_empty() {
+            return;
+        }
+        
+        let color = HIGHLIGHT_COLORS[self.runtime_highlights.len() % HIGHLIGHT_COLORS.len()];
+        self.runtime_highlights.push(Highlight { filter, color });
+        self.apply_highlights();
+    }
+    
+    fn clear_highlights(&mut self) {
+        self.runtime_highlights.clear();
+        self.apply_highlights();
+    }
+    
+    fn apply_highlights(&mut self) {
+        // Runtime rules come first so they win over config rules for the same row
+        let highlights: Vec<Highlight> = self.runtime_highlights.iter()
+            .chain(self.config_highlights.iter())
+            .cloned()
+            .collect();
+        
+        self.host_table_widget.set_highlights(highlights.clone());
+        self.process_host_table_widget.set_highlights(highlights.clone());
+        self.process_table_widget.set_highlights(highlights.clone());
+        self.group_table_widget.set_highlights(highlights.clone());
+        self.listen_table_widget.set_highlights(highlights.clone());
+        self.client_table_widget.set_highlights(highlights);
+    }
+    
+    /// Applies a new filter, remembering the current one for undo.
     fn apply_filter(&mut self, filter: ConnectionFilter) {
+        if filter == self.current_filter {
+            return;
+        }
+        
+        self.filter_history.push(self.current_filter.clone());
+        if self.filter_history.len() > MAX_FILTER_HISTORY {
+            self.filter_history.remove(0);
+        }
+        self.set_active_filter(filter);
+        self.annotate_filter();
+    }
+    
+    fn undo_filter(&mut self) {
+        if let Some(filter) = self.filter_history.pop() {
+            self.set_active_filter(filter);
+            self.annotate_filter();
+        }
+    }
+
+    fn annotate(&self, kind: AnnotationKind, text: String) {
+        if let Ok(mut monitor) = self.monitor.lock() {
+            monitor.annotate(kind, text);
+        }
+    }
+
+    fn annotate_filter(&self) {
+        let text = if self.current_filter.is_empty() {
+            "Filter cleared".to_string()
+        } else {
+            format!("Filter: {}", self.current_filter)
+        };
+        self.annotate(AnnotationKind::Filter, text);
+    }
+    
+    fn set_active_filter(&mut self, filter: ConnectionFilter) {
         self.current_filter = filter.clone();
+        if let Some(api_filter) = &self.api_filter {
+            *api_filter.lock().unwrap() = filter.clone();
+        }
         
         self.host_table_widget.set_filter(filter.clone());
         self.process_host_table_widget.set_filter(filter.clone());
         self.process_table_widget.set_filter(filter.clone());
+        self.group_table_widget.set_filter(filter.clone());
+        self.listen_table_widget.set_filter(filter.clone());
+        self.client_table_widget.set_filter(filter.clone());
+        self.event_log_widget.set_filter(filter.clone());
         self.summary_widget.set_filter(filter.clone());
+        self.top_bars_widget.set_filter(filter.clone());
         self.active_connections_graph_widget.set_filter(filter);
+        self.update_compare_labels();
     }
 
     fn set_sort_by(&mut self, sort_by: SortBy) {
         self.host_table_widget.set_sort_by(sort_by);
         self.process_host_table_widget.set_sort_by(sort_by);
         self.process_table_widget.set_sort_by(sort_by);
+        self.group_table_widget.set_sort_by(sort_by);
+        self.listen_table_widget.set_sort_by(sort_by);
+        self.client_table_widget.set_sort_by(sort_by);
     }
 
     fn exit(&mut self) {
         self.exit = true
     }
-}
\ No newline at end of file
+}
+
+/// A filter as shown in split view titles.
+fn filter_label(filter: &ConnectionFilter) -> String {
+    if filter.is_empty() {
+        "all connections".to_string()
+    } else {
+        filter.to_string()
+    }
+}
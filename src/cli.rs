@@ -1,68 +1,891 @@
-use clap::{Arg, Command};
-use crate::core::filters::ConnectionFilter;
+use std::net::SocketAddr;
+use std::path::PathBuf;
 
-pub fn parse_args() -> ConnectionFilter {
-    let matches = Command::new("tcpcount")
+use std::time::Duration;
+
+use clap::{Arg, ArgAction, Command};
+use clap_complete::ArgValueCompleter;
+use crate::completion;
+use crate::app::{FocusedTable, SortBy};
+use crate::core::dns::{DNS_PORT, DOT_PORT};
+use crate::core::filters::{ConnectionFilter, FilterExpr, QueryField, QueryOp, split_list};
+use crate::output::flows::FlowFormat;
+use crate::output::log_file::{LogFileOptions, LogFormat};
+use crate::output::syslog::{SyslogTarget, facility_code};
+use crate::remote::AgentOptions;
+use crate::selftest::SelftestOptions;
+use crate::widgets::theme::Theme;
+
+pub struct CliArgs {
+    pub filter: ConnectionFilter,
+    pub config_path: Option<PathBuf>,
+    /// Config profile laid over the rest of the config file.
+    pub config_profile: Option<String>,
+    pub aliases: Option<PathBuf>,
+    pub log_file: Option<LogFileOptions>,
+    /// File to append a flow record to for each closed connection, and its format.
+    pub flow_log: Option<(PathBuf, FlowFormat)>,
+    pub syslog: Option<(SyslogTarget, u8)>,
+    pub api_listen: Option<SocketAddr>,
+    pub ascii: bool,
+    pub summary_on_exit: bool,
+    /// Process names whose new PIDs are followed as restarts.
+    pub follow: Vec<String>,
+    /// Whether a restarted process keeps the totals and max of its earlier PIDs.
+    pub carry_restarts: bool,
+    /// Whether sampling speeds up while filtered connections churn.
+    pub adaptive_sampling: bool,
+    /// Whether NAT'd connections are looked up in conntrack for their real destination.
+    pub conntrack: bool,
+    /// Whether connections to a proxy from a process's environment are marked.
+    pub detect_proxies: bool,
+    /// Whether connections are attributed to the threads holding their sockets.
+    pub threads: bool,
+    /// Whether retransmission counters are read from each socket's `tcp_info`.
+    pub tcp_info: bool,
+    /// Whether UDP sockets connected to name servers are sampled, for DNS mode.
+    pub dns: bool,
+    /// Rhai script with hooks for groups, the Script column and alerts.
+    pub script: Option<PathBuf>,
+    pub duration: Option<Duration>,
+    /// Pushgateway receiving the final metrics when the run ends.
+    pub push_gateway: Option<String>,
+    pub profile: Option<PathBuf>,
+    pub sort_by: Option<SortBy>,
+    pub focus: Option<FocusedTable>,
+    /// Aggregation view the group table shows.
+    pub view: Option<String>,
+    pub theme: Theme,
+    pub max_points: Option<usize>,
+    pub mouse: bool,
+    pub command: Option<CliCommand>,
+}
+
+pub enum CliCommand {
+    Daemon(DaemonArgs),
+    Agent(AgentOptions),
+    Aggregator { listen: String },
+    Diff { a: PathBuf, b: PathBuf },
+    Analyze { path: PathBuf },
+    Completions { shell: String },
+    Selftest { options: SelftestOptions, demo: bool },
+    /// Register the daemon as a Windows service with these daemon arguments, or remove it.
+    Service { install: Option<Vec<String>> },
+}
+
+/// Daemon settings given on the command line; unset values fall back to the config file.
+pub struct DaemonArgs {
+    pub interval: Option<Duration>,
+    pub flush_interval: Option<Duration>,
+    pub sinks: Vec<String>,
+    pub systemd_notify: bool,
+    /// Started by the Windows service control manager.
+    pub windows_service: bool,
+    /// Pushgateway receiving the final metrics when the daemon stops.
+    pub push_gateway: Option<String>,
+}
+
+/// The command-line definition, also used by shell completion.
+pub fn build_command() -> Command {
+    Command::new("tcpcount")
         .version("0.1.0")
         .author("Hunter Young")
         .about("Monitor and count TCP connections")
+        .arg(
+            Arg::new("query")
+                .help("Filter query, e.g. 'proc~chrome and (port=443 or port=80) and not host~googleapis'")
+                .value_name("QUERY")
+                .num_args(1)
+        )
+        .arg(
+            Arg::new("filter")
+                .long("filter")
+                .help("Start from a filter copied with Y, e.g. 'pid=42&host=*.s3.amazonaws.com&port=443'; other filter options replace its fields and a query narrows it")
+                .value_name("FILTER")
+                .num_args(1)
+                .global(true)
+        )
         .arg(
             Arg::new("pid")
                 .short('p')
                 .long("pid")
-                .help("Filter by process ID")
+                .help("Filter by process ID (comma-separated for any of several)")
                 .value_name("PID")
                 .num_args(1)
+                .global(true)
         )
         .arg(
             Arg::new("process")
                 .short('n')
                 .long("process-name")
-                .help("Filter by process name (case-sensitive substring match)")
+                .help("Filter by process name (substring match, comma-separated for any of several)")
+                .value_name("NAME")
+                .num_args(1)
+                .add(ArgValueCompleter::new(completion::process_names))
+                .global(true)
+        )
+        .arg(
+            Arg::new("follow")
+                .long("follow")
+                .help("Follow processes by exact name across restarts: show only them, not names containing them, and mark each new PID on the graph (comma-separated for several)")
                 .value_name("NAME")
                 .num_args(1)
+                .add(ArgValueCompleter::new(completion::process_names))
+                .global(true)
+        )
+        .arg(
+            Arg::new("carry-restarts")
+                .long("carry-restarts")
+                .help("Show a process restarted under a new PID running the same executable as one row, carrying its totals and max across restarts")
+                .action(ArgAction::SetTrue)
+                .global(true)
+        )
+        .arg(
+            Arg::new("adaptive-sampling")
+                .long("adaptive-sampling")
+                .help("Sample every 50ms for a while when connections matching the filter open and close quickly, to count short-lived connections (see [sampling] in the config)")
+                .action(ArgAction::SetTrue)
+                .global(true)
+        )
+        .arg(
+            Arg::new("conntrack")
+                .long("conntrack")
+                .help("On Linux, show the real destination of connections that destination NAT sent elsewhere, read from conntrack (needs root)")
+                .action(ArgAction::SetTrue)
+                .global(true)
+        )
+        .arg(
+            Arg::new("detect-proxies")
+                .long("detect-proxies")
+                .help("Mark connections to the proxy set in a process's HTTP_PROXY, HTTPS_PROXY or ALL_PROXY, whose real destinations are hidden behind it")
+                .action(ArgAction::SetTrue)
+                .global(true)
+        )
+        .arg(
+            Arg::new("threads")
+                .long("threads")
+                .help("On Linux, find which threads of a process hold each socket and show them in the host detail view")
+                .action(ArgAction::SetTrue)
+                .global(true)
+        )
+        .arg(
+            Arg::new("tcp-info")
+                .long("tcp-info")
+                .help("On Linux, read each connection's retransmission counters from the kernel's tcp_info and show retransmission rates per host")
+                .action(ArgAction::SetTrue)
+                .global(true)
+        )
+        .arg(
+            Arg::new("dns")
+                .long("dns")
+                .help("DNS mode: also sample UDP sockets connected to a name server on Linux and show only traffic to ports 53 and 853, counting each process's lookups per resolver")
+                .action(ArgAction::SetTrue)
+                .global(true)
+        )
+        .arg(
+            Arg::new("script")
+                .long("script")
+                .help("Run group, host_column, process_column and alert hooks from a Rhai script (needs the scripting feature)")
+                .value_name("PATH")
+                .num_args(1)
+                .global(true)
         )
         .arg(
             Arg::new("host")
                 .short('H')
                 .long("host")
-                .help("Filter by remote host (case-sensitive substring match)")
+                .help("Filter by remote host (substring match, or the whole name with * wildcards like '*.example.com'; comma-separated for any of several)")
                 .value_name("HOST")
                 .num_args(1)
+                .global(true)
         )
         .arg(
             Arg::new("port")
                 .short('P')
                 .long("port")
-                .help("Filter by remote port")
+                .help("Filter by remote port (comma-separated for any of several)")
                 .value_name("PORT")
                 .num_args(1)
+                .add(ArgValueCompleter::new(completion::ports))
+                .global(true)
+        )
+        .arg(
+            Arg::new("group")
+                .short('g')
+                .long("group")
+                .help("Filter by endpoint group defined in the config file (comma-separated for any of several)")
+                .value_name("GROUP")
+                .num_args(1)
+                .global(true)
+        )
+        .arg(
+            Arg::new("ignore-case")
+                .short('i')
+                .long("ignore-case")
+                .help("Match process names and hosts regardless of case")
+                .action(ArgAction::SetTrue)
+                .global(true)
+        )
+        .arg(
+            Arg::new("fuzzy")
+                .long("fuzzy")
+                .help("Match process names and hosts as subsequences (e.g. 'chrm' matches 'chrome')")
+                .action(ArgAction::SetTrue)
+                .global(true)
+        )
+        .arg(
+            Arg::new("ascii")
+                .long("ascii")
+                .help("Draw borders, graphs and arrows with plain ASCII characters")
+                .action(ArgAction::SetTrue)
+        )
+        .arg(
+            Arg::new("sort")
+                .long("sort")
+                .help("Initial sort order of the tables")
+                .value_name("COLUMN")
+                .value_parser(["total", "active", "max"])
+                .num_args(1)
+        )
+        .arg(
+            Arg::new("focus")
+                .long("focus")
+                .help("Table focused on startup")
+                .value_name("TABLE")
+                .value_parser(["process-host", "host", "process", "group", "listen", "clients"])
+                .num_args(1)
+        )
+        .arg(
+            Arg::new("view")
+                .long("view")
+                .help("Aggregation view of the group table on startup; v cycles through them")
+                .value_name("VIEW")
+                .value_parser(["group", "scope", "tunnel", "exe"])
+                .num_args(1)
+        )
+        .arg(
+            Arg::new("theme")
+                .long("theme")
+                .help("Color theme for dark or light terminal backgrounds")
+                .value_name("THEME")
+                .value_parser(["dark", "light"])
+                .default_value("dark")
+                .num_args(1)
+        )
+        .arg(
+            Arg::new("max-points")
+                .long("max-points")
+                .help("Samples of history kept in the active connections graph (default: 300)")
+                .value_name("COUNT")
+                .num_args(1)
+        )
+        .arg(
+            Arg::new("no-mouse")
+                .long("no-mouse")
+                .help("Start without mouse capture, so the terminal's own text selection works (M toggles)")
+                .action(ArgAction::SetTrue)
+        )
+        .arg(
+            Arg::new("summary-on-exit")
+                .long("summary-on-exit")
+                .help("Print a session report (top hosts and processes, alerts) to stdout on quit")
+                .action(ArgAction::SetTrue)
+        )
+        .arg(
+            Arg::new("duration")
+                .long("duration")
+                .help("Stop after this long (e.g. 30s, 10m) and print the session report")
+                .value_name("DURATION")
+                .num_args(1)
+                .global(true)
+        )
+        .arg(
+            Arg::new("push-gateway")
+                .long("push-gateway")
+                .help("Push the final metrics to this Prometheus Pushgateway when the run ends, e.g. after --duration in CI")
+                .value_name("URL")
+                .num_args(1)
+                .global(true)
+        )
+        .arg(
+            Arg::new("profile")
+                .long("profile")
+                .help("Append tcpcount's own CPU, memory, refresh time and DNS lookups after every refresh to a JSON lines file")
+                .value_name("PATH")
+                .num_args(1)
+                .global(true)
+        )
+        .arg(
+            Arg::new("config")
+                .short('c')
+                .long("config")
+                .help("Path to the config file (default: ~/.config/tcpcount/config.toml)")
+                .value_name("PATH")
+                .num_args(1)
+                .global(true)
+        )
+        .arg(
+            Arg::new("config-profile")
+                .long("config-profile")
+                .help("Use a [profiles.<name>] table from the config file, e.g. db-debug, over its other settings")
+                .value_name("NAME")
+                .num_args(1)
+                .global(true)
+        )
+        .arg(
+            Arg::new("aliases")
+                .long("aliases")
+                .help("File naming remote endpoints, e.g. '10.0.3.7 = orders-db' or '*.internal.corp = *'")
+                .value_name("PATH")
+                .num_args(1)
+                .global(true)
+        )
+        .arg(
+            Arg::new("log-file")
+                .long("log-file")
+                .help("Write connection events to a structured log file")
+                .value_name("PATH")
+                .num_args(1)
+                .global(true)
+        )
+        .arg(
+            Arg::new("log-format")
+                .long("log-format")
+                .help("Log file format")
+                .value_name("FORMAT")
+                .value_parser(["json", "logfmt"])
+                .default_value("json")
+                .num_args(1)
+                .global(true)
+        )
+        .arg(
+            Arg::new("log-max-size")
+                .long("log-max-size")
+                .help("Rotate the log file when it exceeds this size (e.g. 10M, 512K)")
+                .value_name("SIZE")
+                .num_args(1)
+                .global(true)
+        )
+        .arg(
+            Arg::new("log-max-files")
+                .long("log-max-files")
+                .help("Number of rotated log files to keep")
+                .value_name("COUNT")
+                .default_value("5")
+                .num_args(1)
+                .global(true)
+        )
+        .arg(
+            Arg::new("flow-log")
+                .long("flow-log")
+                .help("Write a NetFlow/IPFIX-style flow record (start, end, 5-tuple, pid) for each closed connection")
+                .value_name("PATH")
+                .num_args(1)
+                .global(true)
+        )
+        .arg(
+            Arg::new("flow-format")
+                .long("flow-format")
+                .help("Flow log format: JSON keyed by IPFIX element names, or CSV")
+                .value_name("FORMAT")
+                .value_parser(["ipfix", "csv"])
+                .default_value("ipfix")
+                .num_args(1)
+                .global(true)
+        )
+        .arg(
+            Arg::new("syslog")
+                .long("syslog")
+                .help("Send connection events to syslog: 'local' or a remote host[:port] (UDP, RFC 5424)")
+                .value_name("TARGET")
+                .num_args(1)
+                .global(true)
+        )
+        .arg(
+            Arg::new("syslog-facility")
+                .long("syslog-facility")
+                .help("Syslog facility (user, daemon, local0-local7, ...)")
+                .value_name("FACILITY")
+                .default_value("user")
+                .num_args(1)
+                .global(true)
+        )
+        .arg(
+            Arg::new("api-listen")
+                .long("api-listen")
+                .help("Serve live data as JSON over HTTP on this address (e.g. 127.0.0.1:9470)")
+                .value_name("ADDR")
+                .num_args(1)
+        )
+        .subcommand(
+            Command::new("daemon")
+                .about("Run without the TUI, periodically flushing aggregated metrics to sinks")
+                .arg(
+                    Arg::new("interval")
+                        .long("interval")
+                        .help("Sampling interval (e.g. 250ms, 1s; default: 1s)")
+                        .value_name("DURATION")
+                        .num_args(1)
+                )
+                .arg(
+                    Arg::new("flush-interval")
+                        .long("flush-interval")
+                        .help("How often metrics are written to the sinks (default: 60s)")
+                        .value_name("DURATION")
+                        .num_args(1)
+                )
+                .arg(
+                    Arg::new("sink")
+                        .long("sink")
+                        .help("Metrics sink: file:<path>, sqlite:<path>, pushgateway:<url>, statsd:<host[:port]>, or influx:<target> (repeatable)")
+                        .value_name("SINK")
+                        .action(ArgAction::Append)
+                )
+                .arg(
+                    Arg::new("systemd-notify")
+                        .long("systemd-notify")
+                        .help("Send readiness and stopping notifications to systemd")
+                        .action(ArgAction::SetTrue)
+                )
+                .arg(
+                    Arg::new("windows-service")
+                        .long("windows-service")
+                        .help("Run under the Windows service control manager (set by `service install`)")
+                        .action(ArgAction::SetTrue)
+                        .hide(true)
+                )
+        )
+        .subcommand(
+            Command::new("service")
+                .about("Install or remove the daemon as a Windows service")
+                .subcommand_required(true)
+                .subcommand(
+                    Command::new("install")
+                        .about("Register `tcpcount daemon` with these arguments to start with Windows")
+                        .arg(
+                            Arg::new("daemon-args")
+                                .help("Daemon arguments after --, e.g. -- --config C:\\ProgramData\\tcpcount\\config.toml --sink file:C:\\ProgramData\\tcpcount\\metrics.jsonl")
+                                .value_name("ARGS")
+                                .num_args(0..)
+                                .trailing_var_arg(true)
+                                .allow_hyphen_values(true)
+                        )
+                )
+                .subcommand(
+                    Command::new("uninstall")
+                        .about("Stop and remove the Windows service")
+                )
+        )
+        .subcommand(
+            Command::new("agent")
+                .about("Stream this machine's connections to a tcpcount aggregator")
+                .arg(
+                    Arg::new("connect")
+                        .long("connect")
+                        .help("Aggregator address (host:port)")
+                        .value_name("ADDR")
+                        .required(true)
+                        .num_args(1)
+                )
+                .arg(
+                    Arg::new("name")
+                        .long("name")
+                        .help("Name shown for this machine in the aggregator (default: hostname)")
+                        .value_name("NAME")
+                        .num_args(1)
+                )
+                .arg(
+                    Arg::new("interval")
+                        .long("interval")
+                        .help("Sampling interval (e.g. 500ms, 1s; default: 1s)")
+                        .value_name("DURATION")
+                        .num_args(1)
+                )
+        )
+        .subcommand(
+            Command::new("aggregator")
+                .about("Show connections streamed from remote agents in the TUI")
+                .arg(
+                    Arg::new("listen")
+                        .long("listen")
+                        .help("Address to accept agents on (e.g. 0.0.0.0:9471)")
+                        .value_name("ADDR")
+                        .required(true)
+                        .num_args(1)
+                )
+        )
+        .subcommand(
+            Command::new("diff")
+                .about("Compare two snapshots or recorded daemon metrics files per host and process")
+                .arg(
+                    Arg::new("a")
+                        .help("Snapshot or metrics file to compare from")
+                        .value_name("A")
+                        .required(true)
+                )
+                .arg(
+                    Arg::new("b")
+                        .help("Snapshot or metrics file to compare to")
+                        .value_name("B")
+                        .required(true)
+                )
         )
-        .get_matches();
+        .subcommand(
+            Command::new("completions")
+                .about("Print a shell completion script, e.g. source <(tcpcount completions bash)")
+                .arg(
+                    Arg::new("shell")
+                        .help("Shell to complete in")
+                        .value_name("SHELL")
+                        .value_parser(completion::shell_names())
+                        .required(true)
+                )
+        )
+        .subcommand(
+            Command::new("analyze")
+                .about("Explore a snapshot or recorded daemon metrics file in the TUI without live polling")
+                .arg(
+                    Arg::new("file")
+                        .help("Snapshot written with S, or a daemon file: sink")
+                        .value_name("FILE")
+                        .required(true)
+                )
+        )
+        .subcommand(
+            Command::new("selftest")
+                .about("Open and close loopback connections while monitoring them and verify the counters")
+                .arg(
+                    Arg::new("connections")
+                        .long("connections")
+                        .help("Connections opened together in each round (default: 5)")
+                        .value_name("N")
+                        .num_args(1)
+                )
+                .arg(
+                    Arg::new("rounds")
+                        .long("rounds")
+                        .help("Number of open/close rounds (default: 3)")
+                        .value_name("N")
+                        .num_args(1)
+                )
+                .arg(
+                    Arg::new("hold")
+                        .long("hold")
+                        .help("How long connections stay open, and closed, in each round (e.g. 200ms; default: 200ms)")
+                        .value_name("DURATION")
+                        .num_args(1)
+                )
+                .arg(
+                    Arg::new("demo")
+                        .long("demo")
+                        .help("Keep generating connections and show them in the TUI instead of checking counters")
+                        .action(ArgAction::SetTrue)
+                )
+        )
+}
 
-    let mut filter = ConnectionFilter::default();
+pub fn parse_args() -> CliArgs {
+    let matches = build_command().get_matches();
+
+    let mut filter = match matches.get_one::<String>("filter").map(|shared| (shared, ConnectionFilter::parse_shareable(shared))) {
+        Some((_, Ok(filter))) => filter,
+        Some((shared, Err(e))) => {
+            eprintln!("Warning: Invalid filter '{}': {}, ignoring", shared, e);
+            ConnectionFilter::default()
+        }
+        None => ConnectionFilter::default(),
+    };
     
-    if let Some(pid_str) = matches.get_one::<String>("pid") {
-        match pid_str.parse::<u32>() {
-            Ok(pid) => filter.pid = Some(pid),
-            Err(_) => eprintln!("Warning: Invalid PID '{}', ignoring", pid_str),
+    if let Some(pids_str) = matches.get_one::<String>("pid") {
+        filter.pids.clear();
+        for pid_str in split_list(pids_str) {
+            match pid_str.parse::<u32>() {
+                Ok(pid) => filter.pids.push(pid),
+                Err(_) => eprintln!("Warning: Invalid PID '{}', ignoring", pid_str),
+            }
         }
     }
     
-    if let Some(process_name) = matches.get_one::<String>("process") {
-        filter.process_name = Some(process_name.clone());
+    if let Some(process_names) = matches.get_one::<String>("process") {
+        filter.process_names = split_list(process_names).map(str::to_string).collect();
     }
     
-    if let Some(host) = matches.get_one::<String>("host") {
-        filter.remote_host = Some(host.clone());
+    let follow: Vec<String> = matches.get_one::<String>("follow")
+        .map(|names| split_list(names).map(str::to_string).collect())
+        .unwrap_or_default();
+    // Followed names match whole, as restart alerts do, rather than as -n substrings
+    let followed = follow.iter()
+        .map(|name| FilterExpr::Compare { field: QueryField::Process, op: QueryOp::Eq, value: name.clone() })
+        .reduce(|either, term| FilterExpr::Or(Box::new(either), Box::new(term)));
+    if let Some(followed) = followed {
+        filter.and_query(followed);
+    }
+
+    if let Some(hosts) = matches.get_one::<String>("host") {
+        filter.remote_hosts = split_list(hosts).map(str::to_string).collect();
     }
     
-    if let Some(port_str) = matches.get_one::<String>("port") {
-        match port_str.parse::<u16>() {
-            Ok(port) => filter.remote_port = Some(port),
-            Err(_) => eprintln!("Warning: Invalid port '{}', ignoring", port_str),
+    if let Some(ports_str) = matches.get_one::<String>("port") {
+        filter.remote_ports.clear();
+        for port_str in split_list(ports_str) {
+            match port_str.parse::<u16>() {
+                Ok(port) => filter.remote_ports.push(port),
+                Err(_) => eprintln!("Warning: Invalid port '{}', ignoring", port_str),
+            }
         }
     }
     
-    filter
+    // DNS mode looks at name server traffic only, unless told which ports to show
+    if matches.get_flag("dns") && filter.remote_ports.is_empty() {
+        filter.remote_ports = vec![DNS_PORT, DOT_PORT];
+    }
+
+    if let Some(groups) = matches.get_one::<String>("group") {
+        filter.groups = split_list(groups).map(str::to_string).collect();
+    }
+    
+    filter.matching.ignore_case |= matches.get_flag("ignore-case");
+    filter.matching.fuzzy |= matches.get_flag("fuzzy");
+    
+    if let Some(query) = matches.get_one::<String>("query") {
+        match FilterExpr::parse(query) {
+            Ok(query) => filter.and_query(query),
+            Err(e) => eprintln!("Warning: Invalid filter query '{}': {}, ignoring", query, e),
+        }
+    }
+    
+    let config_path = matches.get_one::<String>("config").map(PathBuf::from);
+    
+    let log_file = matches.get_one::<String>("log-file").map(|path| {
+        let format = matches.get_one::<String>("log-format")
+            .and_then(|name| LogFormat::from_name(name))
+            .unwrap_or(LogFormat::Json);
+        
+        let max_size = matches.get_one::<String>("log-max-size").and_then(|size_str| {
+            let size = parse_size(size_str);
+            if size.is_none() {
+                eprintln!("Warning: Invalid log size '{}', rotation disabled", size_str);
+            }
+            size
+        });
+        
+        let max_files = matches.get_one::<String>("log-max-files")
+            .and_then(|count_str| match count_str.parse::<usize>() {
+                Ok(count) => Some(count),
+                Err(_) => {
+                    eprintln!("Warning: Invalid log file count '{}', using 5", count_str);
+                    None
+                }
+            })
+            .unwrap_or(5);
+        
+        LogFileOptions {
+            path: PathBuf::from(path),
+            format,
+            max_size,
+            max_files,
+        }
+    });
+    
+    let flow_log = matches.get_one::<String>("flow-log").map(|path| {
+        let format = matches.get_one::<String>("flow-format")
+            .and_then(|name| FlowFormat::from_name(name))
+            .unwrap_or(FlowFormat::Ipfix);
+        (PathBuf::from(path), format)
+    });
+    
+    let syslog = matches.get_one::<String>("syslog").map(|target| {
+        let facility = matches.get_one::<String>("syslog-facility")
+            .and_then(|name| match facility_code(name) {
+                Some(code) => Some(code),
+                None => {
+                    eprintln!("Warning: Invalid syslog facility '{}', using 'user'", name);
+                    None
+                }
+            })
+            .unwrap_or(1);
+        
+        (SyslogTarget::parse(target), facility)
+    });
+    
+    let api_listen = matches.get_one::<String>("api-listen").and_then(|addr_str| {
+        match addr_str.parse::<SocketAddr>() {
+            Ok(addr) => Some(addr),
+            Err(_) => {
+                eprintln!("Warning: Invalid API listen address '{}', API disabled", addr_str);
+                None
+            }
+        }
+    });
+    
+    let duration = matches.get_one::<String>("duration").and_then(|value| {
+        let duration = parse_duration(value).filter(|duration| !duration.is_zero());
+        if duration.is_none() {
+            eprintln!("Warning: Invalid duration '{}' for --duration, running until stopped", value);
+        }
+        duration
+    });
+    
+    let max_points = matches.get_one::<String>("max-points").and_then(|count_str| {
+        match count_str.parse::<usize>() {
+            Ok(count) if count > 0 => Some(count),
+            _ => {
+                eprintln!("Warning: Invalid graph point count '{}', using 300", count_str);
+                None
+            }
+        }
+    });
+    
+    let command = match matches.subcommand() {
+        Some(("daemon", daemon_matches)) => {
+            let parse_interval = |name: &str| {
+                daemon_matches.get_one::<String>(name).and_then(|value| {
+                    let duration = parse_duration(value);
+                    if duration.is_none() {
+                        eprintln!("Warning: Invalid duration '{}' for --{}, ignoring", value, name);
+                    }
+                    duration
+                })
+            };
+            
+            Some(CliCommand::Daemon(DaemonArgs {
+                interval: parse_interval("interval"),
+                flush_interval: parse_interval("flush-interval"),
+                sinks: daemon_matches.get_many::<String>("sink")
+                    .map(|sinks| sinks.cloned().collect())
+                    .unwrap_or_default(),
+                systemd_notify: daemon_matches.get_flag("systemd-notify"),
+                windows_service: daemon_matches.get_flag("windows-service"),
+                push_gateway: daemon_matches.get_one::<String>("push-gateway").cloned(),
+            }))
+        }
+        Some(("agent", agent_matches)) => {
+            let interval = agent_matches.get_one::<String>("interval").and_then(|value| {
+                let duration = parse_duration(value);
+                if duration.is_none() {
+                    eprintln!("Warning: Invalid duration '{}' for --interval, using 1s", value);
+                }
+                duration
+            });
+            let name = agent_matches.get_one::<String>("name").cloned()
+                .or_else(sysinfo::System::host_name)
+                .unwrap_or_else(|| "agent".to_string());
+            
+            Some(CliCommand::Agent(AgentOptions {
+                connect: agent_matches.get_one::<String>("connect").cloned().unwrap_or_default(),
+                name,
+                interval: interval.unwrap_or(Duration::from_secs(1)),
+            }))
+        }
+        Some(("aggregator", aggregator_matches)) => Some(CliCommand::Aggregator {
+            listen: aggregator_matches.get_one::<String>("listen").cloned().unwrap_or_default(),
+        }),
+        Some(("completions", completions_matches)) => Some(CliCommand::Completions {
+            shell: completions_matches.get_one::<String>("shell").cloned().unwrap_or_default(),
+        }),
+        Some(("analyze", analyze_matches)) => Some(CliCommand::Analyze {
+            path: analyze_matches.get_one::<String>("file").map(PathBuf::from).unwrap_or_default(),
+        }),
+        Some(("selftest", selftest_matches)) => {
+            let parse_count = |name: &str, default: usize| {
+                selftest_matches.get_one::<String>(name).map_or(default, |value| match value.parse::<usize>() {
+                    Ok(count) if count > 0 => count,
+                    _ => {
+                        eprintln!("Warning: Invalid count '{}' for --{}, using {}", value, name, default);
+                        default
+                    }
+                })
+            };
+            let hold = selftest_matches.get_one::<String>("hold").and_then(|value| {
+                let duration = parse_duration(value);
+                if duration.is_none() {
+                    eprintln!("Warning: Invalid duration '{}' for --hold, using 200ms", value);
+                }
+                duration
+            });
+
+            Some(CliCommand::Selftest {
+                options: SelftestOptions {
+                    connections: parse_count("connections", 5),
+                    rounds: parse_count("rounds", 3),
+                    hold: hold.unwrap_or(Duration::from_millis(200)),
+                },
+                demo: selftest_matches.get_flag("demo"),
+            })
+        }
+        Some(("diff", diff_matches)) => Some(CliCommand::Diff {
+            a: diff_matches.get_one::<String>("a").map(PathBuf::from).unwrap_or_default(),
+            b: diff_matches.get_one::<String>("b").map(PathBuf::from).unwrap_or_default(),
+        }),
+        Some(("service", service_matches)) => Some(CliCommand::Service {
+            install: service_matches.subcommand_matches("install").map(|install_matches| {
+                install_matches.get_many::<String>("daemon-args")
+                    .map(|args| args.cloned().collect())
+                    .unwrap_or_default()
+            }),
+        }),
+        _ => None,
+    };
+    
+    CliArgs {
+        filter,
+        config_path,
+        config_profile: matches.get_one::<String>("config-profile").cloned(),
+        aliases: matches.get_one::<String>("aliases").map(PathBuf::from),
+        log_file,
+        flow_log,
+        syslog,
+        api_listen,
+        ascii: matches.get_flag("ascii"),
+        summary_on_exit: matches.get_flag("summary-on-exit"),
+        follow,
+        carry_restarts: matches.get_flag("carry-restarts"),
+        adaptive_sampling: matches.get_flag("adaptive-sampling"),
+        conntrack: matches.get_flag("conntrack"),
+        detect_proxies: matches.get_flag("detect-proxies"),
+        threads: matches.get_flag("threads"),
+        tcp_info: matches.get_flag("tcp-info"),
+        dns: matches.get_flag("dns"),
+        script: matches.get_one::<String>("script").map(PathBuf::from),
+        profile: matches.get_one::<String>("profile").map(PathBuf::from),
+        duration,
+        push_gateway: matches.get_one::<String>("push-gateway").cloned(),
+        sort_by: matches.get_one::<String>("sort").and_then(|name| SortBy::from_name(name)),
+        focus: matches.get_one::<String>("focus").and_then(|name| FocusedTable::from_name(name)),
+        view: matches.get_one::<String>("view").cloned(),
+        theme: matches.get_one::<String>("theme").and_then(|name| Theme::from_name(name)).unwrap_or(Theme::Dark),
+        max_points,
+        mouse: !matches.get_flag("no-mouse"),
+        command,
+    }
+}
+
+/// Parses a duration such as `250ms`, `30s`, `10m`, or `1h`; a bare number is seconds.
+pub fn parse_duration(duration_str: &str) -> Option<Duration> {
+    let duration_str = duration_str.trim();
+    let split = duration_str.find(|c: char| !c.is_ascii_digit()).unwrap_or(duration_str.len());
+    let (digits, unit) = duration_str.split_at(split);
+    let value = digits.parse::<u64>().ok()?;
+
+    match unit.trim() {
+        "ms" => Some(Duration::from_millis(value)),
+        "" | "s" => Some(Duration::from_secs(value)),
+        "m" => value.checked_mul(60).map(Duration::from_secs),
+        "h" => value.checked_mul(3600).map(Duration::from_secs),
+        "d" => value.checked_mul(86400).map(Duration::from_secs),
+        _ => None,
+    }
+}
+
+/// Parses a byte size with an optional K/M/G suffix (powers of 1024).
+fn parse_size(size_str: &str) -> Option<u64> {
+    let size_str = size_str.trim();
+    let (digits, multiplier) = match size_str.chars().last()?.to_ascii_uppercase() {
+        'K' => (&size_str[..size_str.len() - 1], 1024),
+        'M' => (&size_str[..size_str.len() - 1], 1024 * 1024),
+        'G' => (&size_str[..size_str.len() - 1], 1024 * 1024 * 1024),
+        _ => (size_str, 1),
+    };
+
+    digits.trim().parse::<u64>().ok()?.checked_mul(multiplier)
 }
\ No newline at end of file
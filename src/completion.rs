@@ -0,0 +1,73 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::ffi::OsStr;
+use std::io;
+
+use clap::builder::StyledStr;
+use clap_complete::CompletionCandidate;
+use clap_complete::env::Shells;
+use netstat2::{get_sockets_info, AddressFamilyFlags, ProtocolFlags, ProtocolSocketInfo, TcpState};
+use sysinfo::{ProcessRefreshKind, ProcessesToUpdate, RefreshKind, System};
+
+/// Splits a comma-separated value into the entries already typed (with their trailing
+/// comma) and the one being completed.
+fn split_current(current: &OsStr) -> (String, String) {
+    let current = current.to_string_lossy();
+    match current.rfind(',') {
+        Some(idx) => (current[..=idx].to_string(), current[idx + 1..].to_string()),
+        None => (String::new(), current.to_string()),
+    }
+}
+
+/// Names of running processes, for `--process-name`.
+pub fn process_names(current: &OsStr) -> Vec<CompletionCandidate> {
+    let (typed, partial) = split_current(current);
+    let mut system = System::new_with_specifics(RefreshKind::nothing().with_processes(ProcessRefreshKind::nothing()));
+    system.refresh_processes(ProcessesToUpdate::All, true);
+
+    let names: BTreeSet<String> = system.processes().values()
+        .map(|process| process.name().to_string_lossy().to_string())
+        .filter(|name| name.starts_with(&partial))
+        .collect();
+    names.into_iter()
+        .map(|name| CompletionCandidate::new(format!("{}{}", typed, name)))
+        .collect()
+}
+
+/// Remote ports of current TCP connections with how many use each, for `--port`.
+pub fn ports(current: &OsStr) -> Vec<CompletionCandidate> {
+    let (typed, partial) = split_current(current);
+    let Ok(sockets) = get_sockets_info(AddressFamilyFlags::IPV4 | AddressFamilyFlags::IPV6, ProtocolFlags::TCP) else {
+        return Vec::new();
+    };
+
+    let mut counts: BTreeMap<u16, usize> = BTreeMap::new();
+    for socket in sockets {
+        if let ProtocolSocketInfo::Tcp(tcp) = socket.protocol_socket_info {
+            if tcp.state != TcpState::Listen && tcp.remote_port != 0 {
+                *counts.entry(tcp.remote_port).or_insert(0) += 1;
+            }
+        }
+    }
+    counts.into_iter()
+        .filter(|(port, _)| port.to_string().starts_with(&partial))
+        .map(|(port, count)| {
+            let help = StyledStr::from(format!("{} connection{}", count, if count == 1 { "" } else { "s" }));
+            CompletionCandidate::new(format!("{}{}", typed, port)).help(Some(help))
+        })
+        .collect()
+}
+
+/// Names accepted by `tcpcount completions`.
+pub fn shell_names() -> Vec<&'static str> {
+    Shells::builtins().names().collect()
+}
+
+/// Writes the script that registers completions for `shell`. The script calls back into
+/// this binary while completing, so process names and ports reflect the moment of typing.
+pub fn write_registration(shell: &str, out: &mut dyn io::Write) -> io::Result<()> {
+    let shells = Shells::builtins();
+    let completer = shells.completer(shell)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, format!("Unsupported shell '{}'", shell)))?;
+    let exe = std::env::current_exe()?;
+    completer.write_registration("COMPLETE", "tcpcount", "tcpcount", &exe.to_string_lossy(), out)
+}
@@ -0,0 +1,696 @@
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use ratatui::style::Color;
+use serde::{Deserialize, Serialize};
+
+use crate::core::aliases::HostAliases;
+use crate::core::cidr::Cidr;
+use crate::core::filters::{ConnectionFilter, FilterExpr, MatchOptions, SavedFilter};
+use crate::cli::parse_duration;
+use crate::core::groups::EndpointGroup;
+use crate::core::anomaly::AnomalyOptions;
+use crate::core::blocklist::BlocklistOptions;
+use crate::core::backlog::BacklogOptions;
+use crate::core::churn::ChurnOptions;
+use crate::core::retransmits::RetransmitOptions;
+use crate::core::limits::LimitOptions;
+use crate::core::rollup::RollupOptions;
+use crate::core::leaks::LeakOptions;
+use crate::core::pacing::BurstOptions;
+use crate::core::monitor::DEFAULT_IDLE_THRESHOLD;
+use crate::core::watchlist::PinRule;
+use crate::error::{self, Error};
+use crate::widgets::table::{Column, Highlight};
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Filter query applied at startup, narrowed further by one given on the command line.
+    pub filter: Option<String>,
+    pub groups: Vec<GroupConfig>,
+    pub pins: Vec<PinRule>,
+    pub highlights: Vec<HighlightConfig>,
+    pub filters: Vec<FilterConfig>,
+    pub matching: MatchConfig,
+    pub columns: ColumnsConfig,
+    pub leaks: LeakConfig,
+    pub anomaly: AnomalyConfig,
+    pub churn: ChurnConfig,
+    pub limits: LimitsConfig,
+    pub backlog: BacklogConfig,
+    pub retransmits: RetransmitsConfig,
+    pub blocklist: BlocklistConfig,
+    pub aliases: AliasesConfig,
+    pub dns: DnsConfig,
+    pub history: HistoryConfig,
+    pub processes: ProcessesConfig,
+    pub rows: RowsConfig,
+    pub sampling: SamplingConfig,
+    pub daemon: DaemonConfig,
+}
+
+/// Default matching for process name and host filters.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct MatchConfig {
+    pub ignore_case: bool,
+    pub fuzzy: bool,
+}
+
+/// Columns shown by each table, in order; unset tables keep their default columns.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ColumnsConfig {
+    pub process_host: Option<Vec<Column>>,
+    pub process: Option<Vec<Column>>,
+    pub host: Option<Vec<Column>>,
+    pub group: Option<Vec<Column>>,
+    pub listen: Option<Vec<Column>>,
+    pub clients: Option<Vec<Column>>,
+}
+
+/// Flags process-host pairs whose active connections grew at every `interval` for `window`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct LeakConfig {
+    pub enabled: bool,
+    pub window: String,
+    pub interval: String,
+}
+
+impl Default for LeakConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            window: "15m".to_string(),
+            interval: "1m".to_string(),
+        }
+    }
+}
+
+/// Optional detection of rows whose active connections deviate from a learned baseline.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct AnomalyConfig {
+    pub enabled: bool,
+    pub warmup: String,
+    pub z_score: f64,
+}
+
+impl Default for AnomalyConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            warmup: "5m".to_string(),
+            z_score: 3.0,
+        }
+    }
+}
+
+/// Flags process-host pairs that open at least `min_connections` in `window` and
+/// `threshold` times more than they ever hold open at once.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ChurnConfig {
+    pub enabled: bool,
+    pub window: String,
+    pub threshold: f64,
+    pub min_connections: usize,
+}
+
+impl Default for ChurnConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            window: "1m".to_string(),
+            threshold: 10.0,
+            min_connections: 20,
+        }
+    }
+}
+
+/// System-wide active connection count to warn at, and the ceiling it is shown against,
+/// e.g. the conntrack table size or the file descriptor limit.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct LimitsConfig {
+    pub warn_at: Option<usize>,
+    pub ceiling: Option<usize>,
+}
+
+/// Alerts on listening sockets whose accept queue fills past `threshold` of the backlog.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct BacklogConfig {
+    pub enabled: bool,
+    pub threshold: f64,
+}
+
+impl Default for BacklogConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            threshold: 0.8,
+        }
+    }
+}
+
+/// Alerts on hosts whose connections retransmit more than `threshold` of the segments
+/// sent, once at least `min_segments` were; needs `--tcp-info`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct RetransmitsConfig {
+    pub enabled: bool,
+    pub threshold: f64,
+    pub min_segments: u64,
+}
+
+impl Default for RetransmitsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            threshold: 0.02,
+            min_segments: 1000,
+        }
+    }
+}
+
+/// IP/domain blocklists, from files or URLs, that connections are checked against.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct BlocklistConfig {
+    pub sources: Vec<String>,
+    pub refresh: String,
+    pub alert: bool,
+}
+
+impl Default for BlocklistConfig {
+    fn default() -> Self {
+        Self {
+            sources: Vec::new(),
+            refresh: "1h".to_string(),
+            alert: false,
+        }
+    }
+}
+
+/// File of names for remote endpoints, applied before hosts are counted.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct AliasesConfig {
+    pub file: Option<PathBuf>,
+}
+
+/// How long exited processes stay in the process tables; kept for the session if unset.
+/// Also how long a connection must sit established to count as idle.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ProcessesConfig {
+    pub hide_exited_after: Option<String>,
+    pub idle_after: Option<String>,
+}
+
+/// How long host and process rows without open connections stay in the tables; kept for
+/// the session if unset.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct RowsConfig {
+    pub hide_inactive_after: Option<String>,
+}
+
+/// Faster sampling while connections matching the filter open and close, to count
+/// connections shorter than the usual interval.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct SamplingConfig {
+    pub adaptive: bool,
+    pub fast_interval: String,
+    pub trigger: usize,
+    pub hold: String,
+}
+
+impl Default for SamplingConfig {
+    fn default() -> Self {
+        Self {
+            adaptive: false,
+            fast_interval: "50ms".to_string(),
+            trigger: 4,
+            hold: "10s".to_string(),
+        }
+    }
+}
+
+/// How long resolved hostnames are reused before they are looked up again.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct DnsConfig {
+    pub ttl: String,
+}
+
+impl Default for DnsConfig {
+    fn default() -> Self {
+        Self { ttl: "10m".to_string() }
+    }
+}
+
+/// How long graph and memory history is kept as samples, minute rollups and hour rollups.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct HistoryConfig {
+    pub raw: String,
+    pub minutes: String,
+    pub keep: String,
+}
+
+impl Default for HistoryConfig {
+    fn default() -> Self {
+        Self {
+            raw: "1h".to_string(),
+            minutes: "24h".to_string(),
+            keep: "7d".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct GroupConfig {
+    pub name: String,
+    #[serde(default)]
+    pub hosts: Vec<String>,
+    #[serde(default)]
+    pub cidrs: Vec<String>,
+    #[serde(default)]
+    pub ports: Vec<u16>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct HighlightConfig {
+    pub pid: Option<u32>,
+    pub process: Option<String>,
+    pub host: Option<String>,
+    pub port: Option<u16>,
+    pub group: Option<String>,
+    pub color: String,
+}
+
+/// A saved filter; the first five are bound to F1-F5 in the TUI.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FilterConfig {
+    pub name: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub pids: Vec<u32>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub processes: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub hosts: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub ports: Vec<u16>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub groups: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub query: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ignore_case: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fuzzy: Option<bool>,
+}
+
+impl FilterConfig {
+    pub fn from_saved(saved: &SavedFilter) -> Self {
+        let filter = &saved.filter;
+        Self {
+            name: saved.name.clone(),
+            pids: filter.pids.clone(),
+            processes: filter.process_names.clone(),
+            hosts: filter.remote_hosts.clone(),
+            ports: filter.remote_ports.clone(),
+            groups: filter.groups.clone(),
+            query: filter.query.as_ref().map(|query| query.to_string()),
+            ignore_case: filter.matching.ignore_case.then_some(true),
+            fuzzy: filter.matching.fuzzy.then_some(true),
+        }
+    }
+
+    /// Converts to a saved filter; matching flags the entry leaves unset come from `defaults`.
+    pub fn to_saved(&self, defaults: MatchOptions) -> Result<SavedFilter, String> {
+        let query = match &self.query {
+            Some(query) => Some(FilterExpr::parse(query)
+                .map_err(|e| format!("Filter '{}': {}", self.name, e))?),
+            None => None,
+        };
+
+        Ok(SavedFilter {
+            name: self.name.clone(),
+            filter: ConnectionFilter {
+                pids: self.pids.clone(),
+                process_names: self.processes.clone(),
+                remote_hosts: self.hosts.clone(),
+                remote_ports: self.ports.clone(),
+                groups: self.groups.clone(),
+                query,
+                matching: MatchOptions {
+                    ignore_case: self.ignore_case.unwrap_or(defaults.ignore_case),
+                    fuzzy: self.fuzzy.unwrap_or(defaults.fuzzy),
+                },
+            },
+        })
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct DaemonConfig {
+    pub interval: Option<String>,
+    pub flush_interval: Option<String>,
+    pub sinks: Vec<String>,
+    pub systemd_notify: bool,
+}
+
+impl Config {
+    /// Loads the config from `path`, or from the default location if no path is given,
+    /// with the `[profiles.<name>]` tables of `profile` laid over it.
+    /// A missing default config is not an error; a missing explicit one is.
+    pub fn load(path: Option<&Path>, profile: Option<&str>) -> error::Result<Self> {
+        let path = match path {
+            Some(path) => path.to_path_buf(),
+            None => match Self::default_path() {
+                Some(path) if path.exists() => path,
+                _ => match profile {
+                    Some(name) => return Err(Error::Config(format!("Profile '{}' needs a config file with a [profiles.{}] table", name, name))),
+                    None => return Ok(Self::default()),
+                },
+            },
+        };
+
+        let contents = fs::read_to_string(&path)
+            .map_err(|e| Error::Config(format!("Failed to read config '{}': {}", path.display(), e)))?;
+        let config = toml::from_str(&contents)
+            .map_err(|e| Error::Config(format!("Failed to parse config '{}': {}", path.display(), e)))?;
+        let Some(name) = profile else {
+            return Ok(config);
+        };
+
+        let mut table: toml::Table = toml::from_str(&contents)
+            .map_err(|e| Error::Config(format!("Failed to parse config '{}': {}", path.display(), e)))?;
+        let profiles = match table.remove("profiles") {
+            Some(toml::Value::Table(profiles)) => profiles,
+            _ => toml::Table::new(),
+        };
+        let overlay = match profiles.get(name) {
+            Some(toml::Value::Table(overlay)) => overlay.clone(),
+            _ => {
+                let names: Vec<&str> = profiles.keys().map(String::as_str).collect();
+                return Err(Error::Config(format!(
+                    "No profile '{}' in config '{}' (profiles: {})",
+                    name,
+                    path.display(),
+                    if names.is_empty() { "none".to_string() } else { names.join(", ") },
+                )));
+            }
+        };
+        merge_tables(&mut table, overlay);
+        toml::Value::Table(table).try_into()
+            .map_err(|e| Error::Config(format!("Failed to apply profile '{}' from '{}': {}", name, path.display(), e)))
+    }
+
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("tcpcount").join("config.toml"))
+    }
+
+    pub fn match_options(&self) -> MatchOptions {
+        MatchOptions {
+            ignore_case: self.matching.ignore_case,
+            fuzzy: self.matching.fuzzy,
+        }
+    }
+
+    /// Leak detection settings, or `None` if detection is turned off.
+    pub fn leak_options(&self) -> Result<Option<LeakOptions>, String> {
+        if !self.leaks.enabled {
+            return Ok(None);
+        }
+
+        let parse = |value: &str, name: &str| {
+            parse_duration(value)
+                .filter(|duration| !duration.is_zero())
+                .ok_or_else(|| format!("Invalid leaks {} '{}'", name, value))
+        };
+        let window = parse(&self.leaks.window, "window")?;
+        let interval = parse(&self.leaks.interval, "interval")?;
+        if interval > window {
+            return Err(format!("Leak interval '{}' is longer than the window '{}'", self.leaks.interval, self.leaks.window));
+        }
+
+        Ok(Some(LeakOptions { window, interval }))
+    }
+
+    /// Anomaly detection settings, or `None` if detection is off.
+    pub fn anomaly_options(&self) -> Result<Option<AnomalyOptions>, String> {
+        if !self.anomaly.enabled {
+            return Ok(None);
+        }
+
+        let warmup = parse_duration(&self.anomaly.warmup)
+            .ok_or_else(|| format!("Invalid anomaly warmup '{}'", self.anomaly.warmup))?;
+        if !self.anomaly.z_score.is_finite() || self.anomaly.z_score <= 0.0 {
+            return Err(format!("Invalid anomaly z_score {}: must be positive", self.anomaly.z_score));
+        }
+
+        Ok(Some(AnomalyOptions { warmup, z_score: self.anomaly.z_score }))
+    }
+
+    /// Churn tracking settings, or `None` if tracking is turned off.
+    pub fn churn_options(&self) -> Result<Option<ChurnOptions>, String> {
+        if !self.churn.enabled {
+            return Ok(None);
+        }
+
+        let window = parse_duration(&self.churn.window)
+            .filter(|duration| !duration.is_zero())
+            .ok_or_else(|| format!("Invalid churn window '{}'", self.churn.window))?;
+        if !self.churn.threshold.is_finite() || self.churn.threshold <= 0.0 {
+            return Err(format!("Invalid churn threshold {}: must be positive", self.churn.threshold));
+        }
+
+        Ok(Some(ChurnOptions {
+            window,
+            threshold: self.churn.threshold,
+            min_connections: self.churn.min_connections,
+        }))
+    }
+
+    /// System-wide connection limits, or `None` if neither is set nor the conntrack table
+    /// size can be read.
+    pub fn limit_options(&self) -> Result<Option<LimitOptions>, String> {
+        if self.limits.ceiling == Some(0) {
+            return Err("Invalid limits ceiling 0: must be positive".to_string());
+        }
+        if self.limits.warn_at == Some(0) {
+            return Err("Invalid limits warn_at 0: must be positive".to_string());
+        }
+
+        Ok(LimitOptions { warn_at: self.limits.warn_at, ceiling: self.limits.ceiling }.resolve())
+    }
+
+    /// Accept queue alert settings, or `None` if alerts are turned off.
+    pub fn backlog_options(&self) -> Result<Option<BacklogOptions>, String> {
+        if !self.backlog.enabled {
+            return Ok(None);
+        }
+        if !self.backlog.threshold.is_finite() || self.backlog.threshold <= 0.0 {
+            return Err(format!("Invalid backlog threshold {}: must be positive", self.backlog.threshold));
+        }
+
+        Ok(Some(BacklogOptions { threshold: self.backlog.threshold }))
+    }
+
+    /// Retransmission alert settings, or `None` if alerts are turned off.
+    pub fn retransmit_options(&self) -> Result<Option<RetransmitOptions>, String> {
+        if !self.retransmits.enabled {
+            return Ok(None);
+        }
+        if !self.retransmits.threshold.is_finite() || self.retransmits.threshold <= 0.0 {
+            return Err(format!("Invalid retransmits threshold {}: must be positive", self.retransmits.threshold));
+        }
+
+        Ok(Some(RetransmitOptions {
+            threshold: self.retransmits.threshold,
+            min_segments: self.retransmits.min_segments,
+        }))
+    }
+
+    /// Blocklist sources and refresh interval, or `None` if no sources are configured.
+    pub fn blocklist_options(&self) -> Result<Option<BlocklistOptions>, String> {
+        if self.blocklist.sources.is_empty() {
+            return Ok(None);
+        }
+
+        let refresh = parse_duration(&self.blocklist.refresh)
+            .filter(|duration| !duration.is_zero())
+            .ok_or_else(|| format!("Invalid blocklist refresh '{}'", self.blocklist.refresh))?;
+
+        Ok(Some(BlocklistOptions {
+            sources: self.blocklist.sources.clone(),
+            refresh,
+            alert: self.blocklist.alert,
+        }))
+    }
+
+    /// Host aliases from `path`, or the configured file; none if neither is given.
+    pub fn host_aliases(&self, path: Option<&Path>) -> error::Result<HostAliases> {
+        match path.or(self.aliases.file.as_deref()) {
+            Some(path) => HostAliases::load(path),
+            None => Ok(HostAliases::default()),
+        }
+    }
+
+    pub fn rollup_options(&self) -> Result<RollupOptions, String> {
+        let parse = |value: &str, name: &str| {
+            parse_duration(value)
+                .filter(|duration| !duration.is_zero())
+                .ok_or_else(|| format!("Invalid history {} '{}'", name, value))
+        };
+        let options = RollupOptions {
+            raw_age: parse(&self.history.raw, "raw")?,
+            minute_age: parse(&self.history.minutes, "minutes")?,
+            max_age: parse(&self.history.keep, "keep")?,
+        };
+        if options.raw_age > options.minute_age || options.minute_age > options.max_age {
+            return Err(format!(
+                "History ages must not decrease: raw '{}', minutes '{}', keep '{}'",
+                self.history.raw, self.history.minutes, self.history.keep,
+            ));
+        }
+        Ok(options)
+    }
+
+    /// Adaptive sampling settings, or `None` if it is off and `enable` doesn't turn it on.
+    pub fn burst_options(&self, enable: bool) -> Result<Option<BurstOptions>, String> {
+        if !self.sampling.adaptive && !enable {
+            return Ok(None);
+        }
+
+        let parse = |value: &str, name: &str| {
+            parse_duration(value)
+                .filter(|duration| !duration.is_zero())
+                .ok_or_else(|| format!("Invalid sampling {} '{}'", name, value))
+        };
+        if self.sampling.trigger == 0 {
+            return Err("Invalid sampling trigger 0: must be at least 1".to_string());
+        }
+
+        Ok(Some(BurstOptions {
+            interval: parse(&self.sampling.fast_interval, "fast_interval")?,
+            trigger: self.sampling.trigger,
+            hold: parse(&self.sampling.hold, "hold")?,
+        }))
+    }
+
+    pub fn exited_retention(&self) -> Result<Option<Duration>, String> {
+        self.processes.hide_exited_after.as_deref()
+            .map(|value| parse_duration(value).ok_or_else(|| format!("Invalid processes hide_exited_after '{}'", value)))
+            .transpose()
+    }
+
+    pub fn inactive_retention(&self) -> Result<Option<Duration>, String> {
+        self.rows.hide_inactive_after.as_deref()
+            .map(|value| parse_duration(value).ok_or_else(|| format!("Invalid rows hide_inactive_after '{}'", value)))
+            .transpose()
+    }
+
+    pub fn idle_threshold(&self) -> Result<Duration, String> {
+        match self.processes.idle_after.as_deref() {
+            Some(value) => parse_duration(value).ok_or_else(|| format!("Invalid processes idle_after '{}'", value)),
+            None => Ok(DEFAULT_IDLE_THRESHOLD),
+        }
+    }
+
+    pub fn name_ttl(&self) -> Result<Duration, String> {
+        parse_duration(&self.dns.ttl)
+            .filter(|ttl| !ttl.is_zero())
+            .ok_or_else(|| format!("Invalid dns ttl '{}'", self.dns.ttl))
+    }
+
+    /// The startup filter query, if one is set.
+    pub fn filter_query(&self) -> Result<Option<FilterExpr>, String> {
+        self.filter.as_deref()
+            .map(|query| FilterExpr::parse(query).map_err(|e| format!("Invalid filter '{}': {}", query, e)))
+            .transpose()
+    }
+
+    pub fn saved_filters(&self) -> Result<Vec<SavedFilter>, String> {
+        self.filters.iter().map(|filter| filter.to_saved(self.match_options())).collect()
+    }
+
+    /// Appends a saved filter to the config file, leaving the rest of the file untouched.
+    pub fn append_saved_filter(path: &Path, saved: &SavedFilter) -> error::Result<()> {
+        #[derive(Serialize)]
+        struct Entry<'a> {
+            filters: [&'a FilterConfig; 1],
+        }
+
+        let entry = FilterConfig::from_saved(saved);
+        let contents = toml::to_string(&Entry { filters: [&entry] })
+            .map_err(|e| Error::Config(e.to_string()))?;
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        write!(file, "\n{}", contents)?;
+        Ok(())
+    }
+
+    pub fn endpoint_groups(&self) -> Result<Vec<EndpointGroup>, String> {
+        self.groups.iter()
+            .map(|group| {
+                let cidrs = group.cidrs.iter()
+                    .map(|cidr| cidr.parse::<Cidr>())
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|e| format!("Group '{}': {}", group.name, e))?;
+
+                Ok(EndpointGroup::new(group.name.clone())
+                    .with_hosts(group.hosts.clone())
+                    .with_cidrs(cidrs)
+                    .with_ports(group.ports.clone()))
+            })
+            .collect()
+    }
+
+    pub fn highlights(&self) -> Result<Vec<Highlight>, String> {
+        self.highlights.iter()
+            .map(|highlight| {
+                let color = highlight.color.parse::<Color>()
+                    .map_err(|_| format!("Invalid highlight color '{}'", highlight.color))?;
+
+                let filter = ConnectionFilter {
+                    pids: highlight.pid.into_iter().collect(),
+                    process_names: highlight.process.iter().cloned().collect(),
+                    remote_hosts: highlight.host.iter().cloned().collect(),
+                    remote_ports: highlight.port.into_iter().collect(),
+                    groups: highlight.group.iter().cloned().collect(),
+                    query: None,
+                    matching: self.match_options(),
+                };
+
+                Ok(Highlight { filter, color })
+            })
+            .collect()
+    }
+}
+
+/// Lays `overlay` over `base`: tables merge key by key, anything else, lists included, is replaced.
+fn merge_tables(base: &mut toml::Table, overlay: toml::Table) {
+    for (key, value) in overlay {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(base)), toml::Value::Table(overlay)) => merge_tables(base, overlay),
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
@@ -0,0 +1,89 @@
+use std::fs;
+use std::net::IpAddr;
+use std::path::Path;
+
+use super::cidr::Cidr;
+use super::utils::wildcard_match;
+use crate::error::{Error, Result};
+
+/// What an alias rule matches: an address or network, or a resolved hostname with `*`
+/// wildcards.
+#[derive(Debug, Clone, PartialEq)]
+enum AliasPattern {
+    Network(Cidr),
+    Hostname(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct AliasRule {
+    pattern: AliasPattern,
+    name: String,
+}
+
+/// Names shown for remote endpoints in place of their address or reverse DNS name.
+///
+/// The file has one `<pattern> = <name>` rule per line (`->` and `→` also separate them);
+/// `#` starts a comment. Patterns are IP addresses, CIDR networks or hostnames with `*`
+/// wildcards. A `*` in the name stands for the text the pattern's single `*` matched, so
+/// `*.internal.corp = *` strips the suffix. The first matching rule wins.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HostAliases {
+    rules: Vec<AliasRule>,
+}
+
+impl HostAliases {
+    pub fn parse(text: &str) -> std::result::Result<Self, String> {
+        let mut rules = Vec::new();
+        for (idx, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            let (pattern, name) = ["→", "->", "="].iter()
+                .find_map(|separator| line.split_once(separator))
+                .or_else(|| line.split_once(char::is_whitespace))
+                .map(|(pattern, name)| (pattern.trim(), name.trim()))
+                .filter(|(pattern, name)| !pattern.is_empty() && !name.is_empty())
+                .ok_or_else(|| format!("line {}: expected '<pattern> = <name>'", idx + 1))?;
+
+            let pattern = match pattern.parse::<Cidr>() {
+                Ok(cidr) => AliasPattern::Network(cidr),
+                Err(_) => AliasPattern::Hostname(pattern.trim_end_matches('.').to_ascii_lowercase()),
+            };
+            rules.push(AliasRule { pattern, name: name.to_string() });
+        }
+        Ok(Self { rules })
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path)
+            .map_err(|e| Error::Config(format!("Failed to read aliases '{}': {}", path.display(), e)))?;
+        Self::parse(&text)
+            .map_err(|e| Error::Config(format!("Invalid aliases '{}', {}", path.display(), e)))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// The alias of an endpoint, if a rule matches its address or resolved hostname.
+    pub fn name_for(&self, addr: &IpAddr, hostname: Option<&str>) -> Option<String> {
+        let hostname = hostname.map(|hostname| hostname.trim_end_matches('.').to_ascii_lowercase());
+        self.rules.iter().find_map(|rule| match &rule.pattern {
+            AliasPattern::Network(cidr) => cidr.contains(addr).then(|| rule.name.clone()),
+            AliasPattern::Hostname(pattern) => {
+                let hostname = hostname.as_deref()?;
+                if !wildcard_match(pattern, hostname) {
+                    return None;
+                }
+                Some(match pattern.split_once('*') {
+                    Some((prefix, suffix)) if !suffix.contains('*') && rule.name.contains('*') => {
+                        let matched = &hostname[prefix.len()..hostname.len() - suffix.len()];
+                        rule.name.replace('*', matched)
+                    }
+                    _ => rule.name.clone(),
+                })
+            }
+        })
+    }
+}
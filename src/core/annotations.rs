@@ -0,0 +1,71 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::time::SystemTime;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Annotations kept before the oldest is dropped.
+const MAX_ANNOTATIONS: usize = 200;
+
+/// What put an annotation on the graph.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AnnotationKind {
+    /// Typed in by the user, e.g. "deployed v2 here".
+    Manual,
+    /// A followed process came up under a new PID.
+    Restart,
+    /// An alert fired.
+    Alert,
+    /// The active filter changed.
+    Filter,
+}
+
+impl AnnotationKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AnnotationKind::Manual => "note",
+            AnnotationKind::Restart => "restart",
+            AnnotationKind::Alert => "alert",
+            AnnotationKind::Filter => "filter",
+        }
+    }
+}
+
+/// A point in time marked on the connections graph.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Annotation {
+    pub time: DateTime<Utc>,
+    pub kind: AnnotationKind,
+    pub text: String,
+}
+
+impl fmt::Display for Annotation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}] {}", self.kind.as_str(), self.text)
+    }
+}
+
+/// The most recent annotations, oldest first.
+#[derive(Debug, Default)]
+pub struct Annotations {
+    entries: VecDeque<Annotation>,
+}
+
+impl Annotations {
+    pub fn add(&mut self, kind: AnnotationKind, text: String, at: SystemTime) {
+        self.entries.push_back(Annotation { time: at.into(), kind, text });
+        if self.entries.len() > MAX_ANNOTATIONS {
+            self.entries.pop_front();
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Annotation> {
+        self.entries.iter()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
@@ -0,0 +1,116 @@
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime};
+
+/// Smallest standard deviation used for z-scores, so a perfectly steady baseline
+/// isn't flagged for a change of a single connection.
+const MIN_STDDEV: f64 = 1.0;
+
+/// Host and process rows the detector keeps a baseline for.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum AnomalyKey {
+    Host(String, u16),
+    Process(u32),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AnomalyOptions {
+    /// How long to learn baselines before flagging anything.
+    pub warmup: Duration,
+    /// Deviation from the baseline, in standard deviations, that counts as unusual.
+    pub z_score: f64,
+}
+
+impl Default for AnomalyOptions {
+    fn default() -> Self {
+        Self {
+            warmup: Duration::from_secs(5 * 60),
+            z_score: 3.0,
+        }
+    }
+}
+
+/// Moving average and variance of one row's active connections.
+#[derive(Debug, Clone, Copy, Default)]
+struct Baseline {
+    mean: f64,
+    variance: f64,
+    samples: usize,
+}
+
+impl Baseline {
+    /// Adds a sample, weighting it `1/n` until `max_weight_samples` are seen and
+    /// `1/max_weight_samples` after that, so the baseline keeps following slow drift.
+    fn add(&mut self, value: f64, max_weight_samples: usize) {
+        self.samples += 1;
+        let alpha = 1.0 / self.samples.min(max_weight_samples.max(1)) as f64;
+        let delta = value - self.mean;
+        self.mean += alpha * delta;
+        self.variance = (1.0 - alpha) * (self.variance + alpha * delta * delta);
+    }
+
+    fn z_score(&self, value: f64) -> f64 {
+        (value - self.mean) / self.variance.sqrt().max(MIN_STDDEV)
+    }
+}
+
+/// Learns a per-host and per-process baseline of active connections during a warm-up
+/// period, then reports rows that deviate from it by at least the configured z-score.
+#[derive(Debug)]
+pub struct AnomalyDetector {
+    options: AnomalyOptions,
+    started: Option<SystemTime>,
+    warmup_samples: usize,
+    baselines: HashMap<AnomalyKey, Baseline>,
+    anomalies: HashMap<AnomalyKey, f64>,
+}
+
+impl AnomalyDetector {
+    pub fn new(options: AnomalyOptions) -> Self {
+        Self {
+            options,
+            started: None,
+            warmup_samples: 0,
+            baselines: HashMap::new(),
+            anomalies: HashMap::new(),
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.started = None;
+        self.warmup_samples = 0;
+        self.baselines.clear();
+        self.anomalies.clear();
+    }
+
+    /// Z-score of the row if it is currently unusual.
+    pub fn anomaly(&self, key: &AnomalyKey) -> Option<f64> {
+        self.anomalies.get(key).copied()
+    }
+
+    /// Scores the active counts against the baselines, then adds them to the baselines.
+    /// Rows missing from `active` count as zero.
+    pub fn update(&mut self, now: SystemTime, active: &HashMap<AnomalyKey, usize>) {
+        let started = *self.started.get_or_insert(now);
+        let warmed_up = now.duration_since(started).unwrap_or_default() >= self.options.warmup;
+        if !warmed_up {
+            self.warmup_samples += 1;
+        }
+
+        for key in active.keys() {
+            self.baselines.entry(key.clone()).or_default();
+        }
+
+        self.anomalies.clear();
+        for (key, baseline) in self.baselines.iter_mut() {
+            let value = active.get(key).copied().unwrap_or(0) as f64;
+            // Rows first seen after warm-up need a warm-up of their own
+            if warmed_up && baseline.samples >= self.warmup_samples {
+                let z_score = baseline.z_score(value);
+                if z_score.abs() >= self.options.z_score {
+                    self.anomalies.insert(key.clone(), z_score);
+                }
+            }
+            baseline.add(value, self.warmup_samples);
+        }
+    }
+}
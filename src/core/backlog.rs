@@ -0,0 +1,135 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::net::IpAddr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::Result;
+
+/// Kernel cap on every listen backlog on Linux.
+const SOMAXCONN_PATH: &str = "/proc/sys/net/core/somaxconn";
+
+/// Connections waiting to be accepted on a listening socket, against how many may wait
+/// before the kernel drops new ones.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct AcceptQueue {
+    pub queued: u32,
+    /// The `listen()` backlog, capped by `net.core.somaxconn`.
+    pub backlog: u32,
+}
+
+impl AcceptQueue {
+    /// Share of the backlog in use, from 0 to 1 (more once the kernel overshoots it).
+    pub fn saturation(&self) -> f64 {
+        self.queued as f64 / self.backlog.max(1) as f64
+    }
+}
+
+impl fmt::Display for AcceptQueue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.queued, self.backlog)
+    }
+}
+
+/// A listening socket and its accept queue, as sampled.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListenerSample {
+    pub port: u16,
+    pub local_addr: IpAddr,
+    pub pid: Option<u32>,
+    pub process_name: Option<String>,
+    pub queue: AcceptQueue,
+}
+
+/// Accept queues of the TCP listening sockets by socket inode, read over sock_diag.
+/// Empty on systems other than Linux.
+pub fn read_accept_queues() -> Result<HashMap<u64, AcceptQueue>> {
+    #[cfg(target_os = "linux")]
+    {
+        super::sock_diag::listeners()
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        Ok(HashMap::new())
+    }
+}
+
+/// The kernel's cap on listen backlogs, where it can be read.
+pub fn somaxconn() -> Option<u32> {
+    if !cfg!(target_os = "linux") {
+        return None;
+    }
+    std::fs::read_to_string(SOMAXCONN_PATH).ok()?.trim().parse().ok()
+}
+
+/// When a listener's accept queue raises an alert.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BacklogOptions {
+    /// Share of the backlog in use that counts as saturated.
+    pub threshold: f64,
+}
+
+impl Default for BacklogOptions {
+    fn default() -> Self {
+        Self { threshold: 0.8 }
+    }
+}
+
+/// A listening port whose accept queue just filled past the threshold: the server is not
+/// accepting connections as fast as they arrive.
+#[derive(Debug, Clone)]
+pub struct BacklogAlert {
+    pub port: u16,
+    /// Owner of the listener, as `name(pid)` when known.
+    pub process: String,
+    pub queue: AcceptQueue,
+    /// Whether the backlog is as large as `net.core.somaxconn` allows.
+    pub capped: bool,
+}
+
+impl fmt::Display for BacklogAlert {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Accept queue saturated: {} on port {} has {} of {} connections waiting to be accepted",
+            self.process, self.port, self.queue.queued, self.queue.backlog)?;
+        if self.capped {
+            write!(f, " (backlog capped by net.core.somaxconn)")?;
+        }
+        Ok(())
+    }
+}
+
+/// Ports whose accept queue is past the threshold, each flagged once until it drains to
+/// half the threshold.
+#[derive(Debug)]
+pub struct BacklogWatch {
+    options: BacklogOptions,
+    flagged: HashSet<u16>,
+}
+
+impl BacklogWatch {
+    pub fn new(options: BacklogOptions) -> Self {
+        Self { options, flagged: HashSet::new() }
+    }
+
+    /// Whether `queue` counts as saturated.
+    pub fn is_saturated(&self, queue: &AcceptQueue) -> bool {
+        queue.queued > 0 && queue.saturation() >= self.options.threshold
+    }
+
+    /// Takes the fullest queue per port, returning the ports that just became saturated.
+    pub fn update(&mut self, queues: &HashMap<u16, AcceptQueue>) -> Vec<u16> {
+        let threshold = self.options.threshold;
+        self.flagged.retain(|port| queues.get(port).is_some_and(|queue| queue.saturation() >= threshold / 2.0));
+        let mut saturated: Vec<u16> = queues.iter()
+            .filter(|(port, queue)| !self.flagged.contains(port) && self.is_saturated(queue))
+            .map(|(port, _)| *port)
+            .collect();
+        saturated.sort_unstable();
+        self.flagged.extend(saturated.iter().copied());
+        saturated
+    }
+
+    pub fn reset(&mut self) {
+        self.flagged.clear();
+    }
+}
@@ -0,0 +1,186 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::fs;
+use std::net::IpAddr;
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::Duration;
+
+use super::cidr::Cidr;
+use crate::error::{Error, Result};
+
+/// Where blocklists come from and what to do about connections they list.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlocklistOptions {
+    /// Local file paths or `http(s)://` URLs.
+    pub sources: Vec<String>,
+    /// How often every source is loaded again.
+    pub refresh: Duration,
+    /// Raise an alert when a process connects to a listed endpoint.
+    pub alert: bool,
+}
+
+/// Addresses, networks and domains read from one blocklist source.
+///
+/// Lists have one entry per line; `#` starts a comment. Entries are IP addresses, CIDR
+/// networks or domains, which also match their subdomains. Hosts-file lines such as
+/// `0.0.0.0 ads.example.com` list the domain.
+#[derive(Debug, Clone, Default)]
+pub struct Blocklist {
+    pub name: String,
+    addrs: HashSet<IpAddr>,
+    networks: Vec<Cidr>,
+    domains: HashSet<String>,
+}
+
+impl Blocklist {
+    pub fn parse(name: String, text: &str) -> Self {
+        let mut list = Self { name, ..Self::default() };
+        for line in text.lines() {
+            let line = line.split('#').next().unwrap_or("");
+            let mut tokens = line.split_whitespace();
+            let Some(first) = tokens.next() else {
+                continue;
+            };
+            let entry = match tokens.next() {
+                Some(domain) if matches!(first, "0.0.0.0" | "127.0.0.1" | "::" | "::1") => domain,
+                _ => first,
+            };
+
+            match entry.parse::<Cidr>() {
+                Ok(cidr) if cidr.prefix_len == if cidr.addr.is_ipv4() { 32 } else { 128 } => {
+                    list.addrs.insert(cidr.addr);
+                }
+                Ok(cidr) => list.networks.push(cidr),
+                Err(_) => {
+                    let domain = entry.trim_start_matches("*.").trim_matches('.').to_ascii_lowercase();
+                    if !domain.is_empty() && domain != "localhost" {
+                        list.domains.insert(domain);
+                    }
+                }
+            }
+        }
+        list
+    }
+
+    /// Reads a list from a file, or over HTTP for a URL.
+    pub fn load(source: &str) -> Result<Self> {
+        let text = if source.starts_with("http://") || source.starts_with("https://") {
+            ureq::get(source).call()
+                .map_err(|e| Error::Other(format!("Failed to fetch blocklist '{}': {}", source, e)))?
+                .into_string()
+                .map_err(|e| Error::Other(format!("Failed to read blocklist '{}': {}", source, e)))?
+        } else {
+            fs::read_to_string(source)
+                .map_err(|e| Error::Other(format!("Failed to read blocklist '{}': {}", source, e)))?
+        };
+        Ok(Self::parse(source.to_string(), &text))
+    }
+
+    pub fn matches(&self, addr: &IpAddr, hostname: Option<&str>) -> bool {
+        if self.addrs.contains(addr) || self.networks.iter().any(|net| net.contains(addr)) {
+            return true;
+        }
+        let Some(hostname) = hostname else {
+            return false;
+        };
+
+        // Try the name and each parent domain: a.b.example.com, b.example.com, ...
+        let hostname = hostname.trim_end_matches('.').to_ascii_lowercase();
+        let mut domain = hostname.as_str();
+        loop {
+            if self.domains.contains(domain) {
+                return true;
+            }
+            match domain.split_once('.') {
+                Some((_, parent)) => domain = parent,
+                None => return false,
+            }
+        }
+    }
+}
+
+/// All loaded blocklists.
+#[derive(Debug, Clone, Default)]
+pub struct Blocklists {
+    lists: Vec<Blocklist>,
+}
+
+impl Blocklists {
+    pub fn new(lists: Vec<Blocklist>) -> Self {
+        Self { lists }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lists.is_empty()
+    }
+
+    /// Name of the first list containing the address or hostname.
+    pub fn find(&self, addr: &IpAddr, hostname: Option<&str>) -> Option<&str> {
+        self.lists.iter()
+            .find(|list| list.matches(addr, hostname))
+            .map(|list| list.name.as_str())
+    }
+}
+
+/// A process connected to an endpoint on a blocklist.
+#[derive(Debug, Clone)]
+pub struct BlocklistAlert {
+    pub pid: u32,
+    pub process_name: String,
+    pub host: String,
+    pub port: u16,
+    pub list: String,
+}
+
+impl fmt::Display for BlocklistAlert {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Blocklisted endpoint: {}({}) connected to {}:{}, listed in {}",
+            self.process_name, self.pid, self.host, self.port, self.list)
+    }
+}
+
+/// Loads the blocklist sources in the background, again every refresh interval. A source
+/// that fails to load keeps its last good copy.
+pub struct BlocklistFeed {
+    updates: Receiver<(Blocklists, Vec<Error>)>,
+}
+
+impl BlocklistFeed {
+    pub fn start(options: &BlocklistOptions) -> Self {
+        let (sender, updates) = mpsc::channel();
+        let sources = options.sources.clone();
+        let refresh = options.refresh;
+
+        thread::spawn(move || {
+            let mut loaded: Vec<Option<Blocklist>> = vec![None; sources.len()];
+            loop {
+                let mut errors = Vec::new();
+                for (source, slot) in sources.iter().zip(loaded.iter_mut()) {
+                    match Blocklist::load(source) {
+                        Ok(list) => *slot = Some(list),
+                        Err(e) => errors.push(e),
+                    }
+                }
+                let lists = Blocklists::new(loaded.iter().flatten().cloned().collect());
+                if sender.send((lists, errors)).is_err() {
+                    return;
+                }
+                thread::sleep(refresh);
+            }
+        });
+
+        Self { updates }
+    }
+
+    /// The newest lists loaded since the last call, with any load errors.
+    pub fn poll(&self) -> Option<(Blocklists, Vec<Error>)> {
+        let mut latest: Option<(Blocklists, Vec<Error>)> = None;
+        for (lists, errors) in self.updates.try_iter() {
+            let mut all_errors = latest.map(|(_, errors)| errors).unwrap_or_default();
+            all_errors.extend(errors);
+            latest = Some((lists, all_errors));
+        }
+        latest
+    }
+}
@@ -0,0 +1,136 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::time::{Duration, SystemTime};
+
+use super::leaks::ProcessHostKey;
+
+/// Churn up to which a pair counts as reusing its connections: at most two opens per
+/// connection held over the window.
+pub const REUSED_CHURN: f64 = 2.0;
+
+/// When a process-host pair's churn raises an alert.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChurnOptions {
+    /// Period over which opens and concurrency are counted.
+    pub window: Duration,
+    /// Connections opened per concurrent connection in the window that counts as a reconnect storm.
+    pub threshold: f64,
+    /// Opens needed in the window before a pair can be flagged, so a few short requests don't alert.
+    pub min_connections: usize,
+}
+
+impl Default for ChurnOptions {
+    fn default() -> Self {
+        Self {
+            window: Duration::from_secs(60),
+            threshold: 10.0,
+            min_connections: 20,
+        }
+    }
+}
+
+/// A process-host pair whose churn just crossed the threshold.
+#[derive(Debug, Clone)]
+pub struct ChurnAlert {
+    pub pid: u32,
+    pub process_name: String,
+    pub host: String,
+    pub port: u16,
+    pub churn: f64,
+    pub opened: usize,
+}
+
+impl fmt::Display for ChurnAlert {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "High connection churn: {}({}) to {}:{} opened {} connections, {:.1} per concurrent connection",
+            self.process_name, self.pid, self.host, self.port, self.opened, self.churn)
+    }
+}
+
+#[derive(Debug, Default)]
+struct PairWindow {
+    opens: VecDeque<SystemTime>,
+    active: VecDeque<(SystemTime, usize)>,
+}
+
+impl PairWindow {
+    fn churn(&self) -> f64 {
+        let max_concurrent = self.active.iter().map(|(_, count)| *count).max().unwrap_or(0);
+        self.opens.len() as f64 / max_concurrent.max(1) as f64
+    }
+}
+
+/// Tracks connections opened versus the most held open at once per process-host pair
+/// over a sliding window. Many opens for few concurrent connections point to reconnect
+/// loops or missing keep-alive.
+#[derive(Debug)]
+pub struct ChurnTracker {
+    options: ChurnOptions,
+    pairs: HashMap<ProcessHostKey, PairWindow>,
+    flagged: HashSet<ProcessHostKey>,
+}
+
+impl ChurnTracker {
+    pub fn new(options: ChurnOptions) -> Self {
+        Self {
+            options,
+            pairs: HashMap::new(),
+            flagged: HashSet::new(),
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.pairs.clear();
+        self.flagged.clear();
+    }
+
+    /// Connections opened per concurrent connection over the window.
+    pub fn churn(&self, key: &ProcessHostKey) -> f64 {
+        self.pairs.get(key).map_or(0.0, PairWindow::churn)
+    }
+
+    pub fn is_flagged(&self, key: &ProcessHostKey) -> bool {
+        self.flagged.contains(key)
+    }
+
+    /// Records one sampling pass and returns the pairs that just crossed the threshold
+    /// with their churn and opens in the window.
+    pub fn update(
+        &mut self,
+        now: SystemTime,
+        opened: &[ProcessHostKey],
+        active: &HashMap<ProcessHostKey, usize>,
+    ) -> Vec<(ProcessHostKey, f64, usize)> {
+        for key in opened {
+            self.pairs.entry(key.clone()).or_default().opens.push_back(now);
+        }
+        for (key, count) in active {
+            self.pairs.entry(key.clone()).or_default().active.push_back((now, *count));
+        }
+
+        let window_start = now.checked_sub(self.options.window).unwrap_or(SystemTime::UNIX_EPOCH);
+        self.pairs.retain(|_, pair| {
+            while pair.opens.front().is_some_and(|at| *at < window_start) {
+                pair.opens.pop_front();
+            }
+            while pair.active.front().is_some_and(|(at, _)| *at < window_start) {
+                pair.active.pop_front();
+            }
+            !pair.opens.is_empty() || !pair.active.is_empty()
+        });
+
+        let mut newly_flagged = Vec::new();
+        for (key, pair) in &self.pairs {
+            let churn = pair.churn();
+            if pair.opens.len() >= self.options.min_connections && churn >= self.options.threshold {
+                if self.flagged.insert(key.clone()) {
+                    newly_flagged.push((key.clone(), churn, pair.opens.len()));
+                }
+            } else {
+                self.flagged.remove(key);
+            }
+        }
+        self.flagged.retain(|key| self.pairs.contains_key(key));
+        newly_flagged
+    }
+}
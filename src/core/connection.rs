@@ -1,6 +1,16 @@
-use std::net::IpAddr;
+use std::net::{IpAddr, SocketAddr};
 use netstat2::TcpState;
-use std::time::SystemTime;
+use std::time::{Duration, SystemTime};
+
+use super::retransmits::TcpStats;
+use super::threads::SocketThreads;
+
+/// A TCP state a connection entered and when it was first seen in it.
+#[derive(Debug, Clone, Copy)]
+pub struct StateChange {
+    pub state: TcpState,
+    pub at: SystemTime,
+}
 
 #[derive(Debug, Clone)]
 pub struct Connection {
@@ -14,6 +24,19 @@ pub struct Connection {
     pub first_seen: SystemTime,        // When connection was first observed
     pub last_seen: SystemTime,         // When connection was last observed
     pub closed: bool,                  // Whether connection is closed
+    pub group: Option<String>,         // Matching endpoint group, if any
+    pub inbound: bool,                 // Accepted on a local listening port
+    pub blocklist: Option<String>,     // Blocklist the remote endpoint is on, if any
+    pub tunnel: Option<String>,        // VPN/tunnel interface the connection goes over, if any
+    pub nat_from: Option<SocketAddr>,  // Address dialed before destination NAT, if translated
+    pub proxied: bool,                 // Goes to a proxy set in the process environment
+    pub threads: Option<SocketThreads>, // Threads of the process holding the socket, if attributed
+    pub inode: Option<u64>,            // Kernel inode of the socket (Linux)
+    pub fd: Option<u32>,               // Descriptor the process has the socket open on (Linux)
+    pub local_addr: Option<IpAddr>,    // Local address the socket is bound to, if known
+    pub tcp_stats: Option<TcpStats>,   // Segments sent and retransmitted, from tcp_info (Linux)
+    pub udp: bool,                     // A UDP socket connected to a name server
+    pub transitions: Vec<StateChange>, // States entered, oldest first, starting with the initial state
 }
 
 impl Connection {
@@ -24,8 +47,8 @@ impl Connection {
         remote_addr: IpAddr,
         remote_hostname: Option<String>,
         state: TcpState,
+        now: SystemTime,
     ) -> Self {
-        let now = SystemTime::now();
         Self {
             id: rand::random(),
             pid,
@@ -37,16 +60,70 @@ impl Connection {
             first_seen: now,
             last_seen: now,
             closed: false,
+            group: None,
+            inbound: false,
+            blocklist: None,
+            tunnel: None,
+            nat_from: None,
+            proxied: false,
+            threads: None,
+            inode: None,
+            fd: None,
+            tcp_stats: None,
+            udp: false,
+            local_addr: None,
+            transitions: vec![StateChange { state, at: now }],
         }
     }
 
-    pub fn update_state(&mut self, state: TcpState) {
+    /// The name host rows group the connection under: its hostname, or its address when
+    /// unresolved.
+    pub fn host(&self) -> String {
+        self.remote_hostname.clone().unwrap_or_else(|| self.remote_addr.to_string())
+    }
+
+    /// Key of the connection's host row in the host counters.
+    pub fn host_key(&self) -> String {
+        format!("{}:{}", self.host(), self.remote_port)
+    }
+
+    /// Key of the connection's process-host row in the process-host counters.
+    pub fn process_host_key(&self) -> (u32, String, u16) {
+        (self.pid, self.host(), self.remote_port)
+    }
+
+    /// Records the state seen in the sample taken at `now`.
+    pub fn update_state(&mut self, state: TcpState, now: SystemTime) {
+        if state != self.state {
+            self.transitions.push(StateChange { state, at: now });
+        }
         self.state = state;
-        self.last_seen = SystemTime::now();
+        self.last_seen = now;
+    }
+
+    /// How long the connection spent in each state it entered, up to now or until it closed.
+    pub fn state_durations(&self) -> Vec<(TcpState, Duration)> {
+        let end = if self.closed { self.last_seen } else { SystemTime::now() };
+        self.transitions.iter().enumerate()
+            .map(|(idx, change)| {
+                let until = self.transitions.get(idx + 1).map_or(end, |next| next.at);
+                (change.state, until.duration_since(change.at).unwrap_or_default())
+            })
+            .collect()
+    }
+
+    /// How long the connection has been ESTABLISHED without a state change, if it is open
+    /// and established over TCP. Sampling sees no traffic, so this is as close to idle as it gets.
+    pub fn idle_for(&self, now: SystemTime) -> Option<Duration> {
+        if self.closed || self.udp || self.state != TcpState::Established {
+            return None;
+        }
+        let since = self.transitions.last().map_or(self.first_seen, |change| change.at);
+        Some(now.duration_since(since).unwrap_or_default())
     }
 
+    /// Marks the connection gone. `last_seen` stays at the last sample that saw it.
     pub fn mark_closed(&mut self) {
         self.closed = true;
-        self.last_seen = SystemTime::now();
     }
 }
\ No newline at end of file
@@ -0,0 +1,66 @@
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+
+/// Where the kernel tracks connections, with NAT translations, on Linux.
+const CONNTRACK_PATH: &str = "/proc/net/nf_conntrack";
+
+/// Source and destination of a tracked connection as first sent.
+type Tuple = (SocketAddr, SocketAddr);
+
+/// Real destinations of connections whose destination NAT rewrote, e.g. to a Kubernetes
+/// service or a port forward, keyed by the addresses the socket sees.
+#[derive(Debug, Clone, Default)]
+pub struct NatTable {
+    translated: HashMap<Tuple, SocketAddr>,
+}
+
+impl NatTable {
+    /// Reads the kernel's connection tracking table. Empty where it can't be read, such as
+    /// without root, without the conntrack module, or on systems other than Linux.
+    pub fn read() -> Self {
+        if !cfg!(target_os = "linux") {
+            return Self::default();
+        }
+        Self::parse(&std::fs::read_to_string(CONNTRACK_PATH).unwrap_or_default())
+    }
+
+    /// Builds the table from the text of `/proc/net/nf_conntrack`. Each TCP line has the
+    /// original `src`/`dst`/`sport`/`dport`, then the same for the reply; a reply coming
+    /// from somewhere other than the original destination means it was translated.
+    pub fn parse(text: &str) -> Self {
+        let translated = text.lines()
+            .filter(|line| line.split_whitespace().nth(2) == Some("tcp"))
+            .filter_map(|line| {
+                let mut fields = line.split_whitespace().filter_map(|field| field.split_once('='));
+                let original = parse_tuple(&mut fields)?;
+                let (reply_src, _) = parse_tuple(&mut fields)?;
+                (reply_src != original.1).then_some((original, reply_src))
+            })
+            .collect();
+        Self { translated }
+    }
+
+    /// Where a connection from `local` to `remote` really goes, if NAT sent it elsewhere.
+    pub fn destination(&self, local: SocketAddr, remote: SocketAddr) -> Option<SocketAddr> {
+        self.translated.get(&(unmap(local), unmap(remote))).copied()
+    }
+}
+
+/// Reads the next `src`, `dst`, `sport` and `dport` fields, in that order.
+fn parse_tuple<'a>(fields: &mut impl Iterator<Item = (&'a str, &'a str)>) -> Option<Tuple> {
+    let mut next = |key: &str| fields.find(|(name, _)| *name == key).map(|(_, value)| value);
+    let src: IpAddr = next("src")?.parse().ok()?;
+    let dst: IpAddr = next("dst")?.parse().ok()?;
+    let sport: u16 = next("sport")?.parse().ok()?;
+    let dport: u16 = next("dport")?.parse().ok()?;
+    Some((SocketAddr::new(src, sport), SocketAddr::new(dst, dport)))
+}
+
+/// Sockets on dual-stack listeners show IPv4 peers as IPv4-mapped IPv6 addresses, which
+/// conntrack lists as plain IPv4.
+fn unmap(addr: SocketAddr) -> SocketAddr {
+    match addr.ip() {
+        IpAddr::V6(v6) => v6.to_ipv4_mapped().map_or(addr, |v4| SocketAddr::new(IpAddr::V4(v4), addr.port())),
+        IpAddr::V4(_) => addr,
+    }
+}
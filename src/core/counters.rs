@@ -0,0 +1,245 @@
+use std::collections::{HashMap, VecDeque};
+use std::hash::Hash;
+use std::time::{Duration, SystemTime};
+
+use serde::{Deserialize, Serialize};
+
+/// Total, current and max concurrent connections per key. Closing never takes a count
+/// below zero; closes with nothing open under their key are counted as mismatches instead.
+#[derive(Debug, Clone)]
+pub struct ConcurrencyCounter<K> {
+    total: HashMap<K, usize>,
+    current: HashMap<K, usize>,
+    max: HashMap<K, usize>,
+    mismatches: usize,
+}
+
+impl<K> Default for ConcurrencyCounter<K> {
+    fn default() -> Self {
+        Self {
+            total: HashMap::new(),
+            current: HashMap::new(),
+            max: HashMap::new(),
+            mismatches: 0,
+        }
+    }
+}
+
+impl<K: Eq + Hash + Clone> ConcurrencyCounter<K> {
+    /// Counts a connection opened under `key`.
+    pub fn open(&mut self, key: K) {
+        *self.total.entry(key.clone()).or_insert(0) += 1;
+        let current = self.current.entry(key.clone()).or_insert(0);
+        *current += 1;
+        let max = self.max.entry(key).or_insert(0);
+        *max = (*max).max(*current);
+    }
+
+    /// Counts a connection seen before under `key` that is already closed.
+    pub fn count_closed(&mut self, key: K) {
+        *self.total.entry(key).or_insert(0) += 1;
+    }
+
+    /// Counts a connection under `key` closing.
+    pub fn close(&mut self, key: &K) {
+        match self.current.get_mut(key) {
+            Some(current) if *current > 1 => *current -= 1,
+            Some(_) => {
+                self.current.remove(key);
+            }
+            None => self.mismatches += 1,
+        }
+    }
+
+    pub fn total(&self, key: &K) -> usize {
+        self.total.get(key).copied().unwrap_or(0)
+    }
+
+    pub fn current(&self, key: &K) -> usize {
+        self.current.get(key).copied().unwrap_or(0)
+    }
+
+    pub fn max(&self, key: &K) -> usize {
+        self.max.get(key).copied().unwrap_or(0)
+    }
+
+    /// Number of keys with any connections counted.
+    pub fn keys(&self) -> usize {
+        self.total.len()
+    }
+
+    /// Keys with open connections and how many each has.
+    pub fn current_counts(&self) -> impl Iterator<Item = (&K, usize)> {
+        self.current.iter().map(|(key, count)| (key, *count))
+    }
+
+    /// Open connections across all keys.
+    pub fn current_sum(&self) -> usize {
+        self.current.values().sum()
+    }
+
+    /// Closes that found nothing open under their key.
+    pub fn mismatches(&self) -> usize {
+        self.mismatches
+    }
+}
+
+/// Windows of the recent maxima, shortest first.
+pub const RECENT_WINDOWS: [Duration; 3] = [
+    Duration::from_secs(60),
+    Duration::from_secs(5 * 60),
+    Duration::from_secs(60 * 60),
+];
+
+/// Highest concurrent count of a key over each of the `RECENT_WINDOWS`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct RecentMax {
+    pub last_1m: usize,
+    pub last_5m: usize,
+    pub last_1h: usize,
+}
+
+/// Maxima of sampled counts per key over the last minute, five minutes and hour, so an
+/// old spike stops counting once it leaves the window.
+///
+/// Each window keeps the samples that can still be its maximum: newer ones with a lower
+/// or equal count, and older ones with a higher count, in time order. The front is the
+/// maximum.
+#[derive(Debug, Clone)]
+pub struct WindowedMax<K> {
+    windows: HashMap<K, [VecDeque<(SystemTime, usize)>; 3]>,
+}
+
+impl<K> Default for WindowedMax<K> {
+    fn default() -> Self {
+        Self { windows: HashMap::new() }
+    }
+}
+
+impl<K: Eq + Hash + Clone> WindowedMax<K> {
+    /// Records the counts sampled at `now`. Keys tracked before but missing from `counts`
+    /// are recorded at zero, and dropped once every window has forgotten them.
+    pub fn record<'a>(&mut self, counts: impl Iterator<Item = (&'a K, usize)>, now: SystemTime)
+    where
+        K: 'a,
+    {
+        let mut counts: HashMap<K, usize> = counts.map(|(key, count)| (key.clone(), count)).collect();
+        for key in counts.keys() {
+            self.windows.entry(key.clone()).or_default();
+        }
+
+        self.windows.retain(|key, windows| {
+            let count = counts.remove(key).unwrap_or(0);
+            for (samples, window) in windows.iter_mut().zip(RECENT_WINDOWS) {
+                while samples.back().is_some_and(|&(_, older)| older <= count) {
+                    samples.pop_back();
+                }
+                samples.push_back((now, count));
+                let cutoff = now.checked_sub(window).unwrap_or(SystemTime::UNIX_EPOCH);
+                while samples.front().is_some_and(|&(at, _)| at < cutoff) {
+                    samples.pop_front();
+                }
+            }
+            windows.iter().any(|samples| samples.front().is_some_and(|&(_, count)| count > 0))
+        });
+    }
+
+    /// Maxima of `key` over each window as of the last `record`.
+    pub fn get(&self, key: &K) -> RecentMax {
+        let Some(windows) = self.windows.get(key) else {
+            return RecentMax::default();
+        };
+        let max = |idx: usize| windows[idx].front().map_or(0, |&(_, count)| count);
+        RecentMax { last_1m: max(0), last_5m: max(1), last_1h: max(2) }
+    }
+}
+
+/// Average and 95th percentile of a key's concurrent count over the samples taken since
+/// it first had a connection.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct SampledConcurrency {
+    pub average: f64,
+    pub p95: usize,
+}
+
+/// How often each key was seen with each concurrent count, one entry per sample.
+#[derive(Debug, Clone)]
+pub struct ConcurrencyHistogram<K> {
+    /// Samples per count, indexed by the count.
+    histograms: HashMap<K, Vec<u64>>,
+}
+
+impl<K> Default for ConcurrencyHistogram<K> {
+    fn default() -> Self {
+        Self { histograms: HashMap::new() }
+    }
+}
+
+impl<K: Eq + Hash + Clone> ConcurrencyHistogram<K> {
+    /// Records one sample. Keys seen before but missing from `counts` are sampled at zero.
+    pub fn record<'a>(&mut self, counts: impl Iterator<Item = (&'a K, usize)>)
+    where
+        K: 'a,
+    {
+        let mut counts: HashMap<K, usize> = counts.map(|(key, count)| (key.clone(), count)).collect();
+        for key in counts.keys() {
+            self.histograms.entry(key.clone()).or_default();
+        }
+        for (key, histogram) in self.histograms.iter_mut() {
+            let count = counts.remove(key).unwrap_or(0);
+            if histogram.len() <= count {
+                histogram.resize(count + 1, 0);
+            }
+            histogram[count] += 1;
+        }
+    }
+
+    pub fn get(&self, key: &K) -> SampledConcurrency {
+        let Some(histogram) = self.histograms.get(key) else {
+            return SampledConcurrency::default();
+        };
+        let samples: u64 = histogram.iter().sum();
+        if samples == 0 {
+            return SampledConcurrency::default();
+        }
+        let sum: u64 = histogram.iter().enumerate().map(|(count, times)| count as u64 * times).sum();
+
+        // Smallest count that at least 95% of the samples are at or below
+        let needed = (samples * 95).div_ceil(100);
+        let mut seen = 0;
+        let p95 = histogram.iter()
+            .position(|times| {
+                seen += times;
+                seen >= needed
+            })
+            .unwrap_or(0);
+
+        SampledConcurrency { average: sum as f64 / samples as f64, p95 }
+    }
+}
+
+/// One counter's state next to a recount from the connections themselves.
+#[derive(Debug, Clone)]
+pub struct CounterCheck {
+    pub name: &'static str,
+    pub keys: usize,
+    pub counted: usize,
+    pub actual: usize,
+    pub mismatches: usize,
+}
+
+impl CounterCheck {
+    pub fn new<K: Eq + Hash + Clone>(name: &'static str, counter: &ConcurrencyCounter<K>, actual: usize) -> Self {
+        Self {
+            name,
+            keys: counter.keys(),
+            counted: counter.current_sum(),
+            actual,
+            mismatches: counter.mismatches(),
+        }
+    }
+
+    pub fn is_consistent(&self) -> bool {
+        self.counted == self.actual && self.mismatches == 0
+    }
+}
@@ -0,0 +1,23 @@
+use std::collections::HashMap;
+use std::net::SocketAddr;
+
+use crate::error::Result;
+
+/// Port name servers answer plain DNS queries on, over UDP and TCP.
+pub const DNS_PORT: u16 = 53;
+
+/// Port of DNS over TLS.
+pub const DOT_PORT: u16 = 853;
+
+/// UDP sockets connected to a name server's DNS port by inode, with their local and
+/// remote address, read over sock_diag. Empty on systems other than Linux.
+pub fn read_dns_sockets() -> Result<HashMap<u64, (SocketAddr, SocketAddr)>> {
+    #[cfg(target_os = "linux")]
+    {
+        super::sock_diag::udp_peers(DNS_PORT)
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        Ok(HashMap::new())
+    }
+}
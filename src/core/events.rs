@@ -0,0 +1,27 @@
+use std::time::SystemTime;
+
+use super::connection::Connection;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConnectionEventKind {
+    Opened,
+    Closed,
+}
+
+impl ConnectionEventKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ConnectionEventKind::Opened => "opened",
+            ConnectionEventKind::Closed => "closed",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ConnectionEvent {
+    pub seq: u64,                      // Monotonic event number
+    pub time: SystemTime,              // When the change was observed
+    pub kind: ConnectionEventKind,
+    pub process_name: Option<String>,  // Process name at the time of the event
+    pub connection: Connection,        // Connection snapshot at the time of the event
+}
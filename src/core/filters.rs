@@ -1,12 +1,94 @@
+use std::fmt;
+
+use super::cidr::Cidr;
 use super::connection::Connection;
+use super::scope::AddressScope;
+use super::utils::wildcard_match;
 
 
-#[derive(Debug, Clone, Default)]
-pub struct ConnectionFilter {
+/// Attributes of an aggregated table row, `None` where the table does not group by that field.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RowAttributes<'a> {
     pub pid: Option<u32>,
-    pub process_name: Option<String>,
-    pub remote_host: Option<String>,
-    pub remote_port: Option<u16>,
+    pub process_name: Option<&'a str>,
+    pub host: Option<&'a str>,
+    pub port: Option<u16>,
+    pub group: Option<&'a str>,
+    pub scope: Option<AddressScope>,
+    /// Tunnel interface, empty for direct traffic.
+    pub tunnel: Option<&'a str>,
+}
+
+/// How process name and host filters compare against their values.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MatchOptions {
+    pub ignore_case: bool,
+    /// Match the filter as a subsequence, so `chrm` matches `chrome`.
+    pub fuzzy: bool,
+}
+
+impl MatchOptions {
+    pub fn matches(&self, filter: &str, text: &str) -> bool {
+        if self.ignore_case {
+            let (filter, text) = (filter.to_lowercase(), text.to_lowercase());
+            self.matches_exact_case(&filter, &text)
+        } else {
+            self.matches_exact_case(filter, text)
+        }
+    }
+
+    fn matches_exact_case(&self, filter: &str, text: &str) -> bool {
+        if self.fuzzy {
+            let mut chars = text.chars();
+            filter.chars().all(|wanted| chars.any(|c| c == wanted))
+        } else {
+            text.contains(filter)
+        }
+    }
+
+    /// Matches all of `text` against a pattern with `*` wildcards, ignoring case if set.
+    /// Never fuzzy, so `*.example.com` can't match a lookalike domain.
+    pub fn matches_pattern(&self, pattern: &str, text: &str) -> bool {
+        if self.ignore_case {
+            wildcard_match(&pattern.to_lowercase(), &text.to_lowercase())
+        } else {
+            wildcard_match(pattern, text)
+        }
+    }
+
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl fmt::Display for MatchOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut modes = Vec::new();
+        if self.ignore_case {
+            modes.push("ignore case");
+        }
+        if self.fuzzy {
+            modes.push("fuzzy");
+        }
+        if modes.is_empty() {
+            write!(f, "exact case")
+        } else {
+            write!(f, "{}", modes.join(", "))
+        }
+    }
+}
+
+/// Filter over connection fields. Values within a field are alternatives (OR);
+/// fields are combined with AND, and an empty field matches everything.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ConnectionFilter {
+    pub pids: Vec<u32>,
+    pub process_names: Vec<String>,
+    pub remote_hosts: Vec<String>,
+    pub remote_ports: Vec<u16>,
+    pub groups: Vec<String>,
+    pub query: Option<FilterExpr>,
+    pub matching: MatchOptions,
 }
 
 impl ConnectionFilter {
@@ -15,100 +97,751 @@ impl ConnectionFilter {
     }
 
     pub fn with_pid(mut self, pid: u32) -> Self {
-        self.pid = Some(pid);
+        self.pids.push(pid);
         self
     }
 
     pub fn with_process_name(mut self, name: String) -> Self {
-        self.process_name = Some(name);
+        self.process_names.push(name);
         self
     }
 
     pub fn with_remote_host(mut self, host: String) -> Self {
-        self.remote_host = Some(host);
+        self.remote_hosts.push(host);
         self
     }
 
     pub fn with_remote_port(mut self, port: u16) -> Self {
-        self.remote_port = Some(port);
+        self.remote_ports.push(port);
+        self
+    }
+
+    pub fn with_group(mut self, group: String) -> Self {
+        self.groups.push(group);
+        self
+    }
+
+    pub fn with_query(mut self, query: FilterExpr) -> Self {
+        self.query = Some(query);
+        self
+    }
+
+    /// Narrows the query by `term`, or makes `term` the query if there is none.
+    pub fn and_query(&mut self, term: FilterExpr) {
+        self.query = Some(match self.query.take() {
+            Some(query) => FilterExpr::And(Box::new(query), Box::new(term)),
+            None => term,
+        });
+    }
+
+    pub fn with_matching(mut self, matching: MatchOptions) -> Self {
+        self.matching = matching;
         self
     }
 
     pub fn is_empty(&self) -> bool {
-        self.pid.is_none() && 
-        self.process_name.is_none() && 
-        self.remote_host.is_none() && 
-        self.remote_port.is_none()
+        self.pids.is_empty() && 
+        self.process_names.is_empty() && 
+        self.remote_hosts.is_empty() && 
+        self.remote_ports.is_empty() &&
+        self.groups.is_empty() &&
+        self.query.is_none()
+    }
+
+    /// The filter as one URL-style string such as `pid=42&host=*.s3.amazonaws.com&port=443`,
+    /// with the fields always in the same order, that `parse_shareable` turns back into it.
+    pub fn shareable(&self) -> String {
+        let list = |values: &[String]| values.iter().map(|value| encode_component(value)).collect::<Vec<_>>().join(",");
+        let mut fields = Vec::new();
+        if !self.pids.is_empty() {
+            fields.push(format!("pid={}", join_values(&self.pids)));
+        }
+        if !self.process_names.is_empty() {
+            fields.push(format!("process={}", list(&self.process_names)));
+        }
+        if !self.remote_hosts.is_empty() {
+            fields.push(format!("host={}", list(&self.remote_hosts)));
+        }
+        if !self.remote_ports.is_empty() {
+            fields.push(format!("port={}", join_values(&self.remote_ports)));
+        }
+        if !self.groups.is_empty() {
+            fields.push(format!("group={}", list(&self.groups)));
+        }
+        if let Some(ref query) = self.query {
+            fields.push(format!("query={}", encode_component(&query.to_string())));
+        }
+        let mut modes = Vec::new();
+        if self.matching.ignore_case {
+            modes.push("ignore_case");
+        }
+        if self.matching.fuzzy {
+            modes.push("fuzzy");
+        }
+        if !modes.is_empty() {
+            fields.push(format!("match={}", modes.join(",")));
+        }
+        fields.join("&")
+    }
+
+    /// Reads a filter written by `shareable`.
+    pub fn parse_shareable(input: &str) -> Result<Self, String> {
+        let mut filter = Self::default();
+        for field in input.trim().split('&').filter(|field| !field.is_empty()) {
+            let (key, value) = field.split_once('=').ok_or_else(|| format!("Expected key=value, got '{}'", field))?;
+            let list = || split_list(value).map(decode_component).collect::<Result<Vec<_>, _>>();
+            match key {
+                "pid" => filter.pids = parse_list(value).map_err(|pid| format!("Invalid PID '{}'", pid))?,
+                "process" => filter.process_names = list()?,
+                "host" => filter.remote_hosts = list()?,
+                "port" => filter.remote_ports = parse_list(value).map_err(|port| format!("Invalid port '{}'", port))?,
+                "group" => filter.groups = list()?,
+                "query" => filter.query = Some(FilterExpr::parse(&decode_component(value)?)?),
+                "match" => {
+                    for mode in split_list(value) {
+                        match mode {
+                            "ignore_case" => filter.matching.ignore_case = true,
+                            "fuzzy" => filter.matching.fuzzy = true,
+                            _ => return Err(format!("Unknown match mode '{}'", mode)),
+                        }
+                    }
+                }
+                _ => return Err(format!("Unknown filter field '{}'", key)),
+            }
+        }
+        Ok(filter)
+    }
+
+    pub fn matches_pid(&self, pid: u32) -> bool {
+        self.pids.is_empty() || self.pids.contains(&pid)
+    }
+
+    /// Substring match against any of the process name filters, subject to `matching`.
+    pub fn matches_process_name(&self, name: Option<&str>) -> bool {
+        self.process_names.is_empty() ||
+            name.is_some_and(|name| self.process_names.iter().any(|filter| self.matching.matches(filter, name)))
+    }
+
+    /// Substring match against any of the host filters, or a whole-name match for filters
+    /// with `*` wildcards such as `*.internal.example.com`.
+    fn matches_host(&self, host: &str) -> bool {
+        self.remote_hosts.iter().any(|filter| {
+            if filter.contains('*') {
+                self.matching.matches_pattern(filter, host)
+            } else {
+                self.matching.matches(filter, host)
+            }
+        })
+    }
+
+    pub fn matches_connection(&self, conn: &Connection, process_name: Option<&str>) -> bool {
+        // If any filter doesn't match, return false
+        if !self.matches_pid(conn.pid) {
+            return false;
+        }
+
+        if !self.matches_process_name(process_name) {
+            return false;
+        }
+
+        if !self.remote_hosts.is_empty() {
+            // Match the hostname if resolved, otherwise (or additionally) the IP address
+            let addr_str = conn.remote_addr.to_string();
+            let matches_host = conn.remote_hostname.as_ref().is_some_and(|hostname| self.matches_host(hostname)) ||
+                self.matches_host(&addr_str);
+            if !matches_host {
+                return false;
+            }
+        }
+
+        if !self.remote_ports.is_empty() && !self.remote_ports.contains(&conn.remote_port) {
+            return false;
+        }
+
+        if !self.groups.is_empty() && !conn.group.as_ref().is_some_and(|group| self.groups.contains(group)) {
+            return false;
+        }
+
+        if let Some(ref query) = self.query {
+            if !query.matches_connection(conn, process_name, self.matching) {
+                return false;
+            }
+        }
+
+        // If we got here, all specified filters matched
+        true
+    }
+
+    /// Checks an aggregated row against the filter. Fields the row does not have never match.
+    pub fn matches_row(&self, row: &RowAttributes) -> bool {
+        if !self.pids.is_empty() && !row.pid.is_some_and(|pid| self.pids.contains(&pid)) {
+            return false;
+        }
+
+        if !self.process_names.is_empty() && !self.matches_process_name(row.process_name) {
+            return false;
+        }
+
+        if !self.remote_hosts.is_empty() &&
+            !row.host.is_some_and(|host| self.matches_host(host))
+        {
+            return false;
+        }
+
+        if !self.remote_ports.is_empty() && !row.port.is_some_and(|port| self.remote_ports.contains(&port)) {
+            return false;
+        }
+
+        if !self.groups.is_empty() && !row.group.is_some_and(|group| self.groups.iter().any(|g| g == group)) {
+            return false;
+        }
+
+        if let Some(ref query) = self.query {
+            if !query.matches_row(row, self.matching) {
+                return false;
+            }
+        }
+
+        true
     }
+}
 
-    pub fn to_string(&self) -> String {
+impl fmt::Display for ConnectionFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let mut parts = Vec::new();
         
-        if let Some(pid) = self.pid {
-            parts.push(format!("PID: {}", pid));
+        if !self.pids.is_empty() {
+            parts.push(format!("PID: {}", join_values(&self.pids)));
         }
         
-        if let Some(ref process_name) = self.process_name {
-            parts.push(format!("Process: {}", process_name));
+        if !self.process_names.is_empty() {
+            parts.push(format!("Process: {}", self.process_names.join(",")));
         }
         
-        if let Some(ref remote_host) = self.remote_host {
-            parts.push(format!("Host: {}", remote_host));
+        if !self.remote_hosts.is_empty() {
+            parts.push(format!("Host: {}", self.remote_hosts.join(",")));
         }
         
-        if let Some(port) = self.remote_port {
-            parts.push(format!("Port: {}", port));
+        if !self.remote_ports.is_empty() {
+            parts.push(format!("Port: {}", join_values(&self.remote_ports)));
+        }
+
+        if !self.groups.is_empty() {
+            parts.push(format!("Group: {}", self.groups.join(",")));
+        }
+
+        if let Some(ref query) = self.query {
+            parts.push(format!("Query: {}", query));
+        }
+
+        let has_text_filters = !self.process_names.is_empty() || !self.remote_hosts.is_empty();
+        if has_text_filters && !self.matching.is_default() {
+            parts.push(format!("Match: {}", self.matching));
         }
         
         if parts.is_empty() {
-            "No filters".to_string()
+            write!(f, "No filters")
         } else {
-            parts.join(", ")
+            write!(f, "{}", parts.join(", "))
         }
     }
+}
 
-    pub fn matches_connection(&self, conn: &Connection, process_name: Option<&str>) -> bool {
-        // If any filter doesn't match, return false
-        if let Some(pid) = self.pid {
-            if conn.pid != pid {
-                return false;
-            }
+/// A filter stored under a name for quick recall.
+#[derive(Debug, Clone)]
+pub struct SavedFilter {
+    pub name: String,
+    pub filter: ConnectionFilter,
+}
+
+/// Joins values with commas, the same form `parse_list` accepts.
+pub fn join_values<T: fmt::Display>(values: &[T]) -> String {
+    values.iter().map(|value| value.to_string()).collect::<Vec<_>>().join(",")
+}
+
+/// Splits a comma-separated list, trimming whitespace and dropping empty entries.
+pub fn split_list(input: &str) -> impl Iterator<Item = &str> {
+    input.split(',').map(str::trim).filter(|value| !value.is_empty())
+}
+
+/// Parses every entry of a comma-separated list, failing on the first invalid one.
+pub fn parse_list<T: std::str::FromStr>(input: &str) -> Result<Vec<T>, String> {
+    split_list(input)
+        .map(|value| value.parse::<T>().map_err(|_| value.to_string()))
+        .collect()
+}
+
+/// Percent-encodes everything but letters, digits and `-._~*:/`, so a value can't break up
+/// a shareable filter and the string survives being pasted into a shell or chat.
+fn encode_component(value: &str) -> String {
+    let mut encoded = String::new();
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~*:/".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
+
+fn decode_component(value: &str) -> Result<String, String> {
+    let invalid = || format!("Invalid escape in '{}'", value);
+    let mut bytes = Vec::new();
+    let mut rest = value.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        if byte == b'%' {
+            let hex = tail.get(..2).and_then(|hex| std::str::from_utf8(hex).ok()).ok_or_else(invalid)?;
+            bytes.push(u8::from_str_radix(hex, 16).map_err(|_| invalid())?);
+            rest = &tail[2..];
+        } else {
+            bytes.push(byte);
+            rest = tail;
+        }
+    }
+    String::from_utf8(bytes).map_err(|_| invalid())
+}
+
+/// Connection attribute that a filter query can test.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum QueryField {
+    Pid,
+    Process,
+    Host,
+    Port,
+    LocalPort,
+    Group,
+    State,
+    /// Remote address within a CIDR network, e.g. `net=10.0.0.0/8`.
+    Net,
+    /// Remote address scope: loopback, link-local, private or public.
+    Scope,
+    /// Tunnel interface: `yes` for any, `no` for direct traffic, or an interface name.
+    Tunnel,
+}
+
+impl QueryField {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "pid" => Some(QueryField::Pid),
+            "proc" | "process" => Some(QueryField::Process),
+            "host" => Some(QueryField::Host),
+            "port" => Some(QueryField::Port),
+            "lport" | "local_port" => Some(QueryField::LocalPort),
+            "group" => Some(QueryField::Group),
+            "state" => Some(QueryField::State),
+            "net" | "cidr" => Some(QueryField::Net),
+            "scope" => Some(QueryField::Scope),
+            "tunnel" | "vpn" => Some(QueryField::Tunnel),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            QueryField::Pid => "pid",
+            QueryField::Process => "proc",
+            QueryField::Host => "host",
+            QueryField::Port => "port",
+            QueryField::LocalPort => "lport",
+            QueryField::Group => "group",
+            QueryField::State => "state",
+            QueryField::Net => "net",
+            QueryField::Scope => "scope",
+            QueryField::Tunnel => "tunnel",
+        }
+    }
+
+    fn is_numeric(&self) -> bool {
+        matches!(self, QueryField::Pid | QueryField::Port | QueryField::LocalPort)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum QueryOp {
+    Eq,          // `=`: exact match, `*` wildcards allowed for text
+    NotEq,       // `!=`
+    Contains,    // `~`: substring match
+    NotContains, // `!~`
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl QueryOp {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            QueryOp::Eq => "=",
+            QueryOp::NotEq => "!=",
+            QueryOp::Contains => "~",
+            QueryOp::NotContains => "!~",
+            QueryOp::Lt => "<",
+            QueryOp::Le => "<=",
+            QueryOp::Gt => ">",
+            QueryOp::Ge => ">=",
+        }
+    }
+
+    fn is_ordering(&self) -> bool {
+        matches!(self, QueryOp::Lt | QueryOp::Le | QueryOp::Gt | QueryOp::Ge)
+    }
+}
+
+/// A parsed filter query such as `proc~"chrome" and (port=443 or port=80) and not host~"googleapis"`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FilterExpr {
+    Compare { field: QueryField, op: QueryOp, value: String },
+    Not(Box<FilterExpr>),
+    And(Box<FilterExpr>, Box<FilterExpr>),
+    Or(Box<FilterExpr>, Box<FilterExpr>),
+}
+
+impl FilterExpr {
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let tokens = tokenize(input)?;
+        if tokens.is_empty() {
+            return Err("Empty query".to_string());
+        }
+
+        let mut parser = QueryParser { tokens, pos: 0 };
+        let expr = parser.parse_or()?;
+        match parser.peek() {
+            None => Ok(expr),
+            Some(token) => Err(format!("Unexpected {}", token)),
         }
+    }
 
-        if let Some(ref process_filter) = self.process_name {
-            if let Some(name) = process_name {
-                if !name.contains(process_filter) {
-                    return false;
+    /// Evaluates against a connection, comparing process names and hosts with `matching`.
+    pub fn matches_connection(&self, conn: &Connection, process_name: Option<&str>, matching: MatchOptions) -> bool {
+        self.eval(matching, &|field| match field {
+            QueryField::Pid => vec![conn.pid.to_string()],
+            QueryField::Process => process_name.map(str::to_string).into_iter().collect(),
+            QueryField::Host => conn.remote_hostname.iter().cloned()
+                .chain(std::iter::once(conn.remote_addr.to_string()))
+                .collect(),
+            QueryField::Port => vec![conn.remote_port.to_string()],
+            QueryField::LocalPort => vec![conn.local_port.to_string()],
+            QueryField::Group => conn.group.iter().cloned().collect(),
+            QueryField::State => vec![conn.state.to_string()],
+            QueryField::Net => vec![conn.remote_addr.to_string()],
+            QueryField::Scope => vec![AddressScope::of(&conn.remote_addr).to_string()],
+            QueryField::Tunnel => vec![conn.tunnel.clone().unwrap_or_default()],
+        })
+    }
+
+    /// Evaluates against an aggregated row; comparisons on fields the row lacks are false,
+    /// and `!=` and `!~` on them true.
+    pub fn matches_row(&self, row: &RowAttributes, matching: MatchOptions) -> bool {
+        self.eval(matching, &|field| match field {
+            QueryField::Pid => row.pid.map(|pid| pid.to_string()).into_iter().collect(),
+            QueryField::Process => row.process_name.map(str::to_string).into_iter().collect(),
+            QueryField::Host => row.host.map(str::to_string).into_iter().collect(),
+            QueryField::Port => row.port.map(|port| port.to_string()).into_iter().collect(),
+            QueryField::Group => row.group.map(str::to_string).into_iter().collect(),
+            // Rows keyed by address, such as clients, carry it as their host
+            QueryField::Net => row.host.map(str::to_string).into_iter().collect(),
+            QueryField::Scope => row.scope.map(|scope| scope.to_string()).into_iter().collect(),
+            QueryField::Tunnel => row.tunnel.map(str::to_string).into_iter().collect(),
+            QueryField::LocalPort | QueryField::State => Vec::new(),
+        })
+    }
+
+    fn eval(&self, matching: MatchOptions, values: &dyn Fn(QueryField) -> Vec<String>) -> bool {
+        match self {
+            FilterExpr::Compare { field, op, value } => {
+                // Without a value the positive operators fail and the negated ones hold,
+                // as they do for `not`
+                let candidates = values(*field);
+                let matches = |positive: QueryOp| candidates.iter()
+                    .any(|candidate| compare(*field, positive, candidate, value, matching));
+                match op {
+                    QueryOp::NotEq => !matches(QueryOp::Eq),
+                    QueryOp::NotContains => !matches(QueryOp::Contains),
+                    op => matches(*op),
                 }
-            } else {
-                return false;
+            }
+            FilterExpr::Not(inner) => !inner.eval(matching, values),
+            FilterExpr::And(left, right) => left.eval(matching, values) && right.eval(matching, values),
+            FilterExpr::Or(left, right) => left.eval(matching, values) || right.eval(matching, values),
+        }
+    }
+
+    /// Writes the expression, parenthesizing `or` inside `and` and compound operands of `not`.
+    fn fmt_nested(&self, f: &mut fmt::Formatter<'_>, parenthesize: bool) -> fmt::Result {
+        if parenthesize {
+            write!(f, "(")?;
+            self.fmt_nested(f, false)?;
+            return write!(f, ")");
+        }
+
+        match self {
+            FilterExpr::Compare { field, op, value } => {
+                write!(f, "{}{}{}", field.as_str(), op.as_str(), quote_value(value))
+            }
+            FilterExpr::Not(inner) => {
+                write!(f, "not ")?;
+                inner.fmt_nested(f, !matches!(**inner, FilterExpr::Compare { .. }))
+            }
+            FilterExpr::And(left, right) => {
+                left.fmt_nested(f, matches!(**left, FilterExpr::Or(..)))?;
+                write!(f, " and ")?;
+                right.fmt_nested(f, matches!(**right, FilterExpr::Or(..)))
+            }
+            FilterExpr::Or(left, right) => {
+                left.fmt_nested(f, false)?;
+                write!(f, " or ")?;
+                right.fmt_nested(f, false)
             }
         }
+    }
+}
+
+impl fmt::Display for FilterExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_nested(f, false)
+    }
+}
+
+fn compare(field: QueryField, op: QueryOp, candidate: &str, value: &str, matching: MatchOptions) -> bool {
+    if field.is_numeric() && op != QueryOp::Contains {
+        let (Ok(candidate), Ok(value)) = (candidate.parse::<u64>(), value.parse::<u64>()) else {
+            return false;
+        };
+        return match op {
+            QueryOp::Lt => candidate < value,
+            QueryOp::Le => candidate <= value,
+            QueryOp::Gt => candidate > value,
+            QueryOp::Ge => candidate >= value,
+            _ => candidate == value,
+        };
+    }
 
-        if let Some(ref host_filter) = self.remote_host {
-            if let Some(ref hostname) = conn.remote_hostname {
-                if !hostname.contains(host_filter) {
-                    let addr_str = conn.remote_addr.to_string();
-                    if !addr_str.contains(host_filter) {
-                        return false;
+    if field == QueryField::Net {
+        return match (value.parse::<Cidr>(), candidate.parse()) {
+            (Ok(net), Ok(addr)) => net.contains(&addr),
+            _ => false,
+        };
+    }
+
+    // Process names and hosts follow the session's matching, like -n and -H do
+    if matches!(field, QueryField::Process | QueryField::Host) {
+        return match op {
+            QueryOp::Contains => matching.matches(value, candidate),
+            _ => matching.matches_pattern(value, candidate),
+        };
+    }
+
+    match op {
+        QueryOp::Contains => candidate.contains(value),
+        // TCP states print in upper case; let `state=established` match
+        QueryOp::Eq if field == QueryField::State => candidate.eq_ignore_ascii_case(value),
+        QueryOp::Eq if field == QueryField::Scope => AddressScope::from_name(value).is_some_and(|scope| scope.as_str() == candidate),
+        // Direct traffic has no interface to match; `yes` and `no` test for one
+        QueryOp::Eq if field == QueryField::Tunnel => match value.to_ascii_lowercase().as_str() {
+            "yes" | "true" => !candidate.is_empty(),
+            "no" | "false" => candidate.is_empty(),
+            _ => wildcard_match(value, candidate),
+        },
+        _ => wildcard_match(value, candidate),
+    }
+}
+
+fn quote_value(value: &str) -> String {
+    let bare = !value.is_empty() && !value.contains(|c: char| c.is_whitespace() || "()\"=!~<>".contains(c));
+    let keyword = matches!(value.to_ascii_lowercase().as_str(), "and" | "or" | "not");
+    if bare && !keyword {
+        value.to_string()
+    } else {
+        format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    Quoted(String),
+    Op(QueryOp),
+    LParen,
+    RParen,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Word(word) => write!(f, "'{}'", word),
+            Token::Quoted(value) => write!(f, "\"{}\"", value),
+            Token::Op(op) => write!(f, "'{}'", op.as_str()),
+            Token::LParen => write!(f, "'('"),
+            Token::RParen => write!(f, "')'"),
+        }
+    }
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' => {
+                chars.next();
+                tokens.push(Token::LParen);
+            }
+            ')' => {
+                chars.next();
+                tokens.push(Token::RParen);
+            }
+            '"' => {
+                chars.next();
+                let mut value = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(escaped) => value.push(escaped),
+                            None => return Err("Unterminated string".to_string()),
+                        },
+                        Some(c) => value.push(c),
+                        None => return Err("Unterminated string".to_string()),
                     }
                 }
-            } else {
-                // No hostname, check IP address directly
-                let addr_str = conn.remote_addr.to_string();
-                if !addr_str.contains(host_filter) {
-                    return false;
+                tokens.push(Token::Quoted(value));
+            }
+            '=' | '~' | '<' | '>' | '!' => {
+                chars.next();
+                let followed_by_eq = chars.next_if_eq(&'=').is_some();
+                let op = match (c, followed_by_eq) {
+                    ('=', _) => QueryOp::Eq,
+                    ('~', false) => QueryOp::Contains,
+                    ('<', false) => QueryOp::Lt,
+                    ('<', true) => QueryOp::Le,
+                    ('>', false) => QueryOp::Gt,
+                    ('>', true) => QueryOp::Ge,
+                    ('!', true) => QueryOp::NotEq,
+                    ('!', false) if chars.next_if_eq(&'~').is_some() => QueryOp::NotContains,
+                    _ => return Err(format!("Unexpected '{}'", c)),
+                };
+                tokens.push(Token::Op(op));
+            }
+            _ => {
+                let mut word = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c.is_whitespace() || "()\"=~<>!".contains(c) {
+                        break;
+                    }
+                    word.push(c);
+                    chars.next();
                 }
+                tokens.push(Token::Word(word));
             }
         }
+    }
 
-        if let Some(port) = self.remote_port {
-            if conn.remote_port != port {
-                return false;
+    Ok(tokens)
+}
+
+/// Recursive-descent parser; `and` binds tighter than `or`, and adjacent terms are and-ed.
+struct QueryParser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl QueryParser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn peek_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Token::Word(word)) if word.eq_ignore_ascii_case(keyword))
+    }
+
+    fn parse_or(&mut self) -> Result<FilterExpr, String> {
+        let mut expr = self.parse_and()?;
+        while self.peek_keyword("or") {
+            self.pos += 1;
+            let right = self.parse_and()?;
+            expr = FilterExpr::Or(Box::new(expr), Box::new(right));
+        }
+        Ok(expr)
+    }
+
+    fn parse_and(&mut self) -> Result<FilterExpr, String> {
+        let mut expr = self.parse_unary()?;
+        loop {
+            if self.peek_keyword("and") {
+                self.pos += 1;
+            } else if self.peek().is_none() || self.peek_keyword("or") || self.peek() == Some(&Token::RParen) {
+                break;
             }
+            let right = self.parse_unary()?;
+            expr = FilterExpr::And(Box::new(expr), Box::new(right));
         }
+        Ok(expr)
+    }
 
-        // If we got here, all specified filters matched
-        true
+    fn parse_unary(&mut self) -> Result<FilterExpr, String> {
+        if self.peek_keyword("not") {
+            self.pos += 1;
+            return Ok(FilterExpr::Not(Box::new(self.parse_unary()?)));
+        }
+
+        match self.next() {
+            Some(Token::LParen) => {
+                let expr = self.parse_or()?;
+                match self.next() {
+                    Some(Token::RParen) => Ok(expr),
+                    _ => Err("Missing ')'".to_string()),
+                }
+            }
+            Some(Token::Word(name)) => self.parse_comparison(&name),
+            Some(token) => Err(format!("Expected a field name, found {}", token)),
+            None => Err("Query ends early".to_string()),
+        }
+    }
+
+    fn parse_comparison(&mut self, name: &str) -> Result<FilterExpr, String> {
+        let field = QueryField::from_name(name).ok_or_else(|| {
+            format!("Unknown field '{}' (expected pid, proc, host, port, lport, group, state, net, scope, or tunnel)", name)
+        })?;
+
+        let op = match self.next() {
+            Some(Token::Op(op)) => op,
+            _ => return Err(format!("Expected an operator after '{}'", name)),
+        };
+
+        let value = match self.next() {
+            Some(Token::Word(value)) | Some(Token::Quoted(value)) => value,
+            _ => return Err(format!("Expected a value after '{}{}'", name, op.as_str())),
+        };
+
+        if (field.is_numeric() && op != QueryOp::Contains && op != QueryOp::NotContains)
+            && value.parse::<u64>().is_err()
+        {
+            return Err(format!("'{}' needs a number, found '{}'", field.as_str(), value));
+        }
+        if op.is_ordering() && !field.is_numeric() {
+            return Err(format!("'{}' only works with pid, port, and lport", op.as_str()));
+        }
+        if field == QueryField::Net {
+            if !matches!(op, QueryOp::Eq | QueryOp::NotEq) {
+                return Err("'net' only works with = and !=".to_string());
+            }
+            value.parse::<Cidr>()?;
+        }
+        if field == QueryField::Scope && AddressScope::from_name(&value).is_none() {
+            return Err(format!("Unknown scope '{}' (expected loopback, link-local, private, or public)", value));
+        }
+
+        Ok(FilterExpr::Compare { field, op, value })
     }
-} 
\ No newline at end of file
+}
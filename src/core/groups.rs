@@ -0,0 +1,67 @@
+use super::cidr::Cidr;
+use super::connection::Connection;
+use super::utils::wildcard_match;
+
+/// A named set of remote endpoints, e.g. "AWS" or "Postgres".
+///
+/// A connection belongs to the group when its remote address matches any of the
+/// host patterns or CIDRs (if any are given) and its remote port is one of the
+/// listed ports (if any are given).
+#[derive(Debug, Clone)]
+pub struct EndpointGroup {
+    pub name: String,
+    pub hosts: Vec<String>,
+    pub cidrs: Vec<Cidr>,
+    pub ports: Vec<u16>,
+}
+
+impl EndpointGroup {
+    pub fn new(name: String) -> Self {
+        Self {
+            name,
+            hosts: Vec::new(),
+            cidrs: Vec::new(),
+            ports: Vec::new(),
+        }
+    }
+
+    pub fn with_hosts(mut self, hosts: Vec<String>) -> Self {
+        self.hosts = hosts;
+        self
+    }
+
+    pub fn with_cidrs(mut self, cidrs: Vec<Cidr>) -> Self {
+        self.cidrs = cidrs;
+        self
+    }
+
+    pub fn with_ports(mut self, ports: Vec<u16>) -> Self {
+        self.ports = ports;
+        self
+    }
+
+    pub fn matches_connection(&self, conn: &Connection) -> bool {
+        if !self.ports.is_empty() && !self.ports.contains(&conn.remote_port) {
+            return false;
+        }
+
+        if self.hosts.is_empty() && self.cidrs.is_empty() {
+            return true;
+        }
+
+        let addr_str = conn.remote_addr.to_string();
+        let host_matches = self.hosts.iter().any(|pattern| {
+            conn.remote_hostname.as_deref().is_some_and(|hostname| wildcard_match(pattern, hostname))
+                || wildcard_match(pattern, &addr_str)
+        });
+
+        host_matches || self.cidrs.iter().any(|cidr| cidr.contains(&conn.remote_addr))
+    }
+}
+
+/// Returns the name of the first group matching the connection, in config order.
+pub fn classify_connection(groups: &[EndpointGroup], conn: &Connection) -> Option<String> {
+    groups.iter()
+        .find(|group| group.matches_connection(conn))
+        .map(|group| group.name.clone())
+}
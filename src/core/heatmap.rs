@@ -0,0 +1,108 @@
+use std::time::{Duration, SystemTime};
+
+use chrono::{DateTime, Local, NaiveDate, Timelike};
+
+use super::rollup::RollupSeries;
+
+/// How the heatmap's rows and columns divide time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HeatmapLayout {
+    /// One row per hour, one column per minute.
+    HoursByMinutes,
+    /// One row per day, one column per hour.
+    DaysByHours,
+}
+
+impl HeatmapLayout {
+    pub fn columns(&self) -> usize {
+        match self {
+            HeatmapLayout::HoursByMinutes => 60,
+            HeatmapLayout::DaysByHours => 24,
+        }
+    }
+
+    /// Widest rollup that still fits in one cell.
+    fn cell_width(&self) -> Duration {
+        match self {
+            HeatmapLayout::HoursByMinutes => Duration::from_secs(60),
+            HeatmapLayout::DaysByHours => Duration::from_secs(3600),
+        }
+    }
+}
+
+/// One row of cells, starting at the hour or day it covers.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HeatmapRow {
+    pub start: DateTime<Local>,
+    pub cells: Vec<u64>,
+}
+
+/// Totals of a sampled value per minute of each hour, or per hour of each day, in local time.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Heatmap {
+    pub layout: HeatmapLayout,
+    /// Oldest first, ending with the row `now` falls in.
+    pub rows: Vec<HeatmapRow>,
+    pub max: u64,
+}
+
+impl Heatmap {
+    /// Adds up `series` into the last `rows` rows up to `now`. Rollups wider than a cell,
+    /// such as hour rollups on the minute layout, are left out.
+    pub fn build(series: &RollupSeries, layout: HeatmapLayout, now: DateTime<Local>, rows: usize) -> Self {
+        let mut grid = vec![vec![0u64; layout.columns()]; rows];
+        let points = series.rollups()
+            .filter(|rollup| rollup.width <= layout.cell_width())
+            .map(|rollup| (rollup.start, rollup.sum))
+            .chain(series.raw().copied());
+        for (time, value) in points {
+            if let Some((row, column)) = cell_for(layout, now, time, rows) {
+                grid[row][column] += value;
+            }
+        }
+
+        let max = grid.iter().flatten().copied().max().unwrap_or(0);
+        let rows = grid.into_iter().enumerate()
+            .map(|(idx, cells)| HeatmapRow { start: row_start(layout, now, rows - 1 - idx), cells })
+            .collect();
+        Self { layout, rows, max }
+    }
+}
+
+/// Row and column of `time`, or `None` if it is older than the first row or after `now`.
+fn cell_for(layout: HeatmapLayout, now: DateTime<Local>, time: SystemTime, rows: usize) -> Option<(usize, usize)> {
+    let time = DateTime::<Local>::from(time);
+    if time > now {
+        return None;
+    }
+    let (back, column) = match layout {
+        HeatmapLayout::HoursByMinutes => {
+            let back = (hour_start(now) - hour_start(time)).num_hours();
+            (back, time.minute() as usize)
+        }
+        HeatmapLayout::DaysByHours => {
+            let back = (now.date_naive() - time.date_naive()).num_days();
+            (back, time.hour() as usize)
+        }
+    };
+    let back = usize::try_from(back).ok().filter(|back| *back < rows)?;
+    Some((rows - 1 - back, column))
+}
+
+fn row_start(layout: HeatmapLayout, now: DateTime<Local>, back: usize) -> DateTime<Local> {
+    match layout {
+        HeatmapLayout::HoursByMinutes => hour_start(now) - chrono::Duration::hours(back as i64),
+        HeatmapLayout::DaysByHours => {
+            let date = now.date_naive() - chrono::Duration::days(back as i64);
+            day_start(date).unwrap_or(now)
+        }
+    }
+}
+
+fn hour_start(time: DateTime<Local>) -> DateTime<Local> {
+    time.with_minute(0).and_then(|t| t.with_second(0)).and_then(|t| t.with_nanosecond(0)).unwrap_or(time)
+}
+
+fn day_start(date: NaiveDate) -> Option<DateTime<Local>> {
+    date.and_hms_opt(0, 0, 0)?.and_local_timezone(Local).earliest()
+}
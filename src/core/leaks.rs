@@ -0,0 +1,128 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::time::{Duration, SystemTime};
+
+/// A process and the remote host and port it connects to.
+pub type ProcessHostKey = (u32, String, u16);
+
+/// How long and how often active counts must grow before a pair is flagged.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LeakOptions {
+    pub window: Duration,
+    pub interval: Duration,
+}
+
+impl Default for LeakOptions {
+    fn default() -> Self {
+        Self {
+            window: Duration::from_secs(15 * 60),
+            interval: Duration::from_secs(60),
+        }
+    }
+}
+
+impl LeakOptions {
+    /// Samples needed to cover the window: one per interval plus the starting point.
+    fn samples(&self) -> usize {
+        (self.window.as_secs_f64() / self.interval.as_secs_f64()).ceil() as usize + 1
+    }
+}
+
+/// A process-host pair that was just flagged as a probable leak.
+#[derive(Debug, Clone)]
+pub struct LeakAlert {
+    pub pid: u32,
+    pub process_name: String,
+    pub host: String,
+    pub port: u16,
+    /// Active connections when flagged.
+    pub active: usize,
+    /// Growth in active connections over the window.
+    pub growth: usize,
+    pub window: Duration,
+}
+
+impl fmt::Display for LeakAlert {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let secs = self.window.as_secs();
+        let window = if secs.is_multiple_of(3600) {
+            format!("{}h", secs / 3600)
+        } else if secs.is_multiple_of(60) {
+            format!("{}m", secs / 60)
+        } else {
+            format!("{}s", secs)
+        };
+        write!(f, "Possible connection leak: {}({}) to {}:{} has {} active connections, up {} in {}",
+            self.process_name, self.pid, self.host, self.port, self.active, self.growth, window)
+    }
+}
+
+/// Flags process-host pairs whose active connection count grew at every interval
+/// over the whole window, a typical sign of connections that are never closed.
+#[derive(Debug)]
+pub struct LeakDetector {
+    options: LeakOptions,
+    last_sample: Option<SystemTime>,
+    history: HashMap<ProcessHostKey, VecDeque<usize>>,
+    flagged: HashSet<ProcessHostKey>,
+}
+
+impl LeakDetector {
+    pub fn new(options: LeakOptions) -> Self {
+        Self {
+            options,
+            last_sample: None,
+            history: HashMap::new(),
+            flagged: HashSet::new(),
+        }
+    }
+
+    pub fn options(&self) -> LeakOptions {
+        self.options
+    }
+
+    pub fn reset(&mut self) {
+        self.last_sample = None;
+        self.history.clear();
+        self.flagged.clear();
+    }
+
+    pub fn is_flagged(&self, key: &ProcessHostKey) -> bool {
+        self.flagged.contains(key)
+    }
+
+    /// Records the active counts once per interval. Returns the newly flagged pairs
+    /// with their current count and growth over the window.
+    pub fn update(&mut self, now: SystemTime, active: &HashMap<ProcessHostKey, usize>) -> Vec<(ProcessHostKey, usize, usize)> {
+        if let Some(last) = self.last_sample {
+            if now.duration_since(last).unwrap_or_default() < self.options.interval {
+                return Vec::new();
+            }
+        }
+        self.last_sample = Some(now);
+
+        // Pairs that no longer have active connections stop being tracked
+        self.history.retain(|key, _| active.contains_key(key));
+        self.flagged.retain(|key| active.contains_key(key));
+
+        let samples = self.options.samples();
+        let mut newly_flagged = Vec::new();
+        for (key, count) in active {
+            let history = self.history.entry(key.clone()).or_default();
+            history.push_back(*count);
+            if history.len() > samples {
+                history.pop_front();
+            }
+
+            let growing = history.len() == samples &&
+                history.iter().zip(history.iter().skip(1)).all(|(before, after)| after > before);
+            if !growing {
+                self.flagged.remove(key);
+            } else if self.flagged.insert(key.clone()) {
+                let growth = count - history.front().copied().unwrap_or(0);
+                newly_flagged.push((key.clone(), *count, growth));
+            }
+        }
+        newly_flagged
+    }
+}
@@ -0,0 +1,97 @@
+use std::fmt;
+
+/// Most connections the kernel can track on Linux, past which new ones are dropped.
+const CONNTRACK_MAX_PATH: &str = "/proc/sys/net/netfilter/nf_conntrack_max";
+
+/// Share of the ceiling warned at when no count is given.
+const DEFAULT_WARN_PERCENT: usize = 80;
+
+/// Share of the warning count the total has to fall under before it can alert again, so a
+/// count hovering at the limit doesn't alert every sample.
+const REARM_RATIO: f64 = 0.9;
+
+/// System-wide connection counts to warn at and measure against.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct LimitOptions {
+    /// Active connections across the system that raise an alert.
+    pub warn_at: Option<usize>,
+    /// Most connections the host can hold, e.g. the conntrack table size or file descriptor limit.
+    pub ceiling: Option<usize>,
+}
+
+impl LimitOptions {
+    /// Fills in the ceiling from the kernel's conntrack table size when not given, and
+    /// the warning count from the ceiling. `None` when there is nothing to watch.
+    pub fn resolve(self) -> Option<Self> {
+        let ceiling = self.ceiling.or_else(conntrack_max);
+        let warn_at = self.warn_at.or_else(|| ceiling.map(|ceiling| ceiling * DEFAULT_WARN_PERCENT / 100));
+        (warn_at.is_some() || ceiling.is_some()).then_some(Self { warn_at, ceiling })
+    }
+
+    /// Share of the ceiling `active` takes up, in percent.
+    pub fn percent_of_ceiling(&self, active: usize) -> Option<f64> {
+        self.ceiling.filter(|ceiling| *ceiling > 0).map(|ceiling| active as f64 * 100.0 / ceiling as f64)
+    }
+}
+
+/// The kernel's conntrack table size, where it can be read.
+fn conntrack_max() -> Option<usize> {
+    if !cfg!(target_os = "linux") {
+        return None;
+    }
+    std::fs::read_to_string(CONNTRACK_MAX_PATH).ok()?.trim().parse().ok()
+}
+
+/// The system-wide connection count just reached the warning count.
+#[derive(Debug, Clone)]
+pub struct LimitAlert {
+    pub active: usize,
+    pub warn_at: usize,
+    pub ceiling: Option<usize>,
+}
+
+impl fmt::Display for LimitAlert {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "System-wide connections at {}, over the warning level of {}", self.active, self.warn_at)?;
+        if let Some(ceiling) = self.ceiling.filter(|ceiling| *ceiling > 0) {
+            write!(f, " ({:.0}% of {})", self.active as f64 * 100.0 / ceiling as f64, ceiling)?;
+        }
+        Ok(())
+    }
+}
+
+/// Watches the active connection count across the system for the warning count.
+#[derive(Debug)]
+pub struct LimitWatch {
+    options: LimitOptions,
+    alerted: bool,
+}
+
+impl LimitWatch {
+    pub fn new(options: LimitOptions) -> Self {
+        Self { options, alerted: false }
+    }
+
+    pub fn options(&self) -> LimitOptions {
+        self.options
+    }
+
+    /// Takes the latest count, returning an alert when it reaches the warning count after
+    /// having been clear of it.
+    pub fn update(&mut self, active: usize) -> Option<LimitAlert> {
+        let warn_at = self.options.warn_at?;
+        if active >= warn_at {
+            if !self.alerted {
+                self.alerted = true;
+                return Some(LimitAlert { active, warn_at, ceiling: self.options.ceiling });
+            }
+        } else if (active as f64) < warn_at as f64 * REARM_RATIO {
+            self.alerted = false;
+        }
+        None
+    }
+
+    pub fn reset(&mut self) {
+        self.alerted = false;
+    }
+}
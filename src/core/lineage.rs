@@ -0,0 +1,97 @@
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, SystemTime};
+
+/// How long a PID that went away can still be succeeded by a new one.
+const SUCCESSION_WINDOW: Duration = Duration::from_secs(600);
+
+/// Links a PID that went away to the next new PID running the same executable, so a
+/// process restarted by a supervisor keeps one lineage of PIDs.
+#[derive(Debug, Default)]
+pub struct ProcessLineage {
+    /// The PID each restarted PID replaced.
+    predecessor: HashMap<u32, u32>,
+    successor: HashMap<u32, u32>,
+    /// PIDs running each executable at the last sample.
+    live: HashMap<String, HashSet<u32>>,
+    /// PIDs per executable that went away without a successor yet, oldest first.
+    gone: HashMap<String, Vec<(u32, SystemTime)>>,
+}
+
+impl ProcessLineage {
+    /// Takes the PIDs running now with their executables; returns each new PID linked
+    /// to the one it replaced.
+    pub fn observe<'a>(&mut self, running: impl Iterator<Item = (u32, &'a str)>, now: SystemTime) -> Vec<(u32, u32)> {
+        let mut live: HashMap<String, HashSet<u32>> = HashMap::new();
+        for (pid, exe) in running {
+            live.entry(exe.to_string()).or_default().insert(pid);
+        }
+
+        for (exe, pids) in &self.live {
+            let running = live.get(exe);
+            let gone = self.gone.entry(exe.clone()).or_default();
+            gone.extend(pids.iter()
+                .filter(|pid| running.is_none_or(|running| !running.contains(pid)))
+                .map(|pid| (*pid, now)));
+        }
+        for gone in self.gone.values_mut() {
+            gone.retain(|(_, at)| now.duration_since(*at).unwrap_or_default() <= SUCCESSION_WINDOW);
+        }
+        self.gone.retain(|_, gone| !gone.is_empty());
+
+        let mut links = Vec::new();
+        for (exe, pids) in &live {
+            let known = self.live.get(exe);
+            let mut started: Vec<u32> = pids.iter()
+                .filter(|pid| known.is_none_or(|known| !known.contains(pid)))
+                .copied()
+                .collect();
+            started.sort_unstable();
+            for pid in started {
+                // A reused PID starts a lineage of its own
+                self.forget(pid);
+                // The most recent PID to go away is the one restarted
+                let Some((previous, _)) = self.gone.get_mut(exe).and_then(Vec::pop) else {
+                    break;
+                };
+                self.predecessor.insert(pid, previous);
+                self.successor.insert(previous, pid);
+                links.push((pid, previous));
+            }
+        }
+
+        self.live = live;
+        links
+    }
+
+    /// The newest PID in `pid`'s lineage.
+    pub fn latest(&self, pid: u32) -> u32 {
+        let mut latest = pid;
+        while let Some(&next) = self.successor.get(&latest) {
+            latest = next;
+        }
+        latest
+    }
+
+    /// `pid` and the PIDs it replaced, newest first.
+    pub fn ancestors(&self, pid: u32) -> impl Iterator<Item = u32> + '_ {
+        std::iter::successors(Some(pid), |pid| self.predecessor.get(pid).copied())
+    }
+
+    /// How many times `pid`'s lineage restarted before reaching it.
+    pub fn restarts(&self, pid: u32) -> usize {
+        self.ancestors(pid).count() - 1
+    }
+
+    fn forget(&mut self, pid: u32) {
+        if let Some(next) = self.successor.remove(&pid) {
+            self.predecessor.remove(&next);
+        }
+        if let Some(previous) = self.predecessor.remove(&pid) {
+            self.successor.remove(&previous);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+}
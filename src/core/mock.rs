@@ -0,0 +1,238 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::backlog::{AcceptQueue, ListenerSample};
+use super::provider::SocketProvider;
+use super::retransmits::TcpStats;
+use super::sample::{ProcessSample, SamplePass, SocketSample, parse_tcp_state};
+use crate::error::Result;
+
+/// Replays socket opens, state changes and closes from a script, one step per refresh,
+/// so monitor logic can be tested without a live network.
+///
+/// Scripts have one command per line; `#` starts a comment:
+///
+/// ```text
+/// open <name> pid=<pid> remote=<ip:port> [process=<name>] [exe=<path>] [host=<hostname>] [lport=<port>] [state=<STATE>] [inbound=true] [tunnel=<iface>] [nat=<ip:port>] [proxied=true] [segs=<n>] [retrans=<n>] [udp=true] [age=<secs>]
+/// state <name> <STATE>
+/// stats <name> segs=<n> retrans=<n>
+/// host <name> <hostname>
+/// close <name>
+/// listen port=<port> queued=<n> backlog=<n> [pid=<pid>] [process=<name>]
+/// tick
+/// ```
+///
+/// `tick` ends a step. Names only identify sockets within the script, a `listen` replaces
+/// the listener of its port and `age` is how many seconds ago the owner started. Once the
+/// script is used up every refresh returns the last state again.
+pub struct ScriptedSockets {
+    steps: VecDeque<Vec<Command>>,
+    open: BTreeMap<String, SocketSample>,
+    listeners: BTreeMap<u16, ListenerSample>,
+}
+
+#[derive(Debug, Clone)]
+enum Command {
+    Open(String, Box<SocketSample>),
+    State(String, String),
+    Host(String, String),
+    Stats(String, TcpStats),
+    Close(String),
+    Listen(ListenerSample),
+}
+
+impl ScriptedSockets {
+    pub fn parse(script: &str) -> std::result::Result<Self, String> {
+        let mut steps = VecDeque::new();
+        let mut step = Vec::new();
+        let mut next_local_port = 40000;
+
+        for (idx, line) in script.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let err = |message: String| format!("line {}: {}", idx + 1, message);
+            let words: Vec<&str> = line.split_whitespace().collect();
+            match words.as_slice() {
+                ["tick"] => steps.push_back(std::mem::take(&mut step)),
+                ["open", name, options @ ..] => {
+                    let sample = parse_open(options, &mut next_local_port).map_err(err)?;
+                    step.push(Command::Open(name.to_string(), Box::new(sample)));
+                }
+                ["state", name, state] => step.push(Command::State(name.to_string(), state.to_string())),
+                ["host", name, hostname] => step.push(Command::Host(name.to_string(), hostname.to_string())),
+                ["stats", name, options @ ..] => step.push(Command::Stats(name.to_string(), parse_stats(options).map_err(err)?)),
+                ["close", name] => step.push(Command::Close(name.to_string())),
+                ["listen", options @ ..] => step.push(Command::Listen(parse_listen(options).map_err(err)?)),
+                _ => return Err(err(format!("unknown command '{}'", line))),
+            }
+        }
+        if !step.is_empty() {
+            steps.push_back(step);
+        }
+
+        Ok(Self { steps, open: BTreeMap::new(), listeners: BTreeMap::new() })
+    }
+
+    /// Steps not replayed yet.
+    pub fn remaining_steps(&self) -> usize {
+        self.steps.len()
+    }
+}
+
+fn parse_open(options: &[&str], next_local_port: &mut u16) -> std::result::Result<SocketSample, String> {
+    let mut pid = None;
+    let mut remote: Option<SocketAddr> = None;
+    let mut process = None;
+    let mut exe = None;
+    let mut host = None;
+    let mut local_port = None;
+    let mut state = "ESTABLISHED".to_string();
+    let mut inbound = false;
+    let mut tunnel = None;
+    let mut nat_from = None;
+    let mut proxied = false;
+    let mut tcp_stats = None;
+    let mut udp = false;
+    let mut started_at = None;
+
+    for option in options {
+        let (key, value) = option.split_once('=')
+            .ok_or_else(|| format!("expected key=value, got '{}'", option))?;
+        match key {
+            "pid" => pid = Some(value.parse::<u32>().map_err(|_| format!("invalid pid '{}'", value))?),
+            "remote" => remote = Some(value.parse().map_err(|_| format!("invalid remote address '{}'", value))?),
+            "process" => process = Some(value.to_string()),
+            "exe" => exe = Some(value.to_string()),
+            "host" => host = Some(value.to_string()),
+            "lport" => local_port = Some(value.parse::<u16>().map_err(|_| format!("invalid lport '{}'", value))?),
+            "state" => state = value.to_string(),
+            "inbound" => inbound = value.parse().map_err(|_| format!("invalid inbound '{}', expected true or false", value))?,
+            "tunnel" => tunnel = Some(value.to_string()),
+            "nat" => nat_from = Some(value.parse().map_err(|_| format!("invalid nat address '{}'", value))?),
+            "proxied" => proxied = value.parse().map_err(|_| format!("invalid proxied '{}', expected true or false", value))?,
+            "age" => {
+                let age = value.parse::<u64>().map_err(|_| format!("invalid age '{}'", value))?;
+                let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+                started_at = Some(now.saturating_sub(age));
+            }
+            "udp" => udp = value.parse().map_err(|_| format!("invalid udp '{}', expected true or false", value))?,
+            "segs" => tcp_stats.get_or_insert_with(TcpStats::default).segments_out = value.parse().map_err(|_| format!("invalid segs '{}'", value))?,
+            "retrans" => tcp_stats.get_or_insert_with(TcpStats::default).retransmits = value.parse().map_err(|_| format!("invalid retrans '{}'", value))?,
+            _ => return Err(format!("unknown option '{}'", key)),
+        }
+    }
+
+    let remote = remote.ok_or("open needs remote=<ip:port>")?;
+    let local_port = local_port.unwrap_or_else(|| {
+        *next_local_port += 1;
+        *next_local_port
+    });
+    Ok(SocketSample {
+        pid: pid.ok_or("open needs pid=<pid>")?,
+        local_port,
+        remote_addr: remote.ip(),
+        remote_port: remote.port(),
+        remote_hostname: host,
+        state: parse_tcp_state(&state),
+        process: process.map(|name| ProcessSample { name, exe, memory_usage: 0, started_at }),
+        inbound,
+        tunnel,
+        nat_from,
+        proxied,
+        threads: None,
+        inode: None,
+        fd: None,
+        local_addr: None,
+        tcp_stats,
+        udp,
+    })
+}
+
+fn parse_stats(options: &[&str]) -> std::result::Result<TcpStats, String> {
+    let mut stats = TcpStats::default();
+    for option in options {
+        let (key, value) = option.split_once('=')
+            .ok_or_else(|| format!("expected key=value, got '{}'", option))?;
+        let value = value.parse::<u32>().map_err(|_| format!("invalid {} '{}'", key, value))?;
+        match key {
+            "segs" => stats.segments_out = value,
+            "retrans" => stats.retransmits = value,
+            _ => return Err(format!("unknown option '{}'", key)),
+        }
+    }
+    Ok(stats)
+}
+
+fn parse_listen(options: &[&str]) -> std::result::Result<ListenerSample, String> {
+    let mut port = None;
+    let mut queued = None;
+    let mut backlog = None;
+    let mut pid = None;
+    let mut process_name = None;
+
+    for option in options {
+        let (key, value) = option.split_once('=')
+            .ok_or_else(|| format!("expected key=value, got '{}'", option))?;
+        let number = |what: &str| value.parse::<u32>().map_err(|_| format!("invalid {} '{}'", what, value));
+        match key {
+            "port" => port = Some(value.parse::<u16>().map_err(|_| format!("invalid port '{}'", value))?),
+            "queued" => queued = Some(number("queued")?),
+            "backlog" => backlog = Some(number("backlog")?),
+            "pid" => pid = Some(number("pid")?),
+            "process" => process_name = Some(value.to_string()),
+            _ => return Err(format!("unknown option '{}'", key)),
+        }
+    }
+
+    Ok(ListenerSample {
+        port: port.ok_or("listen needs port=<port>")?,
+        local_addr: IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+        pid,
+        process_name,
+        queue: AcceptQueue {
+            queued: queued.ok_or("listen needs queued=<n>")?,
+            backlog: backlog.ok_or("listen needs backlog=<n>")?,
+        },
+    })
+}
+
+impl SocketProvider for ScriptedSockets {
+    fn sample(&mut self) -> Result<SamplePass> {
+        for command in self.steps.pop_front().unwrap_or_default() {
+            match command {
+                Command::Open(name, sample) => {
+                    self.open.insert(name, *sample);
+                }
+                Command::State(name, state) => {
+                    if let Some(sample) = self.open.get_mut(&name) {
+                        sample.state = parse_tcp_state(&state);
+                    }
+                }
+                Command::Host(name, hostname) => {
+                    if let Some(sample) = self.open.get_mut(&name) {
+                        sample.remote_hostname = Some(hostname);
+                    }
+                }
+                Command::Stats(name, stats) => {
+                    if let Some(sample) = self.open.get_mut(&name) {
+                        sample.tcp_stats = Some(stats);
+                    }
+                }
+                Command::Close(name) => {
+                    self.open.remove(&name);
+                }
+                Command::Listen(listener) => {
+                    self.listeners.insert(listener.port, listener);
+                }
+            }
+        }
+        Ok(SamplePass {
+            sockets: self.open.values().cloned().collect(),
+            listeners: self.listeners.values().cloned().collect(),
+            ..SamplePass::default()
+        })
+    }
+}
@@ -3,3 +3,39 @@ pub mod process;
 pub mod monitor;
 pub mod filters;
 pub mod utils;
+pub mod cidr;
+pub mod aliases;
+pub mod scope;
+pub mod tunnel;
+pub mod conntrack;
+pub mod proxy;
+pub mod threads;
+pub mod groups;
+pub mod watchlist;
+pub mod events;
+pub mod snapshot;
+pub mod sample;
+pub mod resolver;
+pub mod dns;
+pub mod leaks;
+pub mod backlog;
+pub mod retransmits;
+#[cfg(target_os = "linux")]
+mod sock_diag;
+pub mod anomaly;
+pub mod churn;
+pub mod limits;
+pub mod restarts;
+pub mod lineage;
+pub mod annotations;
+pub mod blocklist;
+pub mod overhead;
+pub mod pacing;
+pub mod counters;
+pub mod rollup;
+pub mod script;
+pub mod views;
+pub mod heatmap;
+pub mod provider;
+#[cfg(test)]
+pub mod mock;
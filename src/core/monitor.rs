@@ -1,24 +1,92 @@
-use std::collections::{HashMap, HashSet};
-use std::time::SystemTime;
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+use std::net::IpAddr;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
-use netstat2::{get_sockets_info, AddressFamilyFlags, ProtocolFlags, ProtocolSocketInfo, TcpState};
-use sysinfo::{System, RefreshKind, Pid, ProcessStatus, ProcessRefreshKind, ProcessesToUpdate};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
 
 use super::connection::Connection;
+use super::aliases::HostAliases;
+use super::blocklist::{BlocklistAlert, Blocklists};
+use super::counters::{ConcurrencyCounter, ConcurrencyHistogram, CounterCheck, RecentMax, SampledConcurrency, WindowedMax};
+use super::scope::{AddressScope, ScopeCounts};
+use super::events::{ConnectionEvent, ConnectionEventKind};
 use super::process::Process;
-use super::utils::resolve_addr_to_hostname;
-use super::filters::ConnectionFilter;
+use super::provider::{LocalSockets, SocketProvider};
+use super::sample::{ProcessSample, SocketSample};
+use super::filters::{ConnectionFilter, RowAttributes};
+use super::sample::parse_tcp_state;
+use super::snapshot::{ConnectionView, FullSnapshot, MetricsSnapshot};
+use super::groups::{EndpointGroup, classify_connection};
+use super::backlog::{AcceptQueue, BacklogAlert, BacklogOptions, BacklogWatch, ListenerSample, somaxconn};
+use super::churn::{ChurnAlert, ChurnOptions, ChurnTracker};
+use super::limits::{LimitAlert, LimitOptions, LimitWatch};
+use super::restarts::{ProcessRestart, RestartTracker};
+use super::retransmits::{RetransmitAlert, RetransmitOptions, RetransmitWatch, Retransmits};
+use super::lineage::ProcessLineage;
+use super::annotations::{Annotation, AnnotationKind, Annotations};
+use super::rollup::{RollupOptions, RollupSeries};
+use super::script::ScriptHooks;
+use super::views::{AggregationView, GROUP_VIEW, ViewTracker, builtin_views};
+use super::heatmap::{Heatmap, HeatmapLayout};
+use super::overhead::{OverheadStats, OverheadTracker};
+use super::anomaly::{AnomalyDetector, AnomalyKey, AnomalyOptions};
+use super::leaks::{LeakAlert, LeakDetector, LeakOptions, ProcessHostKey};
+use crate::error::{Error, Result};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HostMetrics {
     pub host: String,
     pub port: u16,
     pub current_connections: usize,
     pub total_connections: usize,
     pub max_concurrent: usize,
+    /// When the earliest connection counted here was first seen.
+    pub first_seen: DateTime<Utc>,
+    /// When a connection counted here was last seen open; `None` in snapshots that don't
+    /// record it.
+    #[serde(default)]
+    pub last_seen: Option<DateTime<Utc>>,
+    /// Z-score of the active count against the learned baseline, when it is unusual.
+    pub anomaly_score: Option<f64>,
+    /// Blocklist the host is on, if any.
+    #[serde(default)]
+    pub blocklist: Option<String>,
+    #[serde(default)]
+    pub scope: AddressScope,
+    /// Tunnel interface the connections go over, if any.
+    #[serde(default)]
+    pub tunnel: Option<String>,
+    /// Address the connections were made to before destination NAT, if translated.
+    #[serde(default)]
+    pub nat: Option<String>,
+    /// Whether the connections go to a proxy set in the process environment.
+    #[serde(default)]
+    pub proxied: bool,
+    /// Value of the script's `host_column` hook, if any.
+    #[serde(default)]
+    pub script: Option<String>,
+    /// Max concurrent over the last minute, five minutes and hour.
+    #[serde(default)]
+    pub recent_max: RecentMax,
+    /// Average and p95 concurrent over the samples since the first connection.
+    #[serde(default)]
+    pub sampled: SampledConcurrency,
+    /// Distinct remote addresses the counted connections went to; 0 when not recorded.
+    #[serde(default)]
+    pub remote_ips: usize,
+    /// Distinct local ports the counted connections used; 0 when not recorded.
+    #[serde(default)]
+    pub local_ports: usize,
+    /// Segments sent and retransmitted over the counted connections, when reading `tcp_info`.
+    #[serde(default)]
+    pub retransmits: Option<Retransmits>,
+    /// Whether the retransmission rate is past the alert threshold.
+    #[serde(default)]
+    pub high_retransmits: bool,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProcessMetrics {
     pub pid: u32,
     pub name: String,
@@ -26,9 +94,42 @@ pub struct ProcessMetrics {
     pub total_connections: usize,
     pub max_concurrent: usize,
     pub is_alive: bool,
+    pub first_seen: DateTime<Utc>,
+    /// Resident memory of the process in bytes, as last sampled.
+    pub memory_usage: u64,
+    pub anomaly_score: Option<f64>,
+    /// Max concurrent over the last minute, five minutes and hour.
+    #[serde(default)]
+    pub recent_max: RecentMax,
+    /// Average and p95 concurrent over the samples since the first connection.
+    #[serde(default)]
+    pub sampled: SampledConcurrency,
+    /// Earlier PIDs running the same executable that this one replaced.
+    #[serde(default)]
+    pub restarts: usize,
+    /// When the process was last seen running, if it has exited.
+    #[serde(default)]
+    pub exited_at: Option<DateTime<Utc>>,
+    /// Value of the script's `process_column` hook, if any.
+    #[serde(default)]
+    pub script: Option<String>,
+    /// Open ESTABLISHED connections idle for at least the idle threshold.
+    #[serde(default)]
+    pub idle: usize,
+    /// Distinct remote host and port pairs the counted connections went to; 0 when not
+    /// recorded.
+    #[serde(default)]
+    pub hosts: usize,
+    /// When the process started, as the OS reports it.
+    #[serde(default)]
+    pub started_at: Option<DateTime<Utc>>,
+    /// Connections counted per minute the process has been running while watched, so
+    /// long-running processes compare fairly with new ones.
+    #[serde(default)]
+    pub per_minute: f64,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProcessHostMetrics {
     pub pid: u32,
     pub process_name: String,
@@ -38,184 +139,875 @@ pub struct ProcessHostMetrics {
     pub total_connections: usize,
     pub max_concurrent: usize,
     pub is_alive: bool,
+    pub first_seen: DateTime<Utc>,
+    pub memory_usage: u64,
+    /// Active connections kept growing over the leak detection window.
+    pub leak_suspected: bool,
+    /// Connections opened per concurrent connection over the churn window.
+    pub churn: f64,
+    /// Churn is above the alert threshold.
+    pub high_churn: bool,
+    /// Blocklist the host is on, if any.
+    #[serde(default)]
+    pub blocklist: Option<String>,
+    #[serde(default)]
+    pub scope: AddressScope,
+    /// Tunnel interface the connections go over, if any.
+    #[serde(default)]
+    pub tunnel: Option<String>,
+    /// Address the connections were made to before destination NAT, if translated.
+    #[serde(default)]
+    pub nat: Option<String>,
+    /// Whether the connections go to a proxy set in the process environment.
+    #[serde(default)]
+    pub proxied: bool,
+    /// When the process was last seen running, if it has exited.
+    #[serde(default)]
+    pub exited_at: Option<DateTime<Utc>>,
 }
 
+/// A row of the group table, or of another aggregation view with its key in `group`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GroupMetrics {
+    pub group: String,
+    pub current_connections: usize,
+    pub total_connections: usize,
+    pub max_concurrent: usize,
+    pub first_seen: DateTime<Utc>,
+}
+
+/// Connections accepted on one local listening port.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListenPortMetrics {
+    pub port: u16,
+    /// Names of the processes that accepted them, sorted.
+    pub processes: Vec<String>,
+    pub current_connections: usize,
+    pub total_connections: usize,
+    pub max_concurrent: usize,
+    /// Client addresses with the most connections, most first.
+    pub top_clients: Vec<(IpAddr, usize)>,
+    pub first_seen: DateTime<Utc>,
+    /// Fullest accept queue of the port's listening sockets (Linux).
+    #[serde(default)]
+    pub accept_queue: Option<AcceptQueue>,
+    /// Whether the accept queue is past the backlog alert threshold.
+    #[serde(default)]
+    pub queue_saturated: bool,
+}
+
+/// Client addresses listed per listening port.
+const TOP_CLIENTS: usize = 3;
+
+/// Connections accepted from one remote client address.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClientMetrics {
+    pub addr: IpAddr,
+    pub hostname: Option<String>,
+    /// Local listening ports the client connected to, sorted.
+    pub ports: Vec<u16>,
+    pub current_connections: usize,
+    pub total_connections: usize,
+    pub max_concurrent: usize,
+    pub first_seen: DateTime<Utc>,
+    /// When the client's most recent connection was last seen open.
+    pub last_seen: DateTime<Utc>,
+}
+
+/// Everything known about one remote host and port, for the host detail view.
+#[derive(Debug, Clone)]
+pub struct HostDetail {
+    pub processes: Vec<ProcessHostMetrics>,
+    /// Connections to the host, open ones first, then the most recently closed.
+    pub connections: Vec<Connection>,
+    /// Distinct remote addresses seen behind the hostname.
+    pub addresses: Vec<IpAddr>,
+    /// The host's row, for its concurrency stats; `None` if nothing matches the filter.
+    pub metrics: Option<HostMetrics>,
+    /// Most recent opens and closes, oldest first.
+    pub events: Vec<ConnectionEvent>,
+}
+
+/// Overall counts for the connections matching a filter.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct ConnectionSummary {
+    pub active_connections: usize,
+    pub total_connections: usize,
+    pub max_concurrent: usize,
+    /// Connections, open or closed, to endpoints on a blocklist.
+    #[serde(default)]
+    pub blocklisted: usize,
+    /// Active connections by where their remote address is.
+    #[serde(default)]
+    pub active_by_scope: ScopeCounts,
+    /// Active connections going over a VPN or other tunnel interface.
+    #[serde(default)]
+    pub active_tunneled: usize,
+    /// Distinct remote host:port endpoints among the connections.
+    #[serde(default)]
+    pub unique_hosts: usize,
+    /// Distinct processes among the connections.
+    #[serde(default)]
+    pub unique_processes: usize,
+    /// Connections opened over the last minute, leaving out those already open at the
+    /// first sample; scaled up while less than a minute has been sampled.
+    #[serde(default)]
+    pub opened_per_minute: f64,
+    /// Average lifetime of the closed connections, if any closed.
+    #[serde(default)]
+    pub avg_duration_secs: Option<f64>,
+}
+
+#[derive(Default)]
 pub struct ConnectionMetrics {
-    pub total_connections_by_pid: HashMap<u32, usize>,
-    pub max_concurrent_by_pid: HashMap<u32, usize>,
-    pub current_concurrent_by_pid: HashMap<u32, usize>,
-    pub total_connections_by_host: HashMap<String, usize>,
-    pub max_concurrent_by_host: HashMap<String, usize>,
-    pub current_concurrent_by_host: HashMap<String, usize>,
-    pub total_connections_by_process_host: HashMap<(u32, String, u16), usize>,
-    pub max_concurrent_by_process_host: HashMap<(u32, String, u16), usize>,
-    pub current_concurrent_by_process_host: HashMap<(u32, String, u16), usize>,
-    pub memory_history: HashMap<u32, Vec<(SystemTime, u64)>>,
+    pub by_pid: ConcurrencyCounter<u32>,
+    pub by_host: ConcurrencyCounter<String>,
+    pub by_process_host: ConcurrencyCounter<(u32, String, u16)>,
+    pub by_group: ConcurrencyCounter<String>,
+    pub by_listen_port: ConcurrencyCounter<u16>,
+    pub by_client: ConcurrencyCounter<IpAddr>,
+    pub recent_by_pid: WindowedMax<u32>,
+    pub recent_by_host: WindowedMax<String>,
+    pub sampled_by_pid: ConcurrencyHistogram<u32>,
+    pub sampled_by_host: ConcurrencyHistogram<String>,
+    pub memory_history: HashMap<u32, RollupSeries>,
+    /// Active connections at each sample, kept past `sample_timestamps` as rollups.
+    pub active_history: RollupSeries,
+    /// Connections opened at each sample, for the activity heatmap.
+    pub opens_history: RollupSeries,
     pub sample_timestamps: Vec<SystemTime>,
 }
 
+/// Number of open/close events kept for the event log.
+const MAX_EVENTS: usize = 1000;
+
+/// Time without a state change after which an established connection counts as idle.
+pub const DEFAULT_IDLE_THRESHOLD: Duration = Duration::from_secs(300);
+
 pub struct ConnectionMonitor {
     connections: HashMap<u64, Connection>,
     historical_connections: Vec<Connection>,
+    events: VecDeque<ConnectionEvent>,
+    next_event_seq: u64,
     processes: HashMap<u32, Process>,
-    system_info: System,
+    groups: Vec<EndpointGroup>,
+    aliases: HostAliases,
+    sampled_pids: HashSet<u32>,
+    provider: Box<dyn SocketProvider>,
     last_refresh: SystemTime,
+    leaks: Option<LeakDetector>,
+    leak_alerts: Vec<LeakAlert>,
+    anomalies: Option<AnomalyDetector>,
+    churn: Option<ChurnTracker>,
+    churn_alerts: Vec<ChurnAlert>,
+    limits: Option<LimitWatch>,
+    limit_alerts: Vec<LimitAlert>,
+    /// Fullest listening socket per local port at the last sample.
+    listeners: HashMap<u16, ListenerSample>,
+    backlog: Option<BacklogWatch>,
+    backlog_alerts: Vec<BacklogAlert>,
+    retransmits: Option<RetransmitWatch>,
+    retransmit_alerts: Vec<RetransmitAlert>,
+    restarts: Option<RestartTracker>,
+    restart_alerts: Vec<ProcessRestart>,
+    annotations: Annotations,
+    lineage: ProcessLineage,
+    /// Whether process rows add up every PID of a restarted process.
+    carry_restarts: bool,
+    /// How long exited processes stay in the process views; `None` keeps them.
+    exited_retention: Option<Duration>,
+    /// How long host and process rows without open connections stay in the views; `None` keeps them.
+    inactive_retention: Option<Duration>,
+    /// How long an established connection goes without a state change to count as idle.
+    idle_threshold: Duration,
+    rollup_options: RollupOptions,
+    blocklists: Blocklists,
+    blocklist_alerts: Option<Vec<BlocklistAlert>>,
+    /// Process and address pairs already alerted on, so each is reported once.
+    blocklist_alerted: HashSet<(u32, IpAddr)>,
+    script: Option<ScriptHooks>,
+    script_alerts: Vec<String>,
+    /// Aggregation views, in the order they are cycled through.
+    views: Vec<ViewTracker>,
+    errors: Vec<Error>,
+    revision: u64,
+    started: DateTime<Utc>,
+    samples: u64,
+    /// When the first sample since the start or last reset was taken.
+    first_sample: Option<SystemTime>,
+    overhead: OverheadTracker,
+    offline: Option<MetricsSnapshot>,
     pub metrics: ConnectionMetrics,
 }
 
 impl ConnectionMonitor {
+    /// Monitors this machine's sockets, starting with a first sample.
     pub fn new() -> Self {
-        let refresh_kind = RefreshKind::nothing().with_processes(ProcessRefreshKind::everything());
-        let sys = System::new_with_specifics(refresh_kind);
-        
+        let mut instance = Self::with_provider(Box::new(LocalSockets::new()));
+        if let Err(e) = instance.refresh() {
+            instance.errors.push(e);
+        }
+        instance
+    }
+
+    /// Monitors sockets read from `provider`. Nothing is sampled until the first refresh.
+    pub fn with_provider(provider: Box<dyn SocketProvider>) -> Self {
         let mut instance = Self {
             connections: HashMap::new(),
             historical_connections: Vec::new(),
+            events: VecDeque::new(),
+            next_event_seq: 0,
             processes: HashMap::new(),
-            system_info: sys,
+            groups: Vec::new(),
+            aliases: HostAliases::default(),
+            sampled_pids: HashSet::new(),
+            provider,
             last_refresh: SystemTime::now(),
-            metrics: ConnectionMetrics {
-                total_connections_by_pid: HashMap::new(),
-                max_concurrent_by_pid: HashMap::new(),
-                current_concurrent_by_pid: HashMap::new(),
-                total_connections_by_host: HashMap::new(),
-                max_concurrent_by_host: HashMap::new(),
-                current_concurrent_by_host: HashMap::new(),
-                total_connections_by_process_host: HashMap::new(),
-                max_concurrent_by_process_host: HashMap::new(),
-                current_concurrent_by_process_host: HashMap::new(),
-                memory_history: HashMap::new(),
-                sample_timestamps: Vec::new(),
-            },
+            leaks: Some(LeakDetector::new(LeakOptions::default())),
+            leak_alerts: Vec::new(),
+            anomalies: None,
+            churn: Some(ChurnTracker::new(ChurnOptions::default())),
+            churn_alerts: Vec::new(),
+            limits: None,
+            limit_alerts: Vec::new(),
+            listeners: HashMap::new(),
+            backlog: None,
+            backlog_alerts: Vec::new(),
+            retransmits: None,
+            retransmit_alerts: Vec::new(),
+            restarts: None,
+            restart_alerts: Vec::new(),
+            annotations: Annotations::default(),
+            lineage: ProcessLineage::default(),
+            carry_restarts: false,
+            exited_retention: None,
+            inactive_retention: None,
+            idle_threshold: DEFAULT_IDLE_THRESHOLD,
+            rollup_options: RollupOptions::default(),
+            blocklists: Blocklists::default(),
+            blocklist_alerts: None,
+            blocklist_alerted: HashSet::new(),
+            script: None,
+            script_alerts: Vec::new(),
+            views: Vec::new(),
+            errors: Vec::new(),
+            revision: 0,
+            started: Utc::now(),
+            samples: 0,
+            first_sample: None,
+            overhead: OverheadTracker::new(),
+            offline: None,
+            metrics: ConnectionMetrics::default(),
         };
-        
-        instance.refresh().ok();
+        for view in builtin_views() {
+            instance.add_view(view);
+        }
         instance
     }
 
     pub fn reset(&mut self) {
         self.connections.clear();
         self.historical_connections.clear();
+        self.events.clear();
 
-        self.metrics = ConnectionMetrics {
-            total_connections_by_pid: HashMap::new(),
-            max_concurrent_by_pid: HashMap::new(),
-            current_concurrent_by_pid: HashMap::new(),
-            total_connections_by_host: HashMap::new(),
-            max_concurrent_by_host: HashMap::new(),
-            current_concurrent_by_host: HashMap::new(),
-            total_connections_by_process_host: HashMap::new(),
-            max_concurrent_by_process_host: HashMap::new(),
-            current_concurrent_by_process_host: HashMap::new(),
-            memory_history: HashMap::new(),
-            sample_timestamps: Vec::new(),
-        };
+        self.metrics = ConnectionMetrics::default();
         self.processes.clear();
         self.last_refresh = SystemTime::now();
+        self.started = Utc::now();
+        self.samples = 0;
+        self.first_sample = None;
+        if let Some(leaks) = self.leaks.as_mut() {
+            leaks.reset();
+        }
+        self.leak_alerts.clear();
+        if let Some(anomalies) = self.anomalies.as_mut() {
+            anomalies.reset();
+        }
+        if let Some(churn) = self.churn.as_mut() {
+            churn.reset();
+        }
+        self.churn_alerts.clear();
+        if let Some(limits) = self.limits.as_mut() {
+            limits.reset();
+        }
+        self.limit_alerts.clear();
+        self.listeners.clear();
+        if let Some(backlog) = self.backlog.as_mut() {
+            backlog.reset();
+        }
+        self.backlog_alerts.clear();
+        if let Some(retransmits) = self.retransmits.as_mut() {
+            retransmits.reset();
+        }
+        self.retransmit_alerts.clear();
+        self.restart_alerts.clear();
+        self.annotations.clear();
+        self.lineage.clear();
+        if let Some(alerts) = self.blocklist_alerts.as_mut() {
+            alerts.clear();
+        }
+        self.blocklist_alerted.clear();
+        self.script_alerts.clear();
+        for tracker in &mut self.views {
+            tracker.reset();
+        }
+        self.revision += 1;
     }
 
-    pub fn refresh(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        let now = SystemTime::now();
-        
-        let af_flags = AddressFamilyFlags::IPV4 | AddressFamilyFlags::IPV6;
-        let proto_flags = ProtocolFlags::TCP;
-        let sockets_info = get_sockets_info(af_flags, proto_flags)?;
-        
-        let current_socket_info: Vec<_> = sockets_info.into_iter()
-            .filter(|si| {
-                if let ProtocolSocketInfo::Tcp(tcp_si) = &si.protocol_socket_info { 
-                    tcp_si.state != TcpState::Listen
+    /// Forgets closed connections but keeps the open ones, the graph history and the event
+    /// log. Totals and max restart from the connections open now.
+    pub fn clear_closed(&mut self) {
+        self.connections.retain(|_, conn| !conn.closed);
+        self.historical_connections.clear();
+
+        let mut metrics = ConnectionMetrics {
+            memory_history: std::mem::take(&mut self.metrics.memory_history),
+            active_history: std::mem::take(&mut self.metrics.active_history),
+            opens_history: std::mem::take(&mut self.metrics.opens_history),
+            sample_timestamps: std::mem::take(&mut self.metrics.sample_timestamps),
+            ..ConnectionMetrics::default()
+        };
+        for conn in self.connections.values() {
+            metrics.by_pid.open(conn.pid);
+            metrics.by_host.open(conn.host_key());
+            metrics.by_process_host.open(conn.process_host_key());
+            if let Some(group) = &conn.group {
+                metrics.by_group.open(group.clone());
+            }
+            if conn.inbound {
+                metrics.by_listen_port.open(conn.local_port);
+                metrics.by_client.open(conn.remote_addr);
+            }
+        }
+        self.metrics = metrics;
+        for tracker in &mut self.views {
+            tracker.reset();
+        }
+        self.update_views();
+        self.revision += 1;
+    }
+
+    /// Replaces live data with a saved snapshot for offline analysis. Metrics come from the
+    /// snapshot as recorded; its active connections, if any, back the host details.
+    pub fn load_offline(&mut self, snapshot: FullSnapshot) {
+        self.reset();
+        for view in &snapshot.connections {
+            let Ok(remote_addr) = view.remote_addr.parse() else {
+                continue;
+            };
+            let mut conn = Connection::new(view.pid, view.local_port, view.remote_port, remote_addr,
+                view.remote_host.clone(), parse_tcp_state(&view.state), view.first_seen.into());
+            conn.group = view.group.clone();
+            conn.inbound = view.inbound;
+            conn.tunnel = view.tunnel.clone();
+            conn.nat_from = view.nat_from.as_deref().and_then(|addr| addr.parse().ok());
+            conn.proxied = view.proxied;
+            conn.last_seen = view.last_seen.into();
+            self.processes.entry(view.pid)
+                .or_insert_with(|| Process::new(view.pid, view.process.clone(), None, 0));
+            self.connections.insert(conn.id, conn);
+        }
+        self.started = snapshot.started.unwrap_or(snapshot.metrics.timestamp);
+        self.offline = Some(snapshot.metrics);
+        self.update_views();
+    }
+
+    /// Whether the monitor shows a loaded snapshot instead of live data.
+    pub fn is_offline(&self) -> bool {
+        self.offline.is_some()
+    }
+
+    /// When counting started: at creation, the last reset, or the start of a loaded recording.
+    pub fn started(&self) -> DateTime<Utc> {
+        self.started
+    }
+
+    /// Samples taken since counting started; unknown for a loaded snapshot, so 0.
+    pub fn samples(&self) -> u64 {
+        self.samples
+    }
+
+    /// The time the data is from: now, or when a loaded snapshot was taken.
+    pub fn as_of(&self) -> DateTime<Utc> {
+        self.offline.as_ref().map_or_else(Utc::now, |snapshot| snapshot.timestamp)
+    }
+
+    /// Looks up the real destination of connections that destination NAT sent elsewhere.
+    pub fn set_conntrack(&mut self, enabled: bool) {
+        self.provider.set_conntrack(enabled);
+    }
+
+    /// Marks connections that go to a proxy set in their process's environment.
+    pub fn set_proxy_detection(&mut self, enabled: bool) {
+        self.provider.set_proxy_detection(enabled);
+    }
+
+    /// Attributes each connection to the threads of its process holding the socket.
+    pub fn set_thread_attribution(&mut self, enabled: bool) {
+        self.provider.set_thread_attribution(enabled);
+    }
+
+    /// Sets how long resolved hostnames are reused. Open connections are renamed when a
+    /// later lookup gives a different name.
+    pub fn set_name_ttl(&mut self, ttl: Duration) {
+        self.provider.set_name_ttl(ttl);
+    }
+
+    /// Sets the churn window and alert rule, or turns churn tracking off with `None`.
+    pub fn set_churn_options(&mut self, options: Option<ChurnOptions>) {
+        self.churn = options.map(ChurnTracker::new);
+        self.churn_alerts.clear();
+    }
+
+    /// Sets the system-wide connection count to warn at and the ceiling it is measured
+    /// against, or stops watching with `None`.
+    pub fn set_limit_options(&mut self, options: Option<LimitOptions>) {
+        self.limits = options.map(LimitWatch::new);
+        self.limit_alerts.clear();
+    }
+
+    /// Sets the accept queue saturation that alerts, or turns backlog alerts off with `None`.
+    pub fn set_backlog_options(&mut self, options: Option<BacklogOptions>) {
+        self.backlog = options.map(BacklogWatch::new);
+        self.backlog_alerts.clear();
+    }
+
+    /// Reads each connection's retransmission counters from `tcp_info` (Linux).
+    pub fn set_tcp_info(&mut self, enabled: bool) {
+        self.provider.set_tcp_info(enabled);
+    }
+
+    /// Samples UDP sockets connected to a name server as connections to it (Linux).
+    pub fn set_dns(&mut self, enabled: bool) {
+        self.provider.set_dns(enabled);
+    }
+
+    /// Sets the retransmission rate that alerts, or turns retransmission alerts off with `None`.
+    pub fn set_retransmit_options(&mut self, options: Option<RetransmitOptions>) {
+        self.retransmits = options.map(RetransmitWatch::new);
+        self.retransmit_alerts.clear();
+    }
+
+    /// The system-wide warning count and ceiling being watched, if any.
+    pub fn limit_options(&self) -> Option<LimitOptions> {
+        self.limits.as_ref().map(LimitWatch::options)
+    }
+
+    /// Active connections across the whole system, whatever the filter.
+    pub fn system_active_connections(&self) -> usize {
+        self.connections.values().filter(|conn| !conn.closed).count()
+    }
+
+    /// Sets how long graph and memory history is kept at each resolution.
+    pub fn set_rollup_options(&mut self, options: RollupOptions) {
+        self.rollup_options = options;
+    }
+
+    /// Runs the hooks of a user script: custom groups, columns and alerts.
+    pub fn set_script(&mut self, script: Option<ScriptHooks>) {
+        self.script = script;
+        self.script_alerts.clear();
+    }
+
+    /// Adds an aggregation view, replacing any view of the same name.
+    pub fn add_view(&mut self, view: Box<dyn AggregationView>) {
+        let mut tracker = ViewTracker::new(view);
+        let open = self.connections.values()
+            .filter(|conn| !conn.closed)
+            .map(|conn| (conn, self.processes.get(&conn.pid)));
+        tracker.update(open);
+        match self.views.iter_mut().find(|existing| existing.view.name() == tracker.view.name()) {
+            Some(existing) => *existing = tracker,
+            None => self.views.push(tracker),
+        }
+    }
+
+    /// Names of the aggregation views, in the order they were added.
+    pub fn view_names(&self) -> Vec<String> {
+        self.views.iter().map(|tracker| tracker.view.name().to_string()).collect()
+    }
+
+    /// Header of the key column of the view named `name`.
+    pub fn view_column(&self, name: &str) -> Option<&str> {
+        self.views.iter().find(|tracker| tracker.view.name() == name).map(|tracker| tracker.view.column())
+    }
+
+    /// Messages of the script's `alert` hook raised since the last call.
+    pub fn take_script_alerts(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.script_alerts)
+    }
+
+    /// Churn alerts raised since the last call.
+    pub fn take_churn_alerts(&mut self) -> Vec<ChurnAlert> {
+        std::mem::take(&mut self.churn_alerts)
+    }
+
+    /// Saturated accept queues since the last call.
+    pub fn take_backlog_alerts(&mut self) -> Vec<BacklogAlert> {
+        std::mem::take(&mut self.backlog_alerts)
+    }
+
+    /// Hosts whose retransmission rate turned high since the last call.
+    pub fn take_retransmit_alerts(&mut self) -> Vec<RetransmitAlert> {
+        std::mem::take(&mut self.retransmit_alerts)
+    }
+
+    /// System-wide connection count alerts raised since the last call.
+    pub fn take_limit_alerts(&mut self) -> Vec<LimitAlert> {
+        std::mem::take(&mut self.limit_alerts)
+    }
+
+    /// Watches `names` for new PIDs and reports them as restarts; empty stops following.
+    pub fn set_followed_processes(&mut self, names: &[String]) {
+        self.restarts = (!names.is_empty()).then(|| RestartTracker::new(names));
+        self.restart_alerts.clear();
+    }
+
+    /// Restarts of followed processes since the last call.
+    pub fn take_restart_alerts(&mut self) -> Vec<ProcessRestart> {
+        std::mem::take(&mut self.restart_alerts)
+    }
+
+    /// Shows each restarted process as one row with the totals and max of all its PIDs,
+    /// under its newest PID.
+    pub fn set_carry_restarts(&mut self, carry: bool) {
+        self.carry_restarts = carry;
+        self.revision += 1;
+    }
+
+    /// Hides processes from the process views once they have been gone for `retention`;
+    /// zero hides them as soon as they exit, `None` keeps them.
+    pub fn set_exited_retention(&mut self, retention: Option<Duration>) {
+        self.exited_retention = retention;
+        self.revision += 1;
+    }
+
+    pub fn exited_retention(&self) -> Option<Duration> {
+        self.exited_retention
+    }
+
+    /// Hides host, process and process-host rows with no open connections once none has
+    /// been seen for `retention`; `None` shows them all. Their counts are kept.
+    pub fn set_inactive_retention(&mut self, retention: Option<Duration>) {
+        self.inactive_retention = retention;
+        self.revision += 1;
+    }
+
+    pub fn inactive_retention(&self) -> Option<Duration> {
+        self.inactive_retention
+    }
+
+    pub fn set_idle_threshold(&mut self, threshold: Duration) {
+        self.idle_threshold = threshold;
+        self.revision += 1;
+    }
+
+    pub fn idle_threshold(&self) -> Duration {
+        self.idle_threshold
+    }
+
+    /// Marks the current time on the connections graph.
+    pub fn annotate(&mut self, kind: AnnotationKind, text: String) {
+        self.annotations.add(kind, text, SystemTime::now());
+    }
+
+    /// Graph annotations still retained, oldest first.
+    pub fn get_annotations(&self) -> impl Iterator<Item = &Annotation> {
+        self.annotations.iter()
+    }
+
+    /// Turns on anomaly detection with the given options, or off with `None`.
+    pub fn set_anomaly_options(&mut self, options: Option<AnomalyOptions>) {
+        self.anomalies = options.map(AnomalyDetector::new);
+    }
+
+    /// Sets the leak detection window, or turns detection off with `None`.
+    pub fn set_leak_options(&mut self, options: Option<LeakOptions>) {
+        self.leaks = options.map(LeakDetector::new);
+        self.leak_alerts.clear();
+    }
+
+    /// Leaks flagged since the last call.
+    pub fn take_leak_alerts(&mut self) -> Vec<LeakAlert> {
+        std::mem::take(&mut self.leak_alerts)
+    }
+
+    /// Replaces the loaded blocklists and re-checks every known connection against them.
+    pub fn set_blocklists(&mut self, blocklists: Blocklists) {
+        self.blocklists = blocklists;
+        for conn in self.connections.values_mut().chain(self.historical_connections.iter_mut()) {
+            conn.blocklist = self.blocklists.find(&conn.remote_addr, conn.remote_hostname.as_deref())
+                .map(str::to_string);
+        }
+
+        let open: Vec<u64> = self.connections.values()
+            .filter(|conn| !conn.closed)
+            .map(|conn| conn.id)
+            .collect();
+        self.detect_blocklisted(&open);
+        self.revision += 1;
+    }
+
+    /// Turns alerts on connections to blocklisted endpoints on or off.
+    pub fn set_blocklist_alerts(&mut self, enabled: bool) {
+        self.blocklist_alerts = enabled.then(Vec::new);
+    }
+
+    /// Blocklist alerts raised since the last call.
+    pub fn take_blocklist_alerts(&mut self) -> Vec<BlocklistAlert> {
+        self.blocklist_alerts.as_mut().map(std::mem::take).unwrap_or_default()
+    }
+
+    /// Replaces the endpoint group rules and re-tags every known connection.
+    pub fn set_groups(&mut self, groups: Vec<EndpointGroup>) {
+        self.groups = groups;
+
+        self.metrics.by_group = ConcurrencyCounter::default();
+
+        for conn in self.connections.values_mut().chain(self.historical_connections.iter_mut()) {
+            conn.group = classify_connection(&self.groups, conn);
+        }
+
+        // Closed connections also live in `connections`, so only count them once
+        for conn in self.connections.values() {
+            if let Some(group) = &conn.group {
+                if conn.closed {
+                    self.metrics.by_group.count_closed(group.clone());
                 } else {
-                    false
+                    self.metrics.by_group.open(group.clone());
                 }
-            })
-            .collect();
+            }
+        }
+        self.revision += 1;
+    }
+
+    /// Moves an open connection to a new remote hostname, e.g. after its address was
+    /// resolved again to a different name. Its counts move from the old host rows to the
+    /// new ones, and its group and blocklist are checked again under the new name.
+    fn rename_connection(&mut self, conn_id: u64, hostname: String, resolved: Option<&str>) {
+        let Some(conn) = self.connections.get_mut(&conn_id) else {
+            return;
+        };
+        if conn.remote_hostname.as_deref() == Some(hostname.as_str()) {
+            return;
+        }
+
+        self.metrics.by_host.close(&conn.host_key());
+        self.metrics.by_process_host.close(&conn.process_host_key());
+        conn.remote_hostname = Some(hostname);
+        self.metrics.by_host.open(conn.host_key());
+        self.metrics.by_process_host.open(conn.process_host_key());
+
+        let group = classify_connection(&self.groups, conn);
+        if group != conn.group {
+            if let Some(old) = &conn.group {
+                self.metrics.by_group.close(old);
+            }
+            if let Some(new) = &group {
+                self.metrics.by_group.open(new.clone());
+            }
+            conn.group = group;
+        }
+        if !self.blocklists.is_empty() {
+            conn.blocklist = self.blocklists.find(&conn.remote_addr, resolved).map(str::to_string);
+        }
+        self.revision += 1;
+    }
+
+    /// Replaces the host aliases and renames every known connection they match. Host
+    /// counts are recounted under the new names, so their max restarts from now.
+    pub fn set_aliases(&mut self, aliases: HostAliases) {
+        self.aliases = aliases;
+
+        for conn in self.connections.values_mut().chain(self.historical_connections.iter_mut()) {
+            if let Some(alias) = self.aliases.name_for(&conn.remote_addr, conn.remote_hostname.as_deref()) {
+                conn.remote_hostname = Some(alias);
+            }
+        }
+
+        self.metrics.by_host = ConcurrencyCounter::default();
+        self.metrics.by_process_host = ConcurrencyCounter::default();
+        self.metrics.recent_by_host = WindowedMax::default();
+        self.metrics.sampled_by_host = ConcurrencyHistogram::default();
+        for conn in self.connections.values() {
+            if conn.closed {
+                self.metrics.by_host.count_closed(conn.host_key());
+                self.metrics.by_process_host.count_closed(conn.process_host_key());
+            } else {
+                self.metrics.by_host.open(conn.host_key());
+                self.metrics.by_process_host.open(conn.process_host_key());
+            }
+        }
+        self.revision += 1;
+    }
+
+    pub fn get_groups(&self) -> &[EndpointGroup] {
+        &self.groups
+    }
+
+    /// Samples local sockets. Problems that didn't stop the pass are kept for `take_errors`.
+    pub fn refresh(&mut self) -> Result<()> {
+        let started = Instant::now();
+        let pass = self.provider.sample()?;
+        self.errors.extend(pass.errors);
+        let sockets = pass.sockets.len();
+        self.apply_samples(pass.sockets);
+        self.apply_listeners(pass.listeners);
+        self.overhead.record(started.elapsed(), sockets, pass.dns_lookups, pass.dns_time);
+        Ok(())
+    }
+
+    /// Applies samples reported by remote agents; they did their own DNS lookups.
+    pub fn apply_remote_samples(&mut self, samples: Vec<SocketSample>) {
+        let started = Instant::now();
+        let sockets = samples.len();
+        self.apply_samples(samples);
+        self.overhead.record(started.elapsed(), sockets, 0, Duration::ZERO);
+    }
+
+    /// tcpcount's own resource use during the last refresh.
+    pub fn overhead(&self) -> &OverheadStats {
+        self.overhead.stats()
+    }
+
+    /// The live concurrency counters next to a recount of the open connections they
+    /// should agree with. Empty when analyzing a file, which has no live counters.
+    pub fn counter_checks(&self) -> Vec<CounterCheck> {
+        if self.is_offline() {
+            return Vec::new();
+        }
+        let open: Vec<&Connection> = self.connections.values().filter(|conn| !conn.closed).collect();
+        let grouped = open.iter().filter(|conn| conn.group.is_some()).count();
+        let inbound = open.iter().filter(|conn| conn.inbound).count();
+        vec![
+            CounterCheck::new("process", &self.metrics.by_pid, open.len()),
+            CounterCheck::new("host", &self.metrics.by_host, open.len()),
+            CounterCheck::new("process-host", &self.metrics.by_process_host, open.len()),
+            CounterCheck::new("group", &self.metrics.by_group, grouped),
+            CounterCheck::new("listen-port", &self.metrics.by_listen_port, inbound),
+            CounterCheck::new("client", &self.metrics.by_client, inbound),
+        ]
+    }
+
+    /// Counter bumped whenever connections open, close or change state, so callers can
+    /// tell whether anything they show may have changed.
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// Recoverable errors from sampling since the last call.
+    pub fn take_errors(&mut self) -> Vec<Error> {
+        std::mem::take(&mut self.errors)
+    }
+    
+    /// Updates connections and metrics from one sampling pass, whether local or from a remote agent.
+    pub fn apply_samples(&mut self, samples: Vec<SocketSample>) {
+        let now = SystemTime::now();
         
         let mut seen_connections = HashSet::new();
-        
-        self.system_info.refresh_processes(ProcessesToUpdate::All, true);
+        let mut opened_connections = Vec::new();
+        self.sampled_pids.clear();
         
         // Process current connections
-        for si in current_socket_info {
-            if let ProtocolSocketInfo::Tcp(tcp_si) = &si.protocol_socket_info {
-                if si.associated_pids.is_empty() {
-                    continue;
-                }
-                
-                let pid = si.associated_pids[0];
-                let remote_hostname = resolve_addr_to_hostname(tcp_si.remote_addr);
-                
-                let conn_exists = self.connections.iter().find(|(_, conn)| {
-                    conn.pid == pid &&
-                    conn.local_port == tcp_si.local_port &&
-                    conn.remote_addr == tcp_si.remote_addr &&
-                    conn.remote_port == tcp_si.remote_port
-                });
-                
-                match conn_exists {
-                    Some((id, _)) => {
-                        let conn_id = *id;
-                        seen_connections.insert(conn_id);
-                        
-                        if let Some(conn) = self.connections.get_mut(&conn_id) {
-                            conn.update_state(tcp_si.state);
+        for sample in samples {
+            let pid = sample.pid;
+            self.sampled_pids.insert(pid);
+            let remote_hostname = self.aliases.name_for(&sample.remote_addr, sample.remote_hostname.as_deref())
+                .or_else(|| sample.remote_hostname.clone());
+            
+            // A closed connection's 4-tuple can be reused, which is a new connection
+            let conn_exists = self.connections.iter().find(|(_, conn)| {
+                !conn.closed &&
+                conn.pid == pid &&
+                conn.local_port == sample.local_port &&
+                conn.remote_addr == sample.remote_addr &&
+                conn.remote_port == sample.remote_port &&
+                conn.udp == sample.udp
+            });
+            
+            match conn_exists {
+                Some((id, _)) => {
+                    let conn_id = *id;
+                    seen_connections.insert(conn_id);
+                    
+                    if let Some(conn) = self.connections.get_mut(&conn_id) {
+                        if conn.state != sample.state {
+                            self.revision += 1;
                         }
-                    },
-                    None => {
-                        let new_conn = Connection::new(
-                            pid,
-                            tcp_si.local_port,
-                            tcp_si.remote_port,
-                            tcp_si.remote_addr,
-                            remote_hostname.clone(),
-                            tcp_si.state,
-                        );
-                        
-                        seen_connections.insert(new_conn.id);
-                        self.connections.insert(new_conn.id, new_conn);
-                        
-                        *self.metrics.total_connections_by_pid.entry(pid).or_insert(0) += 1;
-                        *self.metrics.current_concurrent_by_pid.entry(pid).or_insert(0) += 1;
-                        
-                        let current_count = self.metrics.current_concurrent_by_pid[&pid];
-                        let max_entry = self.metrics.max_concurrent_by_pid.entry(pid).or_insert(0);
-                        if current_count > *max_entry {
-                            *max_entry = current_count;
+                        conn.update_state(sample.state, now);
+                        if sample.threads.is_some() {
+                            conn.threads = sample.threads.clone();
                         }
-                        
-                        // Update host metrics
-                        if let Some(hostname) = &remote_hostname {
-                            let host_key = format!("{}:{}", hostname, tcp_si.remote_port);
-                            *self.metrics.total_connections_by_host.entry(host_key.clone()).or_insert(0) += 1;
-                            *self.metrics.current_concurrent_by_host.entry(host_key.clone()).or_insert(0) += 1;
-                            
-                            let current_host_count = self.metrics.current_concurrent_by_host[&host_key];
-                            let max_host_entry = self.metrics.max_concurrent_by_host.entry(host_key).or_insert(0);
-                            if current_host_count > *max_host_entry {
-                                *max_host_entry = current_host_count;
-                            }
+                        if sample.fd.is_some() {
+                            conn.fd = sample.fd;
                         }
-                        
-                        // Update process-host combination metrics
-                        if let Some(hostname) = &remote_hostname {
-                            let process_host_key = (pid, hostname.clone(), tcp_si.remote_port);
-                            *self.metrics.total_connections_by_process_host.entry(process_host_key.clone()).or_insert(0) += 1;
-                            *self.metrics.current_concurrent_by_process_host.entry(process_host_key.clone()).or_insert(0) += 1;
-                            
-                            let current_ph_count = self.metrics.current_concurrent_by_process_host[&process_host_key];
-                            let max_ph_entry = self.metrics.max_concurrent_by_process_host.entry(process_host_key).or_insert(0);
-                            if current_ph_count > *max_ph_entry {
-                                *max_ph_entry = current_ph_count;
-                            }
+                        if sample.tcp_stats.is_some() {
+                            conn.tcp_stats = sample.tcp_stats;
                         }
                     }
+                    // A lookup that gave nothing this time keeps the name already known
+                    if let Some(hostname) = remote_hostname {
+                        self.rename_connection(conn_id, hostname, sample.remote_hostname.as_deref());
+                    }
+                },
+                None => {
+                    let mut new_conn = Connection::new(
+                        pid,
+                        sample.local_port,
+                        sample.remote_port,
+                        sample.remote_addr,
+                        remote_hostname.clone(),
+                        sample.state,
+                        now,
+                    );
+                    new_conn.group = classify_connection(&self.groups, &new_conn);
+                    if new_conn.group.is_none() {
+                        new_conn.group = self.script_group(&new_conn, sample.process.as_ref().map(|p| p.name.clone()));
+                    }
+                    new_conn.inbound = sample.inbound;
+                    new_conn.tunnel = sample.tunnel.clone();
+                    new_conn.nat_from = sample.nat_from;
+                    new_conn.proxied = sample.proxied;
+                    new_conn.threads = sample.threads.clone();
+                    new_conn.inode = sample.inode;
+                    new_conn.fd = sample.fd;
+                    new_conn.local_addr = sample.local_addr;
+                    new_conn.tcp_stats = sample.tcp_stats;
+                    new_conn.udp = sample.udp;
+                    if !self.blocklists.is_empty() {
+                        new_conn.blocklist = self.blocklists.find(&sample.remote_addr, sample.remote_hostname.as_deref())
+                            .map(str::to_string);
+                    }
+                    let group = new_conn.group.clone();
+                    self.metrics.by_pid.open(pid);
+                    self.metrics.by_host.open(new_conn.host_key());
+                    self.metrics.by_process_host.open(new_conn.process_host_key());
+                    
+                    seen_connections.insert(new_conn.id);
+                    opened_connections.push(new_conn.id);
+                    self.connections.insert(new_conn.id, new_conn);
+                    
+                    if let Some(group) = group {
+                        self.metrics.by_group.open(group);
+                    }
+                    if sample.inbound {
+                        self.metrics.by_listen_port.open(sample.local_port);
+                        self.metrics.by_client.open(sample.remote_addr);
+                    }
                 }
-                
-                // Update process information
-                self.update_process_info(pid);
+            }
+            
+            // Update process information
+            if let Some(process) = &sample.process {
+                self.update_process_info(pid, process);
             }
         }
         
+        // Record opens once process info is up to date so events carry the process name
+        for conn_id in &opened_connections {
+            self.record_event(ConnectionEventKind::Opened, *conn_id);
+        }
+        
         let to_close: Vec<u64> = self.connections.iter()
             .filter(|(id, conn)| !seen_connections.contains(id) && !conn.closed)
             .map(|(id, _)| *id)
@@ -225,56 +1017,389 @@ impl ConnectionMonitor {
             if let Some(conn) = self.connections.get_mut(&conn_id) {
                 conn.mark_closed();
                 
-                *self.metrics.current_concurrent_by_pid.entry(conn.pid).or_insert(1) -= 1;
-                
-                if let Some(hostname) = &conn.remote_hostname {
-                    let host_key = format!("{}:{}", hostname, conn.remote_port);
-                    *self.metrics.current_concurrent_by_host.entry(host_key).or_insert(1) -= 1;
-                    
-                    // Update process-host combination metrics
-                    let process_host_key = (conn.pid, hostname.clone(), conn.remote_port);
-                    *self.metrics.current_concurrent_by_process_host.entry(process_host_key).or_insert(1) -= 1;
+                self.metrics.by_pid.close(&conn.pid);
+                self.metrics.by_host.close(&conn.host_key());
+                self.metrics.by_process_host.close(&conn.process_host_key());
+                if let Some(group) = &conn.group {
+                    self.metrics.by_group.close(group);
+                }
+                if conn.inbound {
+                    self.metrics.by_listen_port.close(&conn.local_port);
+                    self.metrics.by_client.close(&conn.remote_addr);
                 }
                 
                 // Move to historical connections
                 let conn_clone = conn.clone();
                 self.historical_connections.push(conn_clone);
             }
+            
+            self.record_event(ConnectionEventKind::Closed, conn_id);
+        }
+        
+        self.detect_leaks(now);
+        self.detect_anomalies(now);
+        self.detect_churn(now, &opened_connections);
+        self.check_limits();
+        self.detect_retransmits();
+        self.detect_blocklisted(&opened_connections);
+        self.detect_restarts(now);
+        self.track_lineage(now);
+        self.mark_alive(now);
+        self.update_views();
+        self.run_script_alert();
+
+        self.metrics.recent_by_pid.record(self.metrics.by_pid.current_counts(), now);
+        self.metrics.recent_by_host.record(self.metrics.by_host.current_counts(), now);
+        self.metrics.sampled_by_pid.record(self.metrics.by_pid.current_counts());
+        self.metrics.sampled_by_host.record(self.metrics.by_host.current_counts());
+
+        // Store the timestamp for historical analysis
+        self.metrics.sample_timestamps.push(now);
+        
+        // Trim timestamp history if it gets too large (keep last 1000 points)
+        if self.metrics.sample_timestamps.len() > 1000 {
+            self.metrics.sample_timestamps.remove(0);
+        }
+
+        let active = self.connections.values().filter(|conn| !conn.closed).count();
+        self.metrics.active_history.push(now, active as u64);
+        self.metrics.active_history.compact(now, &self.rollup_options);
+        self.metrics.opens_history.push(now, opened_connections.len() as u64);
+        self.metrics.opens_history.compact(now, &self.rollup_options);
+        for history in self.metrics.memory_history.values_mut() {
+            history.compact(now, &self.rollup_options);
+        }
+        self.metrics.memory_history.retain(|_, history| !history.is_empty());
+        
+        self.last_refresh = now;
+        self.first_sample.get_or_insert(now);
+        self.samples += 1;
+    }
+    
+    /// The group the script's `group` hook gives a connection no configured group matched.
+    fn script_group(&mut self, conn: &Connection, process: Option<String>) -> Option<String> {
+        let script = self.script.as_ref()?;
+        script.group(&ConnectionView::new(conn, process)).unwrap_or_else(|e| {
+            self.errors.push(Error::Other(format!("Script {}", e)));
+            None
+        })
+    }
+
+    fn update_views(&mut self) {
+        let connections = &self.connections;
+        let processes = &self.processes;
+        for tracker in &mut self.views {
+            let open = connections.values()
+                .filter(|conn| !conn.closed)
+                .map(|conn| (conn, processes.get(&conn.pid)));
+            tracker.update(open);
+        }
+    }
+
+    fn run_script_alert(&mut self) {
+        if self.script.is_none() {
+            return;
+        }
+        let summary = self.get_summary(&ConnectionFilter::default());
+        let Some(script) = self.script.as_mut() else {
+            return;
+        };
+        match script.alert(&summary) {
+            Ok(Some(message)) => self.script_alerts.push(message),
+            Ok(None) => {}
+            Err(e) => self.errors.push(Error::Other(format!("Script {}", e))),
+        }
+    }
+
+    fn detect_restarts(&mut self, now: SystemTime) {
+        let Some(tracker) = self.restarts.as_mut() else {
+            return;
+        };
+        let active = self.provider.running_pids().into_iter()
+            .chain(self.sampled_pids.iter().copied())
+            .collect::<HashSet<u32>>();
+        let running = active.iter()
+            .filter_map(|pid| self.processes.get(pid).and_then(|p| p.name.as_deref()).map(|name| (*pid, name)));
+        for restart in tracker.observe(running, now) {
+            self.annotations.add(AnnotationKind::Restart, restart.to_string(), restart.at);
+            self.restart_alerts.push(restart);
+        }
+    }
+
+    /// Links new PIDs to the PIDs running the same executable that just went away.
+    fn track_lineage(&mut self, now: SystemTime) {
+        let active = self.get_active_pids();
+        let running = active.iter()
+            .filter_map(|pid| self.processes.get(pid).and_then(|p| p.exe.as_deref()).map(|exe| (*pid, exe)));
+        if !self.lineage.observe(running, now).is_empty() {
+            self.revision += 1;
+        }
+    }
+
+    fn mark_alive(&mut self, now: SystemTime) {
+        for pid in self.get_active_pids() {
+            if let Some(process) = self.processes.get_mut(&pid) {
+                process.last_alive = now;
+            }
+        }
+    }
+
+    /// When `pid` was last seen running, if it has exited.
+    fn exited_at(&self, pid: u32, active_pids: &HashSet<u32>) -> Option<SystemTime> {
+        if active_pids.contains(&pid) {
+            return None;
+        }
+        self.get_process(pid).map(|process| process.last_alive)
+    }
+
+    /// `total` connections per minute a process started at `started_at` ran until `until`,
+    /// counting only the time since counting started and at least a minute.
+    fn per_minute_running(&self, total: usize, started_at: Option<SystemTime>, until: SystemTime) -> f64 {
+        let watched_from = SystemTime::from(self.started);
+        let since = started_at.map_or(watched_from, |started| started.max(watched_from));
+        let minutes = until.duration_since(since).unwrap_or_default().as_secs_f64() / 60.0;
+        total as f64 / minutes.max(1.0)
+    }
+
+    /// Whether a row with `current` open connections, last seen at `last_seen`, is past the
+    /// retention for inactive rows.
+    fn is_inactive_pruned(&self, current: usize, last_seen: SystemTime) -> bool {
+        match self.inactive_retention {
+            Some(retention) => current == 0 && last_seen.elapsed().unwrap_or_default() >= retention,
+            None => false,
+        }
+    }
+
+    /// Whether a process that exited at `exited_at` is past the retention for exited processes.
+    fn is_exit_pruned(&self, exited_at: Option<SystemTime>) -> bool {
+        match (self.exited_retention, exited_at) {
+            (Some(retention), Some(at)) => at.elapsed().unwrap_or_default() >= retention,
+            _ => false,
+        }
+    }
+
+    /// The PID a connection's process row is counted under.
+    fn process_row_pid(&self, pid: u32) -> u32 {
+        if self.carry_restarts {
+            self.lineage.latest(pid)
+        } else {
+            pid
+        }
+    }
+
+    /// Active connections per process-host pair.
+    fn active_by_process_host(&self) -> HashMap<ProcessHostKey, usize> {
+        let mut active: HashMap<ProcessHostKey, usize> = HashMap::new();
+        for conn in self.connections.values().filter(|conn| !conn.closed) {
+            *active.entry(conn.process_host_key()).or_insert(0) += 1;
+        }
+        active
+    }
+
+    fn process_name_or_unknown(&self, pid: u32) -> String {
+        self.processes.get(&pid)
+            .and_then(|p| p.name.clone())
+            .unwrap_or_else(|| "Unknown".to_string())
+    }
+
+    fn detect_leaks(&mut self, now: SystemTime) {
+        if self.leaks.is_none() {
+            return;
+        }
+        let active = self.active_by_process_host();
+        let Some(leaks) = self.leaks.as_mut() else {
+            return;
+        };
+
+        let window = leaks.options().window;
+        for ((pid, host, port), active, growth) in leaks.update(now, &active) {
+            let process_name = self.process_name_or_unknown(pid);
+            self.leak_alerts.push(LeakAlert { pid, process_name, host, port, active, growth, window });
+        }
+    }
+
+    fn detect_churn(&mut self, now: SystemTime, opened_connections: &[u64]) {
+        if self.churn.is_none() {
+            return;
+        }
+        let active = self.active_by_process_host();
+        let opened: Vec<ProcessHostKey> = opened_connections.iter()
+            .filter_map(|id| self.connections.get(id))
+            .map(Connection::process_host_key)
+            .collect();
+        let Some(churn) = self.churn.as_mut() else {
+            return;
+        };
+
+        for ((pid, host, port), churn, opened) in churn.update(now, &opened, &active) {
+            let process_name = self.process_name_or_unknown(pid);
+            self.churn_alerts.push(ChurnAlert { pid, process_name, host, port, churn, opened });
+        }
+    }
+
+    /// Keeps the fullest listening socket per port and alerts on ports whose accept queue
+    /// just became saturated.
+    fn apply_listeners(&mut self, listeners: Vec<ListenerSample>) {
+        self.listeners.clear();
+        for listener in listeners {
+            let fuller = self.listeners.get(&listener.port)
+                .is_none_or(|current| listener.queue.saturation() > current.queue.saturation());
+            if fuller {
+                self.listeners.insert(listener.port, listener);
+            }
+        }
+
+        let queues: HashMap<u16, AcceptQueue> = self.listeners.iter()
+            .map(|(port, listener)| (*port, listener.queue))
+            .collect();
+        let Some(saturated) = self.backlog.as_mut().map(|backlog| backlog.update(&queues)) else {
+            return;
+        };
+        let somaxconn = if saturated.is_empty() { None } else { somaxconn() };
+        for port in saturated {
+            let listener = &self.listeners[&port];
+            let name = listener.process_name.clone()
+                .or_else(|| listener.pid.map(|pid| self.process_name_or_unknown(pid)))
+                .unwrap_or_else(|| "Unknown".to_string());
+            let process = match listener.pid {
+                Some(pid) => format!("{}({})", name, pid),
+                None => name,
+            };
+            self.backlog_alerts.push(BacklogAlert {
+                port,
+                process,
+                queue: listener.queue,
+                capped: somaxconn == Some(listener.queue.backlog),
+            });
+        }
+    }
+
+    /// Alerts on hosts whose connections retransmit more than the threshold, summed over
+    /// every connection to them with `tcp_info` counters.
+    fn detect_retransmits(&mut self) {
+        if self.retransmits.is_none() {
+            return;
+        }
+        let mut hosts: HashMap<String, Retransmits> = HashMap::new();
+        let counted = self.connections.values()
+            .filter(|conn| !conn.closed)
+            .chain(self.historical_connections.iter());
+        for conn in counted {
+            if let Some(stats) = conn.tcp_stats {
+                hosts.entry(conn.host_key()).or_default().add(stats);
+            }
+        }
+        let Some(watch) = self.retransmits.as_mut() else {
+            return;
+        };
+        for host in watch.update(&hosts) {
+            let retransmits = hosts[&host];
+            self.retransmit_alerts.push(RetransmitAlert { host, retransmits });
+        }
+    }
+
+    fn check_limits(&mut self) {
+        if self.limits.is_none() {
+            return;
+        }
+        let active = self.system_active_connections();
+        if let Some(alert) = self.limits.as_mut().and_then(|limits| limits.update(active)) {
+            self.limit_alerts.push(alert);
+        }
+    }
+
+    /// Alerts on blocklisted connections among `conn_ids`, once per process and address.
+    fn detect_blocklisted(&mut self, conn_ids: &[u64]) {
+        if self.blocklist_alerts.is_none() {
+            return;
+        }
+        let mut alerts = Vec::new();
+        for conn in conn_ids.iter().filter_map(|id| self.connections.get(id)) {
+            let Some(list) = &conn.blocklist else {
+                continue;
+            };
+            if !self.blocklist_alerted.insert((conn.pid, conn.remote_addr)) {
+                continue;
+            }
+            alerts.push(BlocklistAlert {
+                pid: conn.pid,
+                process_name: self.process_name_or_unknown(conn.pid),
+                host: conn.host(),
+                port: conn.remote_port,
+                list: list.clone(),
+            });
+        }
+        if let Some(pending) = self.blocklist_alerts.as_mut() {
+            pending.extend(alerts);
+        }
+    }
+
+    /// Hosts with a blocklisted connection, keyed like host rows, with the list they are on.
+    fn blocklisted_hosts(&self) -> HashMap<(String, u16), String> {
+        self.connections.values()
+            .filter(|conn| !conn.closed)
+            .chain(self.historical_connections.iter())
+            .filter_map(|conn| {
+                let list = conn.blocklist.clone()?;
+                let host = conn.host();
+                Some(((host, conn.remote_port), list))
+            })
+            .collect()
+    }
+
+    fn detect_anomalies(&mut self, now: SystemTime) {
+        let Some(anomalies) = self.anomalies.as_mut() else {
+            return;
+        };
+
+        let mut active: HashMap<AnomalyKey, usize> = HashMap::new();
+        for conn in self.connections.values().filter(|conn| !conn.closed) {
+            let host = conn.host();
+            *active.entry(AnomalyKey::Host(host, conn.remote_port)).or_insert(0) += 1;
+            *active.entry(AnomalyKey::Process(conn.pid)).or_insert(0) += 1;
         }
+        anomalies.update(now, &active);
+    }
+
+    fn anomaly_score(&self, key: &AnomalyKey) -> Option<f64> {
+        self.anomalies.as_ref().and_then(|anomalies| anomalies.anomaly(key))
+    }
+
+    fn record_event(&mut self, kind: ConnectionEventKind, conn_id: u64) {
+        let conn = match self.connections.get(&conn_id) {
+            Some(conn) => conn.clone(),
+            None => return,
+        };
+        let process_name = self.get_process(conn.pid).and_then(|p| p.name.clone());
         
-        // Store the timestamp for historical analysis
-        self.metrics.sample_timestamps.push(now);
+        self.revision += 1;
+        self.events.push_back(ConnectionEvent {
+            seq: self.next_event_seq,
+            time: SystemTime::now(),
+            kind,
+            process_name,
+            connection: conn,
+        });
+        self.next_event_seq += 1;
         
-        // Trim timestamp history if it gets too large (keep last 1000 points)
-        if self.metrics.sample_timestamps.len() > 1000 {
-            self.metrics.sample_timestamps.remove(0);
+        if self.events.len() > MAX_EVENTS {
+            self.events.pop_front();
         }
-        
-        self.last_refresh = now;
-        Ok(())
     }
     
-    fn update_process_info(&mut self, pid: u32) {
-        if let Some(proc) = self.system_info.process(Pid::from(pid as usize)) {
-            let name = proc.name().to_string_lossy().to_string();
-            let exe = proc.exe().map(|p| p.to_string_lossy().to_string());
-            let memory_usage = proc.memory();
-            
-            if let Some(process) = self.processes.get_mut(&pid) {
-                process.update(Some(name), exe, memory_usage);
-            } else {
-                let new_process = Process::new(pid, Some(name), exe, memory_usage);
-                self.processes.insert(pid, new_process);
-            }
-            
-            let memory_entry = self.metrics.memory_history.entry(pid).or_insert_with(Vec::new);
-            memory_entry.push((SystemTime::now(), memory_usage));
-            
-            // Trim memory history if it gets too large
-            if memory_entry.len() > 1000 {
-                memory_entry.remove(0);
-            }
+    fn update_process_info(&mut self, pid: u32, sample: &ProcessSample) {
+        let name = sample.name.clone();
+        let exe = sample.exe.clone();
+        let memory_usage = sample.memory_usage;
+        
+        let process = self.processes.entry(pid)
+            .and_modify(|process| process.update(Some(name.clone()), exe.clone(), memory_usage))
+            .or_insert_with(|| Process::new(pid, Some(name), exe, memory_usage));
+        if let Some(secs) = sample.started_at {
+            process.started_at = Some(UNIX_EPOCH + Duration::from_secs(secs));
         }
+        
+        self.metrics.memory_history.entry(pid).or_default().push(SystemTime::now(), memory_usage);
     }
     
     pub fn get_active_connections(&self) -> Vec<&Connection> {
@@ -308,6 +1433,32 @@ impl ConnectionMonitor {
             .collect()
     }
     
+    pub fn get_filtered_events(&self, filter: &ConnectionFilter) -> Vec<&ConnectionEvent> {
+        self.events.iter()
+            .filter(|event| filter.matches_connection(&event.connection, event.process_name.as_deref()))
+            .collect()
+    }
+    
+    /// The number the next open or close event will get.
+    pub fn next_event_seq(&self) -> u64 {
+        self.next_event_seq
+    }
+
+    /// Opens and closes numbered `seq` or later of connections matching the filter.
+    pub fn count_filtered_events_since(&self, filter: &ConnectionFilter, seq: u64) -> usize {
+        self.events.iter()
+            .filter(|event| event.seq >= seq)
+            .filter(|event| filter.matches_connection(&event.connection, event.process_name.as_deref()))
+            .count()
+    }
+
+    /// Events numbered `seq` or later that are still retained.
+    pub fn get_events_since(&self, seq: u64) -> Vec<&ConnectionEvent> {
+        self.events.iter()
+            .filter(|event| event.seq >= seq)
+            .collect()
+    }
+    
     pub fn get_process(&self, pid: u32) -> Option<&Process> {
         self.processes.get(&pid)
     }
@@ -319,23 +1470,7 @@ impl ConnectionMonitor {
     pub fn get_filtered_processes(&self, filter: &ConnectionFilter) -> Vec<&Process> {
         self.processes.values()
             .filter(|process| {
-                if let Some(pid) = filter.pid {
-                    if process.pid != pid {
-                        return false;
-                    }
-                }
-                
-                if let Some(ref name_filter) = filter.process_name {
-                    if let Some(ref name) = process.name {
-                        if !name.contains(name_filter) {
-                            return false;
-                        }
-                    } else {
-                        return false;
-                    }
-                }
-                
-                true
+                filter.matches_pid(process.pid) && filter.matches_process_name(process.name.as_deref())
             })
             .collect()
     }
@@ -347,11 +1482,22 @@ impl ConnectionMonitor {
         end_time: Option<SystemTime>
     ) -> Vec<(SystemTime, usize)> {
         let all_connections: Vec<&Connection> = self.connections.values()
+            .filter(|conn| !conn.closed)
             .chain(self.historical_connections.iter())
             .collect();
         
         let mut filtered_history = Vec::new();
-        
+
+        // Before the oldest sample still recounted, the unfiltered totals have rollups
+        if filter.is_empty() {
+            let oldest = self.metrics.sample_timestamps.first().copied();
+            filtered_history.extend(self.metrics.active_history.history().into_iter()
+                .filter(|(time, _)| oldest.is_none_or(|oldest| *time < oldest))
+                .filter(|(time, _)| start_time.is_none_or(|start| *time >= start))
+                .filter(|(time, _)| end_time.is_none_or(|end| *time <= end))
+                .map(|(time, active)| (time, active as usize)));
+        }
+
         for &timestamp in &self.metrics.sample_timestamps {
             if let Some(start) = start_time {
                 if timestamp < start {
@@ -385,6 +1531,11 @@ impl ConnectionMonitor {
         filtered_history
     }
     
+    /// Connections opened over the last `rows` hours or days, across all connections.
+    pub fn get_activity_heatmap(&self, layout: HeatmapLayout, rows: usize) -> Heatmap {
+        Heatmap::build(&self.metrics.opens_history, layout, chrono::Local::now(), rows)
+    }
+
     pub fn get_memory_history_filtered(
         &self,
         filter: &ConnectionFilter,
@@ -393,17 +1544,11 @@ impl ConnectionMonitor {
     ) -> HashMap<u32, Vec<(SystemTime, u64)>> {
         let mut result = HashMap::new();
         
-        let pids_to_include: Vec<u32> = if let Some(pid) = filter.pid {
-            vec![pid]
-        } else if let Some(ref process_name) = filter.process_name {
+        let pids_to_include: Vec<u32> = if !filter.pids.is_empty() {
+            filter.pids.clone()
+        } else if !filter.process_names.is_empty() {
             self.processes.iter()
-                .filter(|(_, process)| {
-                    if let Some(ref name) = process.name {
-                        name.contains(process_name)
-                    } else {
-                        false
-                    }
-                })
+                .filter(|(_, process)| filter.matches_process_name(process.name.as_deref()))
                 .map(|(pid, _)| *pid)
                 .collect()
         } else {
@@ -412,7 +1557,7 @@ impl ConnectionMonitor {
         
         for pid in pids_to_include {
             if let Some(history) = self.metrics.memory_history.get(&pid) {
-                let filtered_history: Vec<(SystemTime, u64)> = history.iter()
+                let filtered_history: Vec<(SystemTime, u64)> = history.history().into_iter()
                     .filter(|(time, _)| {
                         let after_start = if let Some(start) = start_time {
                             *time >= start
@@ -428,7 +1573,6 @@ impl ConnectionMonitor {
                         
                         after_start && before_end
                     })
-                    .cloned()
                     .collect();
                 
                 if !filtered_history.is_empty() {
@@ -440,11 +1584,131 @@ impl ConnectionMonitor {
         result
     }
 
+    pub fn get_summary(&self, filter: &ConnectionFilter) -> ConnectionSummary {
+        if let Some(snapshot) = &self.offline {
+            if filter.is_empty() {
+                return snapshot.summary;
+            }
+            // Without the recorded history, concurrency across hosts is only known per host
+            let hosts = self.get_host_metrics(filter);
+            let pids: HashSet<u32> = self.get_process_host_metrics(filter).iter().map(|m| m.pid).collect();
+            return ConnectionSummary {
+                active_connections: hosts.iter().map(|m| m.current_connections).sum(),
+                total_connections: hosts.iter().map(|m| m.total_connections).sum(),
+                max_concurrent: hosts.iter().map(|m| m.max_concurrent).max().unwrap_or(0),
+                blocklisted: hosts.iter().filter(|m| m.blocklist.is_some()).map(|m| m.total_connections).sum(),
+                active_by_scope: hosts.iter().fold(ScopeCounts::default(), |mut counts, m| {
+                    counts.add(m.scope, m.current_connections);
+                    counts
+                }),
+                active_tunneled: hosts.iter().filter(|m| m.tunnel.is_some()).map(|m| m.current_connections).sum(),
+                unique_hosts: hosts.len(),
+                unique_processes: pids.len(),
+                opened_per_minute: 0.0,
+                avg_duration_secs: None,
+            };
+        }
+
+        let active = self.get_filtered_active_connections(filter);
+        let historical = self.get_filtered_historical_connections(filter);
+        let blocklisted = active.iter().chain(historical.iter())
+            .filter(|conn| conn.blocklist.is_some())
+            .count();
+        let mut active_by_scope = ScopeCounts::default();
+        for conn in &active {
+            active_by_scope.add(AddressScope::of(&conn.remote_addr), 1);
+        }
+        
+        let history = self.get_connection_history_filtered(filter, None, None);
+        let max_concurrent = history.iter().map(|(_, count)| *count).max().unwrap_or(0);
+
+        let all = || active.iter().chain(historical.iter());
+        let unique_hosts: HashSet<String> = all().map(|conn| conn.host_key()).collect();
+        let unique_processes: HashSet<u32> = all().map(|conn| conn.pid).collect();
+
+        let window = Duration::from_secs(60);
+        let opened_per_minute = match self.first_sample {
+            Some(first_sample) => {
+                let since = self.last_refresh.checked_sub(window).unwrap_or(first_sample).max(first_sample);
+                let sampled = self.last_refresh.duration_since(since).unwrap_or_default();
+                let opened = all()
+                    .filter(|conn| conn.first_seen > first_sample && conn.first_seen >= since)
+                    .count();
+                if sampled.is_zero() { 0.0 } else { opened as f64 * 60.0 / sampled.as_secs_f64() }
+            }
+            None => 0.0,
+        };
+
+        let durations: Vec<f64> = historical.iter()
+            .map(|conn| conn.last_seen.duration_since(conn.first_seen).unwrap_or_default().as_secs_f64())
+            .collect();
+        let avg_duration_secs = (!durations.is_empty())
+            .then(|| durations.iter().sum::<f64>() / durations.len() as f64);
+
+        ConnectionSummary {
+            active_connections: active.len(),
+            total_connections: historical.len() + active.len(),
+            max_concurrent,
+            blocklisted,
+            active_by_scope,
+            active_tunneled: active.iter().filter(|conn| conn.tunnel.is_some()).count(),
+            unique_hosts: unique_hosts.len(),
+            unique_processes: unique_processes.len(),
+            opened_per_minute,
+            avg_duration_secs,
+        }
+    }
+
     pub fn get_host_metrics(&self, filter: &ConnectionFilter) -> Vec<HostMetrics> {
+        if let Some(snapshot) = &self.offline {
+            if filter.is_empty() {
+                return snapshot.hosts.clone();
+            }
+            // Host rows don't record their processes, so filtered rows are rebuilt from the
+            // matching process-host rows; max is then the highest of any one process.
+            let mut hosts: Vec<HostMetrics> = Vec::new();
+            for row in self.get_process_host_metrics(filter) {
+                match hosts.iter_mut().find(|m| m.host == row.host && m.port == row.port) {
+                    Some(metrics) => {
+                        metrics.current_connections += row.current_connections;
+                        metrics.total_connections += row.total_connections;
+                        metrics.max_concurrent = metrics.max_concurrent.max(row.max_concurrent);
+                        metrics.first_seen = metrics.first_seen.min(row.first_seen);
+                    }
+                    None => hosts.push(HostMetrics {
+                        host: row.host,
+                        port: row.port,
+                        current_connections: row.current_connections,
+                        total_connections: row.total_connections,
+                        max_concurrent: row.max_concurrent,
+                        first_seen: row.first_seen,
+                        last_seen: None,
+                        anomaly_score: None,
+                        blocklist: row.blocklist,
+                        scope: row.scope,
+                        tunnel: row.tunnel,
+                        nat: row.nat,
+                        proxied: row.proxied,
+                        script: None,
+                        recent_max: RecentMax::default(),
+                        sampled: SampledConcurrency::default(),
+                        remote_ips: 0,
+                        local_ports: 0,
+                        retransmits: None,
+                        high_retransmits: false,
+                    }),
+                }
+            }
+            return hosts;
+        }
+
         let mut host_metrics = Vec::new();
-        let mut host_map: HashMap<(String, u16), (usize, usize, usize)> = HashMap::new();
+        let mut host_map: HashMap<(String, u16), RowTally> = HashMap::new();
+        let mut fan_out: HashMap<(String, u16), (HashSet<IpAddr>, HashSet<u16>)> = HashMap::new();
+        let mut retransmits: HashMap<(String, u16), Retransmits> = HashMap::new();
         
         let all_connections: Vec<_> = self.connections.values()
+            .filter(|conn| !conn.closed)
             .chain(self.historical_connections.iter())
             .collect();
         
@@ -454,42 +1718,83 @@ impl ConnectionMonitor {
                 continue;
             }
             
-            let host = conn.remote_hostname.clone().unwrap_or_else(|| conn.remote_addr.to_string());
+            let host = conn.host();
             let key = (host.clone(), conn.remote_port);
             
-            let entry = host_map.entry(key).or_insert((0, 0, 0));
-            
-            entry.1 += 1;
-            
-            if !conn.closed {
-                entry.0 += 1;
+            let (addrs, local_ports) = fan_out.entry(key.clone()).or_default();
+            addrs.insert(conn.remote_addr);
+            local_ports.insert(conn.local_port);
+            if let Some(stats) = conn.tcp_stats {
+                retransmits.entry(key.clone()).or_default().add(stats);
             }
+            host_map.entry(key).or_insert_with(|| RowTally::new(conn)).count(conn);
         }
         
         // Add max concurrent from metrics
-        for ((host, port), (current, total, _)) in host_map {
+        let mut blocklisted = self.blocklisted_hosts();
+        for ((host, port), RowTally { current, total, first_seen, last_seen, scope, tunnel, nat, proxied }) in host_map {
+            if self.is_inactive_pruned(current, last_seen) {
+                continue;
+            }
+            let blocklist = blocklisted.remove(&(host.clone(), port));
+            let (addrs, local_ports) = fan_out.remove(&(host.clone(), port)).unwrap_or_default();
+            let retransmits = retransmits.remove(&(host.clone(), port));
             let host_key = format!("{}:{}", host, port);
-            let max_concurrent = self.metrics.max_concurrent_by_host.get(&host_key).cloned().unwrap_or(0);
+            let max_concurrent = self.metrics.by_host.max(&host_key);
             
+            let anomaly_score = self.anomaly_score(&AnomalyKey::Host(host.clone(), port));
             host_metrics.push(HostMetrics {
                 host,
                 port,
                 current_connections: current,
                 total_connections: total,
                 max_concurrent,
+                first_seen: first_seen.into(),
+                last_seen: Some(last_seen.into()),
+                anomaly_score,
+                blocklist,
+                scope,
+                tunnel,
+                nat,
+                proxied,
+                script: None,
+                recent_max: self.metrics.recent_by_host.get(&host_key),
+                sampled: self.metrics.sampled_by_host.get(&host_key),
+                remote_ips: addrs.len(),
+                local_ports: local_ports.len(),
+                retransmits,
+                high_retransmits: retransmits.zip(self.retransmits.as_ref())
+                    .is_some_and(|(retransmits, watch)| watch.is_high(&retransmits)),
             });
         }
+        if let Some(script) = &self.script {
+            for metrics in &mut host_metrics {
+                metrics.script = script.host_column(metrics).unwrap_or_else(|_| Some("error".to_string()));
+            }
+        }
         
         host_metrics
     }
     
     pub fn get_process_metrics(&self, filter: &ConnectionFilter) -> Vec<ProcessMetrics> {
+        if let Some(snapshot) = &self.offline {
+            return offline_rows(&snapshot.processes, filter, |m| RowAttributes {
+                pid: Some(m.pid),
+                process_name: Some(&m.name),
+                ..Default::default()
+            });
+        }
+
         let mut process_metrics = Vec::new();
-        let mut process_map: HashMap<u32, (usize, usize)> = HashMap::new();
+        let mut process_map: HashMap<u32, (usize, usize, SystemTime, SystemTime)> = HashMap::new();
+        let mut endpoints: HashMap<u32, HashSet<(String, u16)>> = HashMap::new();
+        let mut idle: HashMap<u32, usize> = HashMap::new();
+        let now = SystemTime::now();
         
         let active_pids = self.get_active_pids();
         
         let all_connections: Vec<_> = self.connections.values()
+            .filter(|conn| !conn.closed)
             .chain(self.historical_connections.iter())
             .collect();
         
@@ -499,20 +1804,39 @@ impl ConnectionMonitor {
                 continue;
             }
             
-            let entry = process_map.entry(conn.pid).or_insert((0, 0));
+            let row_pid = self.process_row_pid(conn.pid);
+            endpoints.entry(row_pid).or_default().insert((conn.host(), conn.remote_port));
+            if conn.idle_for(now).is_some_and(|idle_for| idle_for >= self.idle_threshold) {
+                *idle.entry(row_pid).or_default() += 1;
+            }
+            let entry = process_map.entry(row_pid).or_insert((0, 0, conn.first_seen, conn.last_seen));
             
             entry.1 += 1;
+            entry.2 = entry.2.min(conn.first_seen);
+            entry.3 = entry.3.max(conn.last_seen);
             
             if !conn.closed {
                 entry.0 += 1;
             }
         }
         
-        for (pid, (current, total)) in process_map {
+        for (pid, (current, total, first_seen, last_seen)) in process_map {
+            if self.is_inactive_pruned(current, last_seen) {
+                continue;
+            }
             let process = self.get_process(pid);
             let name = process.and_then(|p| p.name.clone()).unwrap_or_else(|| "Unknown".to_string());
-            let max_concurrent = self.metrics.max_concurrent_by_pid.get(&pid).cloned().unwrap_or(0);
+            let max_concurrent = if self.carry_restarts {
+                self.lineage.ancestors(pid).map(|pid| self.metrics.by_pid.max(&pid)).max().unwrap_or(0)
+            } else {
+                self.metrics.by_pid.max(&pid)
+            };
             let is_alive = active_pids.contains(&pid);
+            let exited_at = self.exited_at(pid, &active_pids);
+            if self.is_exit_pruned(exited_at) {
+                continue;
+            }
+            let started_at = process.and_then(|p| p.started_at);
             
             process_metrics.push(ProcessMetrics {
                 pid,
@@ -521,19 +1845,49 @@ impl ConnectionMonitor {
                 total_connections: total,
                 max_concurrent,
                 is_alive,
+                first_seen: first_seen.into(),
+                memory_usage: process.map_or(0, |p| p.current_memory_usage),
+                anomaly_score: self.anomaly_score(&AnomalyKey::Process(pid)),
+                recent_max: self.metrics.recent_by_pid.get(&pid),
+                sampled: self.metrics.sampled_by_pid.get(&pid),
+                restarts: self.lineage.restarts(pid),
+                exited_at: exited_at.map(Into::into),
+                script: None,
+                idle: idle.get(&pid).copied().unwrap_or(0),
+                hosts: endpoints.get(&pid).map_or(0, HashSet::len),
+                started_at: started_at.map(Into::into),
+                per_minute: self.per_minute_running(total, started_at, exited_at.unwrap_or(now)),
             });
         }
+        if let Some(script) = &self.script {
+            for metrics in &mut process_metrics {
+                metrics.script = script.process_column(metrics).unwrap_or_else(|_| Some("error".to_string()));
+            }
+        }
         
         process_metrics
     }
     
     pub fn get_process_host_metrics(&self, filter: &ConnectionFilter) -> Vec<ProcessHostMetrics> {
+        if let Some(snapshot) = &self.offline {
+            return offline_rows(&snapshot.process_hosts, filter, |m| RowAttributes {
+                pid: Some(m.pid),
+                process_name: Some(&m.process_name),
+                host: Some(&m.host),
+                port: Some(m.port),
+                scope: Some(m.scope),
+                tunnel: Some(m.tunnel.as_deref().unwrap_or("")),
+                ..Default::default()
+            });
+        }
+
         let mut process_host_metrics = Vec::new();
-        let mut process_host_map: HashMap<(u32, String, u16), (usize, usize)> = HashMap::new();
+        let mut process_host_map: HashMap<(u32, String, u16), RowTally> = HashMap::new();
         
         let active_pids = self.get_active_pids();
 
         let all_connections: Vec<_> = self.connections.values()
+            .filter(|conn| !conn.closed)
             .chain(self.historical_connections.iter())
             .collect();
         
@@ -543,26 +1897,32 @@ impl ConnectionMonitor {
                 continue;
             }
             
-            let host = conn.remote_hostname.clone().unwrap_or_else(|| conn.remote_addr.to_string());
+            let host = conn.host();
             let key = (conn.pid, host.clone(), conn.remote_port);
             
-            let entry = process_host_map.entry(key).or_insert((0, 0));
-            
-            entry.1 += 1;
-            
-            if !conn.closed {
-                entry.0 += 1;
-            }
+            process_host_map.entry(key).or_insert_with(|| RowTally::new(conn)).count(conn);
         }
         
-        for ((pid, host, port), (current, total)) in process_host_map {
+        let blocklisted = self.blocklisted_hosts();
+        for ((pid, host, port), RowTally { current, total, first_seen, last_seen, scope, tunnel, nat, proxied }) in process_host_map {
+            if self.is_inactive_pruned(current, last_seen) {
+                continue;
+            }
             let process = self.get_process(pid);
             let process_name = process
                 .and_then(|p| p.exe.clone().or(p.name.clone()))
                 .unwrap_or_else(|| "Unknown".to_string());
             let process_host_key = (pid, host.clone(), port);
-            let max_concurrent = self.metrics.max_concurrent_by_process_host.get(&process_host_key).cloned().unwrap_or(0);
+            let max_concurrent = self.metrics.by_process_host.max(&process_host_key);
             let is_alive = active_pids.contains(&pid);
+            let exited_at = self.exited_at(pid, &active_pids);
+            if self.is_exit_pruned(exited_at) {
+                continue;
+            }
+            let leak_suspected = self.leaks.as_ref().is_some_and(|leaks| leaks.is_flagged(&process_host_key));
+            let churn = self.churn.as_ref().map_or(0.0, |churn| churn.churn(&process_host_key));
+            let high_churn = self.churn.as_ref().is_some_and(|churn| churn.is_flagged(&process_host_key));
+            let blocklist = blocklisted.get(&(host.clone(), port)).cloned();
             
             process_host_metrics.push(ProcessHostMetrics {
                 pid,
@@ -573,19 +1933,352 @@ impl ConnectionMonitor {
                 total_connections: total,
                 max_concurrent,
                 is_alive,
+                first_seen: first_seen.into(),
+                memory_usage: process.map_or(0, |p| p.current_memory_usage),
+                leak_suspected,
+                churn,
+                high_churn,
+                blocklist,
+                scope,
+                tunnel,
+                nat,
+                proxied,
+                exited_at: exited_at.map(Into::into),
             });
         }
         
         process_host_metrics
     }
 
-    fn get_active_pids(&self) -> HashSet<u32> {
-        self.system_info.processes()
-            .iter()
-            .filter(|(_, process)| {
-                !matches!(process.status(), ProcessStatus::Dead | ProcessStatus::Zombie | ProcessStatus::Stop)
+    /// Processes, connections, addresses and the last `max_events` events for `host`:`port` among connections matching the filter.
+    pub fn get_host_detail(&self, filter: &ConnectionFilter, host: &str, port: u16, max_events: usize) -> HostDetail {
+        let is_target = |conn: &Connection| {
+            conn.remote_port == port &&
+                conn.host() == host
+        };
+
+        let processes = self.get_process_host_metrics(filter).into_iter()
+            .filter(|metrics| metrics.host == host && metrics.port == port)
+            .collect();
+
+        let mut connections: Vec<Connection> = self.connections.values()
+            .filter(|conn| !conn.closed)
+            .chain(self.historical_connections.iter())
+            .filter(|conn| is_target(conn))
+            .filter(|conn| {
+                let process_name = self.get_process(conn.pid).and_then(|p| p.name.as_deref());
+                filter.matches_connection(conn, process_name)
+            })
+            .cloned()
+            .collect();
+        connections.sort_by(|a, b| a.closed.cmp(&b.closed).then_with(|| b.last_seen.cmp(&a.last_seen)));
+        let addresses: BTreeSet<IpAddr> = connections.iter().map(|conn| conn.remote_addr).collect();
+
+        let events: Vec<ConnectionEvent> = self.get_filtered_events(filter).into_iter()
+            .filter(|event| is_target(&event.connection))
+            .cloned()
+            .collect();
+        let skip = events.len().saturating_sub(max_events);
+        let metrics = self.get_host_metrics(filter).into_iter()
+            .find(|metrics| metrics.host == host && metrics.port == port);
+
+        HostDetail {
+            processes,
+            connections,
+            addresses: addresses.into_iter().collect(),
+            metrics,
+            events: events.into_iter().skip(skip).collect(),
+        }
+    }
+
+    pub fn get_group_metrics(&self, filter: &ConnectionFilter) -> Vec<GroupMetrics> {
+        if let Some(snapshot) = &self.offline {
+            return offline_rows(&snapshot.groups, filter, |m| RowAttributes {
+                group: Some(&m.group),
+                ..Default::default()
+            });
+        }
+
+        let mut group_metrics = Vec::new();
+        let mut group_map: HashMap<String, (usize, usize, SystemTime)> = HashMap::new();
+
+        let all_connections: Vec<_> = self.connections.values()
+            .filter(|conn| !conn.closed)
+            .chain(self.historical_connections.iter())
+            .collect();
+
+        for conn in all_connections {
+            let group = match &conn.group {
+                Some(group) => group,
+                None => continue,
+            };
+
+            let process_name = self.get_process(conn.pid).and_then(|p| p.name.as_deref());
+            if !filter.matches_connection(conn, process_name) {
+                continue;
+            }
+
+            let entry = group_map.entry(group.clone()).or_insert((0, 0, conn.first_seen));
+
+            entry.1 += 1;
+            entry.2 = entry.2.min(conn.first_seen);
+
+            if !conn.closed {
+                entry.0 += 1;
+            }
+        }
+
+        for (group, (current, total, first_seen)) in group_map {
+            let max_concurrent = self.metrics.by_group.max(&group);
+
+            group_metrics.push(GroupMetrics {
+                group,
+                current_connections: current,
+                total_connections: total,
+                max_concurrent,
+                first_seen: first_seen.into(),
+            });
+        }
+
+        group_metrics
+    }
+
+    /// Rows of the aggregation view named `name`, each keyed in `group`. Endpoint groups of
+    /// a loaded snapshot come as recorded; other views only see its open connections.
+    pub fn get_view_metrics(&self, name: &str, filter: &ConnectionFilter) -> Vec<GroupMetrics> {
+        if self.offline.is_some() && name == GROUP_VIEW {
+            return self.get_group_metrics(filter);
+        }
+        let Some(tracker) = self.views.iter().find(|tracker| tracker.view.name() == name) else {
+            return Vec::new();
+        };
+
+        let mut rows: HashMap<String, (usize, usize, SystemTime)> = HashMap::new();
+        let all_connections = self.connections.values()
+            .filter(|conn| !conn.closed)
+            .chain(self.historical_connections.iter());
+        for conn in all_connections {
+            let process = self.get_process(conn.pid);
+            if !filter.matches_connection(conn, process.and_then(|p| p.name.as_deref())) {
+                continue;
+            }
+            let Some(key) = tracker.view.key(conn, process) else {
+                continue;
+            };
+
+            let entry = rows.entry(key).or_insert((0, 0, conn.first_seen));
+            entry.1 += 1;
+            entry.2 = entry.2.min(conn.first_seen);
+            if !conn.closed {
+                entry.0 += 1;
+            }
+        }
+
+        rows.into_iter()
+            .map(|(key, (current, total, first_seen))| GroupMetrics {
+                max_concurrent: tracker.max(&key).max(current),
+                group: key,
+                current_connections: current,
+                total_connections: total,
+                first_seen: first_seen.into(),
+            })
+            .collect()
+    }
+
+    /// Inbound connections per local listening port, with the processes that accepted them
+    /// and their busiest clients.
+    pub fn get_listen_port_metrics(&self, filter: &ConnectionFilter) -> Vec<ListenPortMetrics> {
+        if let Some(snapshot) = &self.offline {
+            return snapshot.listen_ports.iter()
+                .filter(|m| filter.matches_row(&RowAttributes {
+                    process_name: m.processes.first().map(String::as_str),
+                    ..Default::default()
+                }))
+                .cloned()
+                .collect();
+        }
+
+        struct PortRows {
+            current: usize,
+            total: usize,
+            first_seen: SystemTime,
+            processes: BTreeSet<String>,
+            clients: HashMap<IpAddr, usize>,
+        }
+        let mut port_map: HashMap<u16, PortRows> = HashMap::new();
+
+        let all_connections = self.connections.values()
+            .filter(|conn| !conn.closed)
+            .chain(self.historical_connections.iter())
+            .filter(|conn| conn.inbound);
+
+        for conn in all_connections {
+            let process_name = self.get_process(conn.pid).and_then(|p| p.name.as_deref());
+            if !filter.matches_connection(conn, process_name) {
+                continue;
+            }
+
+            let entry = port_map.entry(conn.local_port).or_insert_with(|| PortRows {
+                current: 0,
+                total: 0,
+                first_seen: conn.first_seen,
+                processes: BTreeSet::new(),
+                clients: HashMap::new(),
+            });
+            entry.total += 1;
+            entry.first_seen = entry.first_seen.min(conn.first_seen);
+            if !conn.closed {
+                entry.current += 1;
+            }
+            entry.processes.insert(process_name.unwrap_or("Unknown").to_string());
+            *entry.clients.entry(conn.remote_addr).or_insert(0) += 1;
+        }
+
+        // Connections waiting in a full accept queue have no owner yet, so a server too
+        // slow to accept may have none of its own
+        for listener in self.listeners.values().filter(|listener| listener.queue.queued > 0) {
+            let process_name = listener.process_name.as_deref()
+                .or_else(|| listener.pid.and_then(|pid| self.get_process(pid)).and_then(|p| p.name.as_deref()));
+            if port_map.contains_key(&listener.port)
+                || !filter.matches_row(&RowAttributes { process_name, ..Default::default() }) {
+                continue;
+            }
+            port_map.insert(listener.port, PortRows {
+                current: 0,
+                total: 0,
+                first_seen: SystemTime::now(),
+                processes: process_name.map(str::to_string).into_iter().collect(),
+                clients: HashMap::new(),
+            });
+        }
+
+        port_map.into_iter()
+            .map(|(port, rows)| {
+                let mut top_clients: Vec<(IpAddr, usize)> = rows.clients.into_iter().collect();
+                top_clients.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+                top_clients.truncate(TOP_CLIENTS);
+                let accept_queue = self.listeners.get(&port).map(|listener| listener.queue);
+                ListenPortMetrics {
+                    port,
+                    processes: rows.processes.into_iter().collect(),
+                    current_connections: rows.current,
+                    total_connections: rows.total,
+                    max_concurrent: self.metrics.by_listen_port.max(&port),
+                    top_clients,
+                    first_seen: rows.first_seen.into(),
+                    accept_queue,
+                    queue_saturated: accept_queue.zip(self.backlog.as_ref())
+                        .is_some_and(|(queue, backlog)| backlog.is_saturated(&queue)),
+                }
+            })
+            .collect()
+    }
+
+    /// Inbound connections per remote client address.
+    pub fn get_client_metrics(&self, filter: &ConnectionFilter) -> Vec<ClientMetrics> {
+        if let Some(snapshot) = &self.offline {
+            return snapshot.clients.iter()
+                .filter(|m| filter.matches_row(&RowAttributes {
+                    host: Some(&m.addr.to_string()),
+                    ..Default::default()
+                }))
+                .cloned()
+                .collect();
+        }
+
+        let mut client_map: HashMap<IpAddr, ClientMetrics> = HashMap::new();
+
+        let all_connections = self.connections.values()
+            .filter(|conn| !conn.closed)
+            .chain(self.historical_connections.iter())
+            .filter(|conn| conn.inbound);
+
+        for conn in all_connections {
+            let process_name = self.get_process(conn.pid).and_then(|p| p.name.as_deref());
+            if !filter.matches_connection(conn, process_name) {
+                continue;
+            }
+
+            let entry = client_map.entry(conn.remote_addr).or_insert_with(|| ClientMetrics {
+                addr: conn.remote_addr,
+                hostname: None,
+                ports: Vec::new(),
+                current_connections: 0,
+                total_connections: 0,
+                max_concurrent: self.metrics.by_client.max(&conn.remote_addr),
+                first_seen: conn.first_seen.into(),
+                last_seen: conn.last_seen.into(),
+            });
+            entry.total_connections += 1;
+            if !conn.closed {
+                entry.current_connections += 1;
+            }
+            entry.first_seen = entry.first_seen.min(conn.first_seen.into());
+            entry.last_seen = entry.last_seen.max(conn.last_seen.into());
+            if entry.hostname.is_none() {
+                entry.hostname = conn.remote_hostname.clone();
+            }
+            if !entry.ports.contains(&conn.local_port) {
+                entry.ports.push(conn.local_port);
+            }
+        }
+
+        client_map.into_values()
+            .map(|mut client| {
+                client.ports.sort_unstable();
+                client
             })
-            .map(|(pid, _)| pid.as_u32())
             .collect()
     }
-}
\ No newline at end of file
+
+    /// Processes that are running locally or owned a socket in the latest sample (which may be remote).
+    fn get_active_pids(&self) -> HashSet<u32> {
+        let mut pids = self.provider.running_pids();
+        pids.extend(self.sampled_pids.iter().copied());
+        pids
+    }
+}
+
+/// Connections counted into one host or process-host row.
+struct RowTally {
+    current: usize,
+    total: usize,
+    first_seen: SystemTime,
+    last_seen: SystemTime,
+    scope: AddressScope,
+    tunnel: Option<String>,
+    nat: Option<String>,
+    proxied: bool,
+}
+
+impl RowTally {
+    fn new(conn: &Connection) -> Self {
+        Self {
+            current: 0,
+            total: 0,
+            first_seen: conn.first_seen,
+            last_seen: conn.last_seen,
+            scope: AddressScope::of(&conn.remote_addr),
+            tunnel: conn.tunnel.clone(),
+            nat: conn.nat_from.map(|addr| addr.to_string()),
+            proxied: conn.proxied,
+        }
+    }
+
+    fn count(&mut self, conn: &Connection) {
+        self.total += 1;
+        self.first_seen = self.first_seen.min(conn.first_seen);
+        self.last_seen = self.last_seen.max(conn.last_seen);
+        if !conn.closed {
+            self.current += 1;
+        }
+    }
+}
+
+/// Rows of a loaded snapshot matching the filter on the attributes they have.
+fn offline_rows<T: Clone>(rows: &[T], filter: &ConnectionFilter, attributes: impl Fn(&T) -> RowAttributes) -> Vec<T> {
+    rows.iter()
+        .filter(|row| filter.matches_row(&attributes(row)))
+        .cloned()
+        .collect()
+}
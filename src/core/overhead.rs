@@ -0,0 +1,78 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, RefreshKind, System};
+
+/// tcpcount's own resource use during the last refresh.
+#[derive(Debug, Clone, Serialize)]
+pub struct OverheadStats {
+    pub timestamp: DateTime<Utc>,
+    /// CPU used by tcpcount since the previous refresh, in percent of one core.
+    pub cpu_percent: f32,
+    pub rss_bytes: u64,
+    /// Time taken to sample sockets and update metrics.
+    pub refresh_ms: f64,
+    pub sockets: usize,
+    /// Reverse lookups made during the refresh. They run inline, so this is the
+    /// number of addresses that were waiting to be resolved.
+    pub dns_lookups: usize,
+    pub dns_ms: f64,
+}
+
+impl Default for OverheadStats {
+    fn default() -> Self {
+        Self {
+            timestamp: Utc::now(),
+            cpu_percent: 0.0,
+            rss_bytes: 0,
+            refresh_ms: 0.0,
+            sockets: 0,
+            dns_lookups: 0,
+            dns_ms: 0.0,
+        }
+    }
+}
+
+/// Measures tcpcount's own process after each refresh.
+#[derive(Debug)]
+pub struct OverheadTracker {
+    system: System,
+    pid: Option<Pid>,
+    stats: OverheadStats,
+}
+
+impl OverheadTracker {
+    pub fn new() -> Self {
+        let refresh_kind = RefreshKind::nothing().with_processes(ProcessRefreshKind::nothing().with_cpu().with_memory());
+        Self {
+            system: System::new_with_specifics(refresh_kind),
+            pid: sysinfo::get_current_pid().ok(),
+            stats: OverheadStats::default(),
+        }
+    }
+
+    pub fn stats(&self) -> &OverheadStats {
+        &self.stats
+    }
+
+    /// Records one refresh and re-reads this process's CPU and memory use.
+    pub fn record(&mut self, refresh: Duration, sockets: usize, dns_lookups: usize, dns: Duration) {
+        let mut stats = OverheadStats {
+            refresh_ms: refresh.as_secs_f64() * 1000.0,
+            sockets,
+            dns_lookups,
+            dns_ms: dns.as_secs_f64() * 1000.0,
+            ..OverheadStats::default()
+        };
+        if let Some(pid) = self.pid {
+            let refresh_kind = ProcessRefreshKind::nothing().with_cpu().with_memory();
+            self.system.refresh_processes_specifics(ProcessesToUpdate::Some(&[pid]), true, refresh_kind);
+            if let Some(process) = self.system.process(pid) {
+                stats.cpu_percent = process.cpu_usage();
+                stats.rss_bytes = process.memory();
+            }
+        }
+        self.stats = stats;
+    }
+}
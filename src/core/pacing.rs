@@ -0,0 +1,169 @@
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// Slow refreshes in a row before the interval is lengthened.
+const SLOW_STREAK: usize = 3;
+/// Fast refreshes in a row before the interval is shortened again.
+const FAST_STREAK: usize = 20;
+/// Longest interval the pacer backs off to.
+const MAX_INTERVAL: Duration = Duration::from_secs(10);
+
+/// When sampling speeds up to catch connections that open and close between samples.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BurstOptions {
+    /// Interval sampled at while connections churn.
+    pub interval: Duration,
+    /// Opens and closes of the watched connections in one refresh that start fast sampling.
+    pub trigger: usize,
+    /// How long fast sampling lasts after the last refresh that churned.
+    pub hold: Duration,
+}
+
+impl Default for BurstOptions {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_millis(50),
+            trigger: 4,
+            hold: Duration::from_secs(10),
+        }
+    }
+}
+
+/// A change of sampling interval made by the pacer.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PaceChange {
+    /// Refreshes routinely took longer than the interval.
+    Slower { interval: Duration, refresh: Duration },
+    /// Refreshes became fast enough to sample more often again.
+    Faster { interval: Duration },
+    /// Watched connections churned, so sampling speeds up for a while.
+    Burst { interval: Duration, changes: usize },
+    /// Churn settled and sampling is back to the usual interval.
+    Steady { interval: Duration },
+}
+
+/// Adapts the sampling interval to how long refreshes take, so a machine with a huge
+/// socket table samples less often instead of falling further behind every tick.
+#[derive(Debug, Clone)]
+pub struct SamplePacer {
+    base: Duration,
+    interval: Duration,
+    slow_streak: usize,
+    fast_streak: usize,
+    burst: Option<BurstOptions>,
+    /// End of the current fast sampling, if churning.
+    burst_until: Option<Instant>,
+    last_refresh: Duration,
+}
+
+impl SamplePacer {
+    pub fn new(base: Duration) -> Self {
+        Self {
+            base,
+            interval: base,
+            slow_streak: 0,
+            fast_streak: 0,
+            burst: None,
+            burst_until: None,
+            last_refresh: Duration::ZERO,
+        }
+    }
+
+    /// Samples faster while the watched connections churn.
+    pub fn with_burst(mut self, burst: Option<BurstOptions>) -> Self {
+        self.burst = burst;
+        self
+    }
+
+    /// The interval to sample at now.
+    pub fn interval(&self) -> Duration {
+        match (self.burst, self.burst_until) {
+            // Leave at least as much idle time as a refresh takes
+            (Some(burst), Some(_)) => burst.interval.max(self.last_refresh * 2).min(self.interval),
+            _ => self.interval,
+        }
+    }
+
+    /// Whether sampling speeds up when connections churn.
+    pub fn has_burst(&self) -> bool {
+        self.burst.is_some()
+    }
+
+    /// Whether sampling is sped up because of churn.
+    pub fn is_bursting(&self) -> bool {
+        self.burst_until.is_some()
+    }
+
+    /// Whether the interval was lengthened beyond the configured one.
+    pub fn is_backed_off(&self) -> bool {
+        self.interval > self.base
+    }
+
+    /// Records how long a refresh took and returns any change of interval.
+    pub fn record(&mut self, refresh: Duration) -> Option<PaceChange> {
+        self.last_refresh = refresh;
+        if refresh > self.interval {
+            self.slow_streak += 1;
+            self.fast_streak = 0;
+        } else if refresh * 4 < self.interval {
+            self.fast_streak += 1;
+            self.slow_streak = 0;
+        } else {
+            self.slow_streak = 0;
+            self.fast_streak = 0;
+        }
+
+        if self.slow_streak >= SLOW_STREAK && self.interval < MAX_INTERVAL {
+            self.slow_streak = 0;
+            // A machine too slow for the usual interval can't sample faster either
+            self.burst_until = None;
+            // Leave at least as much idle time as a refresh takes
+            self.interval = (self.interval * 2).max(refresh * 2).min(MAX_INTERVAL);
+            return Some(PaceChange::Slower { interval: self.interval, refresh });
+        }
+        if self.fast_streak >= FAST_STREAK && self.is_backed_off() {
+            self.fast_streak = 0;
+            self.interval = (self.interval / 2).max(self.base);
+            return Some(PaceChange::Faster { interval: self.interval });
+        }
+        None
+    }
+
+    /// Records how many watched connections opened or closed in the last refresh and
+    /// returns any change of interval.
+    pub fn record_churn(&mut self, changes: usize, now: Instant) -> Option<PaceChange> {
+        let burst = self.burst?;
+        if changes >= burst.trigger && !self.is_backed_off() {
+            let started = self.burst_until.is_none();
+            self.burst_until = Some(now + burst.hold);
+            return started.then(|| PaceChange::Burst { interval: self.interval(), changes });
+        }
+        if self.burst_until.is_some_and(|until| now >= until) {
+            self.burst_until = None;
+            return Some(PaceChange::Steady { interval: self.interval });
+        }
+        None
+    }
+}
+
+impl fmt::Display for PaceChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PaceChange::Slower { interval, refresh } => write!(f, "Refresh takes {} ms, sampling every {} to keep up",
+                refresh.as_millis(), format_interval(*interval)),
+            PaceChange::Faster { interval } => write!(f, "Refresh is fast again, sampling every {}", format_interval(*interval)),
+            PaceChange::Burst { interval, changes } => write!(f, "{} connections opened or closed in one refresh, sampling every {}",
+                changes, format_interval(*interval)),
+            PaceChange::Steady { interval } => write!(f, "Churn settled, sampling every {}", format_interval(*interval)),
+        }
+    }
+}
+
+/// Formats a sampling interval, e.g. `250ms` or `1.5s`.
+pub fn format_interval(interval: Duration) -> String {
+    if interval < Duration::from_secs(1) {
+        format!("{}ms", interval.as_millis())
+    } else {
+        format!("{:.1}s", interval.as_secs_f64())
+    }
+}
@@ -1,4 +1,8 @@
-use std::time::SystemTime;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, Signal, System};
+
+use crate::error::{Error, Result};
 
 pub struct Process {
     pub pid: u32,
@@ -7,7 +11,11 @@ pub struct Process {
     pub current_memory_usage: u64,
     pub max_memory_usage: u64,
     pub first_seen: SystemTime,
+    /// When the process actually started, as the OS reports it.
+    pub started_at: Option<SystemTime>,
     pub last_seen: SystemTime,
+    /// Last sample the process was still running at, with or without sockets.
+    pub last_alive: SystemTime,
 }
 
 impl Process {
@@ -25,7 +33,9 @@ impl Process {
             current_memory_usage: memory_usage,
             max_memory_usage: memory_usage,
             first_seen: now,
+            started_at: None,
             last_seen: now,
+            last_alive: now,
         }
     }
 
@@ -40,4 +50,24 @@ impl Process {
         self.max_memory_usage = self.max_memory_usage.max(memory_usage);
         self.last_seen = SystemTime::now();
     }
-}
\ No newline at end of file
+}
+
+/// Asks process `pid` to exit with SIGTERM, once it is confirmed to still be the sampled
+/// process: the same name and, when known, the same start time, so a reused PID is left alone.
+pub fn terminate(pid: u32, name: &str, started_at: Option<SystemTime>) -> Result<()> {
+    let pid = Pid::from_u32(pid);
+    let mut system = System::new();
+    system.refresh_processes_specifics(ProcessesToUpdate::Some(&[pid]), true, ProcessRefreshKind::nothing());
+    let process = system.process(pid)
+        .ok_or_else(|| Error::Other(format!("No process with PID {}", pid)))?;
+
+    let started = UNIX_EPOCH + Duration::from_secs(process.start_time());
+    if process.name().to_string_lossy() != name || started_at.is_some_and(|expected| expected != started) {
+        return Err(Error::Other(format!("PID {} now belongs to a different process", pid)));
+    }
+    match process.kill_with(Signal::Term) {
+        Some(true) => Ok(()),
+        Some(false) => Err(Error::PermissionDenied(format!("could not signal PID {}", pid))),
+        None => Err(Error::Other("SIGTERM is not available on this platform".to_string())),
+    }
+}
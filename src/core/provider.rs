@@ -0,0 +1,96 @@
+use std::collections::HashSet;
+use std::time::Duration;
+
+use sysinfo::{ProcessRefreshKind, ProcessStatus, RefreshKind, System};
+
+use super::resolver::Resolver;
+use super::sample::{SampleOptions, SamplePass, sample_sockets};
+use crate::error::Result;
+
+/// Where the monitor gets its sockets from on each refresh.
+pub trait SocketProvider: Send {
+    /// Reads the sockets open right now.
+    fn sample(&mut self) -> Result<SamplePass>;
+
+    /// Sets how long resolved hostnames are reused before being looked up again.
+    fn set_name_ttl(&mut self, _ttl: Duration) {}
+
+    /// Sets whether connections that destination NAT sent elsewhere are looked up in
+    /// conntrack for their real destination.
+    fn set_conntrack(&mut self, _enabled: bool) {}
+
+    /// Sets whether connections to a proxy from the owner's environment are marked.
+    fn set_proxy_detection(&mut self, _enabled: bool) {}
+
+    /// Sets whether each socket is attributed to the threads of its process holding it.
+    fn set_thread_attribution(&mut self, _enabled: bool) {}
+
+    /// Sets whether each socket's retransmission counters are read from `tcp_info`.
+    fn set_tcp_info(&mut self, _enabled: bool) {}
+
+    /// Sets whether UDP sockets connected to a name server are sampled too.
+    fn set_dns(&mut self, _enabled: bool) {}
+
+    /// Processes alive at the last sample, including ones without sockets.
+    fn running_pids(&self) -> HashSet<u32> {
+        HashSet::new()
+    }
+}
+
+/// Sockets and processes of this machine.
+pub struct LocalSockets {
+    system: System,
+    resolver: Resolver,
+    options: SampleOptions,
+}
+
+impl LocalSockets {
+    pub fn new() -> Self {
+        let refresh_kind = RefreshKind::nothing().with_processes(ProcessRefreshKind::everything());
+        Self {
+            system: System::new_with_specifics(refresh_kind),
+            resolver: Resolver::new(),
+            options: SampleOptions::default(),
+        }
+    }
+}
+
+impl SocketProvider for LocalSockets {
+    fn sample(&mut self) -> Result<SamplePass> {
+        sample_sockets(&mut self.system, &mut self.resolver, self.options)
+    }
+
+    fn set_name_ttl(&mut self, ttl: Duration) {
+        self.resolver.set_name_ttl(ttl);
+    }
+
+    fn set_conntrack(&mut self, enabled: bool) {
+        self.options.conntrack = enabled;
+    }
+
+    fn set_proxy_detection(&mut self, enabled: bool) {
+        self.options.proxies = enabled;
+    }
+
+    fn set_thread_attribution(&mut self, enabled: bool) {
+        self.options.threads = enabled;
+    }
+
+    fn set_tcp_info(&mut self, enabled: bool) {
+        self.options.tcp_info = enabled;
+    }
+
+    fn set_dns(&mut self, enabled: bool) {
+        self.options.dns = enabled;
+    }
+
+    fn running_pids(&self) -> HashSet<u32> {
+        self.system.processes()
+            .iter()
+            .filter(|(_, process)| {
+                !matches!(process.status(), ProcessStatus::Dead | ProcessStatus::Zombie | ProcessStatus::Stop)
+            })
+            .map(|(pid, _)| pid.as_u32())
+            .collect()
+    }
+}
@@ -0,0 +1,176 @@
+use std::collections::HashMap;
+use std::fs;
+use std::net::{IpAddr, ToSocketAddrs};
+use std::path::PathBuf;
+use std::time::{Duration, Instant, SystemTime};
+
+use super::utils::{is_resolvable, resolve_addr_to_hostname};
+use crate::error::Result;
+
+/// How long a reverse lookup answer, including "no name", is reused by default.
+pub const DEFAULT_NAME_TTL: Duration = Duration::from_secs(600);
+/// How long an address whose lookup failed is left alone before trying again.
+const FAILURE_TTL: Duration = Duration::from_secs(30);
+
+#[cfg(windows)]
+const HOSTS_PATH: &str = r"C:\Windows\System32\drivers\etc\hosts";
+#[cfg(not(windows))]
+const HOSTS_PATH: &str = "/etc/hosts";
+
+/// Names from a hosts file: the first name listed for each address.
+#[derive(Debug, Clone, Default)]
+pub struct HostsFile {
+    names: HashMap<IpAddr, String>,
+}
+
+impl HostsFile {
+    pub fn parse(text: &str) -> Self {
+        let mut names = HashMap::new();
+        for line in text.lines() {
+            let line = line.split('#').next().unwrap_or("");
+            let mut tokens = line.split_whitespace();
+            let (Some(addr), Some(name)) = (tokens.next(), tokens.next()) else {
+                continue;
+            };
+            // Scoped IPv6 addresses such as fe80::1%eth0 name the same address
+            let addr = addr.split('%').next().unwrap_or(addr);
+            if let Ok(addr) = addr.parse::<IpAddr>() {
+                names.entry(addr).or_insert_with(|| name.to_string());
+            }
+        }
+        Self { names }
+    }
+
+    /// The name of `addr`; IPv4-mapped IPv6 addresses match their IPv4 entry.
+    pub fn name(&self, addr: &IpAddr) -> Option<&str> {
+        let addr = match addr {
+            IpAddr::V6(v6) => v6.to_ipv4_mapped().map_or(*addr, IpAddr::V4),
+            IpAddr::V4(_) => *addr,
+        };
+        self.names.get(&addr).map(String::as_str)
+    }
+}
+
+struct CachedName {
+    name: Option<String>,
+    expires: Option<Instant>,
+}
+
+struct CachedAddrs {
+    addrs: Vec<IpAddr>,
+    expires: Option<Instant>,
+}
+
+/// Whether a cached answer is still good; one without an expiry, cached with a ttl too
+/// long to reach, always is.
+fn is_fresh(expires: Option<Instant>, now: Instant) -> bool {
+    expires.is_none_or(|expires| expires > now)
+}
+
+/// Reverse-resolves remote addresses: the hosts file first, then the platform's name
+/// services (NSS on Linux, which may also ask DNS). Answers are cached, so an address is
+/// looked up again once its entry expires, so long-lived connections pick up new names.
+pub struct Resolver {
+    hosts_path: PathBuf,
+    hosts: HostsFile,
+    hosts_modified: Option<SystemTime>,
+    cache: HashMap<IpAddr, CachedName>,
+    addrs: HashMap<String, CachedAddrs>,
+    name_ttl: Duration,
+    lookups: usize,
+    lookup_time: Duration,
+}
+
+impl Resolver {
+    pub fn new() -> Self {
+        let mut resolver = Self {
+            hosts_path: PathBuf::from(HOSTS_PATH),
+            hosts: HostsFile::default(),
+            hosts_modified: None,
+            cache: HashMap::new(),
+            addrs: HashMap::new(),
+            name_ttl: DEFAULT_NAME_TTL,
+            lookups: 0,
+            lookup_time: Duration::ZERO,
+        };
+        resolver.reload_hosts();
+        resolver
+    }
+
+    /// Sets how long answers are reused. Cached answers expire on their old schedule.
+    pub fn set_name_ttl(&mut self, ttl: Duration) {
+        self.name_ttl = ttl;
+    }
+
+    /// Reads the hosts file again if it changed since it was last read.
+    pub fn reload_hosts(&mut self) {
+        let modified = fs::metadata(&self.hosts_path).and_then(|meta| meta.modified()).ok();
+        if modified.is_some() && modified == self.hosts_modified {
+            return;
+        }
+        self.hosts = fs::read_to_string(&self.hosts_path)
+            .map(|text| HostsFile::parse(&text))
+            .unwrap_or_default();
+        self.hosts_modified = modified;
+    }
+
+    /// The name of `addr`, if it has one. Lookups that failed are errors the first time
+    /// and give `Ok(None)` until they are retried.
+    pub fn resolve(&mut self, addr: IpAddr) -> Result<Option<String>> {
+        if addr.is_loopback() {
+            return Ok(None);
+        }
+        if let Some(name) = self.hosts.name(&addr) {
+            return Ok(Some(name.to_string()));
+        }
+        if !is_resolvable(addr) {
+            return Ok(None);
+        }
+
+        let now = Instant::now();
+        if let Some(cached) = self.cache.get(&addr).filter(|cached| is_fresh(cached.expires, now)) {
+            return Ok(cached.name.clone());
+        }
+
+        let result = resolve_addr_to_hostname(addr);
+        self.lookups += 1;
+        self.lookup_time += now.elapsed();
+
+        let (name, ttl) = match &result {
+            Ok(name) => (name.clone(), self.name_ttl),
+            Err(_) => (None, FAILURE_TTL),
+        };
+        self.cache.retain(|_, cached| is_fresh(cached.expires, now));
+        self.cache.insert(addr, CachedName { name, expires: now.checked_add(ttl) });
+        result
+    }
+
+    /// The addresses `host` resolves to, cached like reverse answers. A failed lookup
+    /// gives no addresses.
+    pub fn addresses(&mut self, host: &str) -> Vec<IpAddr> {
+        let now = Instant::now();
+        if let Some(cached) = self.addrs.get(host).filter(|cached| is_fresh(cached.expires, now)) {
+            return cached.addrs.clone();
+        }
+
+        let result = (host, 0).to_socket_addrs();
+        self.lookups += 1;
+        self.lookup_time += now.elapsed();
+
+        let (addrs, ttl) = match result {
+            Ok(found) => (found.map(|addr| addr.ip()).collect(), self.name_ttl),
+            Err(_) => (Vec::new(), FAILURE_TTL),
+        };
+        self.addrs.retain(|_, cached| is_fresh(cached.expires, now));
+        self.addrs.insert(host.to_string(), CachedAddrs { addrs: addrs.clone(), expires: now.checked_add(ttl) });
+        addrs
+    }
+
+    /// Network lookups made, and the time they took, since the last call.
+    pub fn take_lookup_stats(&mut self) -> (usize, Duration) {
+        let stats = (self.lookups, self.lookup_time);
+        self.lookups = 0;
+        self.lookup_time = Duration::ZERO;
+        stats
+    }
+}
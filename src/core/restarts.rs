@@ -0,0 +1,80 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::time::SystemTime;
+
+/// A followed process name that came up under a new PID.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProcessRestart {
+    pub name: String,
+    pub pid: u32,
+    /// PIDs with the name that went away since the previous sample.
+    pub replaced: Vec<u32>,
+    pub at: SystemTime,
+}
+
+impl fmt::Display for ProcessRestart {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Process restarted: {} is now PID {}", self.name, self.pid)?;
+        if !self.replaced.is_empty() {
+            let replaced: Vec<String> = self.replaced.iter().map(u32::to_string).collect();
+            write!(f, " (was {})", replaced.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+struct FollowedName {
+    name: String,
+    live: HashSet<u32>,
+    /// Whether the name has been seen running, so its first PID isn't taken for a restart.
+    seen: bool,
+}
+
+/// Watches process names for new PIDs, e.g. workers restarted by a supervisor.
+#[derive(Debug)]
+pub struct RestartTracker {
+    followed: Vec<FollowedName>,
+}
+
+impl RestartTracker {
+    /// Follows `names`, matched exactly but ignoring case.
+    pub fn new(names: &[String]) -> Self {
+        Self {
+            followed: names.iter()
+                .map(|name| FollowedName { name: name.clone(), live: HashSet::new(), seen: false })
+                .collect(),
+        }
+    }
+
+    /// Takes the PIDs running now with their names; returns the PIDs new to a followed name.
+    pub fn observe<'a>(&mut self, running: impl Iterator<Item = (u32, &'a str)> + Clone, now: SystemTime) -> Vec<ProcessRestart> {
+        let mut restarts = Vec::new();
+        for followed in &mut self.followed {
+            let live: HashSet<u32> = running.clone()
+                .filter(|(_, name)| name.eq_ignore_ascii_case(&followed.name))
+                .map(|(pid, _)| pid)
+                .collect();
+
+            if followed.seen {
+                let mut replaced: Vec<u32> = followed.live.difference(&live).copied().collect();
+                replaced.sort_unstable();
+                let mut started: Vec<u32> = live.difference(&followed.live).copied().collect();
+                started.sort_unstable();
+                restarts.extend(started.into_iter().map(|pid| ProcessRestart {
+                    name: followed.name.clone(),
+                    pid,
+                    replaced: replaced.clone(),
+                    at: now,
+                }));
+            }
+
+            followed.seen |= !live.is_empty();
+            // Keep the last PIDs while the name is down, so the next one shows what it replaced
+            if !live.is_empty() {
+                followed.live = live;
+            }
+        }
+        restarts
+    }
+}
@@ -0,0 +1,116 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::Result;
+
+/// Segments a socket sent and how many of them were retransmissions, from the kernel's
+/// `tcp_info` (Linux). Both count from when the socket was created.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct TcpStats {
+    pub retransmits: u32,
+    pub segments_out: u32,
+}
+
+/// Retransmission counters of the connected TCP sockets by inode, read over sock_diag.
+/// Empty on systems other than Linux.
+pub fn read_tcp_stats() -> Result<HashMap<u64, TcpStats>> {
+    #[cfg(target_os = "linux")]
+    {
+        super::sock_diag::tcp_stats()
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        Ok(HashMap::new())
+    }
+}
+
+/// Segments sent to a host and retransmitted, summed over its connections.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct Retransmits {
+    pub retransmits: u64,
+    pub segments_out: u64,
+}
+
+impl Retransmits {
+    pub fn add(&mut self, stats: TcpStats) {
+        self.retransmits += u64::from(stats.retransmits);
+        self.segments_out += u64::from(stats.segments_out);
+    }
+
+    /// Share of the segments sent that were retransmitted, or `None` before any was sent.
+    pub fn rate(&self) -> Option<f64> {
+        (self.segments_out > 0).then(|| self.retransmits as f64 / self.segments_out as f64)
+    }
+}
+
+/// When a host's retransmission rate raises an alert.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetransmitOptions {
+    /// Share of segments retransmitted that points at loss on the network path.
+    pub threshold: f64,
+    /// Segments sent to the host before it can be flagged, so a few lost packets on a
+    /// short connection don't alert.
+    pub min_segments: u64,
+}
+
+impl Default for RetransmitOptions {
+    fn default() -> Self {
+        Self {
+            threshold: 0.02,
+            min_segments: 1000,
+        }
+    }
+}
+
+/// A host whose retransmission rate just crossed the threshold.
+#[derive(Debug, Clone)]
+pub struct RetransmitAlert {
+    pub host: String,
+    pub retransmits: Retransmits,
+}
+
+impl fmt::Display for RetransmitAlert {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "High retransmission rate to {}: {:.1}% of {} segments retransmitted, pointing at packet loss on the network path",
+            self.host, self.retransmits.rate().unwrap_or(0.0) * 100.0, self.retransmits.segments_out)
+    }
+}
+
+/// Hosts whose retransmission rate is past the threshold, each flagged once until it
+/// falls under half the threshold.
+#[derive(Debug)]
+pub struct RetransmitWatch {
+    options: RetransmitOptions,
+    flagged: HashSet<String>,
+}
+
+impl RetransmitWatch {
+    pub fn new(options: RetransmitOptions) -> Self {
+        Self { options, flagged: HashSet::new() }
+    }
+
+    /// Whether `retransmits` counts as a high rate.
+    pub fn is_high(&self, retransmits: &Retransmits) -> bool {
+        retransmits.segments_out >= self.options.min_segments
+            && retransmits.rate().is_some_and(|rate| rate >= self.options.threshold)
+    }
+
+    /// Takes the counters per host, returning the hosts whose rate just became high.
+    pub fn update(&mut self, hosts: &HashMap<String, Retransmits>) -> Vec<String> {
+        let rearm = self.options.threshold / 2.0;
+        self.flagged.retain(|host| hosts.get(host).and_then(Retransmits::rate).is_some_and(|rate| rate >= rearm));
+        let mut high: Vec<String> = hosts.iter()
+            .filter(|(host, retransmits)| !self.flagged.contains(*host) && self.is_high(retransmits))
+            .map(|(host, _)| host.clone())
+            .collect();
+        high.sort();
+        self.flagged.extend(high.iter().cloned());
+        high
+    }
+
+    pub fn reset(&mut self) {
+        self.flagged.clear();
+    }
+}
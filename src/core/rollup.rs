@@ -0,0 +1,139 @@
+use std::collections::VecDeque;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const MINUTE: Duration = Duration::from_secs(60);
+const HOUR: Duration = Duration::from_secs(3600);
+
+/// How long each resolution of a history is kept.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RollupOptions {
+    /// Samples older than this are compacted into minute rollups.
+    pub raw_age: Duration,
+    /// Minute rollups older than this are compacted into hour rollups.
+    pub minute_age: Duration,
+    /// Hour rollups older than this are dropped.
+    pub max_age: Duration,
+}
+
+impl Default for RollupOptions {
+    fn default() -> Self {
+        Self {
+            raw_age: HOUR,
+            minute_age: Duration::from_secs(24 * 3600),
+            max_age: Duration::from_secs(7 * 24 * 3600),
+        }
+    }
+}
+
+/// Min, max and average of the samples taken in one minute or hour.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rollup {
+    pub start: SystemTime,
+    pub width: Duration,
+    pub samples: usize,
+    pub min: u64,
+    pub max: u64,
+    pub sum: u64,
+}
+
+impl Rollup {
+    fn new(start: SystemTime, width: Duration) -> Self {
+        Self { start, width, samples: 0, min: u64::MAX, max: 0, sum: 0 }
+    }
+
+    fn add(&mut self, value: u64) {
+        self.samples += 1;
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+        self.sum += value;
+    }
+
+    fn merge(&mut self, other: &Rollup) {
+        self.samples += other.samples;
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+        self.sum += other.sum;
+    }
+
+    pub fn average(&self) -> f64 {
+        if self.samples == 0 {
+            0.0
+        } else {
+            self.sum as f64 / self.samples as f64
+        }
+    }
+}
+
+/// A sampled value over a long session: recent samples as taken, older ones as minute
+/// and then hour rollups, so memory stays bounded however long the session runs.
+#[derive(Debug, Clone, Default)]
+pub struct RollupSeries {
+    raw: VecDeque<(SystemTime, u64)>,
+    minutes: VecDeque<Rollup>,
+    hours: VecDeque<Rollup>,
+}
+
+impl RollupSeries {
+    pub fn push(&mut self, time: SystemTime, value: u64) {
+        self.raw.push_back((time, value));
+    }
+
+    /// Moves samples and rollups that aged out of their resolution into the next one.
+    pub fn compact(&mut self, now: SystemTime, options: &RollupOptions) {
+        let raw_cutoff = now.checked_sub(options.raw_age).unwrap_or(UNIX_EPOCH);
+        while let Some(&(time, value)) = self.raw.front().filter(|(time, _)| *time < raw_cutoff) {
+            self.raw.pop_front();
+            let start = bucket_start(time, MINUTE);
+            if self.minutes.back().is_none_or(|last| last.start != start) {
+                self.minutes.push_back(Rollup::new(start, MINUTE));
+            }
+            if let Some(last) = self.minutes.back_mut() {
+                last.add(value);
+            }
+        }
+
+        let minute_cutoff = now.checked_sub(options.minute_age).unwrap_or(UNIX_EPOCH);
+        while let Some(minute) = self.minutes.front().filter(|minute| minute.start < minute_cutoff).copied() {
+            self.minutes.pop_front();
+            let start = bucket_start(minute.start, HOUR);
+            if self.hours.back().is_none_or(|last| last.start != start) {
+                self.hours.push_back(Rollup::new(start, HOUR));
+            }
+            if let Some(last) = self.hours.back_mut() {
+                last.merge(&minute);
+            }
+        }
+
+        let hour_cutoff = now.checked_sub(options.max_age).unwrap_or(UNIX_EPOCH);
+        while self.hours.front().is_some_and(|hour| hour.start < hour_cutoff) {
+            self.hours.pop_front();
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.raw.is_empty() && self.minutes.is_empty() && self.hours.is_empty()
+    }
+
+    /// Hour and minute rollups, oldest first; all of them precede the raw samples.
+    pub fn rollups(&self) -> impl Iterator<Item = &Rollup> {
+        self.hours.iter().chain(self.minutes.iter())
+    }
+
+    /// Samples not yet compacted, oldest first.
+    pub fn raw(&self) -> impl Iterator<Item = &(SystemTime, u64)> {
+        self.raw.iter()
+    }
+
+    /// The whole history as points, rollups by their rounded average.
+    pub fn history(&self) -> Vec<(SystemTime, u64)> {
+        self.rollups()
+            .map(|rollup| (rollup.start, rollup.average().round() as u64))
+            .chain(self.raw().copied())
+            .collect()
+    }
+}
+
+fn bucket_start(time: SystemTime, width: Duration) -> SystemTime {
+    let secs = time.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+    UNIX_EPOCH + Duration::from_secs(secs - secs % width.as_secs())
+}
@@ -0,0 +1,333 @@
+use std::collections::{HashMap, HashSet};
+use std::net::{IpAddr, SocketAddr};
+use std::time::Duration;
+
+use netstat2::{get_sockets_info, AddressFamilyFlags, ProtocolFlags, ProtocolSocketInfo, SocketInfo, TcpSocketInfo, TcpState};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System, UpdateKind};
+
+use super::backlog::{ListenerSample, read_accept_queues};
+use super::conntrack::NatTable;
+use super::dns::read_dns_sockets;
+use super::proxy::ProxyEndpoint;
+use super::resolver::Resolver;
+use super::retransmits::{TcpStats, read_tcp_stats};
+use super::threads::{DescriptorCache, SocketThreads};
+use super::tunnel::RoutingTable;
+use crate::error::{self, Error};
+
+/// Details of the process owning a socket at sampling time.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProcessSample {
+    pub name: String,
+    pub exe: Option<String>,
+    pub memory_usage: u64,
+    /// When the process started, in seconds since the Unix epoch.
+    #[serde(default)]
+    pub started_at: Option<u64>,
+}
+
+/// One non-listening TCP socket observed during a sampling pass.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SocketSample {
+    pub pid: u32,
+    pub local_port: u16,
+    pub remote_addr: IpAddr,
+    pub remote_port: u16,
+    pub remote_hostname: Option<String>,
+    #[serde(serialize_with = "serialize_state", deserialize_with = "deserialize_state")]
+    pub state: TcpState,
+    pub process: Option<ProcessSample>,
+    /// Accepted on a port this machine listens on, rather than opened to a remote one.
+    #[serde(default)]
+    pub inbound: bool,
+    /// VPN or other tunnel interface the connection goes over.
+    #[serde(default)]
+    pub tunnel: Option<String>,
+    /// Address the socket connected to when destination NAT sent the connection on to
+    /// `remote_addr`:`remote_port`.
+    #[serde(default)]
+    pub nat_from: Option<SocketAddr>,
+    /// Goes to a proxy from the owner's `HTTP_PROXY`-style environment, so the real
+    /// destination is hidden behind `remote_addr`.
+    #[serde(default)]
+    pub proxied: bool,
+    /// Threads of the owner holding the socket, when attributing sockets to threads.
+    #[serde(default)]
+    pub threads: Option<SocketThreads>,
+    /// Kernel inode of the socket (Linux), as `ss -e` and `lsof` show it.
+    #[serde(default)]
+    pub inode: Option<u64>,
+    /// Descriptor number the owner has the socket open on (Linux).
+    #[serde(default)]
+    pub fd: Option<u32>,
+    /// Local address the socket is bound to.
+    #[serde(default)]
+    pub local_addr: Option<IpAddr>,
+    /// Segments sent and retransmitted, when reading `tcp_info` (Linux).
+    #[serde(default)]
+    pub tcp_stats: Option<TcpStats>,
+    /// A UDP socket connected to a name server rather than a TCP connection.
+    #[serde(default)]
+    pub udp: bool,
+}
+
+/// Optional, costlier details gathered while sampling.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SampleOptions {
+    /// Give connections that destination NAT sent elsewhere their real destination.
+    pub conntrack: bool,
+    /// Mark connections to a proxy from the owner's environment.
+    pub proxies: bool,
+    /// Find the threads holding each socket (Linux only).
+    pub threads: bool,
+    /// Read each socket's retransmission counters from `tcp_info` (Linux only).
+    pub tcp_info: bool,
+    /// Also sample UDP sockets connected to a name server (Linux only).
+    pub dns: bool,
+}
+
+/// Sockets seen in one sampling pass, plus problems that did not stop the pass.
+#[derive(Debug, Default)]
+pub struct SamplePass {
+    pub sockets: Vec<SocketSample>,
+    /// Listening sockets with their accept queues (Linux).
+    pub listeners: Vec<ListenerSample>,
+    pub errors: Vec<Error>,
+    pub dns_lookups: usize,
+    pub dns_time: Duration,
+}
+
+/// Samples the TCP sockets on this machine, refreshing `system` for process details and
+/// naming remote addresses with `resolver`; `options` turns on the optional details.
+/// Failing to read the socket table is an error; failed DNS lookups and sockets whose
+/// owner isn't visible are reported in the pass.
+pub fn sample_sockets(system: &mut System, resolver: &mut Resolver, options: SampleOptions) -> error::Result<SamplePass> {
+    let af_flags = AddressFamilyFlags::IPV4 | AddressFamilyFlags::IPV6;
+    let proto_flags = if options.dns { ProtocolFlags::TCP | ProtocolFlags::UDP } else { ProtocolFlags::TCP };
+    let sockets_info = get_sockets_info(af_flags, proto_flags)?;
+
+    if options.proxies {
+        // A process's environment is fixed once it starts, so it's only read for new ones
+        let refresh_kind = ProcessRefreshKind::nothing()
+            .with_memory()
+            .with_cpu()
+            .with_disk_usage()
+            .with_exe(UpdateKind::OnlyIfNotSet)
+            .with_environ(UpdateKind::OnlyIfNotSet);
+        system.refresh_processes_specifics(ProcessesToUpdate::All, true, refresh_kind);
+    } else {
+        system.refresh_processes(ProcessesToUpdate::All, true);
+    }
+    let routes = RoutingTable::read();
+    let nat = if options.conntrack { NatTable::read() } else { NatTable::default() };
+    resolver.reload_hosts();
+
+    let listen_ports: HashSet<u16> = sockets_info.iter()
+        .filter_map(|si| match &si.protocol_socket_info {
+            ProtocolSocketInfo::Tcp(tcp_si) if tcp_si.state == TcpState::Listen => Some(tcp_si.local_port),
+            _ => None,
+        })
+        .collect();
+
+    let mut pass = SamplePass::default();
+    pass.listeners = sample_listeners(&sockets_info, system).unwrap_or_else(|e| {
+        pass.errors.push(e);
+        Vec::new()
+    });
+    let tcp_stats = if options.tcp_info {
+        read_tcp_stats().unwrap_or_else(|e| {
+            pass.errors.push(e);
+            HashMap::new()
+        })
+    } else {
+        HashMap::new()
+    };
+    if options.dns {
+        sample_dns_sockets(&sockets_info, system, resolver, &routes, &mut pass);
+    }
+    let mut hidden_owners = 0;
+    let mut process_proxies: HashMap<u32, Vec<ProxyEndpoint>> = HashMap::new();
+    let mut descriptors = DescriptorCache::default();
+    for si in sockets_info {
+        let tcp_si = match &si.protocol_socket_info {
+            ProtocolSocketInfo::Tcp(tcp_si) if tcp_si.state != TcpState::Listen => tcp_si,
+            _ => continue,
+        };
+        let Some(&pid) = si.associated_pids.first() else {
+            // Closing sockets are often orphaned, an established one without an owner
+            // belongs to a process we may not inspect
+            if tcp_si.state == TcpState::Established {
+                hidden_owners += 1;
+            }
+            continue;
+        };
+
+        let owner = system.process(Pid::from(pid as usize));
+        let process = owner.map(process_sample);
+
+        let dialed = SocketAddr::new(tcp_si.remote_addr, tcp_si.remote_port);
+        let destination = nat.destination(SocketAddr::new(tcp_si.local_addr, tcp_si.local_port), dialed);
+        let remote = destination.unwrap_or(dialed);
+        let remote_hostname = resolver.resolve(remote.ip()).unwrap_or_else(|e| {
+            pass.errors.push(e);
+            None
+        });
+        let proxied = options.proxies && process_proxies.entry(pid)
+            .or_insert_with(|| owner.map(|proc| ProxyEndpoint::from_environ(proc.environ())).unwrap_or_default())
+            .iter()
+            .any(|proxy| proxy.matches(remote, remote_hostname.as_deref(), |host| resolver.addresses(host)));
+        let inode = socket_inode(&si);
+        let fd = inode.and_then(|inode| descriptors.fd(pid, inode));
+        let threads = inode
+            .filter(|_| options.threads)
+            .and_then(|inode| descriptors.owners(pid, inode));
+
+        pass.sockets.push(SocketSample {
+            pid,
+            local_port: tcp_si.local_port,
+            remote_addr: remote.ip(),
+            remote_port: remote.port(),
+            remote_hostname,
+            state: tcp_si.state,
+            process,
+            inbound: listen_ports.contains(&tcp_si.local_port),
+            tunnel: routes.tunnel_for(&tcp_si.local_addr, &tcp_si.remote_addr),
+            nat_from: destination.map(|_| dialed),
+            proxied,
+            threads,
+            inode,
+            fd,
+            local_addr: Some(tcp_si.local_addr),
+            tcp_stats: inode.and_then(|inode| tcp_stats.get(&inode).copied()),
+            udp: false,
+        });
+    }
+
+    (pass.dns_lookups, pass.dns_time) = resolver.take_lookup_stats();
+    if hidden_owners > 0 {
+        pass.errors.push(Error::PermissionDenied(format!(
+            "owner of {} established connection{} is not visible, run as root to see all processes",
+            hidden_owners, if hidden_owners == 1 { "" } else { "s" },
+        )));
+    }
+    Ok(pass)
+}
+
+fn process_sample(proc: &sysinfo::Process) -> ProcessSample {
+    ProcessSample {
+        name: proc.name().to_string_lossy().to_string(),
+        exe: proc.exe().map(|p| p.to_string_lossy().to_string()),
+        memory_usage: proc.memory(),
+        started_at: Some(proc.start_time()).filter(|secs| *secs > 0),
+    }
+}
+
+/// Adds the UDP sockets among `sockets_info` that are connected to a name server as
+/// connections to it. Stub resolvers open one per lookup, so each sampled socket counts
+/// as a query.
+fn sample_dns_sockets(sockets_info: &[SocketInfo], system: &System, resolver: &mut Resolver, routes: &RoutingTable, pass: &mut SamplePass) {
+    let peers = match read_dns_sockets() {
+        Ok(peers) => peers,
+        Err(e) => {
+            pass.errors.push(e);
+            return;
+        }
+    };
+    for si in sockets_info.iter().filter(|si| matches!(si.protocol_socket_info, ProtocolSocketInfo::Udp(_))) {
+        let Some((inode, (local, remote))) = socket_inode(si).and_then(|inode| Some((inode, peers.get(&inode)?))) else {
+            continue;
+        };
+        let Some(&pid) = si.associated_pids.first() else {
+            continue;
+        };
+        let remote_hostname = resolver.resolve(remote.ip()).unwrap_or_else(|e| {
+            pass.errors.push(e);
+            None
+        });
+        pass.sockets.push(SocketSample {
+            pid,
+            local_port: local.port(),
+            remote_addr: remote.ip(),
+            remote_port: remote.port(),
+            remote_hostname,
+            state: TcpState::Established,
+            process: system.process(Pid::from(pid as usize)).map(process_sample),
+            inbound: false,
+            tunnel: routes.tunnel_for(&local.ip(), &remote.ip()),
+            nat_from: None,
+            proxied: false,
+            threads: None,
+            inode: Some(inode),
+            fd: None,
+            local_addr: Some(local.ip()),
+            tcp_stats: None,
+            udp: true,
+        });
+    }
+}
+
+/// Listening sockets among `sockets_info` with their accept queues, where the kernel
+/// reports them.
+fn sample_listeners(sockets_info: &[SocketInfo], system: &System) -> error::Result<Vec<ListenerSample>> {
+    let listening: Vec<(&SocketInfo, &TcpSocketInfo)> = sockets_info.iter()
+        .filter_map(|si| match &si.protocol_socket_info {
+            ProtocolSocketInfo::Tcp(tcp_si) if tcp_si.state == TcpState::Listen => Some((si, tcp_si)),
+            _ => None,
+        })
+        .collect();
+    if listening.is_empty() {
+        return Ok(Vec::new());
+    }
+    let queues = read_accept_queues()?;
+
+    Ok(listening.into_iter()
+        .filter_map(|(si, tcp_si)| {
+            let queue = *queues.get(&socket_inode(si)?)?;
+            let pid = si.associated_pids.first().copied();
+            let process_name = pid
+                .and_then(|pid| system.process(Pid::from(pid as usize)))
+                .map(|proc| proc.name().to_string_lossy().to_string());
+            Some(ListenerSample { port: tcp_si.local_port, local_addr: tcp_si.local_addr, pid, process_name, queue })
+        })
+        .collect())
+}
+
+/// The inode identifying the socket, which only Linux reports.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn socket_inode(si: &SocketInfo) -> Option<u64> {
+    Some(u64::from(si.inode))
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+fn socket_inode(_si: &SocketInfo) -> Option<u64> {
+    None
+}
+
+/// Parses the names produced by `TcpState`'s `Display` impl.
+pub fn parse_tcp_state(name: &str) -> TcpState {
+    match name {
+        "CLOSED" => TcpState::Closed,
+        "LISTEN" => TcpState::Listen,
+        "SYN_SENT" => TcpState::SynSent,
+        "SYN_RCVD" => TcpState::SynReceived,
+        "ESTABLISHED" => TcpState::Established,
+        "FIN_WAIT_1" => TcpState::FinWait1,
+        "FIN_WAIT_2" => TcpState::FinWait2,
+        "CLOSE_WAIT" => TcpState::CloseWait,
+        "CLOSING" => TcpState::Closing,
+        "LAST_ACK" => TcpState::LastAck,
+        "TIME_WAIT" => TcpState::TimeWait,
+        "DELETE_TCB" => TcpState::DeleteTcb,
+        _ => TcpState::Unknown,
+    }
+}
+
+fn serialize_state<S: Serializer>(state: &TcpState, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(state)
+}
+
+fn deserialize_state<'de, D: Deserializer<'de>>(deserializer: D) -> Result<TcpState, D::Error> {
+    let name = String::deserialize(deserializer)?;
+    Ok(parse_tcp_state(&name))
+}
@@ -0,0 +1,121 @@
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use serde::{Deserialize, Serialize};
+
+/// Where a remote address is, as far as traffic leaving the machine goes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AddressScope {
+    /// 127.0.0.0/8 and ::1: never leaves the machine.
+    Loopback,
+    /// 169.254.0.0/16 and fe80::/10: the local network segment only.
+    LinkLocal,
+    /// RFC 1918 networks and IPv6 unique local addresses (fc00::/7).
+    Private,
+    /// Everything else.
+    #[default]
+    Public,
+}
+
+impl AddressScope {
+    pub const ALL: [AddressScope; 4] = [
+        AddressScope::Loopback,
+        AddressScope::LinkLocal,
+        AddressScope::Private,
+        AddressScope::Public,
+    ];
+
+    pub fn of(addr: &IpAddr) -> Self {
+        match addr {
+            IpAddr::V4(v4) => Self::of_v4(v4),
+            IpAddr::V6(v6) => match v6.to_ipv4_mapped() {
+                Some(v4) => Self::of_v4(&v4),
+                None => Self::of_v6(v6),
+            },
+        }
+    }
+
+    fn of_v4(addr: &Ipv4Addr) -> Self {
+        if addr.is_loopback() {
+            AddressScope::Loopback
+        } else if addr.is_link_local() {
+            AddressScope::LinkLocal
+        } else if addr.is_private() {
+            AddressScope::Private
+        } else {
+            AddressScope::Public
+        }
+    }
+
+    fn of_v6(addr: &Ipv6Addr) -> Self {
+        let first = addr.segments()[0];
+        if addr.is_loopback() {
+            AddressScope::Loopback
+        } else if first & 0xffc0 == 0xfe80 {
+            AddressScope::LinkLocal
+        } else if first & 0xfe00 == 0xfc00 {
+            AddressScope::Private
+        } else {
+            AddressScope::Public
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "loopback" | "lo" => Some(AddressScope::Loopback),
+            "link-local" | "linklocal" => Some(AddressScope::LinkLocal),
+            "private" => Some(AddressScope::Private),
+            "public" => Some(AddressScope::Public),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AddressScope::Loopback => "loopback",
+            AddressScope::LinkLocal => "link-local",
+            AddressScope::Private => "private",
+            AddressScope::Public => "public",
+        }
+    }
+}
+
+impl fmt::Display for AddressScope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Connections per address scope.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct ScopeCounts {
+    pub loopback: usize,
+    pub link_local: usize,
+    pub private: usize,
+    pub public: usize,
+}
+
+impl ScopeCounts {
+    pub fn add(&mut self, scope: AddressScope, count: usize) {
+        *self.get_mut(scope) += count;
+    }
+
+    pub fn get(&self, scope: AddressScope) -> usize {
+        match scope {
+            AddressScope::Loopback => self.loopback,
+            AddressScope::LinkLocal => self.link_local,
+            AddressScope::Private => self.private,
+            AddressScope::Public => self.public,
+        }
+    }
+
+    fn get_mut(&mut self, scope: AddressScope) -> &mut usize {
+        match scope {
+            AddressScope::Loopback => &mut self.loopback,
+            AddressScope::LinkLocal => &mut self.link_local,
+            AddressScope::Private => &mut self.private,
+            AddressScope::Public => &mut self.public,
+        }
+    }
+}
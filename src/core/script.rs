@@ -0,0 +1,99 @@
+use std::path::Path;
+
+use super::monitor::{ConnectionSummary, HostMetrics, ProcessMetrics};
+use super::snapshot::ConnectionView;
+use crate::error::Result;
+
+/// Operations a hook call may take before it is stopped, so a runaway loop can't stall
+/// sampling.
+#[cfg(feature = "scripting")]
+const MAX_OPERATIONS: u64 = 100_000;
+
+/// User hooks from a Rhai script, each optional:
+///
+/// - `group(conn)` names the group of a connection no configured group matched
+/// - `host_column(row)` and `process_column(row)` fill the Script column of those tables
+/// - `alert(summary)` raises an alert with the message it returns; it fires again only
+///   once the message changes
+///
+/// Connections, rows and the summary are passed as maps with the fields of their JSON
+/// form. Returning `()` means no group, no value or no alert.
+#[cfg(feature = "scripting")]
+pub struct ScriptHooks {
+    engine: rhai::Engine,
+    ast: rhai::AST,
+    last_alert: Option<String>,
+}
+
+#[cfg(feature = "scripting")]
+impl ScriptHooks {
+    pub fn load(path: &Path) -> Result<Self> {
+        let mut engine = rhai::Engine::new();
+        engine.set_max_operations(MAX_OPERATIONS);
+        let ast = engine.compile_file(path.to_path_buf())
+            .map_err(|e| crate::error::Error::Config(format!("Failed to load script '{}': {}", path.display(), e)))?;
+        Ok(Self { engine, ast, last_alert: None })
+    }
+
+    pub fn group(&self, conn: &ConnectionView) -> std::result::Result<Option<String>, String> {
+        self.call("group", conn)
+    }
+
+    pub fn host_column(&self, row: &HostMetrics) -> std::result::Result<Option<String>, String> {
+        self.call("host_column", row)
+    }
+
+    pub fn process_column(&self, row: &ProcessMetrics) -> std::result::Result<Option<String>, String> {
+        self.call("process_column", row)
+    }
+
+    /// The alert to raise for `summary`, if its message differs from the last one.
+    pub fn alert(&mut self, summary: &ConnectionSummary) -> std::result::Result<Option<String>, String> {
+        let message = self.call("alert", summary)?.filter(|message| !message.is_empty());
+        if message == self.last_alert {
+            return Ok(None);
+        }
+        self.last_alert = message.clone();
+        Ok(message)
+    }
+
+    /// Calls `name` with `arg` as a map; `Ok(None)` if the script doesn't define it.
+    fn call(&self, name: &str, arg: &impl serde::Serialize) -> std::result::Result<Option<String>, String> {
+        if !self.ast.iter_functions().any(|f| f.name == name && f.params.len() == 1) {
+            return Ok(None);
+        }
+        let arg = rhai::serde::to_dynamic(arg).map_err(|e| format!("{}(): {}", name, e))?;
+        let options = rhai::CallFnOptions::new().eval_ast(false);
+        let value: rhai::Dynamic = self.engine
+            .call_fn_with_options(options, &mut rhai::Scope::new(), &self.ast, name, (arg,))
+            .map_err(|e| format!("{}(): {}", name, e))?;
+        Ok(if value.is_unit() { None } else { Some(value.to_string()) })
+    }
+}
+
+/// Without the `scripting` feature there are no hooks; loading a script is an error.
+#[cfg(not(feature = "scripting"))]
+pub enum ScriptHooks {}
+
+#[cfg(not(feature = "scripting"))]
+impl ScriptHooks {
+    pub fn load(_path: &Path) -> Result<Self> {
+        Err(crate::error::Error::Config("Scripting requires building with --features scripting".to_string()))
+    }
+
+    pub fn group(&self, _conn: &ConnectionView) -> std::result::Result<Option<String>, String> {
+        match *self {}
+    }
+
+    pub fn host_column(&self, _row: &HostMetrics) -> std::result::Result<Option<String>, String> {
+        match *self {}
+    }
+
+    pub fn process_column(&self, _row: &ProcessMetrics) -> std::result::Result<Option<String>, String> {
+        match *self {}
+    }
+
+    pub fn alert(&mut self, _summary: &ConnectionSummary) -> std::result::Result<Option<String>, String> {
+        match *self {}
+    }
+}
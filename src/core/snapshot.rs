@@ -0,0 +1,155 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Local, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::annotations::Annotation;
+use super::connection::Connection;
+use super::filters::ConnectionFilter;
+use super::monitor::{
+    ClientMetrics,
+    ConnectionMonitor,
+    ConnectionSummary,
+    GroupMetrics,
+    HostMetrics,
+    ListenPortMetrics,
+    ProcessHostMetrics,
+    ProcessMetrics,
+};
+
+/// Aggregated metrics for the connections matching a filter at a point in time.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetricsSnapshot {
+    pub timestamp: DateTime<Utc>,
+    pub filter: String,
+    pub summary: ConnectionSummary,
+    pub hosts: Vec<HostMetrics>,
+    pub processes: Vec<ProcessMetrics>,
+    pub process_hosts: Vec<ProcessHostMetrics>,
+    pub groups: Vec<GroupMetrics>,
+    #[serde(default)]
+    pub listen_ports: Vec<ListenPortMetrics>,
+    #[serde(default)]
+    pub clients: Vec<ClientMetrics>,
+}
+
+impl MetricsSnapshot {
+    pub fn capture(monitor: &ConnectionMonitor, filter: &ConnectionFilter) -> Self {
+        Self {
+            timestamp: Utc::now(),
+            filter: filter.to_string(),
+            summary: monitor.get_summary(filter),
+            hosts: monitor.get_host_metrics(filter),
+            processes: monitor.get_process_metrics(filter),
+            process_hosts: monitor.get_process_host_metrics(filter),
+            groups: monitor.get_group_metrics(filter),
+            listen_ports: monitor.get_listen_port_metrics(filter),
+            clients: monitor.get_client_metrics(filter),
+        }
+    }
+}
+
+/// An active connection as exposed in JSON output.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConnectionView {
+    pub pid: u32,
+    pub process: Option<String>,
+    pub local_port: u16,
+    pub remote_addr: String,
+    pub remote_host: Option<String>,
+    pub remote_port: u16,
+    pub state: String,
+    pub group: Option<String>,
+    #[serde(default)]
+    pub inbound: bool,
+    #[serde(default)]
+    pub tunnel: Option<String>,
+    /// Address dialed before destination NAT, if translated.
+    #[serde(default)]
+    pub nat_from: Option<String>,
+    /// Goes to a proxy set in the process environment.
+    #[serde(default)]
+    pub proxied: bool,
+    pub first_seen: DateTime<Utc>,
+    pub last_seen: DateTime<Utc>,
+}
+
+impl ConnectionView {
+    pub fn new(conn: &Connection, process: Option<String>) -> Self {
+        Self {
+            pid: conn.pid,
+            process,
+            local_port: conn.local_port,
+            remote_addr: conn.remote_addr.to_string(),
+            remote_host: conn.remote_hostname.clone(),
+            remote_port: conn.remote_port,
+            state: conn.state.to_string(),
+            group: conn.group.clone(),
+            inbound: conn.inbound,
+            tunnel: conn.tunnel.clone(),
+            nat_from: conn.nat_from.map(|addr| addr.to_string()),
+            proxied: conn.proxied,
+            first_seen: conn.first_seen.into(),
+            last_seen: conn.last_seen.into(),
+        }
+    }
+}
+
+/// Metrics plus the active connections behind them, dumped to a file on demand.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FullSnapshot {
+    /// When monitoring started; missing in daemon metrics files.
+    #[serde(default)]
+    pub started: Option<DateTime<Utc>>,
+    #[serde(flatten)]
+    pub metrics: MetricsSnapshot,
+    #[serde(default)]
+    pub connections: Vec<ConnectionView>,
+    #[serde(default)]
+    pub annotations: Vec<Annotation>,
+}
+
+impl FullSnapshot {
+    pub fn capture(monitor: &ConnectionMonitor, filter: &ConnectionFilter, started: DateTime<Utc>) -> Self {
+        let connections = monitor.get_filtered_active_connections(filter)
+            .into_iter()
+            .map(|conn| {
+                let process = monitor.get_process(conn.pid).and_then(|p| p.name.clone());
+                ConnectionView::new(conn, process)
+            })
+            .collect();
+
+        Self {
+            started: Some(started),
+            metrics: MetricsSnapshot::capture(monitor, filter),
+            connections,
+            annotations: monitor.get_annotations().cloned().collect(),
+        }
+    }
+
+    /// Loads a snapshot written with `S`, or the last line of a daemon `file:` sink, which
+    /// holds the totals for the whole recorded session.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read '{}': {}", path.display(), e))?;
+        if let Ok(snapshot) = serde_json::from_str(&contents) {
+            return Ok(snapshot);
+        }
+
+        let last_line = contents.lines().rev().find(|line| !line.trim().is_empty())
+            .ok_or_else(|| format!("'{}' is empty", path.display()))?;
+        serde_json::from_str(last_line)
+            .map_err(|e| format!("'{}' is not a tcpcount snapshot or metrics file: {}", path.display(), e))
+    }
+
+    /// Writes the snapshot as pretty-printed JSON to `tcpcount-snapshot-YYYYmmdd-HHMMSS.json` in `dir`.
+    pub fn write_to(&self, dir: &Path) -> io::Result<PathBuf> {
+        let timestamp: DateTime<Local> = self.metrics.timestamp.into();
+        let path = dir.join(format!("tcpcount-snapshot-{}.json", timestamp.format("%Y%m%d-%H%M%S")));
+        let json = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+        fs::write(&path, json)?;
+        Ok(path)
+    }
+}
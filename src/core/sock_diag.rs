@@ -0,0 +1,119 @@
+use std::collections::HashMap;
+use std::net::SocketAddr as PeerAddr;
+
+use netlink_packet_core::{NetlinkHeader, NetlinkMessage, NetlinkPayload, NLM_F_DUMP, NLM_F_REQUEST};
+use netlink_packet_sock_diag::constants::{AF_INET, AF_INET6, IPPROTO_TCP, IPPROTO_UDP};
+use netlink_packet_sock_diag::inet::{nlas::Nla, ExtensionFlags, InetRequest, InetResponse, SocketId, StateFlags};
+use netlink_packet_sock_diag::SockDiagMessage;
+use netlink_sys::{protocols::NETLINK_SOCK_DIAG, Socket, SocketAddr};
+
+use super::backlog::AcceptQueue;
+use super::retransmits::TcpStats;
+use crate::error::{Error, Result};
+
+/// Offsets of `tcpi_total_retrans` and `tcpi_segs_out` in the kernel's `struct tcp_info`.
+const TOTAL_RETRANS_OFFSET: usize = 100;
+const SEGS_OUT_OFFSET: usize = 136;
+
+/// Accept queues of the TCP listening sockets by inode. For listeners the kernel reports
+/// the connections waiting to be accepted as the receive queue and the backlog as the send
+/// queue, as `ss -l` shows them.
+pub fn listeners() -> Result<HashMap<u64, AcceptQueue>> {
+    let mut queues = HashMap::new();
+    dump_tcp(StateFlags::LISTEN, ExtensionFlags::empty(), |response| {
+        queues.insert(response.header.inode as u64, AcceptQueue {
+            queued: response.header.recv_queue,
+            backlog: response.header.send_queue,
+        });
+    })?;
+    Ok(queues)
+}
+
+/// Retransmission counters of the connected TCP sockets by inode, from their `tcp_info`.
+pub fn tcp_stats() -> Result<HashMap<u64, TcpStats>> {
+    let mut stats = HashMap::new();
+    dump_tcp(StateFlags::all() - StateFlags::LISTEN, ExtensionFlags::INFO, |response| {
+        let info = response.nlas.iter().find_map(|nla| match nla {
+            Nla::TcpInfo(bytes) => parse_tcp_info(bytes),
+            _ => None,
+        });
+        if let Some(info) = info {
+            stats.insert(response.header.inode as u64, info);
+        }
+    })?;
+    Ok(stats)
+}
+
+/// Local and remote addresses of the connected UDP sockets sending to `port`, by inode.
+/// Resolvers such as glibc's connect their UDP socket to the name server for each lookup.
+pub fn udp_peers(port: u16) -> Result<HashMap<u64, (PeerAddr, PeerAddr)>> {
+    let mut peers = HashMap::new();
+    let mut each = |response: &InetResponse| {
+        let id = &response.header.socket_id;
+        if id.destination_port == port {
+            peers.insert(response.header.inode as u64, (
+                PeerAddr::new(id.source_address, id.source_port),
+                PeerAddr::new(id.destination_address, id.destination_port),
+            ));
+        }
+    };
+    // Connected UDP sockets are reported as established
+    dump(AF_INET, IPPROTO_UDP, SocketId::new_v4(), StateFlags::ESTABLISHED, ExtensionFlags::empty(), &mut each)?;
+    dump(AF_INET6, IPPROTO_UDP, SocketId::new_v6(), StateFlags::ESTABLISHED, ExtensionFlags::empty(), &mut each)?;
+    Ok(peers)
+}
+
+/// Reads the counters out of a raw `tcp_info`; older kernels without `tcpi_segs_out` give `None`.
+fn parse_tcp_info(bytes: &[u8]) -> Option<TcpStats> {
+    let field = |offset: usize| Some(u32::from_ne_bytes(bytes.get(offset..offset + 4)?.try_into().ok()?));
+    Some(TcpStats {
+        retransmits: field(TOTAL_RETRANS_OFFSET)?,
+        segments_out: field(SEGS_OUT_OFFSET)?,
+    })
+}
+
+/// Dumps the IPv4 and IPv6 TCP sockets in `states`, with the `extensions` attributes.
+fn dump_tcp(states: StateFlags, extensions: ExtensionFlags, mut each: impl FnMut(&InetResponse)) -> Result<()> {
+    dump(AF_INET, IPPROTO_TCP, SocketId::new_v4(), states, extensions, &mut each)?;
+    dump(AF_INET6, IPPROTO_TCP, SocketId::new_v6(), states, extensions, &mut each)
+}
+
+fn dump(family: u8, protocol: u8, socket_id: SocketId, states: StateFlags, extensions: ExtensionFlags, each: &mut impl FnMut(&InetResponse)) -> Result<()> {
+    let mut socket = Socket::new(NETLINK_SOCK_DIAG)?;
+    socket.bind_auto()?;
+    socket.connect(&SocketAddr::new(0, 0))?;
+
+    let mut header = NetlinkHeader::default();
+    header.flags = NLM_F_REQUEST | NLM_F_DUMP;
+    let mut packet = NetlinkMessage::new(header, SockDiagMessage::InetRequest(InetRequest {
+        family,
+        protocol,
+        extensions,
+        states,
+        socket_id,
+    }).into());
+    packet.finalize();
+    let mut request = vec![0; packet.buffer_len()];
+    packet.serialize(&mut request[..]);
+    socket.send(&request, 0)?;
+
+    let mut buf = vec![0; 32 * 1024];
+    loop {
+        let size = socket.recv(&mut &mut buf[..], 0)?;
+        let mut offset = 0;
+        while offset < size {
+            let message = NetlinkMessage::<SockDiagMessage>::deserialize(&buf[offset..size])
+                .map_err(|e| Error::Netstat(format!("invalid sock_diag reply: {}", e)))?;
+            if message.header.length == 0 {
+                break;
+            }
+            offset += message.header.length as usize;
+            match message.payload {
+                NetlinkPayload::InnerMessage(SockDiagMessage::InetResponse(response)) => each(&response),
+                NetlinkPayload::Done(_) => return Ok(()),
+                NetlinkPayload::Error(e) => return Err(Error::Netstat(format!("sock_diag: {}", e))),
+                _ => {}
+            }
+        }
+    }
+}
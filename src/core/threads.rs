@@ -0,0 +1,144 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+/// A thread of a process, as named in `/proc/<pid>/task/<tid>/comm`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ThreadInfo {
+    pub tid: u32,
+    pub name: String,
+}
+
+/// Which threads of its process hold a socket open.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SocketThreads {
+    /// Every one of this many threads, as threads share their process's descriptor table
+    /// unless created with their own.
+    All(usize),
+    /// Only these threads.
+    Some(Vec<ThreadInfo>),
+}
+
+impl fmt::Display for SocketThreads {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SocketThreads::All(count) => write!(f, "all {} threads (shared fd table)", count),
+            SocketThreads::Some(threads) => {
+                let names: Vec<String> = threads.iter()
+                    .map(|thread| format!("{} {}", thread.tid, thread.name))
+                    .collect();
+                write!(f, "{}", names.join(", "))
+            }
+        }
+    }
+}
+
+/// Socket inodes each thread of a process holds, read from `/proc/<pid>/task/*/fd`.
+#[derive(Debug, Clone, Default)]
+pub struct ThreadSockets {
+    threads: Vec<(ThreadInfo, Vec<u64>)>,
+}
+
+impl ThreadSockets {
+    /// Reads the threads of `pid`. Empty where they can't be read, such as for another
+    /// user's process without root, or on systems other than Linux.
+    pub fn read(pid: u32) -> Self {
+        if !cfg!(target_os = "linux") {
+            return Self::default();
+        }
+        let task_dir = Path::new("/proc").join(pid.to_string()).join("task");
+        let Ok(tasks) = fs::read_dir(&task_dir) else {
+            return Self::default();
+        };
+
+        let mut threads = Vec::new();
+        for task in tasks.flatten() {
+            let Some(tid) = task.file_name().to_str().and_then(|name| name.parse().ok()) else {
+                continue;
+            };
+            let name = fs::read_to_string(task.path().join("comm"))
+                .map(|comm| comm.trim_end().to_string())
+                .unwrap_or_default();
+            let inodes = socket_fds(&task.path().join("fd")).into_keys().collect();
+            threads.push((ThreadInfo { tid, name }, inodes));
+        }
+        Self::from_threads(threads)
+    }
+
+    /// Builds the table from each thread and the socket inodes it holds.
+    pub fn from_threads(mut threads: Vec<(ThreadInfo, Vec<u64>)>) -> Self {
+        threads.sort_by_key(|(thread, _)| thread.tid);
+        Self { threads }
+    }
+
+    /// The threads holding the socket with `inode`; `None` if none of them does.
+    pub fn owners(&self, inode: u64) -> Option<SocketThreads> {
+        let owners: Vec<ThreadInfo> = self.threads.iter()
+            .filter(|(_, inodes)| inodes.contains(&inode))
+            .map(|(thread, _)| thread.clone())
+            .collect();
+        match owners.len() {
+            0 => None,
+            count if count > 1 && count == self.threads.len() => Some(SocketThreads::All(count)),
+            _ => Some(SocketThreads::Some(owners)),
+        }
+    }
+}
+
+/// Socket inodes of a process, mapped to the descriptor number each is open on, read
+/// from `/proc/<pid>/fd`. Empty where it can't be read or on systems other than Linux.
+pub fn process_socket_fds(pid: u32) -> HashMap<u64, u32> {
+    if !cfg!(target_os = "linux") {
+        return HashMap::new();
+    }
+    socket_fds(&Path::new("/proc").join(pid.to_string()).join("fd"))
+}
+
+/// Socket inodes in a descriptor directory, mapped to the lowest descriptor each is on.
+fn socket_fds(dir: &Path) -> HashMap<u64, u32> {
+    let mut sockets = HashMap::new();
+    let Ok(fds) = fs::read_dir(dir) else {
+        return sockets;
+    };
+    for fd in fds.flatten() {
+        let Some(number) = fd.file_name().to_str().and_then(|name| name.parse::<u32>().ok()) else {
+            continue;
+        };
+        let Some(inode) = fs::read_link(fd.path()).ok().and_then(|target| socket_inode(&target.to_string_lossy())) else {
+            continue;
+        };
+        sockets.entry(inode)
+            .and_modify(|lowest: &mut u32| *lowest = (*lowest).min(number))
+            .or_insert(number);
+    }
+    sockets
+}
+
+/// The inode of a `socket:[12345]` descriptor target.
+fn socket_inode(target: &str) -> Option<u64> {
+    target.strip_prefix("socket:[")?.strip_suffix(']')?.parse().ok()
+}
+
+/// Descriptor and thread tables of the processes asked about, each read once per
+/// sampling pass.
+#[derive(Debug, Default)]
+pub struct DescriptorCache {
+    fds: HashMap<u32, HashMap<u64, u32>>,
+    threads: HashMap<u32, ThreadSockets>,
+}
+
+impl DescriptorCache {
+    /// The descriptor number `pid` has the socket with `inode` open on.
+    pub fn fd(&mut self, pid: u32, inode: u64) -> Option<u32> {
+        self.fds.entry(pid).or_insert_with(|| process_socket_fds(pid)).get(&inode).copied()
+    }
+
+    /// The threads of `pid` holding the socket with `inode`.
+    pub fn owners(&mut self, pid: u32, inode: u64) -> Option<SocketThreads> {
+        self.threads.entry(pid).or_insert_with(|| ThreadSockets::read(pid)).owners(inode)
+    }
+}
@@ -0,0 +1,107 @@
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use super::cidr::Cidr;
+
+/// Interface name prefixes of VPNs and other tunnels: tun/tap devices, WireGuard,
+/// macOS utun, PPP links, Tailscale and ZeroTier.
+const TUNNEL_PREFIXES: [&str; 8] = ["tun", "tap", "wg", "utun", "ppp", "ipsec", "tailscale", "zt"];
+
+pub fn is_tunnel_interface(name: &str) -> bool {
+    TUNNEL_PREFIXES.iter().any(|prefix| name.starts_with(prefix))
+}
+
+/// A route to a network through an interface.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Route {
+    pub network: Cidr,
+    pub interface: String,
+    pub metric: u32,
+}
+
+/// The kernel routing table, used to tell which interface a connection goes over.
+#[derive(Debug, Clone, Default)]
+pub struct RoutingTable {
+    routes: Vec<Route>,
+}
+
+impl RoutingTable {
+    /// Reads the main routing table. Empty where it can't be read, including on systems
+    /// other than Linux, so nothing is taken for tunneled.
+    pub fn read() -> Self {
+        if !cfg!(target_os = "linux") {
+            return Self::default();
+        }
+        let ipv4 = std::fs::read_to_string("/proc/net/route").unwrap_or_default();
+        let ipv6 = std::fs::read_to_string("/proc/net/ipv6_route").unwrap_or_default();
+        Self::parse(&ipv4, &ipv6)
+    }
+
+    /// Builds the table from the text of `/proc/net/route` and `/proc/net/ipv6_route`.
+    pub fn parse(ipv4: &str, ipv6: &str) -> Self {
+        let mut routes = parse_ipv4_routes(ipv4);
+        routes.extend(parse_ipv6_routes(ipv6));
+        Self { routes }
+    }
+
+    /// Interface of the most specific route to `addr`, the lowest metric among equals.
+    pub fn interface_for(&self, addr: &IpAddr) -> Option<&str> {
+        let addr = match addr {
+            IpAddr::V6(v6) => v6.to_ipv4_mapped().map_or(*addr, IpAddr::V4),
+            IpAddr::V4(_) => *addr,
+        };
+        self.routes.iter()
+            .filter(|route| route.network.addr.is_ipv4() == addr.is_ipv4() && route.network.contains(&addr))
+            .min_by_key(|route| (std::cmp::Reverse(route.network.prefix_len), route.metric))
+            .map(|route| route.interface.as_str())
+    }
+
+    /// The tunnel interface a connection goes over, if any. A local address on a tunnel's
+    /// network means the socket is bound to it; otherwise the route to the remote end
+    /// decides, which catches full-tunnel VPNs that take over the default route.
+    pub fn tunnel_for(&self, local: &IpAddr, remote: &IpAddr) -> Option<String> {
+        [local, remote].into_iter()
+            .filter(|addr| !addr.is_unspecified() && !addr.is_loopback())
+            .filter_map(|addr| self.interface_for(addr))
+            .find(|interface| is_tunnel_interface(interface))
+            .map(str::to_string)
+    }
+}
+
+/// Parses `/proc/net/route`: addresses and masks are hex in host byte order.
+fn parse_ipv4_routes(text: &str) -> Vec<Route> {
+    text.lines().skip(1).filter_map(|line| {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let [interface, destination, _gateway, _flags, _refcnt, _use, metric, mask, ..] = fields[..] else {
+            return None;
+        };
+        let destination = u32::from_str_radix(destination, 16).ok()?;
+        let mask = u32::from_str_radix(mask, 16).ok()?;
+        Some(Route {
+            network: Cidr {
+                addr: IpAddr::V4(Ipv4Addr::from(destination.to_ne_bytes())),
+                prefix_len: mask.count_ones() as u8,
+            },
+            interface: interface.to_string(),
+            metric: metric.parse().ok()?,
+        })
+    }).collect()
+}
+
+/// Parses `/proc/net/ipv6_route`: destination and prefix length in hex, then source,
+/// next hop, metric, use counts, flags and the interface.
+fn parse_ipv6_routes(text: &str) -> Vec<Route> {
+    text.lines().filter_map(|line| {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let [destination, prefix_len, _source, _source_prefix_len, _next_hop, metric, _refcnt, _use, _flags, interface] = fields[..] else {
+            return None;
+        };
+        Some(Route {
+            network: Cidr {
+                addr: IpAddr::V6(Ipv6Addr::from(u128::from_str_radix(destination, 16).ok()?)),
+                prefix_len: u8::from_str_radix(prefix_len, 16).ok()?,
+            },
+            interface: interface.to_string(),
+            metric: u32::from_str_radix(metric, 16).ok()?,
+        })
+    }).collect()
+}
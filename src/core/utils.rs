@@ -1,18 +1,52 @@
-use std::net::IpAddr;
-use dns_lookup::lookup_addr;
+use std::net::{IpAddr, SocketAddr};
+use dns_lookup::getnameinfo;
 
-pub fn resolve_addr_to_hostname(addr: IpAddr) -> Option<String> {
+use crate::error::{Error, Result};
+
+/// Whether `addr` is worth a reverse lookup; loopback and link-local addresses are not.
+pub fn is_resolvable(addr: IpAddr) -> bool {
     match addr {
-        IpAddr::V4(ipv4_addr) => {
-            if ipv4_addr.is_link_local() || ipv4_addr.is_loopback() {
-                return None
-            }
-        }
-        IpAddr::V6(ipv6_addr) => {
-            if ipv6_addr.is_unicast_link_local() || ipv6_addr.is_loopback() {
-                return None
-            }
+        IpAddr::V4(ipv4_addr) => !(ipv4_addr.is_link_local() || ipv4_addr.is_loopback()),
+        IpAddr::V6(ipv6_addr) => !(ipv6_addr.is_unicast_link_local() || ipv6_addr.is_loopback()),
+    }
+}
+
+/// Reverse-resolves `addr`. Addresses without a name give `Ok(None)`; lookups that could
+/// not be answered, e.g. because the DNS server timed out, are errors.
+pub fn resolve_addr_to_hostname(addr: IpAddr) -> Result<Option<String>> {
+    if !is_resolvable(addr) {
+        return Ok(None);
+    }
+    // Without NI_NAMEREQD an address with no name comes back in numeric form
+    match getnameinfo(&SocketAddr::new(addr, 0), 0) {
+        Ok((name, _)) if name.parse::<IpAddr>().is_ok() => Ok(None),
+        Ok((name, _)) => Ok(Some(name)),
+        Err(e) => Err(Error::Dns { addr, message: std::io::Error::from(e).to_string() }),
+    }
+}
+
+/// Matches `text` against a pattern where `*` stands for any run of characters.
+pub fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let parts: Vec<&str> = pattern.split('*').collect();
+    if parts.len() == 1 {
+        return pattern == text;
+    }
+
+    let first = parts[0];
+    let last = parts[parts.len() - 1];
+    if text.len() < first.len() + last.len() {
+        return false;
+    }
+    let Some(mut remaining) = text.strip_prefix(first).and_then(|rest| rest.strip_suffix(last)) else {
+        return false;
+    };
+
+    for part in &parts[1..parts.len() - 1] {
+        match remaining.find(part) {
+            Some(idx) => remaining = &remaining[idx + part.len()..],
+            None => return false,
         }
     }
-    lookup_addr(&addr).ok()
-} 
\ No newline at end of file
+
+    true
+}
@@ -0,0 +1,146 @@
+use std::collections::HashMap;
+
+use super::connection::Connection;
+use super::counters::ConcurrencyCounter;
+use super::process::Process;
+use super::scope::AddressScope;
+
+/// Name of the view over endpoint groups, shown by default.
+pub const GROUP_VIEW: &str = "group";
+
+/// A way of grouping connections into the rows of a table, picked at runtime. Each row
+/// shows the active, total and max connections of one key.
+pub trait AggregationView: Send {
+    /// Name the view is selected by, e.g. with `--view`.
+    fn name(&self) -> &str;
+
+    /// Header of the key column, also used in the table title.
+    fn column(&self) -> &str;
+
+    /// The row a connection counts under; `None` leaves it out of the view.
+    fn key(&self, conn: &Connection, process: Option<&Process>) -> Option<String>;
+}
+
+/// Connections by the endpoint group they matched.
+pub struct EndpointGroupView;
+
+impl AggregationView for EndpointGroupView {
+    fn name(&self) -> &str {
+        GROUP_VIEW
+    }
+
+    fn column(&self) -> &str {
+        "Group"
+    }
+
+    fn key(&self, conn: &Connection, _process: Option<&Process>) -> Option<String> {
+        conn.group.clone()
+    }
+}
+
+/// Connections by whether their remote address is loopback, link-local, private or public.
+pub struct ScopeView;
+
+impl AggregationView for ScopeView {
+    fn name(&self) -> &str {
+        "scope"
+    }
+
+    fn column(&self) -> &str {
+        "Scope"
+    }
+
+    fn key(&self, conn: &Connection, _process: Option<&Process>) -> Option<String> {
+        Some(AddressScope::of(&conn.remote_addr).to_string())
+    }
+}
+
+/// Connections by the tunnel interface they go over, or `direct`.
+pub struct TunnelView;
+
+impl AggregationView for TunnelView {
+    fn name(&self) -> &str {
+        "tunnel"
+    }
+
+    fn column(&self) -> &str {
+        "Tunnel"
+    }
+
+    fn key(&self, conn: &Connection, _process: Option<&Process>) -> Option<String> {
+        Some(conn.tunnel.clone().unwrap_or_else(|| "direct".to_string()))
+    }
+}
+
+/// Connections by the executable of their process, where it could be read.
+pub struct ExecutableView;
+
+impl AggregationView for ExecutableView {
+    fn name(&self) -> &str {
+        "exe"
+    }
+
+    fn column(&self) -> &str {
+        "Executable"
+    }
+
+    fn key(&self, _conn: &Connection, process: Option<&Process>) -> Option<String> {
+        process.and_then(|process| process.exe.clone())
+    }
+}
+
+/// The views every monitor starts with, endpoint groups first.
+pub fn builtin_views() -> Vec<Box<dyn AggregationView>> {
+    vec![
+        Box::new(EndpointGroupView),
+        Box::new(ScopeView),
+        Box::new(TunnelView),
+        Box::new(ExecutableView),
+    ]
+}
+
+/// A view with the max concurrent connections of each of its keys, kept as connections
+/// open, close and change key.
+pub struct ViewTracker {
+    pub view: Box<dyn AggregationView>,
+    /// Key of each open connection as last counted.
+    open: HashMap<u64, String>,
+    counter: ConcurrencyCounter<String>,
+}
+
+impl ViewTracker {
+    pub fn new(view: Box<dyn AggregationView>) -> Self {
+        Self {
+            view,
+            open: HashMap::new(),
+            counter: ConcurrencyCounter::default(),
+        }
+    }
+
+    /// Counts the connections open now under their current keys.
+    pub fn update<'a>(&mut self, open: impl Iterator<Item = (&'a Connection, Option<&'a Process>)>) {
+        let keys: HashMap<u64, String> = open
+            .filter_map(|(conn, process)| Some((conn.id, self.view.key(conn, process)?)))
+            .collect();
+        for (id, key) in &self.open {
+            if keys.get(id) != Some(key) {
+                self.counter.close(key);
+            }
+        }
+        for (id, key) in &keys {
+            if self.open.get(id) != Some(key) {
+                self.counter.open(key.clone());
+            }
+        }
+        self.open = keys;
+    }
+
+    pub fn max(&self, key: &str) -> usize {
+        self.counter.max(&key.to_string())
+    }
+
+    pub fn reset(&mut self) {
+        self.open.clear();
+        self.counter = ConcurrencyCounter::default();
+    }
+}
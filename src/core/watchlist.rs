@@ -0,0 +1,85 @@
+use serde::Deserialize;
+
+use super::utils::wildcard_match;
+
+/// A pinned host, process, or process-host combination.
+///
+/// Rules pin rows in every table that has all of the rule's fields: a host rule
+/// pins rows in the host and process-host tables, a process rule pins rows in
+/// the process and process-host tables, and a combined rule only pins
+/// process-host rows. Names and hosts accept `*` wildcards.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct PinRule {
+    pub process: Option<String>,
+    pub host: Option<String>,
+    pub port: Option<u16>,
+}
+
+impl PinRule {
+    pub fn for_host(host: String, port: u16) -> Self {
+        Self { process: None, host: Some(host), port: Some(port) }
+    }
+
+    pub fn for_process(process: String) -> Self {
+        Self { process: Some(process), host: None, port: None }
+    }
+
+    pub fn for_process_host(process: String, host: String, port: u16) -> Self {
+        Self { process: Some(process), host: Some(host), port: Some(port) }
+    }
+
+    fn process_matches(&self, name: &str) -> bool {
+        self.process.as_deref().is_none_or(|pattern| wildcard_match(pattern, name))
+    }
+
+    fn host_matches(&self, host: &str, port: u16) -> bool {
+        self.host.as_deref().is_none_or(|pattern| wildcard_match(pattern, host))
+            && self.port.is_none_or(|p| p == port)
+    }
+
+    fn has_host(&self) -> bool {
+        self.host.is_some() || self.port.is_some()
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Watchlist {
+    rules: Vec<PinRule>,
+}
+
+impl Watchlist {
+    pub fn new(rules: Vec<PinRule>) -> Self {
+        Self { rules }
+    }
+
+    /// Adds the rule, or removes it if it is already pinned. Returns whether it is now pinned.
+    pub fn toggle(&mut self, rule: PinRule) -> bool {
+        if let Some(idx) = self.rules.iter().position(|r| *r == rule) {
+            self.rules.remove(idx);
+            false
+        } else {
+            self.rules.push(rule);
+            true
+        }
+    }
+
+    pub fn is_host_pinned(&self, host: &str, port: u16) -> bool {
+        self.rules.iter().any(|rule| {
+            rule.process.is_none() && rule.has_host() && rule.host_matches(host, port)
+        })
+    }
+
+    pub fn is_process_pinned(&self, name: &str) -> bool {
+        self.rules.iter().any(|rule| {
+            rule.process.is_some() && !rule.has_host() && rule.process_matches(name)
+        })
+    }
+
+    pub fn is_process_host_pinned(&self, name: &str, host: &str, port: u16) -> bool {
+        self.rules.iter().any(|rule| {
+            (rule.process.is_some() || rule.has_host())
+                && rule.process_matches(name)
+                && rule.host_matches(host, port)
+        })
+    }
+}
@@ -0,0 +1,396 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::cli::{DaemonArgs, parse_duration};
+use crate::config::DaemonConfig;
+use crate::core::aliases::HostAliases;
+use crate::core::filters::ConnectionFilter;
+use crate::core::groups::EndpointGroup;
+use crate::core::anomaly::AnomalyOptions;
+use crate::core::blocklist::{BlocklistFeed, BlocklistOptions};
+use crate::core::backlog::BacklogOptions;
+use crate::core::churn::ChurnOptions;
+use crate::core::retransmits::RetransmitOptions;
+use crate::core::leaks::LeakOptions;
+use crate::core::limits::LimitOptions;
+use crate::core::monitor::{ConnectionMonitor, DEFAULT_IDLE_THRESHOLD};
+use crate::core::pacing::{BurstOptions, SamplePacer};
+use crate::core::rollup::RollupOptions;
+use crate::core::script::ScriptHooks;
+use crate::core::resolver::DEFAULT_NAME_TTL;
+use crate::core::snapshot::MetricsSnapshot;
+use crate::core::watchlist::Watchlist;
+use crate::error::{self, ErrorLog};
+use crate::output::{EventDispatcher, MetricsSink, MetricsSinkSpec};
+use crate::output::profile_log::ProfileLog;
+use crate::output::pushgateway;
+use crate::service;
+
+pub struct DaemonOptions {
+    pub interval: Duration,
+    pub flush_interval: Duration,
+    pub sinks: Vec<MetricsSinkSpec>,
+    pub systemd_notify: bool,
+    /// Whether the Windows service control manager started the daemon.
+    pub windows_service: bool,
+    /// Stop after this long instead of waiting for a signal.
+    pub duration: Option<Duration>,
+    /// File receiving tcpcount's own overhead after every refresh.
+    pub profile: Option<ProfileLog>,
+    /// Blocklists to check connections against.
+    pub blocklist: Option<BlocklistOptions>,
+    /// Names for remote endpoints.
+    pub aliases: HostAliases,
+    /// How long resolved hostnames are reused.
+    pub name_ttl: Duration,
+    /// How long memory history is kept at each resolution.
+    pub rollups: RollupOptions,
+    /// Process names whose restarts are reported.
+    pub follow: Vec<String>,
+    /// Whether process metrics are carried across restarts.
+    pub carry_restarts: bool,
+    /// How long exited processes are still reported.
+    pub exited_retention: Option<Duration>,
+    /// How long an established connection must go unchanged to count as idle.
+    pub idle_threshold: Duration,
+    /// System-wide connection count to warn at, if watched.
+    pub limits: Option<LimitOptions>,
+    /// Accept queue saturation to alert at, if on.
+    pub backlog: Option<BacklogOptions>,
+    /// Whether retransmission counters are read from `tcp_info`.
+    pub tcp_info: bool,
+    /// Whether UDP sockets connected to a name server are sampled.
+    pub dns: bool,
+    /// Retransmission rate to alert at, if on.
+    pub retransmits: Option<RetransmitOptions>,
+    /// Faster sampling while filtered connections churn, if on.
+    pub burst: Option<BurstOptions>,
+    /// Whether NAT'd connections get their real destination from conntrack.
+    pub conntrack: bool,
+    /// Whether connections to a proxy from the process environment are marked.
+    pub proxies: bool,
+    /// Whether connections are attributed to the threads holding their sockets.
+    pub threads: bool,
+    /// Script hooks for groups, the Script column and alerts.
+    pub script: Option<ScriptHooks>,
+    /// Pinned hosts, which the MQTT sink publishes individually.
+    pub watchlist: Watchlist,
+    /// Pushgateway receiving the final metrics on exit.
+    pub push_gateway: Option<String>,
+}
+
+impl DaemonOptions {
+    /// Combines command-line arguments with the config file, preferring the command line.
+    pub fn resolve(args: &DaemonArgs, config: &DaemonConfig) -> Result<Self, String> {
+        let config_duration = |value: &Option<String>, name: &str| -> Result<Option<Duration>, String> {
+            match value {
+                Some(value) => parse_duration(value)
+                    .map(Some)
+                    .ok_or_else(|| format!("Invalid daemon {} '{}'", name, value)),
+                None => Ok(None),
+            }
+        };
+
+        let interval = match args.interval {
+            Some(interval) => interval,
+            None => config_duration(&config.interval, "interval")?.unwrap_or(Duration::from_secs(1)),
+        };
+        let flush_interval = match args.flush_interval {
+            Some(flush_interval) => flush_interval,
+            None => config_duration(&config.flush_interval, "flush_interval")?.unwrap_or(Duration::from_secs(60)),
+        };
+
+        let sink_specs = if args.sinks.is_empty() { &config.sinks } else { &args.sinks };
+        let sinks = sink_specs.iter()
+            .map(|spec| MetricsSinkSpec::parse(spec))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if sinks.is_empty() && args.push_gateway.is_none() {
+            return Err("Daemon mode needs at least one --sink, [daemon] sinks entry or --push-gateway".to_string());
+        }
+
+        Ok(Self {
+            interval,
+            flush_interval,
+            sinks,
+            systemd_notify: args.systemd_notify || config.systemd_notify,
+            windows_service: args.windows_service,
+            duration: None,
+            profile: None,
+            blocklist: None,
+            aliases: HostAliases::default(),
+            name_ttl: DEFAULT_NAME_TTL,
+            rollups: RollupOptions::default(),
+            follow: Vec::new(),
+            carry_restarts: false,
+            exited_retention: None,
+            idle_threshold: DEFAULT_IDLE_THRESHOLD,
+            limits: None,
+            backlog: None,
+            tcp_info: false,
+            dns: false,
+            retransmits: None,
+            burst: None,
+            conntrack: false,
+            proxies: false,
+            threads: false,
+            script: None,
+            watchlist: Watchlist::default(),
+            push_gateway: args.push_gateway.clone(),
+        })
+    }
+}
+
+/// Samples connections without a TUI until SIGINT/SIGTERM, flushing metrics periodically.
+/// As a Windows service, the service control manager's stop request ends it instead.
+pub fn run(
+    options: DaemonOptions,
+    groups: Vec<EndpointGroup>,
+    leak_options: Option<LeakOptions>,
+    anomaly_options: Option<AnomalyOptions>,
+    churn_options: Option<ChurnOptions>,
+    filter: ConnectionFilter,
+    events: EventDispatcher,
+) -> error::Result<()> {
+    let windows_service = options.windows_service;
+    let daemon = Daemon { options, groups, leak_options, anomaly_options, churn_options, filter, events };
+    if windows_service {
+        return service::run(|shutdown| daemon.run_until(&shutdown));
+    }
+
+    let shutdown = Arc::new(AtomicBool::new(false));
+    let shutdown_flag = Arc::clone(&shutdown);
+    ctrlc::set_handler(move || shutdown_flag.store(true, Ordering::SeqCst))?;
+    daemon.run_until(&shutdown)
+}
+
+/// Everything the sampling loop needs, gathered so it can run under either shutdown source.
+struct Daemon {
+    options: DaemonOptions,
+    groups: Vec<EndpointGroup>,
+    leak_options: Option<LeakOptions>,
+    anomaly_options: Option<AnomalyOptions>,
+    churn_options: Option<ChurnOptions>,
+    filter: ConnectionFilter,
+    events: EventDispatcher,
+}
+
+impl Daemon {
+    fn run_until(self, shutdown: &AtomicBool) -> error::Result<()> {
+        let Daemon { mut options, groups, leak_options, anomaly_options, churn_options, filter, mut events } = self;
+
+        let mut sinks: Vec<Box<dyn MetricsSink>> = options.sinks.iter()
+            .map(|spec| spec.open(&options.watchlist))
+            .collect::<Result<_, _>>()?;
+
+        let mut monitor = ConnectionMonitor::new();
+        monitor.set_name_ttl(options.name_ttl);
+        monitor.set_rollup_options(options.rollups);
+        monitor.set_followed_processes(&options.follow);
+        monitor.set_carry_restarts(options.carry_restarts);
+        monitor.set_exited_retention(options.exited_retention);
+        monitor.set_idle_threshold(options.idle_threshold);
+        monitor.set_limit_options(options.limits);
+        monitor.set_backlog_options(options.backlog);
+        monitor.set_tcp_info(options.tcp_info);
+        monitor.set_dns(options.dns);
+        monitor.set_retransmit_options(options.retransmits);
+        monitor.set_conntrack(options.conntrack);
+        monitor.set_proxy_detection(options.proxies);
+        monitor.set_thread_attribution(options.threads);
+        monitor.set_script(options.script.take());
+        if !options.aliases.is_empty() {
+            monitor.set_aliases(std::mem::take(&mut options.aliases));
+        }
+        monitor.set_groups(groups);
+        monitor.set_leak_options(leak_options);
+        monitor.set_anomaly_options(anomaly_options);
+        monitor.set_churn_options(churn_options);
+        let blocklists = options.blocklist.as_ref().map(|blocklist| {
+            monitor.set_blocklist_alerts(blocklist.alert);
+            BlocklistFeed::start(blocklist)
+        });
+
+        let mut watchdog = None;
+        if options.systemd_notify {
+            sd_notify("READY=1").ok();
+            watchdog = Watchdog::from_env();
+        }
+
+        let mut error_log = ErrorLog::default();
+        let mut pacer = SamplePacer::new(options.interval).with_burst(options.burst);
+        let mut churn_seq = 0;
+        let started = Instant::now();
+        let mut last_flush = Instant::now();
+        while !shutdown.load(Ordering::SeqCst) {
+            if options.duration.is_some_and(|duration| started.elapsed() >= duration) {
+                break;
+            }
+            sleep_unless_shutdown(pacer.interval(), shutdown, watchdog.as_mut());
+
+            let mut errors = Vec::new();
+            if let Some((lists, load_errors)) = blocklists.as_ref().and_then(BlocklistFeed::poll) {
+                monitor.set_blocklists(lists);
+                errors.extend(load_errors);
+            }
+            let refresh_started = Instant::now();
+            if let Err(e) = monitor.refresh() {
+                errors.push(e);
+            }
+            if let Some(watchdog) = watchdog.as_mut() {
+                watchdog.ping_if_due();
+            }
+            if let Some(change) = pacer.record(refresh_started.elapsed()) {
+                eprintln!("tcpcount: {}", change);
+            }
+            if pacer.has_burst() {
+                let churn = monitor.count_filtered_events_since(&filter, churn_seq);
+                churn_seq = monitor.next_event_seq();
+                if let Some(change) = pacer.record_churn(churn, Instant::now()) {
+                    eprintln!("tcpcount: {}", change);
+                }
+            }
+            errors.extend(monitor.take_errors());
+            if let Some(profile) = options.profile.as_mut() {
+                if let Err(e) = profile.write(monitor.overhead()) {
+                    errors.push(e);
+                }
+            }
+            if let Err(e) = events.dispatch(&monitor) {
+                errors.push(e);
+            }
+            error_log.log(&errors);
+            for alert in monitor.take_leak_alerts() {
+                eprintln!("tcpcount: {}", alert);
+            }
+            for alert in monitor.take_churn_alerts() {
+                eprintln!("tcpcount: {}", alert);
+            }
+            for alert in monitor.take_limit_alerts() {
+                eprintln!("tcpcount: {}", alert);
+            }
+            for alert in monitor.take_backlog_alerts() {
+                eprintln!("tcpcount: {}", alert);
+            }
+            for alert in monitor.take_retransmit_alerts() {
+                eprintln!("tcpcount: {}", alert);
+            }
+            for restart in monitor.take_restart_alerts() {
+                eprintln!("tcpcount: {}", restart);
+            }
+            for alert in monitor.take_blocklist_alerts() {
+                eprintln!("tcpcount: {}", alert);
+            }
+            for alert in monitor.take_script_alerts() {
+                eprintln!("tcpcount: {}", alert);
+            }
+
+            if last_flush.elapsed() >= options.flush_interval {
+                flush_all(&mut sinks, &MetricsSnapshot::capture(&monitor, &filter));
+                last_flush = Instant::now();
+            }
+        }
+
+        if options.systemd_notify {
+            sd_notify("STOPPING=1").ok();
+        }
+
+        // Final flush so the last partial interval is not lost
+        let snapshot = MetricsSnapshot::capture(&monitor, &filter);
+        flush_all(&mut sinks, &snapshot);
+        if let Some(url) = &options.push_gateway {
+            pushgateway::push_final(url, &snapshot);
+        }
+        Ok(())
+    }
+}
+
+fn flush_all(sinks: &mut [Box<dyn MetricsSink>], snapshot: &MetricsSnapshot) {
+    for sink in sinks.iter_mut() {
+        if let Err(e) = sink.flush(snapshot) {
+            eprintln!("tcpcount: metrics flush failed: {}", e);
+        }
+    }
+}
+
+/// Sleeps for `duration` in short steps, returning early on shutdown and keeping the
+/// watchdog fed while waiting. A duration too long to reach sleeps until shutdown.
+fn sleep_unless_shutdown(duration: Duration, shutdown: &AtomicBool, mut watchdog: Option<&mut Watchdog>) {
+    let deadline = Instant::now().checked_add(duration);
+    while !shutdown.load(Ordering::SeqCst) {
+        if let Some(watchdog) = watchdog.as_deref_mut() {
+            watchdog.ping_if_due();
+        }
+        let remaining = deadline.map_or(Duration::MAX, |deadline| deadline.saturating_duration_since(Instant::now()));
+        if remaining.is_zero() {
+            break;
+        }
+        thread::sleep(remaining.min(Duration::from_millis(100)));
+    }
+}
+
+/// Keep-alive pings for a unit with `WatchdogSec=` set, sent at half the timeout so a
+/// daemon stuck in a refresh gets restarted by systemd.
+struct Watchdog {
+    ping_interval: Duration,
+    last_ping: Instant,
+}
+
+impl Watchdog {
+    /// The watchdog systemd set up for this process through `WATCHDOG_USEC`, if any.
+    fn from_env() -> Option<Self> {
+        if let Ok(pid) = std::env::var("WATCHDOG_PID") {
+            if pid.parse::<u32>().ok() != Some(std::process::id()) {
+                return None;
+            }
+        }
+        let usec: u64 = std::env::var("WATCHDOG_USEC").ok()?.parse().ok().filter(|usec| *usec > 0)?;
+        let ping_interval = Duration::from_micros(usec) / 2;
+        sd_notify("WATCHDOG=1").ok();
+        Some(Self { ping_interval, last_ping: Instant::now() })
+    }
+
+    fn ping_if_due(&mut self) {
+        if self.last_ping.elapsed() >= self.ping_interval {
+            sd_notify("WATCHDOG=1").ok();
+            self.last_ping = Instant::now();
+        }
+    }
+}
+
+/// Sends a state update to systemd if running under a unit with `NOTIFY_SOCKET` set.
+#[cfg(unix)]
+fn sd_notify(state: &str) -> std::io::Result<()> {
+    use std::os::unix::ffi::OsStrExt;
+    use std::os::unix::net::UnixDatagram;
+
+    let path = match std::env::var_os("NOTIFY_SOCKET") {
+        Some(path) => path,
+        None => return Ok(()),
+    };
+
+    let socket = UnixDatagram::unbound()?;
+    let path_bytes = path.as_bytes();
+    if let Some(abstract_name) = path_bytes.strip_prefix(b"@") {
+        #[cfg(target_os = "linux")]
+        {
+            use std::os::linux::net::SocketAddrExt;
+            use std::os::unix::net::SocketAddr;
+
+            let addr = SocketAddr::from_abstract_name(abstract_name)?;
+            socket.send_to_addr(state.as_bytes(), &addr)?;
+        }
+        #[cfg(not(target_os = "linux"))]
+        let _ = abstract_name;
+    } else {
+        socket.send_to(state.as_bytes(), &path)?;
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn sd_notify(_state: &str) -> std::io::Result<()> {
+    Ok(())
+}
@@ -0,0 +1,81 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+use chrono::{DateTime, Local};
+
+use crate::core::snapshot::{FullSnapshot, MetricsSnapshot};
+use crate::error::Result;
+
+/// Total and max concurrent connections of one row in both snapshots.
+#[derive(Debug, Default, Clone, Copy)]
+struct Counts {
+    total: usize,
+    max: usize,
+}
+
+fn host_counts(snapshot: &MetricsSnapshot) -> BTreeMap<String, Counts> {
+    snapshot.hosts.iter()
+        .map(|host| (format!("{}:{}", host.host, host.port), Counts { total: host.total_connections, max: host.max_concurrent }))
+        .collect()
+}
+
+/// Processes are matched by name since PIDs change between runs. Totals of processes
+/// sharing a name are added up; max is the highest of any one of them.
+fn process_counts(snapshot: &MetricsSnapshot) -> BTreeMap<String, Counts> {
+    let mut counts: BTreeMap<String, Counts> = BTreeMap::new();
+    for process in &snapshot.processes {
+        let entry = counts.entry(process.name.clone()).or_default();
+        entry.total += process.total_connections;
+        entry.max = entry.max.max(process.max_concurrent);
+    }
+    counts
+}
+
+fn delta(before: usize, after: usize) -> String {
+    let delta = after as i64 - before as i64;
+    if delta == 0 { "0".to_string() } else { format!("{:+}", delta) }
+}
+
+fn print_section(title: &str, before: &BTreeMap<String, Counts>, after: &BTreeMap<String, Counts>) {
+    let keys: BTreeSet<&String> = before.keys().chain(after.keys()).collect();
+    let mut rows: Vec<(&String, Counts, Counts)> = keys.into_iter()
+        .map(|key| (key, before.get(key).copied().unwrap_or_default(), after.get(key).copied().unwrap_or_default()))
+        .filter(|(_, a, b)| a.total != b.total || a.max != b.max)
+        .collect();
+    // Largest changes first
+    rows.sort_by_key(|(key, a, b)| (std::cmp::Reverse((b.total as i64 - a.total as i64).abs()), *key));
+
+    println!();
+    println!("{:<40} {:>8} {:>8} {:>8}  {:>6} {:>6} {:>6}", title, "Total A", "Total B", "Delta", "Max A", "Max B", "Delta");
+    if rows.is_empty() {
+        println!("  no changes");
+    }
+    for (key, a, b) in rows {
+        println!("{:<40} {:>8} {:>8} {:>8}  {:>6} {:>6} {:>6}",
+            key, a.total, b.total, delta(a.total, b.total), a.max, b.max, delta(a.max, b.max));
+    }
+}
+
+/// Prints per-host and per-process changes in total and max connections from `a` to `b`.
+pub fn run(a: &Path, b: &Path) -> Result<()> {
+    let before = FullSnapshot::load(a)?.metrics;
+    let after = FullSnapshot::load(b)?.metrics;
+
+    for (label, path, snapshot) in [("A", a, &before), ("B", b, &after)] {
+        let time: DateTime<Local> = snapshot.timestamp.into();
+        println!("{}: {} ({}, {})", label, path.display(), time.format("%Y-%m-%d %H:%M:%S"), snapshot.filter);
+    }
+
+    println!();
+    println!("{:<40} {:>8} {:>8} {:>8}", "Summary", "A", "B", "Delta");
+    println!("{:<40} {:>8} {:>8} {:>8}", "Total connections",
+        before.summary.total_connections, after.summary.total_connections,
+        delta(before.summary.total_connections, after.summary.total_connections));
+    println!("{:<40} {:>8} {:>8} {:>8}", "Max concurrent",
+        before.summary.max_concurrent, after.summary.max_concurrent,
+        delta(before.summary.max_concurrent, after.summary.max_concurrent));
+
+    print_section("Hosts", &host_counts(&before), &host_counts(&after));
+    print_section("Processes", &process_counts(&before), &process_counts(&after));
+    Ok(())
+}
@@ -0,0 +1,133 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::io;
+use std::net::IpAddr;
+
+/// Errors raised by tcpcount. Sampling errors are usually recoverable: the TUI shows them
+/// in the status bar and keeps running, the daemon logs them.
+#[derive(Debug)]
+pub enum Error {
+    /// The OS refused access, e.g. to other users' sockets or processes.
+    PermissionDenied(String),
+    /// The socket table could not be read.
+    Netstat(String),
+    /// A reverse lookup failed for a reason other than the address having no name.
+    Dns { addr: IpAddr, message: String },
+    /// The config file could not be read, parsed or written.
+    Config(String),
+    /// A metrics or event sink could not be written.
+    Sink(String),
+    /// A file such as a snapshot held invalid data.
+    Parse(String),
+    Io(io::Error),
+    Other(String),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::PermissionDenied(message) => write!(f, "Permission denied: {}", message),
+            Error::Netstat(message) => write!(f, "Failed to read sockets: {}", message),
+            Error::Dns { addr, message } => write!(f, "DNS lookup for {} failed: {}", addr, message),
+            Error::Config(message) => write!(f, "{}", message),
+            Error::Sink(message) => write!(f, "{}", message),
+            Error::Parse(message) => write!(f, "{}", message),
+            Error::Io(e) => write!(f, "{}", e),
+            Error::Other(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+impl From<netstat2::error::Error> for Error {
+    fn from(e: netstat2::error::Error) -> Self {
+        // netstat2's messages are terse, the underlying OS error says what went wrong
+        let os_error = match &e {
+            netstat2::error::Error::OsError(os)
+            | netstat2::error::Error::FailedToListProcesses(os)
+            | netstat2::error::Error::FailedToQueryFileDescriptors(os) => Some(os),
+            _ => None,
+        };
+        match os_error {
+            Some(os) if os.kind() == io::ErrorKind::PermissionDenied => {
+                Error::PermissionDenied("cannot read the socket table, try running as root".to_string())
+            }
+            Some(os) => Error::Netstat(format!("{}: {}", e, os)),
+            None => Error::Netstat(e.to_string()),
+        }
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        Error::Parse(e.to_string())
+    }
+}
+
+impl From<ureq::Error> for Error {
+    fn from(e: ureq::Error) -> Self {
+        Error::Sink(e.to_string())
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl From<rusqlite::Error> for Error {
+    fn from(e: rusqlite::Error) -> Self {
+        Error::Sink(e.to_string())
+    }
+}
+
+impl From<ctrlc::Error> for Error {
+    fn from(e: ctrlc::Error) -> Self {
+        Error::Other(format!("Failed to install signal handler: {}", e))
+    }
+}
+
+impl From<String> for Error {
+    fn from(message: String) -> Self {
+        Error::Other(message)
+    }
+}
+
+impl From<&str> for Error {
+    fn from(message: &str) -> Self {
+        Error::Other(message.to_string())
+    }
+}
+
+/// Prints errors to stderr in the headless modes, skipping those already printed for the
+/// previous pass so a lasting problem isn't repeated every interval.
+#[derive(Debug, Default)]
+pub struct ErrorLog {
+    previous: HashSet<String>,
+}
+
+impl ErrorLog {
+    pub fn log(&mut self, errors: &[Error]) {
+        let mut current = HashSet::new();
+        for error in errors {
+            let message = error.to_string();
+            if !self.previous.contains(&message) && !current.contains(&message) {
+                eprintln!("tcpcount: {}", message);
+            }
+            current.insert(message);
+        }
+        self.previous = current;
+    }
+}
@@ -1,24 +1,230 @@
+mod api;
 mod app;
 mod core;
 mod widgets;
 mod cli;
+mod completion;
+mod config;
+mod daemon;
+mod diff;
+mod error;
+mod output;
+mod remote;
+mod selftest;
+mod service;
+mod summary;
+#[cfg(test)]
+mod tests;
+
+use std::net::TcpListener;
+
+use clap_complete::CompleteEnv;
 
 use app::App;
-use cli::parse_args;
+use cli::{CliCommand, parse_args};
+use config::Config;
+use core::sample::SampleOptions;
+use core::script::ScriptHooks;
+use core::snapshot::FullSnapshot;
+use core::watchlist::Watchlist;
+use error::Error;
+use daemon::DaemonOptions;
+use output::EventDispatcher;
+use output::flows::FlowLogSink;
+use output::log_file::LogFileSink;
+use output::profile_log::ProfileLog;
+use output::syslog::SyslogSink;
+use remote::Aggregator;
+use selftest::LoadGenerator;
 
-use ratatui;
+fn main() {
+    // Answers shell completion requests from the script printed by `tcpcount completions`
+    CompleteEnv::with_factory(cli::build_command).complete();
+    
+    if let Err(e) = run() {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
+}
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let initial_filter = parse_args();
+fn run() -> error::Result<()> {
+    let mut args = parse_args();
+    let config = Config::load(args.config_path.as_deref(), args.config_profile.as_deref())?;
+    if let Some(query) = config.filter_query()? {
+        args.filter.and_query(query);
+    }
+    
+    // Command-line matching flags add to the config defaults for the whole session
+    let mut matching = config.match_options();
+    matching.ignore_case |= args.filter.matching.ignore_case;
+    matching.fuzzy |= args.filter.matching.fuzzy;
+    args.filter.matching = matching;
+    
+    let groups = config.endpoint_groups()?;
+    let highlights = config.highlights()?;
+    let saved_filters = config.saved_filters()?;
+    let leak_options = config.leak_options()?;
+    let anomaly_options = config.anomaly_options()?;
+    let churn_options = config.churn_options()?;
+    let limit_options = config.limit_options()?;
+    let backlog_options = config.backlog_options()?;
+    let retransmit_options = config.retransmit_options()?;
+    let blocklist_options = config.blocklist_options()?;
+    let aliases = config.host_aliases(args.aliases.as_deref())?;
+    let name_ttl = config.name_ttl()?;
+    let rollup_options = config.rollup_options()?;
+    let exited_retention = config.exited_retention()?;
+    let inactive_retention = config.inactive_retention()?;
+    let idle_threshold = config.idle_threshold()?;
+    let burst_options = config.burst_options(args.adaptive_sampling)?;
+    let mut script = args.script.as_deref().map(ScriptHooks::load).transpose()?;
     
+    let mut events = EventDispatcher::new();
+    if let Some(options) = args.log_file {
+        let path = options.path.clone();
+        let sink = LogFileSink::open(options)
+            .map_err(|e| format!("Failed to open log file '{}': {}", path.display(), e))?;
+        events.add_sink(Box::new(sink));
+    }
+    if let Some((path, format)) = &args.flow_log {
+        let sink = FlowLogSink::open(path, *format)
+            .map_err(|e| format!("Failed to open flow log '{}': {}", path.display(), e))?;
+        events.add_sink(Box::new(sink));
+    }
+    if let Some((target, facility)) = args.syslog {
+        let sink = SyslogSink::connect(&target, facility)
+            .map_err(|e| format!("Failed to connect to syslog: {}", e))?;
+        events.add_sink(Box::new(sink));
+    }
+    
+    let mut profile = match &args.profile {
+        Some(path) => Some(ProfileLog::open(path)
+            .map_err(|e| Error::Sink(format!("Failed to open profile log '{}': {}", path.display(), e)))?),
+        None => None,
+    };
+    
+    let mut app = App::new();
+    match args.command {
+        Some(CliCommand::Daemon(daemon_args)) => {
+            let mut options = DaemonOptions::resolve(&daemon_args, &config.daemon)?;
+            options.duration = args.duration;
+            options.profile = profile.take();
+            options.blocklist = blocklist_options;
+            options.aliases = aliases;
+            options.name_ttl = name_ttl;
+            options.rollups = rollup_options;
+            options.follow = args.follow.clone();
+            options.carry_restarts = args.carry_restarts;
+            options.exited_retention = exited_retention;
+            options.idle_threshold = idle_threshold;
+            options.limits = limit_options;
+            options.backlog = backlog_options;
+            options.burst = burst_options;
+            options.conntrack = args.conntrack;
+            options.proxies = args.detect_proxies;
+            options.threads = args.threads;
+            options.tcp_info = args.tcp_info;
+            options.dns = args.dns;
+            options.retransmits = retransmit_options;
+            options.script = script.take();
+            options.watchlist = Watchlist::new(config.pins.clone());
+            return daemon::run(options, groups, leak_options, anomaly_options, churn_options, args.filter, events);
+        }
+        Some(CliCommand::Agent(options)) => {
+            let sample_options = SampleOptions {
+                conntrack: args.conntrack,
+                proxies: args.detect_proxies,
+                threads: args.threads,
+                tcp_info: args.tcp_info,
+                dns: args.dns,
+            };
+            return remote::run_agent(options, name_ttl, sample_options);
+        }
+        Some(CliCommand::Diff { a, b }) => return diff::run(&a, &b),
+        Some(CliCommand::Service { install: Some(daemon_args) }) => return service::install(&daemon_args),
+        Some(CliCommand::Service { install: None }) => return service::uninstall(),
+        Some(CliCommand::Completions { shell }) => {
+            completion::write_registration(&shell, &mut std::io::stdout())?;
+            return Ok(());
+        }
+        Some(CliCommand::Analyze { path }) => {
+            let snapshot = FullSnapshot::load(&path)?;
+            app = app.with_offline(snapshot, &path);
+        }
+        Some(CliCommand::Selftest { options, demo: false }) => return selftest::run(options),
+        Some(CliCommand::Selftest { options, demo: true }) => {
+            let generator = LoadGenerator::start(options)?;
+            args.filter = selftest::generated_filter(generator.port()).with_matching(args.filter.matching);
+            generator.spawn_demo();
+        }
+        Some(CliCommand::Aggregator { listen }) => {
+            let listener = TcpListener::bind(&listen)
+                .map_err(|e| format!("Failed to listen on '{}': {}", listen, e))?;
+            app = app.with_aggregator(Aggregator::listen(listener));
+        }
+        None => {}
+    }
+    
+    if let Some(addr) = args.api_listen {
+        let listener = TcpListener::bind(addr)
+            .map_err(|e| format!("Failed to listen on '{}': {}", addr, e))?;
+        app = app.with_api(listener);
+    }
+    
+    widgets::glyphs::set_ascii(args.ascii);
+    widgets::theme::set_theme(args.theme);
     let mut terminal = ratatui::init();
     
-    let app_result = App::new()
-        .with_filter(initial_filter)
-        .run(&mut terminal);
+    let summary_on_exit = args.summary_on_exit || args.duration.is_some();
+    let mut app = app
+        .with_events(events)
+        .with_duration(args.duration)
+        .with_profile_log(profile)
+        .with_aliases(aliases)
+        .with_name_ttl(name_ttl)
+        .with_rollup_options(rollup_options)
+        .with_followed_processes(&args.follow)
+        .with_carry_restarts(args.carry_restarts)
+        .with_exited_retention(exited_retention)
+        .with_inactive_retention(inactive_retention)
+        .with_idle_threshold(idle_threshold)
+        .with_burst_sampling(burst_options)
+        .with_conntrack(args.conntrack)
+        .with_proxy_detection(args.detect_proxies)
+        .with_thread_attribution(args.threads)
+        .with_script(script)
+        .with_groups(groups)
+        .with_leak_options(leak_options)
+        .with_anomaly_options(anomaly_options)
+        .with_churn_options(churn_options)
+        .with_limit_options(limit_options)
+        .with_backlog_options(backlog_options)
+        .with_tcp_info(args.tcp_info)
+        .with_dns(args.dns)
+        .with_retransmit_options(retransmit_options)
+        .with_blocklists(blocklist_options)
+        .with_watchlist(Watchlist::new(config.pins))
+        .with_highlights(highlights)
+        .with_columns(&config.columns)
+        .with_match_defaults(matching)
+        .with_saved_filters(saved_filters, args.config_path.clone().or_else(Config::default_path))
+        .with_filter(args.filter)
+        .with_sort_by(args.sort_by)
+        .with_view(args.view)
+        .with_focus(args.focus)
+        .with_max_points(args.max_points)
+        .with_mouse(args.mouse);
+    let app_result = app.run(&mut terminal);
     
     ratatui::restore();
     
+    if summary_on_exit {
+        print!("{}", app.session_summary());
+    }
+    if let Some((url, snapshot)) = args.push_gateway.as_deref().zip(app.metrics_snapshot()) {
+        output::pushgateway::push_final(url, &snapshot);
+    }
+    
     app_result?;
     
     Ok(())
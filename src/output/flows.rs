@@ -0,0 +1,152 @@
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::net::IpAddr;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde_json::{Map, Value};
+
+use crate::core::events::{ConnectionEvent, ConnectionEventKind};
+use super::EventSink;
+
+/// IANA protocol number of TCP, the only protocol tcpcount sees.
+const PROTOCOL_TCP: u8 = 6;
+
+const CSV_HEADER: &str = "start,end,proto,src_addr,src_port,dst_addr,dst_port,direction,pid,process";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FlowFormat {
+    /// One JSON object per line, keyed by IPFIX information element names.
+    Ipfix,
+    /// Comma-separated values with a header line.
+    Csv,
+}
+
+impl FlowFormat {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "ipfix" => Some(FlowFormat::Ipfix),
+            "csv" => Some(FlowFormat::Csv),
+            _ => None,
+        }
+    }
+}
+
+/// One finished connection as a flow: when it was seen first and last, its 5-tuple with the
+/// initiator as source, and the owning process. Byte counts aren't sampled, so none are given.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlowRecord {
+    pub start: SystemTime,
+    pub end: SystemTime,
+    pub src_addr: Option<IpAddr>,
+    pub src_port: u16,
+    pub dst_addr: Option<IpAddr>,
+    pub dst_port: u16,
+    pub inbound: bool,
+    pub pid: u32,
+    pub process: Option<String>,
+}
+
+impl FlowRecord {
+    /// The flow a close event ends; `None` for other events.
+    pub fn from_event(event: &ConnectionEvent) -> Option<Self> {
+        if event.kind != ConnectionEventKind::Closed {
+            return None;
+        }
+        let conn = &event.connection;
+        let (local, remote) = ((conn.local_addr, conn.local_port), (Some(conn.remote_addr), conn.remote_port));
+        let ((src_addr, src_port), (dst_addr, dst_port)) = if conn.inbound { (remote, local) } else { (local, remote) };
+        Some(Self {
+            start: conn.first_seen,
+            end: event.time,
+            src_addr,
+            src_port,
+            dst_addr,
+            dst_port,
+            inbound: conn.inbound,
+            pid: conn.pid,
+            process: event.process_name.clone(),
+        })
+    }
+
+    /// The record as IPFIX-named JSON. Addresses go in the IPv4 or IPv6 element to match
+    /// their family; `flowDirection` is 0 for connections accepted here, 1 for ones made.
+    pub fn to_ipfix(&self) -> String {
+        let mut map = Map::new();
+        map.insert("flowStartMilliseconds".to_string(), Value::from(epoch_millis(self.start)));
+        map.insert("flowEndMilliseconds".to_string(), Value::from(epoch_millis(self.end)));
+        map.insert("protocolIdentifier".to_string(), Value::from(PROTOCOL_TCP));
+        if let Some(addr) = self.src_addr {
+            map.insert(address_element("source", &addr), Value::from(addr.to_string()));
+        }
+        map.insert("sourceTransportPort".to_string(), Value::from(self.src_port));
+        if let Some(addr) = self.dst_addr {
+            map.insert(address_element("destination", &addr), Value::from(addr.to_string()));
+        }
+        map.insert("destinationTransportPort".to_string(), Value::from(self.dst_port));
+        map.insert("flowDirection".to_string(), Value::from(if self.inbound { 0 } else { 1 }));
+        map.insert("processId".to_string(), Value::from(self.pid));
+        if let Some(process) = &self.process {
+            map.insert("processName".to_string(), Value::from(process.clone()));
+        }
+        Value::Object(map).to_string()
+    }
+
+    /// The record as a CSV row matching `CSV_HEADER`, times in epoch milliseconds.
+    pub fn to_csv(&self) -> String {
+        let addr = |addr: Option<IpAddr>| addr.map(|addr| addr.to_string()).unwrap_or_default();
+        format!(
+            "{},{},{},{},{},{},{},{},{},{}",
+            epoch_millis(self.start), epoch_millis(self.end), PROTOCOL_TCP,
+            addr(self.src_addr), self.src_port, addr(self.dst_addr), self.dst_port,
+            if self.inbound { "in" } else { "out" }, self.pid,
+            csv_quote(self.process.as_deref().unwrap_or("")),
+        )
+    }
+}
+
+/// Appends a flow record to a file for each connection that closes.
+pub struct FlowLogSink {
+    file: File,
+    format: FlowFormat,
+}
+
+impl FlowLogSink {
+    /// Opens `path` for appending, starting a new CSV file with its header.
+    pub fn open(path: &Path, format: FlowFormat) -> io::Result<Self> {
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        if format == FlowFormat::Csv && file.metadata()?.len() == 0 {
+            writeln!(file, "{}", CSV_HEADER)?;
+        }
+        Ok(Self { file, format })
+    }
+}
+
+impl EventSink for FlowLogSink {
+    fn write_event(&mut self, event: &ConnectionEvent) -> io::Result<()> {
+        let Some(record) = FlowRecord::from_event(event) else {
+            return Ok(());
+        };
+        let line = match self.format {
+            FlowFormat::Ipfix => record.to_ipfix(),
+            FlowFormat::Csv => record.to_csv(),
+        };
+        writeln!(self.file, "{}", line)
+    }
+}
+
+fn epoch_millis(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH).map_or(0, |since| since.as_millis() as u64)
+}
+
+fn address_element(side: &str, addr: &IpAddr) -> String {
+    let family = if addr.is_ipv4() { "IPv4" } else { "IPv6" };
+    format!("{}{}Address", side, family)
+}
+
+fn csv_quote(value: &str) -> String {
+    if !value.contains([',', '"', '\n']) {
+        return value.to_string();
+    }
+    format!("\"{}\"", value.replace('"', "\"\""))
+}
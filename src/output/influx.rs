@@ -0,0 +1,102 @@
+use std::fmt::Write;
+use std::io;
+use std::net::{ToSocketAddrs, UdpSocket};
+
+use crate::core::snapshot::MetricsSnapshot;
+use crate::error::Result;
+use super::MetricsSink;
+
+enum Transport {
+    Udp(UdpSocket),
+    Http(String),
+}
+
+/// Writes snapshots in InfluxDB line protocol over UDP or to an HTTP write endpoint.
+pub struct InfluxSink {
+    transport: Transport,
+}
+
+impl InfluxSink {
+    /// Accepts `udp://host:port` or an `http(s)://` write URL such as `http://localhost:8086/write?db=tcpcount`.
+    pub fn connect(target: &str) -> io::Result<Self> {
+        if target.starts_with("http://") || target.starts_with("https://") {
+            return Ok(Self { transport: Transport::Http(target.to_string()) });
+        }
+
+        let addr = target.strip_prefix("udp://").unwrap_or(target)
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "influx host did not resolve"))?;
+        let bind_addr = if addr.is_ipv4() { "0.0.0.0:0" } else { "[::]:0" };
+        let socket = UdpSocket::bind(bind_addr)?;
+        socket.connect(addr)?;
+
+        Ok(Self { transport: Transport::Udp(socket) })
+    }
+}
+
+impl MetricsSink for InfluxSink {
+    fn flush(&mut self, snapshot: &MetricsSnapshot) -> Result<()> {
+        let lines = render(snapshot);
+        match &self.transport {
+            Transport::Udp(socket) => {
+                // One point per datagram so large snapshots never exceed the packet size
+                for line in lines.lines() {
+                    socket.send(line.as_bytes())?;
+                }
+            }
+            Transport::Http(url) => {
+                ureq::post(url)
+                    .set("Content-Type", "text/plain; charset=utf-8")
+                    .send_string(&lines)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Renders a snapshot as InfluxDB line protocol, one point per line.
+pub fn render(snapshot: &MetricsSnapshot) -> String {
+    let timestamp = snapshot.timestamp.timestamp_nanos_opt().unwrap_or_default();
+    let mut out = String::new();
+    let mut point = |measurement: &str, tags: &[(&str, &str)], active: usize, total: usize, max: usize| {
+        let tags: String = tags.iter()
+            .map(|(key, value)| format!(",{}={}", key, escape_tag(value)))
+            .collect();
+        let _ = writeln!(
+            out,
+            "{}{} active={}i,total={}i,max={}i {}",
+            measurement, tags, active, total, max, timestamp,
+        );
+    };
+
+    let summary = &snapshot.summary;
+    point("tcpcount", &[], summary.active_connections, summary.total_connections, summary.max_concurrent);
+
+    for m in &snapshot.processes {
+        point("tcpcount_process", &[("process", &m.name), ("pid", &m.pid.to_string())],
+            m.current_connections, m.total_connections, m.max_concurrent);
+    }
+    for m in &snapshot.hosts {
+        point("tcpcount_host", &[("host", &m.host), ("port", &m.port.to_string())],
+            m.current_connections, m.total_connections, m.max_concurrent);
+    }
+    for m in &snapshot.process_hosts {
+        point("tcpcount_process_host",
+            &[("process", &m.process_name), ("pid", &m.pid.to_string()), ("host", &m.host), ("port", &m.port.to_string())],
+            m.current_connections, m.total_connections, m.max_concurrent);
+    }
+    for m in &snapshot.groups {
+        point("tcpcount_group", &[("group", &m.group)],
+            m.current_connections, m.total_connections, m.max_concurrent);
+    }
+
+    out
+}
+
+fn escape_tag(value: &str) -> String {
+    if value.is_empty() {
+        return "unknown".to_string();
+    }
+    value.replace('\\', "\\\\").replace(',', "\\,").replace('=', "\\=").replace(' ', "\\ ")
+}
@@ -0,0 +1,129 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use serde_json::{Map, Value};
+
+use crate::core::events::ConnectionEvent;
+use super::{EventSink, event_fields};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LogFormat {
+    Json,
+    Logfmt,
+}
+
+impl LogFormat {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "json" => Some(LogFormat::Json),
+            "logfmt" => Some(LogFormat::Logfmt),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LogFileOptions {
+    pub path: PathBuf,
+    pub format: LogFormat,
+    pub max_size: Option<u64>, // Rotate once the file would grow past this many bytes
+    pub max_files: usize,      // Number of rotated files to keep (path.1 .. path.N)
+}
+
+/// Appends one structured line per event to a file, rotating it by size.
+pub struct LogFileSink {
+    options: LogFileOptions,
+    file: File,
+    size: u64,
+}
+
+impl LogFileSink {
+    pub fn open(options: LogFileOptions) -> io::Result<Self> {
+        let file = open_append(&options.path)?;
+        let size = file.metadata()?.len();
+
+        Ok(Self { options, file, size })
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+
+        if self.options.max_files == 0 {
+            self.file = File::create(&self.options.path)?;
+            self.size = 0;
+            return Ok(());
+        }
+
+        for idx in (1..self.options.max_files).rev() {
+            let from = rotated_path(&self.options.path, idx);
+            if from.exists() {
+                fs::rename(&from, rotated_path(&self.options.path, idx + 1))?;
+            }
+        }
+        fs::rename(&self.options.path, rotated_path(&self.options.path, 1))?;
+
+        self.file = open_append(&self.options.path)?;
+        self.size = 0;
+        Ok(())
+    }
+}
+
+impl EventSink for LogFileSink {
+    fn write_event(&mut self, event: &ConnectionEvent) -> io::Result<()> {
+        let fields = event_fields(event);
+        let mut line = match self.options.format {
+            LogFormat::Json => format_json(fields),
+            LogFormat::Logfmt => format_logfmt(fields),
+        };
+        line.push('\n');
+
+        if let Some(max_size) = self.options.max_size {
+            if self.size > 0 && self.size + line.len() as u64 > max_size {
+                self.rotate()?;
+            }
+        }
+
+        self.file.write_all(line.as_bytes())?;
+        self.size += line.len() as u64;
+        Ok(())
+    }
+}
+
+fn open_append(path: &Path) -> io::Result<File> {
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+fn rotated_path(path: &Path, idx: usize) -> PathBuf {
+    let mut rotated = path.as_os_str().to_owned();
+    rotated.push(format!(".{}", idx));
+    PathBuf::from(rotated)
+}
+
+fn format_json(fields: Vec<(&'static str, Value)>) -> String {
+    let map: Map<String, Value> = fields.into_iter()
+        .filter(|(_, value)| !value.is_null())
+        .map(|(key, value)| (key.to_string(), value))
+        .collect();
+
+    Value::Object(map).to_string()
+}
+
+fn format_logfmt(fields: Vec<(&'static str, Value)>) -> String {
+    fields.into_iter()
+        .filter_map(|(key, value)| match value {
+            Value::Null => None,
+            Value::String(s) => Some(format!("{}={}", key, logfmt_quote(&s))),
+            other => Some(format!("{}={}", key, other)),
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn logfmt_quote(value: &str) -> String {
+    if !value.is_empty() && !value.contains(|c: char| c.is_whitespace() || c == '"' || c == '=') {
+        return value.to_string();
+    }
+
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
@@ -0,0 +1,29 @@
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+
+use crate::core::snapshot::MetricsSnapshot;
+use crate::error::Result;
+use super::MetricsSink;
+
+/// Appends each flushed snapshot to a file as one JSON line.
+pub struct MetricsFileSink {
+    file: File,
+}
+
+impl MetricsFileSink {
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self { file })
+    }
+}
+
+impl MetricsSink for MetricsFileSink {
+    fn flush(&mut self, snapshot: &MetricsSnapshot) -> Result<()> {
+        let mut line = serde_json::to_string(snapshot)?;
+        line.push('\n');
+        self.file.write_all(line.as_bytes())?;
+        self.file.flush()?;
+        Ok(())
+    }
+}
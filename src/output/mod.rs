@@ -0,0 +1,132 @@
+pub mod log_file;
+pub mod flows;
+pub mod syslog;
+pub mod metrics_file;
+pub mod prometheus;
+pub mod pushgateway;
+pub mod statsd;
+pub mod influx;
+pub mod mqtt;
+pub mod profile_log;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+
+use std::io;
+use std::path::PathBuf;
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde_json::Value;
+
+use crate::core::events::ConnectionEvent;
+use crate::core::monitor::ConnectionMonitor;
+use crate::core::snapshot::MetricsSnapshot;
+use crate::core::watchlist::Watchlist;
+use crate::error::{self, Error};
+
+/// Destination for connection events as they are observed.
+pub trait EventSink {
+    fn write_event(&mut self, event: &ConnectionEvent) -> io::Result<()>;
+}
+
+/// Destination for periodically flushed aggregate metrics.
+pub trait MetricsSink {
+    fn flush(&mut self, snapshot: &MetricsSnapshot) -> error::Result<()>;
+}
+
+/// Forwards new monitor events to the event sinks, remembering which ones were already sent.
+#[derive(Default)]
+pub struct EventDispatcher {
+    sinks: Vec<Box<dyn EventSink>>,
+    next_seq: u64,
+}
+
+impl EventDispatcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_sink(&mut self, sink: Box<dyn EventSink>) {
+        self.sinks.push(sink);
+    }
+
+    /// Returns the first write failure; events are still offered to every sink.
+    pub fn dispatch(&mut self, monitor: &ConnectionMonitor) -> error::Result<()> {
+        if self.sinks.is_empty() {
+            return Ok(());
+        }
+
+        let mut first_error = None;
+        for event in monitor.get_events_since(self.next_seq) {
+            for sink in self.sinks.iter_mut() {
+                if let Err(e) = sink.write_event(event) {
+                    first_error.get_or_insert(Error::Sink(format!("Failed to write event: {}", e)));
+                }
+            }
+            self.next_seq = event.seq + 1;
+        }
+        first_error.map_or(Ok(()), Err)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MetricsSinkSpec {
+    File(PathBuf),
+    Sqlite(PathBuf),
+    Pushgateway(String),
+    Statsd(String),
+    Influx(String),
+    Mqtt(String),
+}
+
+impl MetricsSinkSpec {
+    /// Parses `file:<path>`, `sqlite:<path>`, `pushgateway:<url>`, `statsd:<host[:port]>`, `influx:<target>`,
+    /// or `mqtt:<target>`.
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        match spec.split_once(':') {
+            Some(("file", path)) => Ok(MetricsSinkSpec::File(PathBuf::from(path))),
+            Some(("sqlite", path)) => Ok(MetricsSinkSpec::Sqlite(PathBuf::from(path))),
+            Some(("pushgateway", url)) => Ok(MetricsSinkSpec::Pushgateway(url.to_string())),
+            Some(("statsd", target)) => Ok(MetricsSinkSpec::Statsd(target.to_string())),
+            Some(("influx", target)) => Ok(MetricsSinkSpec::Influx(target.to_string())),
+            Some(("mqtt", target)) => Ok(MetricsSinkSpec::Mqtt(target.to_string())),
+            _ => Err(format!(
+                "Invalid metrics sink '{}', expected file:, sqlite:, pushgateway:, statsd:, influx:, or mqtt:",
+                spec,
+            )),
+        }
+    }
+
+    /// Opens the sink; `watchlist` chooses the hosts the MQTT sink publishes one by one.
+    pub fn open(&self, watchlist: &Watchlist) -> error::Result<Box<dyn MetricsSink>> {
+        match self {
+            MetricsSinkSpec::File(path) => Ok(Box::new(metrics_file::MetricsFileSink::open(path)?)),
+            #[cfg(feature = "sqlite")]
+            MetricsSinkSpec::Sqlite(path) => Ok(Box::new(sqlite::SqliteSink::open(path)?)),
+            #[cfg(not(feature = "sqlite"))]
+            MetricsSinkSpec::Sqlite(_) => Err(Error::Sink("SQLite support requires building with --features sqlite".to_string())),
+            MetricsSinkSpec::Pushgateway(url) => Ok(Box::new(pushgateway::PushgatewaySink::new(url))),
+            MetricsSinkSpec::Statsd(target) => Ok(Box::new(statsd::StatsdSink::connect(target)?)),
+            MetricsSinkSpec::Influx(target) => Ok(Box::new(influx::InfluxSink::connect(target)?)),
+            MetricsSinkSpec::Mqtt(target) => Ok(Box::new(mqtt::MqttSink::connect(target, watchlist.clone())?)),
+        }
+    }
+}
+
+/// Flattened key/value fields describing an event, shared by the structured outputs.
+pub fn event_fields(event: &ConnectionEvent) -> Vec<(&'static str, Value)> {
+    let conn = &event.connection;
+    let time: DateTime<Utc> = event.time.into();
+
+    vec![
+        ("ts", Value::from(time.to_rfc3339_opts(SecondsFormat::Millis, true))),
+        ("event", Value::from(event.kind.as_str())),
+        ("pid", Value::from(conn.pid)),
+        ("process", event.process_name.clone().map_or(Value::Null, Value::from)),
+        ("local_port", Value::from(conn.local_port)),
+        ("remote_addr", Value::from(conn.remote_addr.to_string())),
+        ("remote_host", conn.remote_hostname.clone().map_or(Value::Null, Value::from)),
+        ("remote_port", Value::from(conn.remote_port)),
+        ("state", Value::from(conn.state.to_string())),
+        ("group", conn.group.clone().map_or(Value::Null, Value::from)),
+    ]
+}
@@ -0,0 +1,218 @@
+use std::io::{self, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+use serde_json::json;
+
+use crate::core::snapshot::MetricsSnapshot;
+use crate::core::watchlist::Watchlist;
+use crate::error::{Error, Result};
+use super::MetricsSink;
+
+const DEFAULT_PORT: u16 = 1883;
+const DEFAULT_PREFIX: &str = "tcpcount";
+/// How long the broker gets to accept a connection or a write.
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Where and as whom to publish, from `[user[:password]@]host[:port][/topic/prefix]`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MqttTarget {
+    pub host: String,
+    pub port: u16,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub prefix: String,
+}
+
+impl MqttTarget {
+    pub fn parse(target: &str) -> Self {
+        let (authority, prefix) = target.split_once('/').unwrap_or((target, ""));
+        let (credentials, address) = match authority.rsplit_once('@') {
+            Some((credentials, address)) => (Some(credentials), address),
+            None => (None, authority),
+        };
+        let (username, password) = match credentials.map(|c| c.split_once(':').unwrap_or((c, ""))) {
+            Some((username, password)) => (Some(username.to_string()), Some(password.to_string()).filter(|p| !p.is_empty())),
+            None => (None, None),
+        };
+        let (host, port) = match address.rsplit_once(':') {
+            Some((host, port)) if !host.ends_with(':') => match port.parse() {
+                Ok(port) => (host, port),
+                Err(_) => (address, DEFAULT_PORT),
+            },
+            _ => (address, DEFAULT_PORT),
+        };
+        let prefix = prefix.trim_matches('/');
+
+        Self {
+            host: host.trim_start_matches('[').trim_end_matches(']').to_string(),
+            port,
+            username,
+            password,
+            prefix: if prefix.is_empty() { DEFAULT_PREFIX.to_string() } else { prefix.to_string() },
+        }
+    }
+}
+
+/// Publishes the summary and each watched host's active, total and max connections to an
+/// MQTT broker as retained JSON messages, so dashboards show the latest values right away.
+pub struct MqttSink {
+    target: MqttTarget,
+    watchlist: Watchlist,
+    stream: Option<TcpStream>,
+}
+
+impl MqttSink {
+    /// Connects to the broker; a dropped connection is made again on the next flush.
+    pub fn connect(target: &str, watchlist: Watchlist) -> Result<Self> {
+        let mut sink = Self {
+            target: MqttTarget::parse(target),
+            watchlist,
+            stream: None,
+        };
+        sink.stream = Some(sink.open_session()?);
+        Ok(sink)
+    }
+
+    fn open_session(&self) -> Result<TcpStream> {
+        let addr = (self.target.host.as_str(), self.target.port).to_socket_addrs()?
+            .next()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "mqtt broker did not resolve"))?;
+        let mut stream = TcpStream::connect_timeout(&addr, TIMEOUT)?;
+        stream.set_read_timeout(Some(TIMEOUT))?;
+        stream.set_write_timeout(Some(TIMEOUT))?;
+
+        let client_id = format!("tcpcount-{}", std::process::id());
+        stream.write_all(&connect_packet(&client_id, self.target.username.as_deref(), self.target.password.as_deref()))?;
+
+        let mut connack = [0u8; 4];
+        stream.read_exact(&mut connack)?;
+        match connack {
+            [0x20, 0x02, _, 0] => Ok(stream),
+            [0x20, 0x02, _, code] => Err(Error::Sink(format!("MQTT broker refused the connection: {}", refusal_reason(code)))),
+            _ => Err(Error::Sink("MQTT broker sent an unexpected reply to CONNECT".to_string())),
+        }
+    }
+
+    fn publish_all(&mut self, messages: &[(String, String)]) -> Result<()> {
+        let stream = match &mut self.stream {
+            Some(stream) => stream,
+            None => self.stream.insert(self.open_session()?),
+        };
+        for (topic, payload) in messages {
+            stream.write_all(&publish_packet(topic, payload.as_bytes()))?;
+        }
+        stream.flush()?;
+        Ok(())
+    }
+}
+
+impl MetricsSink for MqttSink {
+    fn flush(&mut self, snapshot: &MetricsSnapshot) -> Result<()> {
+        let messages = messages(snapshot, &self.target.prefix, &self.watchlist);
+        let result = self.publish_all(&messages);
+        if result.is_err() {
+            self.stream = None;
+        }
+        result
+    }
+}
+
+impl Drop for MqttSink {
+    fn drop(&mut self) {
+        if let Some(stream) = &mut self.stream {
+            // DISCONNECT, so the broker doesn't treat the session as lost
+            stream.write_all(&[0xE0, 0x00]).ok();
+        }
+    }
+}
+
+/// Topics and JSON payloads for a snapshot: `<prefix>/summary`, and
+/// `<prefix>/host/<host>/<port>` for each host row the watchlist pins.
+pub fn messages(snapshot: &MetricsSnapshot, prefix: &str, watchlist: &Watchlist) -> Vec<(String, String)> {
+    let payload = |active: usize, total: usize, max: usize| {
+        json!({ "active": active, "total": total, "max": max }).to_string()
+    };
+
+    let summary = &snapshot.summary;
+    let mut messages = vec![(
+        format!("{}/summary", prefix),
+        payload(summary.active_connections, summary.total_connections, summary.max_concurrent),
+    )];
+    for m in snapshot.hosts.iter().filter(|m| watchlist.is_host_pinned(&m.host, m.port)) {
+        messages.push((
+            format!("{}/host/{}/{}", prefix, topic_level(&m.host), m.port),
+            payload(m.current_connections, m.total_connections, m.max_concurrent),
+        ));
+    }
+    messages
+}
+
+/// An MQTT 3.1.1 CONNECT with a clean session and no keep-alive.
+pub fn connect_packet(client_id: &str, username: Option<&str>, password: Option<&str>) -> Vec<u8> {
+    let mut flags = 0x02;
+    let mut body = Vec::new();
+    push_string(&mut body, "MQTT");
+    body.push(4); // Protocol level 3.1.1
+    if username.is_some() {
+        flags |= 0x80;
+    }
+    if password.is_some() {
+        flags |= 0x40;
+    }
+    body.push(flags);
+    body.extend_from_slice(&[0, 0]);
+    push_string(&mut body, client_id);
+    for field in [username, password].into_iter().flatten() {
+        push_string(&mut body, field);
+    }
+    packet(0x10, &body)
+}
+
+/// A retained MQTT PUBLISH at QoS 0.
+pub fn publish_packet(topic: &str, payload: &[u8]) -> Vec<u8> {
+    let mut body = Vec::new();
+    push_string(&mut body, topic);
+    body.extend_from_slice(payload);
+    packet(0x31, &body)
+}
+
+fn packet(header: u8, body: &[u8]) -> Vec<u8> {
+    let mut packet = vec![header];
+    // Remaining length: 7 bits per byte, high bit set while more follow
+    let mut remaining = body.len();
+    loop {
+        let mut byte = (remaining % 128) as u8;
+        remaining /= 128;
+        if remaining > 0 {
+            byte |= 0x80;
+        }
+        packet.push(byte);
+        if remaining == 0 {
+            break;
+        }
+    }
+    packet.extend_from_slice(body);
+    packet
+}
+
+fn push_string(buf: &mut Vec<u8>, value: &str) {
+    buf.extend_from_slice(&(value.len() as u16).to_be_bytes());
+    buf.extend_from_slice(value.as_bytes());
+}
+
+/// Makes a name safe for one topic level: no level separators or wildcards.
+fn topic_level(name: &str) -> String {
+    name.replace(['/', '+', '#'], "_")
+}
+
+fn refusal_reason(code: u8) -> &'static str {
+    match code {
+        1 => "unacceptable protocol version",
+        2 => "client identifier rejected",
+        3 => "server unavailable",
+        4 => "bad user name or password",
+        5 => "not authorized",
+        _ => "unknown reason",
+    }
+}
@@ -0,0 +1,25 @@
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+
+use crate::core::overhead::OverheadStats;
+use crate::error::Result;
+
+/// Appends tcpcount's own overhead after every refresh as one JSON line, for `--profile`.
+pub struct ProfileLog {
+    file: File,
+}
+
+impl ProfileLog {
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self { file })
+    }
+
+    pub fn write(&mut self, stats: &OverheadStats) -> Result<()> {
+        let mut line = serde_json::to_string(stats)?;
+        line.push('\n');
+        self.file.write_all(line.as_bytes())?;
+        Ok(())
+    }
+}
@@ -0,0 +1,85 @@
+use std::fmt::Write;
+
+use crate::core::snapshot::MetricsSnapshot;
+
+/// Renders a snapshot in the Prometheus text exposition format.
+pub fn render(snapshot: &MetricsSnapshot) -> String {
+    let mut out = String::new();
+
+    let summary = &snapshot.summary;
+    write_family(&mut out, "tcpcount_active_connections", "Currently active connections", "gauge",
+        [(String::new(), summary.active_connections)]);
+    write_family(&mut out, "tcpcount_connections_total", "Connections observed since start", "counter",
+        [(String::new(), summary.total_connections)]);
+    write_family(&mut out, "tcpcount_max_concurrent_connections", "Maximum concurrent connections", "gauge",
+        [(String::new(), summary.max_concurrent)]);
+
+    let host_labels = |host: &str, port: u16| labels(&[("host", host), ("port", &port.to_string())]);
+    write_family(&mut out, "tcpcount_host_active_connections", "Active connections per remote host", "gauge",
+        snapshot.hosts.iter().map(|m| (host_labels(&m.host, m.port), m.current_connections)));
+    write_family(&mut out, "tcpcount_host_connections_total", "Connections per remote host since start", "counter",
+        snapshot.hosts.iter().map(|m| (host_labels(&m.host, m.port), m.total_connections)));
+    write_family(&mut out, "tcpcount_host_max_concurrent_connections", "Maximum concurrent connections per remote host", "gauge",
+        snapshot.hosts.iter().map(|m| (host_labels(&m.host, m.port), m.max_concurrent)));
+    write_family(&mut out, "tcpcount_host_average_concurrent_connections", "Average concurrent connections per remote host over the session", "gauge",
+        snapshot.hosts.iter().map(|m| (host_labels(&m.host, m.port), m.sampled.average)));
+    write_family(&mut out, "tcpcount_host_p95_concurrent_connections", "95th percentile of concurrent connections per remote host over the session", "gauge",
+        snapshot.hosts.iter().map(|m| (host_labels(&m.host, m.port), m.sampled.p95)));
+
+    let process_labels = |pid: u32, name: &str| labels(&[("pid", &pid.to_string()), ("process", name)]);
+    write_family(&mut out, "tcpcount_process_active_connections", "Active connections per process", "gauge",
+        snapshot.processes.iter().map(|m| (process_labels(m.pid, &m.name), m.current_connections)));
+    write_family(&mut out, "tcpcount_process_connections_total", "Connections per process since start", "counter",
+        snapshot.processes.iter().map(|m| (process_labels(m.pid, &m.name), m.total_connections)));
+    write_family(&mut out, "tcpcount_process_max_concurrent_connections", "Maximum concurrent connections per process", "gauge",
+        snapshot.processes.iter().map(|m| (process_labels(m.pid, &m.name), m.max_concurrent)));
+    write_family(&mut out, "tcpcount_process_average_concurrent_connections", "Average concurrent connections per process over the session", "gauge",
+        snapshot.processes.iter().map(|m| (process_labels(m.pid, &m.name), m.sampled.average)));
+    write_family(&mut out, "tcpcount_process_p95_concurrent_connections", "95th percentile of concurrent connections per process over the session", "gauge",
+        snapshot.processes.iter().map(|m| (process_labels(m.pid, &m.name), m.sampled.p95)));
+
+    let process_host_labels = |pid: u32, name: &str, host: &str, port: u16| {
+        labels(&[("pid", &pid.to_string()), ("process", name), ("host", host), ("port", &port.to_string())])
+    };
+    write_family(&mut out, "tcpcount_process_host_active_connections", "Active connections per process and remote host", "gauge",
+        snapshot.process_hosts.iter()
+            .map(|m| (process_host_labels(m.pid, &m.process_name, &m.host, m.port), m.current_connections)));
+    write_family(&mut out, "tcpcount_process_host_connections_total", "Connections per process and remote host since start", "counter",
+        snapshot.process_hosts.iter()
+            .map(|m| (process_host_labels(m.pid, &m.process_name, &m.host, m.port), m.total_connections)));
+
+    let group_labels = |group: &str| labels(&[("group", group)]);
+    write_family(&mut out, "tcpcount_group_active_connections", "Active connections per endpoint group", "gauge",
+        snapshot.groups.iter().map(|m| (group_labels(&m.group), m.current_connections)));
+    write_family(&mut out, "tcpcount_group_connections_total", "Connections per endpoint group since start", "counter",
+        snapshot.groups.iter().map(|m| (group_labels(&m.group), m.total_connections)));
+    write_family(&mut out, "tcpcount_group_max_concurrent_connections", "Maximum concurrent connections per endpoint group", "gauge",
+        snapshot.groups.iter().map(|m| (group_labels(&m.group), m.max_concurrent)));
+
+    out
+}
+
+fn write_family(
+    out: &mut String,
+    name: &str,
+    help: &str,
+    kind: &str,
+    samples: impl IntoIterator<Item = (String, impl std::fmt::Display)>,
+) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+    for (labels, value) in samples {
+        let _ = writeln!(out, "{}{} {}", name, labels, value);
+    }
+}
+
+fn labels(pairs: &[(&str, &str)]) -> String {
+    let formatted: Vec<String> = pairs.iter()
+        .map(|(key, value)| format!("{}=\"{}\"", key, escape_label(value)))
+        .collect();
+    format!("{{{}}}", formatted.join(","))
+}
+
+fn escape_label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
@@ -0,0 +1,36 @@
+use sysinfo::System;
+
+use crate::core::snapshot::MetricsSnapshot;
+use crate::error::Result;
+use super::{MetricsSink, prometheus};
+
+/// Pushes each snapshot to a Prometheus Pushgateway, replacing the previous push for this instance.
+pub struct PushgatewaySink {
+    push_url: String,
+}
+
+impl PushgatewaySink {
+    pub fn new(base_url: &str) -> Self {
+        let instance = System::host_name().unwrap_or_else(|| "unknown".to_string());
+        Self {
+            push_url: format!("{}/metrics/job/tcpcount/instance/{}", base_url.trim_end_matches('/'), instance),
+        }
+    }
+}
+
+/// Pushes the metrics of a finished run once, warning instead of failing the run if the
+/// gateway can't be reached.
+pub fn push_final(base_url: &str, snapshot: &MetricsSnapshot) {
+    if let Err(e) = PushgatewaySink::new(base_url).flush(snapshot) {
+        eprintln!("tcpcount: pushing final metrics to {} failed: {}", base_url, e);
+    }
+}
+
+impl MetricsSink for PushgatewaySink {
+    fn flush(&mut self, snapshot: &MetricsSnapshot) -> Result<()> {
+        ureq::put(&self.push_url)
+            .set("Content-Type", "text/plain; version=0.0.4")
+            .send_string(&prometheus::render(snapshot))?;
+        Ok(())
+    }
+}
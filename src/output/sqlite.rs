@@ -0,0 +1,82 @@
+use std::path::Path;
+
+use rusqlite::{Connection, params};
+
+use crate::core::snapshot::MetricsSnapshot;
+use crate::error::Result;
+use super::MetricsSink;
+
+/// Stores each snapshot as rows of a `samples` table, one per aggregated entry.
+pub struct SqliteSink {
+    conn: Connection,
+}
+
+impl SqliteSink {
+    pub fn open(path: &Path) -> rusqlite::Result<Self> {
+        let conn = Connection::open(path)?;
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS samples (
+                ts TEXT NOT NULL,
+                scope TEXT NOT NULL,
+                pid INTEGER,
+                name TEXT,
+                host TEXT,
+                port INTEGER,
+                active INTEGER NOT NULL,
+                total INTEGER NOT NULL,
+                max_concurrent INTEGER NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS samples_ts ON samples (ts);",
+        )?;
+        Ok(Self { conn })
+    }
+}
+
+impl MetricsSink for SqliteSink {
+    fn flush(&mut self, snapshot: &MetricsSnapshot) -> Result<()> {
+        let ts = snapshot.timestamp.to_rfc3339();
+        let tx = self.conn.transaction()?;
+        {
+            let mut insert = tx.prepare(
+                "INSERT INTO samples (ts, scope, pid, name, host, port, active, total, max_concurrent)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            )?;
+
+            let summary = &snapshot.summary;
+            insert.execute(params![
+                ts, "summary", None::<u32>, None::<String>, None::<String>, None::<u16>,
+                summary.active_connections, summary.total_connections, summary.max_concurrent,
+            ])?;
+
+            for m in &snapshot.hosts {
+                insert.execute(params![
+                    ts, "host", None::<u32>, None::<String>, m.host, m.port,
+                    m.current_connections, m.total_connections, m.max_concurrent,
+                ])?;
+            }
+
+            for m in &snapshot.processes {
+                insert.execute(params![
+                    ts, "process", m.pid, m.name, None::<String>, None::<u16>,
+                    m.current_connections, m.total_connections, m.max_concurrent,
+                ])?;
+            }
+
+            for m in &snapshot.process_hosts {
+                insert.execute(params![
+                    ts, "process_host", m.pid, m.process_name, m.host, m.port,
+                    m.current_connections, m.total_connections, m.max_concurrent,
+                ])?;
+            }
+
+            for m in &snapshot.groups {
+                insert.execute(params![
+                    ts, "group", None::<u32>, m.group, None::<String>, None::<u16>,
+                    m.current_connections, m.total_connections, m.max_concurrent,
+                ])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,92 @@
+use std::io;
+use std::net::{ToSocketAddrs, UdpSocket};
+
+use crate::core::snapshot::MetricsSnapshot;
+use crate::error::Result;
+use super::MetricsSink;
+
+/// Keeps datagrams under a typical Ethernet MTU.
+const MAX_PACKET_SIZE: usize = 1400;
+
+/// Sends per-process, per-host, and per-group connection counts to a StatsD server as gauges.
+pub struct StatsdSink {
+    socket: UdpSocket,
+    prefix: String,
+}
+
+impl StatsdSink {
+    /// Connects to `host[:port]` (port defaults to 8125).
+    pub fn connect(target: &str) -> io::Result<Self> {
+        let target = if target.rsplit_once(':').is_some_and(|(_, port)| port.parse::<u16>().is_ok()) {
+            target.to_string()
+        } else {
+            format!("{}:8125", target)
+        };
+
+        let addr = target.to_socket_addrs()?
+            .next()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "statsd host did not resolve"))?;
+        let bind_addr = if addr.is_ipv4() { "0.0.0.0:0" } else { "[::]:0" };
+        let socket = UdpSocket::bind(bind_addr)?;
+        socket.connect(addr)?;
+
+        Ok(Self {
+            socket,
+            prefix: "tcpcount".to_string(),
+        })
+    }
+
+    fn gauges(&self, snapshot: &MetricsSnapshot) -> Vec<String> {
+        let mut lines = Vec::new();
+        let mut push = |path: String, active: usize, total: usize, max: usize| {
+            lines.push(format!("{}.{}.active:{}|g", self.prefix, path, active));
+            lines.push(format!("{}.{}.total:{}|g", self.prefix, path, total));
+            lines.push(format!("{}.{}.max:{}|g", self.prefix, path, max));
+        };
+
+        let summary = &snapshot.summary;
+        push("connections".to_string(), summary.active_connections, summary.total_connections, summary.max_concurrent);
+
+        for m in &snapshot.processes {
+            let path = format!("process.{}.{}", sanitize(&m.name), m.pid);
+            push(path, m.current_connections, m.total_connections, m.max_concurrent);
+        }
+        for m in &snapshot.hosts {
+            let path = format!("host.{}.{}", sanitize(&m.host), m.port);
+            push(path, m.current_connections, m.total_connections, m.max_concurrent);
+        }
+        for m in &snapshot.groups {
+            let path = format!("group.{}", sanitize(&m.group));
+            push(path, m.current_connections, m.total_connections, m.max_concurrent);
+        }
+
+        lines
+    }
+}
+
+impl MetricsSink for StatsdSink {
+    fn flush(&mut self, snapshot: &MetricsSnapshot) -> Result<()> {
+        let mut packet = String::new();
+        for line in self.gauges(snapshot) {
+            if !packet.is_empty() && packet.len() + line.len() + 1 > MAX_PACKET_SIZE {
+                self.socket.send(packet.as_bytes())?;
+                packet.clear();
+            }
+            if !packet.is_empty() {
+                packet.push('\n');
+            }
+            packet.push_str(&line);
+        }
+        if !packet.is_empty() {
+            self.socket.send(packet.as_bytes())?;
+        }
+        Ok(())
+    }
+}
+
+/// Makes a name safe for one segment of a dotted StatsD metric path.
+fn sanitize(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect()
+}
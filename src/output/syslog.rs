@@ -0,0 +1,160 @@
+use std::io;
+use std::net::{ToSocketAddrs, UdpSocket};
+#[cfg(unix)]
+use std::os::unix::net::UnixDatagram;
+use std::process;
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde_json::Value;
+use sysinfo::System;
+
+use crate::core::events::ConnectionEvent;
+use super::{EventSink, event_fields};
+
+/// Structured-data ID for event fields (32473 is the enterprise number reserved for examples).
+const SD_ID: &str = "tcpcount@32473";
+const SEVERITY_INFO: u8 = 6;
+
+#[cfg(target_os = "macos")]
+const LOCAL_SOCKET_PATH: &str = "/var/run/syslog";
+#[cfg(not(target_os = "macos"))]
+const LOCAL_SOCKET_PATH: &str = "/dev/log";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SyslogTarget {
+    Local,
+    Remote(String), // host:port, sent over UDP
+}
+
+impl SyslogTarget {
+    /// Parses `local` or `host[:port]` (port defaults to 514).
+    pub fn parse(target: &str) -> Self {
+        if target == "local" {
+            return SyslogTarget::Local;
+        }
+
+        let target = target.strip_prefix("udp://").unwrap_or(target);
+        if target.rsplit_once(':').is_some_and(|(_, port)| port.parse::<u16>().is_ok()) {
+            SyslogTarget::Remote(target.to_string())
+        } else {
+            SyslogTarget::Remote(format!("{}:514", target))
+        }
+    }
+}
+
+/// Maps a facility name to its RFC 5424 code.
+pub fn facility_code(name: &str) -> Option<u8> {
+    let code = match name {
+        "kern" => 0,
+        "user" => 1,
+        "daemon" => 3,
+        "auth" => 4,
+        "syslog" => 5,
+        "local0" => 16,
+        "local1" => 17,
+        "local2" => 18,
+        "local3" => 19,
+        "local4" => 20,
+        "local5" => 21,
+        "local6" => 22,
+        "local7" => 23,
+        _ => return None,
+    };
+    Some(code)
+}
+
+enum Transport {
+    #[cfg(unix)]
+    Local(UnixDatagram),
+    Udp(UdpSocket),
+}
+
+/// Sends each event as an RFC 5424 message to the local syslog socket or a remote collector.
+pub struct SyslogSink {
+    transport: Transport,
+    facility: u8,
+    hostname: String,
+    pid: u32,
+}
+
+impl SyslogSink {
+    pub fn connect(target: &SyslogTarget, facility: u8) -> io::Result<Self> {
+        let transport = match target {
+            #[cfg(unix)]
+            SyslogTarget::Local => {
+                let socket = UnixDatagram::unbound()?;
+                socket.connect(LOCAL_SOCKET_PATH)?;
+                Transport::Local(socket)
+            }
+            #[cfg(not(unix))]
+            SyslogTarget::Local => {
+                return Err(io::Error::new(io::ErrorKind::Unsupported, "local syslog is only available on Unix"));
+            }
+            SyslogTarget::Remote(addr) => {
+                let addr = addr.to_socket_addrs()?
+                    .next()
+                    .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "syslog host did not resolve"))?;
+                let bind_addr = if addr.is_ipv4() { "0.0.0.0:0" } else { "[::]:0" };
+                let socket = UdpSocket::bind(bind_addr)?;
+                socket.connect(addr)?;
+                Transport::Udp(socket)
+            }
+        };
+
+        Ok(Self {
+            transport,
+            facility,
+            hostname: System::host_name().unwrap_or_else(|| "-".to_string()),
+            pid: process::id(),
+        })
+    }
+
+    fn send(&self, message: &str) -> io::Result<()> {
+        match &self.transport {
+            #[cfg(unix)]
+            Transport::Local(socket) => socket.send(message.as_bytes()).map(|_| ()),
+            Transport::Udp(socket) => socket.send(message.as_bytes()).map(|_| ()),
+        }
+    }
+}
+
+impl EventSink for SyslogSink {
+    fn write_event(&mut self, event: &ConnectionEvent) -> io::Result<()> {
+        let conn = &event.connection;
+        let time: DateTime<Utc> = event.time.into();
+        let pri = self.facility as u16 * 8 + SEVERITY_INFO as u16;
+
+        // The timestamp is already in the header
+        let params: Vec<String> = event_fields(event).into_iter()
+            .filter(|(key, _)| *key != "ts")
+            .filter_map(|(key, value)| match value {
+                Value::Null => None,
+                Value::String(s) => Some(format!("{}=\"{}\"", key, escape_param(&s))),
+                other => Some(format!("{}=\"{}\"", key, other)),
+            })
+            .collect();
+
+        let host = conn.remote_hostname.clone().unwrap_or_else(|| conn.remote_addr.to_string());
+        let message = format!(
+            "<{}>1 {} {} tcpcount {} {} [{} {}] {}({}) -> {}:{} {}",
+            pri,
+            time.to_rfc3339_opts(SecondsFormat::Millis, true),
+            self.hostname,
+            self.pid,
+            event.kind.as_str(),
+            SD_ID,
+            params.join(" "),
+            event.process_name.as_deref().unwrap_or("Unknown"),
+            conn.pid,
+            host,
+            conn.remote_port,
+            event.kind.as_str(),
+        );
+
+        self.send(&message)
+    }
+}
+
+fn escape_param(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace(']', "\\]")
+}
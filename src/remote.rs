@@ -0,0 +1,187 @@
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use sysinfo::{ProcessRefreshKind, RefreshKind, System};
+
+use crate::core::resolver::Resolver;
+use crate::core::sample::{ProcessSample, SampleOptions, SocketSample, sample_sockets};
+use crate::error::{self, ErrorLog};
+
+/// Agents that have not reported for this long are dropped, closing their connections.
+const STALE_AFTER: Duration = Duration::from_secs(10);
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// One line of the agent protocol: a full sampling pass as JSON.
+#[derive(Debug, Serialize, Deserialize)]
+struct AgentReport {
+    agent: String,
+    sockets: Vec<SocketSample>,
+}
+
+pub struct AgentOptions {
+    pub connect: String,
+    pub name: String,
+    pub interval: Duration,
+}
+
+/// Samples local sockets and streams them to an aggregator until SIGINT/SIGTERM.
+pub fn run_agent(options: AgentOptions, name_ttl: Duration, sample_options: SampleOptions) -> error::Result<()> {
+    let shutdown = Arc::new(AtomicBool::new(false));
+    let shutdown_flag = Arc::clone(&shutdown);
+    ctrlc::set_handler(move || shutdown_flag.store(true, Ordering::SeqCst))?;
+
+    let refresh_kind = RefreshKind::nothing().with_processes(ProcessRefreshKind::everything());
+    let mut system = System::new_with_specifics(refresh_kind);
+    let mut resolver = Resolver::new();
+    resolver.set_name_ttl(name_ttl);
+    let mut stream: Option<TcpStream> = None;
+    let mut last_attempt: Option<Instant> = None;
+    let mut error_log = ErrorLog::default();
+
+    while !shutdown.load(Ordering::SeqCst) {
+        if stream.is_none() && last_attempt.is_none_or(|at| at.elapsed() >= RECONNECT_DELAY) {
+            last_attempt = Some(Instant::now());
+            match TcpStream::connect(&options.connect) {
+                Ok(connected) => {
+                    eprintln!("tcpcount: connected to aggregator {}", options.connect);
+                    stream = Some(connected);
+                }
+                Err(e) => eprintln!("tcpcount: cannot reach aggregator {}: {}", options.connect, e),
+            }
+        }
+
+        if let Some(connected) = stream.as_mut() {
+            let sockets = match sample_sockets(&mut system, &mut resolver, sample_options) {
+                Ok(pass) => {
+                    error_log.log(&pass.errors);
+                    pass.sockets
+                }
+                Err(e) => {
+                    error_log.log(&[e]);
+                    Vec::new()
+                }
+            };
+            let report = AgentReport {
+                agent: options.name.clone(),
+                sockets,
+            };
+            let mut line = serde_json::to_string(&report)?;
+            line.push('\n');
+
+            if let Err(e) = connected.write_all(line.as_bytes()) {
+                eprintln!("tcpcount: lost connection to aggregator: {}", e);
+                stream = None;
+            }
+        }
+
+        thread::sleep(options.interval);
+    }
+
+    Ok(())
+}
+
+struct AgentState {
+    sockets: Vec<SocketSample>,
+    last_report: Instant,
+}
+
+/// Collects reports from remote agents and merges them into samples for the local monitor.
+pub struct Aggregator {
+    agents: Arc<Mutex<HashMap<String, AgentState>>>,
+    // Agents' PIDs can collide, so each (agent, pid) pair gets a local one
+    pids: HashMap<(String, u32), u32>,
+    next_pid: u32,
+}
+
+impl Aggregator {
+    /// Accepts agent connections on `listener` in the background.
+    pub fn listen(listener: TcpListener) -> Self {
+        let agents = Arc::new(Mutex::new(HashMap::new()));
+
+        let accept_agents = Arc::clone(&agents);
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let agents = Arc::clone(&accept_agents);
+                thread::spawn(move || read_reports(stream, agents));
+            }
+        });
+
+        Self {
+            agents,
+            pids: HashMap::new(),
+            next_pid: 1,
+        }
+    }
+
+    /// Number of agents that reported recently.
+    pub fn agent_count(&self) -> usize {
+        self.agents.lock().unwrap().len()
+    }
+
+    /// Latest sockets from every live agent, with PIDs remapped and process names prefixed by agent.
+    pub fn samples(&mut self) -> Vec<SocketSample> {
+        let mut agents = self.agents.lock().unwrap();
+        agents.retain(|_, state| state.last_report.elapsed() < STALE_AFTER);
+
+        let mut samples = Vec::new();
+        for (agent, state) in agents.iter() {
+            for socket in &state.sockets {
+                let key = (agent.clone(), socket.pid);
+                let pid = match self.pids.get(&key) {
+                    Some(pid) => *pid,
+                    None => {
+                        let pid = self.next_pid;
+                        self.next_pid += 1;
+                        self.pids.insert(key, pid);
+                        pid
+                    }
+                };
+
+                let process = match &socket.process {
+                    Some(process) => ProcessSample {
+                        name: format!("{}/{}", agent, process.name),
+                        ..process.clone()
+                    },
+                    None => ProcessSample {
+                        name: format!("{}/unknown", agent),
+                        exe: None,
+                        memory_usage: 0,
+                        started_at: None,
+                    },
+                };
+
+                samples.push(SocketSample {
+                    pid,
+                    process: Some(process),
+                    ..socket.clone()
+                });
+            }
+        }
+        samples
+    }
+}
+
+fn read_reports(stream: TcpStream, agents: Arc<Mutex<HashMap<String, AgentState>>>) {
+    let reader = BufReader::new(stream);
+    for line in reader.lines() {
+        let line = match line {
+            Ok(line) => line,
+            Err(_) => break,
+        };
+        let report: AgentReport = match serde_json::from_str(&line) {
+            Ok(report) => report,
+            Err(_) => continue,
+        };
+
+        agents.lock().unwrap().insert(report.agent, AgentState {
+            sockets: report.sockets,
+            last_report: Instant::now(),
+        });
+    }
+}
@@ -0,0 +1,120 @@
+use std::io;
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
+use std::thread;
+use std::time::Duration;
+
+use crate::core::filters::ConnectionFilter;
+use crate::core::monitor::{ConnectionMonitor, ConnectionSummary};
+use crate::error::{Error, Result};
+
+/// Pattern of loopback connections opened by `tcpcount selftest`.
+#[derive(Debug, Clone, Copy)]
+pub struct SelftestOptions {
+    /// Connections opened together in each round.
+    pub connections: usize,
+    /// Open/close rounds; the demo repeats them until quit.
+    pub rounds: usize,
+    /// How long connections stay open, and closed, in each round.
+    pub hold: Duration,
+}
+
+/// A loopback listener plus clients opening and closing connections to it.
+pub struct LoadGenerator {
+    options: SelftestOptions,
+    port: u16,
+}
+
+impl LoadGenerator {
+    /// Starts listening on an unused loopback port. Accepted connections are read until
+    /// the client closes them.
+    pub fn start(options: SelftestOptions) -> Result<Self> {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))?;
+        let port = listener.local_addr()?.port();
+
+        thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                thread::spawn(move || io::copy(&mut stream, &mut io::sink()));
+            }
+        });
+
+        Ok(Self { options, port })
+    }
+
+    /// Port the generated connections go to.
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    /// Opens one round of connections, closed again when dropped.
+    pub fn open_round(&self) -> Result<Vec<TcpStream>> {
+        (0..self.options.connections)
+            .map(|_| TcpStream::connect((Ipv4Addr::LOCALHOST, self.port)).map_err(Error::from))
+            .collect()
+    }
+
+    /// Opens and closes rounds forever in the background, for the demo.
+    pub fn spawn_demo(self) {
+        thread::spawn(move || loop {
+            if let Ok(streams) = self.open_round() {
+                thread::sleep(self.options.hold);
+                drop(streams);
+            }
+            thread::sleep(self.options.hold);
+        });
+    }
+}
+
+/// The generated connections as seen by the monitor, leaving out the server side.
+pub fn generated_filter(port: u16) -> ConnectionFilter {
+    ConnectionFilter::new()
+        .with_pid(std::process::id())
+        .with_remote_port(port)
+}
+
+/// Runs the open/close rounds while monitoring them and checks the counters after each
+/// step. Fails when any check does.
+pub fn run(options: SelftestOptions) -> Result<()> {
+    if options.connections == 0 || options.rounds == 0 {
+        return Err(Error::Config("selftest needs at least one connection and one round".to_string()));
+    }
+
+    let generator = LoadGenerator::start(options)?;
+    let filter = generated_filter(generator.port());
+    let mut monitor = ConnectionMonitor::new();
+    let n = options.connections;
+
+    println!("Self-test: {} round(s) of {} connection(s) to 127.0.0.1:{}", options.rounds, n, generator.port());
+    let mut failures = 0;
+    let mut check = |step: String, summary: ConnectionSummary, active: usize, total: usize, max: usize| {
+        let ok = summary.active_connections == active
+            && summary.total_connections == total
+            && summary.max_concurrent == max;
+        println!(
+            "  {:<24} active {:>3}  total {:>3}  max {:>3}  {}",
+            step, summary.active_connections, summary.total_connections, summary.max_concurrent,
+            if ok { "ok".to_string() } else { format!("FAILED (expected {}/{}/{})", active, total, max) },
+        );
+        if !ok {
+            failures += 1;
+        }
+    };
+
+    for round in 1..=options.rounds {
+        let streams = generator.open_round()?;
+        thread::sleep(options.hold);
+        monitor.refresh()?;
+        check(format!("round {}: opened", round), monitor.get_summary(&filter), n, n * round, n);
+
+        drop(streams);
+        thread::sleep(options.hold);
+        monitor.refresh()?;
+        check(format!("round {}: closed", round), monitor.get_summary(&filter), 0, n * round, n);
+    }
+
+    let steps = options.rounds * 2;
+    if failures > 0 {
+        return Err(Error::Other(format!("self-test failed: {} of {} checks failed", failures, steps)));
+    }
+    println!("Self-test passed: {} checks", steps);
+    Ok(())
+}
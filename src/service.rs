@@ -0,0 +1,177 @@
+//! Windows service entry point for `tcpcount daemon`, and its installation.
+
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+
+use crate::error;
+
+/// Registers the daemon as a service started with Windows, running as LocalSystem with
+/// `daemon_args` after `tcpcount daemon`.
+pub fn install(daemon_args: &[String]) -> error::Result<()> {
+    imp::install(daemon_args)
+}
+
+/// Stops the service if it runs and removes it.
+pub fn uninstall() -> error::Result<()> {
+    imp::uninstall()
+}
+
+/// Runs `daemon` as the service the service control manager started, passing it the flag
+/// a stop request sets. The daemon runs on the calling thread.
+pub fn run(daemon: impl FnOnce(Arc<AtomicBool>) -> error::Result<()>) -> error::Result<()> {
+    imp::run(daemon)
+}
+
+#[cfg(windows)]
+mod imp {
+    use std::ffi::OsString;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::mpsc::{self, Receiver, Sender};
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+    use std::time::Duration;
+
+    use windows_service::define_windows_service;
+    use windows_service::service::{
+        ServiceAccess, ServiceControl, ServiceControlAccept, ServiceErrorControl, ServiceExitCode,
+        ServiceInfo, ServiceStartType, ServiceState, ServiceStatus, ServiceType,
+    };
+    use windows_service::service_control_handler::{self, ServiceControlHandlerResult};
+    use windows_service::service_dispatcher;
+    use windows_service::service_manager::{ServiceManager, ServiceManagerAccess};
+
+    use crate::error::{self, Error};
+
+    /// Name the service is registered and controlled under, as in `sc stop tcpcount`.
+    const SERVICE_NAME: &str = "tcpcount";
+
+    /// Channels between the service main, called by the dispatcher without context, and
+    /// the daemon: the stop flag goes out once the service runs, its exit code comes back.
+    static SERVICE: Mutex<Option<(Sender<Arc<AtomicBool>>, Receiver<u32>)>> = Mutex::new(None);
+
+    pub fn install(daemon_args: &[String]) -> error::Result<()> {
+        let manager = ServiceManager::local_computer(None::<&str>, ServiceManagerAccess::CONNECT | ServiceManagerAccess::CREATE_SERVICE)
+            .map_err(service_error)?;
+        let mut launch_arguments = vec![OsString::from("daemon"), OsString::from("--windows-service")];
+        launch_arguments.extend(daemon_args.iter().map(OsString::from));
+        let info = ServiceInfo {
+            name: OsString::from(SERVICE_NAME),
+            display_name: OsString::from("tcpcount connection monitor"),
+            service_type: ServiceType::OWN_PROCESS,
+            start_type: ServiceStartType::AutoStart,
+            error_control: ServiceErrorControl::Normal,
+            executable_path: std::env::current_exe()?,
+            launch_arguments,
+            dependencies: Vec::new(),
+            account_name: None,
+            account_password: None,
+        };
+        let service = manager.create_service(&info, ServiceAccess::CHANGE_CONFIG).map_err(service_error)?;
+        service.set_description("Counts TCP connections per host and process and flushes the metrics to sinks")
+            .map_err(service_error)
+    }
+
+    pub fn uninstall() -> error::Result<()> {
+        let manager = ServiceManager::local_computer(None::<&str>, ServiceManagerAccess::CONNECT).map_err(service_error)?;
+        let service = manager.open_service(SERVICE_NAME, ServiceAccess::QUERY_STATUS | ServiceAccess::STOP | ServiceAccess::DELETE)
+            .map_err(service_error)?;
+        if service.query_status().map_err(service_error)?.current_state != ServiceState::Stopped {
+            service.stop().map_err(service_error)?;
+        }
+        service.delete().map_err(service_error)
+    }
+
+    pub fn run(daemon: impl FnOnce(Arc<AtomicBool>) -> error::Result<()>) -> error::Result<()> {
+        let (shutdown_tx, shutdown_rx) = mpsc::channel();
+        let (exit_tx, exit_rx) = mpsc::channel();
+        if let Ok(mut service) = SERVICE.lock() {
+            *service = Some((shutdown_tx, exit_rx));
+        }
+
+        // The dispatcher blocks until the service stops, so the daemon keeps this thread
+        let dispatcher = thread::spawn(|| service_dispatcher::start(SERVICE_NAME, ffi_service_main));
+        let shutdown = match shutdown_rx.recv() {
+            Ok(shutdown) => shutdown,
+            Err(_) => {
+                return match dispatcher.join() {
+                    Ok(Err(e)) => Err(service_error(e)),
+                    _ => Err(Error::Other("The Windows service did not start".to_string())),
+                };
+            }
+        };
+        let result = daemon(shutdown);
+        exit_tx.send(if result.is_ok() { 0 } else { 1 }).ok();
+        dispatcher.join().ok();
+        result
+    }
+
+    define_windows_service!(ffi_service_main, service_main);
+
+    fn service_main(_arguments: Vec<OsString>) {
+        let Some((shutdown_tx, exit_rx)) = SERVICE.lock().ok().and_then(|mut service| service.take()) else {
+            return;
+        };
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let stop = Arc::clone(&shutdown);
+        let handler = move |control: ServiceControl| match control {
+            ServiceControl::Stop | ServiceControl::Shutdown => {
+                stop.store(true, Ordering::SeqCst);
+                ServiceControlHandlerResult::NoError
+            }
+            ServiceControl::Interrogate => ServiceControlHandlerResult::NoError,
+            _ => ServiceControlHandlerResult::NotImplemented,
+        };
+        let Ok(status) = service_control_handler::register(SERVICE_NAME, handler) else {
+            return;
+        };
+        let report = |state: ServiceState, controls: ServiceControlAccept, exit_code: u32| {
+            status.set_service_status(ServiceStatus {
+                service_type: ServiceType::OWN_PROCESS,
+                current_state: state,
+                controls_accepted: controls,
+                exit_code: ServiceExitCode::Win32(exit_code),
+                checkpoint: 0,
+                wait_hint: Duration::default(),
+                process_id: None,
+            }).ok();
+        };
+
+        report(ServiceState::Running, ServiceControlAccept::STOP | ServiceControlAccept::SHUTDOWN, 0);
+        if shutdown_tx.send(shutdown).is_err() {
+            report(ServiceState::Stopped, ServiceControlAccept::empty(), 1);
+            return;
+        }
+        let exit_code = exit_rx.recv().unwrap_or(1);
+        report(ServiceState::Stopped, ServiceControlAccept::empty(), exit_code);
+    }
+
+    fn service_error(e: windows_service::Error) -> Error {
+        Error::Other(format!("Windows service: {}", e))
+    }
+}
+
+#[cfg(not(windows))]
+mod imp {
+    use std::sync::Arc;
+    use std::sync::atomic::AtomicBool;
+
+    use super::unsupported;
+    use crate::error;
+
+    pub fn install(_daemon_args: &[String]) -> error::Result<()> {
+        Err(unsupported())
+    }
+
+    pub fn uninstall() -> error::Result<()> {
+        Err(unsupported())
+    }
+
+    pub fn run(_daemon: impl FnOnce(Arc<AtomicBool>) -> error::Result<()>) -> error::Result<()> {
+        Err(unsupported())
+    }
+}
+
+#[cfg(not(windows))]
+fn unsupported() -> error::Error {
+    error::Error::Other("Windows services are only available on Windows; on Linux run the daemon with the systemd unit in contrib/systemd".to_string())
+}
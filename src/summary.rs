@@ -0,0 +1,78 @@
+use std::fmt::Write;
+
+use chrono::{DateTime, Local, Utc};
+
+use crate::core::filters::ConnectionFilter;
+use crate::core::monitor::{ConnectionMonitor, HostMetrics, ProcessMetrics};
+use crate::widgets::table::format_age;
+
+/// Rows listed in each top-10 section.
+const TOP_ROWS: usize = 10;
+
+/// An alert raised during the session, as shown in the status bar.
+#[derive(Debug, Clone)]
+pub struct FiredAlert {
+    pub time: DateTime<Local>,
+    pub message: String,
+}
+
+/// Plain-text report of a monitoring session, printed on exit with `--summary-on-exit`.
+pub fn session_summary(
+    monitor: &ConnectionMonitor,
+    filter: &ConnectionFilter,
+    started: DateTime<Utc>,
+    alerts: &[FiredAlert],
+) -> String {
+    let summary = monitor.get_summary(filter);
+    let hosts = monitor.get_host_metrics(filter);
+    let processes = monitor.get_process_metrics(filter);
+
+    let mut out = String::new();
+    let _ = writeln!(out, "tcpcount session summary");
+    let _ = writeln!(out, "========================");
+    let _ = writeln!(out, "Started:     {}", DateTime::<Local>::from(started).format("%Y-%m-%d %H:%M:%S"));
+    let _ = writeln!(out, "Duration:    {}", format_age(started));
+    if !filter.is_empty() {
+        let _ = writeln!(out, "Filter:      {}", filter);
+        let _ = writeln!(out, "             --filter '{}'", filter.shareable());
+    }
+    let _ = writeln!(out, "Connections: {} observed, {} max concurrent", summary.total_connections, summary.max_concurrent);
+
+    let host_rows = |key: fn(&HostMetrics) -> usize| {
+        let mut rows: Vec<_> = hosts.iter().map(|h| (key(h), format!("{}:{}", h.host, h.port))).collect();
+        rows.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+        rows
+    };
+    let process_rows = |key: fn(&ProcessMetrics) -> usize| {
+        let mut rows: Vec<_> = processes.iter().map(|p| (key(p), format!("{} ({})", p.name, p.pid))).collect();
+        rows.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+        rows
+    };
+
+    write_section(&mut out, "Top hosts by total connections", &host_rows(|h| h.total_connections));
+    write_section(&mut out, "Top hosts by max concurrent", &host_rows(|h| h.max_concurrent));
+    write_section(&mut out, "Top processes by total connections", &process_rows(|p| p.total_connections));
+    write_section(&mut out, "Top processes by max concurrent", &process_rows(|p| p.max_concurrent));
+
+    let _ = writeln!(out);
+    let _ = writeln!(out, "Alerts ({})", alerts.len());
+    if alerts.is_empty() {
+        let _ = writeln!(out, "  none");
+    }
+    for alert in alerts {
+        let _ = writeln!(out, "  {} {}", alert.time.format("%H:%M:%S"), alert.message);
+    }
+    out
+}
+
+fn write_section(out: &mut String, title: &str, rows: &[(usize, String)]) {
+    let _ = writeln!(out);
+    let _ = writeln!(out, "{}", title);
+    if rows.is_empty() {
+        let _ = writeln!(out, "  none");
+    }
+    let width = rows.first().map_or(1, |(count, _)| count.to_string().len());
+    for (count, label) in rows.iter().take(TOP_ROWS) {
+        let _ = writeln!(out, "  {:>width$}  {}", count, label, width = width);
+    }
+}
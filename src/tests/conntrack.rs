@@ -0,0 +1,27 @@
+use std::net::SocketAddr;
+
+use crate::core::conntrack::NatTable;
+
+const CONNTRACK: &str = "\
+ipv4     2 tcp      6 431999 ESTABLISHED src=10.0.0.2 dst=10.96.0.10 sport=41234 dport=443 src=10.244.1.7 dst=10.0.0.2 sport=8443 dport=41234 [ASSURED] mark=0 zone=0 use=2
+ipv4     2 tcp      6 431999 ESTABLISHED src=10.0.0.2 dst=93.184.216.34 sport=41240 dport=443 src=93.184.216.34 dst=10.0.0.2 sport=443 dport=41240 [ASSURED] mark=0 zone=0 use=2
+ipv4     2 udp      17 29 src=10.0.0.2 dst=10.96.0.53 sport=5353 dport=53 src=10.244.1.9 dst=10.0.0.2 sport=53 dport=5353 mark=0 zone=0 use=2
+ipv4     2 tcp      6 118 SYN_SENT src=10.0.0.2 dst=10.96.0.11 sport=41250 dport=80 [UNREPLIED] src=10.244.2.3 dst=10.0.0.2 sport=8080 dport=41250 mark=0 zone=0 use=2
+";
+
+fn addr(text: &str) -> SocketAddr {
+    text.parse().unwrap()
+}
+
+#[test]
+fn conntrack_gives_the_real_destination_of_translated_connections() {
+    let nat = NatTable::parse(CONNTRACK);
+
+    assert_eq!(nat.destination(addr("10.0.0.2:41234"), addr("10.96.0.10:443")), Some(addr("10.244.1.7:8443")));
+    assert_eq!(nat.destination(addr("10.0.0.2:41250"), addr("10.96.0.11:80")), Some(addr("10.244.2.3:8080")));
+    // IPv4-mapped socket addresses match the plain IPv4 entries
+    assert_eq!(nat.destination(addr("[::ffff:10.0.0.2]:41234"), addr("[::ffff:10.96.0.10]:443")), Some(addr("10.244.1.7:8443")));
+    // Untranslated TCP and any UDP are left alone
+    assert_eq!(nat.destination(addr("10.0.0.2:41240"), addr("93.184.216.34:443")), None);
+    assert_eq!(nat.destination(addr("10.0.0.2:5353"), addr("10.96.0.53:53")), None);
+}
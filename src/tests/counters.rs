@@ -0,0 +1,67 @@
+use std::time::{Duration, SystemTime};
+
+use crate::core::counters::{ConcurrencyCounter, ConcurrencyHistogram, RecentMax, SampledConcurrency, WindowedMax};
+
+#[test]
+fn close_without_open_saturates_and_counts_a_mismatch() {
+    let mut counter = ConcurrencyCounter::default();
+    counter.open("a");
+    counter.close(&"a");
+    counter.close(&"a");
+    counter.close(&"b");
+
+    assert_eq!(counter.current(&"a"), 0);
+    assert_eq!(counter.current(&"b"), 0);
+    assert_eq!(counter.current_sum(), 0);
+    assert_eq!(counter.mismatches(), 2);
+    assert_eq!((counter.total(&"a"), counter.max(&"a")), (1, 1));
+}
+
+#[test]
+fn max_tracks_the_peak_not_the_latest_count() {
+    let mut counter = ConcurrencyCounter::default();
+    counter.open(1);
+    counter.open(1);
+    counter.close(&1);
+    counter.open(1);
+    counter.count_closed(1);
+
+    assert_eq!((counter.current(&1), counter.total(&1), counter.max(&1)), (2, 4, 2));
+}
+
+#[test]
+fn windowed_max_forgets_spikes_that_left_the_window() {
+    let start = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000);
+    let at = |secs: u64| start + Duration::from_secs(secs);
+    let mut recent = WindowedMax::default();
+
+    recent.record([(&"a", 9)].into_iter(), at(0));
+    recent.record([(&"a", 2)].into_iter(), at(10));
+    assert_eq!(recent.get(&"a"), RecentMax { last_1m: 9, last_5m: 9, last_1h: 9 });
+
+    recent.record([(&"a", 3)].into_iter(), at(90));
+    assert_eq!(recent.get(&"a"), RecentMax { last_1m: 3, last_5m: 9, last_1h: 9 });
+
+    // Gone from the counts is a zero; the key is dropped once the hour forgets it
+    recent.record(std::iter::empty(), at(350));
+    assert_eq!(recent.get(&"a"), RecentMax { last_1m: 0, last_5m: 3, last_1h: 9 });
+    recent.record(std::iter::empty(), at(3700));
+    assert_eq!(recent.get(&"a"), RecentMax::default());
+}
+
+#[test]
+fn sampled_concurrency_discounts_a_single_spike() {
+    let mut sampled = ConcurrencyHistogram::default();
+    for _ in 0..19 {
+        sampled.record([(&"a", 2)].into_iter());
+    }
+    sampled.record([(&"a", 40)].into_iter());
+    assert_eq!(sampled.get(&"a"), SampledConcurrency { average: 3.9, p95: 2 });
+
+    // Samples without the key count as zero once it has been seen
+    for _ in 0..20 {
+        sampled.record(std::iter::empty());
+    }
+    assert_eq!(sampled.get(&"a"), SampledConcurrency { average: 1.95, p95: 2 });
+    assert_eq!(sampled.get(&"b"), SampledConcurrency::default());
+}
@@ -0,0 +1,32 @@
+use crate::output::flows::FlowRecord;
+
+use super::replay;
+
+#[test]
+fn closed_connections_become_flows_from_their_initiator() {
+    let monitor = replay("\
+open out pid=7 remote=93.184.216.34:443 process=curl lport=50000
+open in pid=9 remote=10.0.0.5:41000 process=nginx lport=8080 inbound=true
+tick
+close out
+close in
+tick
+");
+    let flows: Vec<FlowRecord> = monitor.get_events_since(0).iter()
+        .filter_map(|event| FlowRecord::from_event(event))
+        .collect();
+    assert_eq!(flows.len(), 2);
+
+    let outbound = flows.iter().find(|flow| !flow.inbound).unwrap();
+    assert_eq!((outbound.src_port, outbound.dst_addr, outbound.dst_port), (50000, Some("93.184.216.34".parse().unwrap()), 443));
+    assert!(outbound.to_csv().ends_with(",6,,50000,93.184.216.34,443,out,7,curl"));
+
+    let inbound = flows.iter().find(|flow| flow.inbound).unwrap();
+    assert_eq!((inbound.src_addr, inbound.src_port, inbound.dst_port), (Some("10.0.0.5".parse().unwrap()), 41000, 8080));
+    let ipfix: serde_json::Value = serde_json::from_str(&inbound.to_ipfix()).unwrap();
+    assert_eq!(ipfix["sourceIPv4Address"], "10.0.0.5");
+    assert_eq!(ipfix["destinationTransportPort"], 8080);
+    assert_eq!(ipfix["flowDirection"], 0);
+    assert_eq!(ipfix["processName"], "nginx");
+    assert!(ipfix["flowEndMilliseconds"].as_u64() >= ipfix["flowStartMilliseconds"].as_u64());
+}
@@ -0,0 +1,36 @@
+//! Monitor tests replaying scripted sockets from `tests/fixtures`.
+
+mod conntrack;
+mod counters;
+mod flows;
+mod monitor;
+mod mqtt;
+mod pacing;
+mod proxy;
+mod query;
+mod resolver;
+mod rollup;
+#[cfg(feature = "scripting")]
+mod script;
+mod threads;
+mod tunnel;
+mod views;
+
+use crate::core::mock::ScriptedSockets;
+use crate::core::monitor::ConnectionMonitor;
+
+/// Runs every step of `script` through a fresh monitor, after `setup` configured it.
+fn replay_with(script: &str, setup: impl FnOnce(&mut ConnectionMonitor)) -> ConnectionMonitor {
+    let sockets = ScriptedSockets::parse(script).expect("fixture should parse");
+    let steps = sockets.remaining_steps();
+    let mut monitor = ConnectionMonitor::with_provider(Box::new(sockets));
+    setup(&mut monitor);
+    for _ in 0..steps {
+        monitor.refresh().expect("scripted refresh cannot fail");
+    }
+    monitor
+}
+
+fn replay(script: &str) -> ConnectionMonitor {
+    replay_with(script, |_| {})
+}
@@ -0,0 +1,812 @@
+use std::time::Duration;
+
+use netstat2::TcpState;
+
+use super::{replay, replay_with};
+use crate::core::aliases::HostAliases;
+use crate::core::annotations::AnnotationKind;
+use crate::core::backlog::BacklogOptions;
+use crate::core::blocklist::{Blocklist, Blocklists};
+use crate::core::dns::{DNS_PORT, DOT_PORT};
+use crate::core::events::ConnectionEventKind;
+use crate::core::filters::{ConnectionFilter, FilterExpr, MatchOptions};
+use crate::core::anomaly::AnomalyOptions;
+use crate::core::groups::EndpointGroup;
+use crate::core::leaks::LeakOptions;
+use crate::core::limits::LimitOptions;
+use crate::core::mock::ScriptedSockets;
+use crate::core::monitor::ConnectionMonitor;
+use crate::core::retransmits::{RetransmitOptions, Retransmits};
+use crate::core::utils::wildcard_match;
+
+const POOL: &str = include_str!("../../tests/fixtures/pool.script");
+const STATES: &str = include_str!("../../tests/fixtures/states.script");
+const REOPEN: &str = include_str!("../../tests/fixtures/reopen.script");
+const SERVER: &str = include_str!("../../tests/fixtures/server.script");
+
+#[test]
+fn summary_counts_active_total_and_max() {
+    let monitor = replay(POOL);
+    let summary = monitor.get_summary(&ConnectionFilter::default());
+
+    assert_eq!(summary.active_connections, 3);
+    assert_eq!(summary.total_connections, 5);
+    assert_eq!(summary.max_concurrent, 4);
+}
+
+#[test]
+fn host_metrics_keep_max_after_closes() {
+    let monitor = replay(POOL);
+    let hosts = monitor.get_host_metrics(&ConnectionFilter::default());
+
+    let api = hosts.iter().find(|h| h.host == "api.example.com" && h.port == 443).expect("api host");
+    assert_eq!(api.current_connections, 2);
+    assert_eq!(api.total_connections, 4);
+    assert_eq!(api.max_concurrent, 3);
+
+    let db = hosts.iter().find(|h| h.host == "db.internal" && h.port == 5432).expect("db host");
+    assert_eq!((db.current_connections, db.total_connections, db.max_concurrent), (1, 1, 1));
+}
+
+#[test]
+fn host_last_activity_stops_when_its_connections_close() {
+    let monitor = replay("\
+open old pid=10 process=app remote=10.0.0.1:443
+tick
+close old
+tick
+open hot pid=10 process=app remote=10.0.0.2:443
+tick
+");
+    let hosts = monitor.get_host_metrics(&ConnectionFilter::default());
+    let host = |name: &str| hosts.iter().find(|h| h.host == name).expect("host");
+    let (old, hot) = (host("10.0.0.1"), host("10.0.0.2"));
+
+    assert_eq!((old.current_connections, hot.current_connections), (0, 1));
+    assert!(old.last_seen.unwrap() < hot.last_seen.unwrap());
+    assert!(old.first_seen <= old.last_seen.unwrap());
+}
+
+#[test]
+fn host_metrics_count_distinct_addresses_and_local_ports() {
+    // Three connections to two addresses, the last reusing the first one's local port
+    let monitor = replay("\
+open a pid=1 process=svc remote=10.0.0.1:443 host=lb.example lport=40000
+open b pid=1 process=svc remote=10.0.0.2:443 host=lb.example lport=40001
+tick
+close a
+close b
+tick
+open c pid=1 process=svc remote=10.0.0.1:443 host=lb.example lport=40000
+tick
+");
+    let hosts = monitor.get_host_metrics(&ConnectionFilter::default());
+    let lb = hosts.iter().find(|h| h.host == "lb.example").expect("lb host");
+
+    assert_eq!(lb.total_connections, 3);
+    assert_eq!((lb.remote_ips, lb.local_ports), (2, 2));
+}
+
+#[test]
+fn process_metrics_count_distinct_endpoints() {
+    let monitor = replay(POOL);
+    let processes = monitor.get_process_metrics(&ConnectionFilter::default());
+
+    // curl reconnects to the same endpoint, so its four connections count as one host
+    let curl = processes.iter().find(|p| p.name == "curl").expect("curl process");
+    assert_eq!((curl.total_connections, curl.hosts), (4, 1));
+}
+
+#[test]
+fn idle_counts_established_connections_past_the_threshold() {
+    let mut monitor = replay(POOL);
+    let filter = ConnectionFilter::default();
+    let curl = |monitor: &ConnectionMonitor| monitor.get_process_metrics(&filter).into_iter()
+        .find(|p| p.name == "curl").expect("curl process");
+
+    // Nothing in a replay is five minutes old
+    assert_eq!(curl(&monitor).idle, 0);
+    monitor.set_idle_threshold(Duration::ZERO);
+    assert_eq!(curl(&monitor).idle, curl(&monitor).current_connections);
+}
+
+#[test]
+fn limit_alerts_once_until_the_count_falls_back() {
+    let limits = LimitOptions { warn_at: Some(3), ceiling: Some(8) };
+    let mut monitor = replay_with(POOL, |monitor| monitor.set_limit_options(Some(limits)));
+
+    // 2, 4, 2 then 3 active: dropping under the warning count re-arms it
+    let alerts = monitor.take_limit_alerts();
+    assert_eq!(alerts.iter().map(|alert| alert.active).collect::<Vec<_>>(), vec![4, 3]);
+    assert_eq!(alerts[0].to_string(), "System-wide connections at 4, over the warning level of 3 (50% of 8)");
+    assert_eq!(monitor.system_active_connections(), 3);
+    assert_eq!(limits.percent_of_ceiling(monitor.system_active_connections()), Some(37.5));
+}
+
+/// A pair opening one more connection every sample, then holding steady.
+const LEAKING: &str = "\
+open a pid=1 process=app remote=10.0.0.1:5432
+tick
+open b pid=1 process=app remote=10.0.0.1:5432
+tick
+open c pid=1 process=app remote=10.0.0.1:5432
+tick
+open d pid=1 process=app remote=10.0.0.1:5432
+tick
+";
+
+#[test]
+fn leaks_are_flagged_once_while_growing_and_cleared_on_a_plateau() {
+    // Nanosecond intervals, so every refresh of the replay is a sample; three samples per window
+    let options = LeakOptions { window: Duration::from_nanos(4), interval: Duration::from_nanos(2) };
+    let leak_suspected = |monitor: &ConnectionMonitor| monitor.get_process_host_metrics(&ConnectionFilter::default())
+        .into_iter().find(|pair| pair.pid == 1).expect("app pair").leak_suspected;
+
+    let mut growing = replay_with(LEAKING, |monitor| monitor.set_leak_options(Some(options)));
+    // 1, 2, 3 flags the pair; still growing at 4 raises nothing new
+    let alerts = growing.take_leak_alerts();
+    assert_eq!(alerts.iter().map(|alert| (alert.active, alert.growth)).collect::<Vec<_>>(), vec![(3, 2)]);
+    assert!(leak_suspected(&growing));
+
+    let mut plateau = replay_with(&format!("{}tick\n", LEAKING), |monitor| monitor.set_leak_options(Some(options)));
+    assert_eq!(plateau.take_leak_alerts().len(), 1);
+    assert!(!leak_suspected(&plateau));
+}
+
+#[test]
+fn anomalies_are_only_scored_after_warm_up() {
+    let mut script = String::from("open a pid=1 process=app remote=10.0.0.1:443\ntick\ntick\ntick\n");
+    // An outlier during warm-up, then back to one connection
+    for name in ["b", "c", "d", "e"] {
+        script.push_str(&format!("open {} pid=1 process=app remote=10.0.0.1:443\n", name));
+    }
+    script.push_str("tick\nclose b\nclose c\nclose d\nclose e\ntick\ntick\n");
+    // The same outlier, and more, once warmed up
+    for name in ["f", "g", "h", "i", "j", "k", "l", "m", "n"] {
+        script.push_str(&format!("open {} pid=1 process=app remote=10.0.0.1:443\n", name));
+    }
+    script.push_str("tick\n");
+
+    let sockets = ScriptedSockets::parse(&script).expect("script should parse");
+    let mut monitor = ConnectionMonitor::with_provider(Box::new(sockets));
+    let warmup = Duration::from_millis(100);
+    monitor.set_anomaly_options(Some(AnomalyOptions { warmup, z_score: 3.0 }));
+    let score = |monitor: &mut ConnectionMonitor| {
+        monitor.refresh().expect("scripted refresh cannot fail");
+        monitor.get_process_metrics(&ConnectionFilter::default()).into_iter()
+            .find(|process| process.pid == 1).expect("app process").anomaly_score
+    };
+
+    let warming_up: Vec<_> = (0..5).map(|_| score(&mut monitor)).collect();
+    assert_eq!(warming_up, vec![None; 5], "five connections while warming up");
+    std::thread::sleep(warmup);
+    assert_eq!(score(&mut monitor), None, "back to one connection");
+    assert!(score(&mut monitor).is_some_and(|z| z >= 3.0), "ten connections after warm-up");
+}
+
+#[test]
+fn process_and_process_host_metrics() {
+    let monitor = replay(POOL);
+    let filter = ConnectionFilter::default();
+
+    let processes = monitor.get_process_metrics(&filter);
+    let curl = processes.iter().find(|p| p.pid == 100).expect("curl process");
+    assert_eq!(curl.name, "curl");
+    assert_eq!((curl.current_connections, curl.total_connections, curl.max_concurrent), (2, 4, 3));
+
+    let pairs = monitor.get_process_host_metrics(&filter);
+    let psql = pairs.iter().find(|p| p.pid == 200).expect("psql pair");
+    assert_eq!(psql.process_name, "psql");
+    assert_eq!((psql.host.as_str(), psql.port), ("db.internal", 5432));
+    assert_eq!((psql.current_connections, psql.total_connections, psql.max_concurrent), (1, 1, 1));
+}
+
+#[test]
+fn process_start_times_come_from_the_os() {
+    let monitor = replay("\
+open a pid=10 process=cron remote=10.0.0.1:443 age=2592000
+open b pid=20 process=deploy remote=10.0.0.1:443 age=300
+open c pid=20 process=deploy remote=10.0.0.1:443
+tick
+");
+    let processes = monitor.get_process_metrics(&ConnectionFilter::default());
+    let process = |pid: u32| processes.iter().find(|p| p.pid == pid).expect("process");
+    let (cron, deploy) = (process(10), process(20));
+
+    let started_apart = deploy.started_at.unwrap() - cron.started_at.unwrap();
+    assert!((started_apart.num_seconds() - (2592000 - 300)).abs() <= 1);
+    // Both started before counting did, so both rates cover the same (minimum) minute
+    assert_eq!((cron.per_minute, deploy.per_minute), (1.0, 2.0));
+}
+
+#[test]
+fn filters_apply_to_summary() {
+    let monitor = replay(POOL);
+    let filter = ConnectionFilter::default().with_process_name("psql".to_string());
+    let summary = monitor.get_summary(&filter);
+
+    assert_eq!((summary.active_connections, summary.total_connections, summary.max_concurrent), (1, 1, 1));
+}
+
+#[test]
+fn wildcard_host_filters_match_whole_names() {
+    let monitor = replay("\
+open a pid=1 process=app remote=10.0.0.1:443 host=api.internal.example.com
+open b pid=1 process=app remote=10.0.0.2:443 host=api.internal.example.com.evil.net
+open c pid=1 process=app remote=10.0.0.3:443 host=notinternal.example.com
+tick
+");
+    let hosts = |pattern: &str| {
+        let filter = ConnectionFilter::default().with_remote_host(pattern.to_string());
+        let mut hosts: Vec<String> = monitor.get_host_metrics(&filter).into_iter().map(|m| m.host).collect();
+        hosts.sort();
+        hosts
+    };
+
+    assert_eq!(hosts("*.internal.example.com"), vec!["api.internal.example.com"]);
+    // Without a wildcard the filter is still a substring match
+    assert_eq!(hosts("internal.example.com").len(), 3);
+    assert_eq!(hosts("10.0.0.*").len(), 3);
+}
+
+#[test]
+fn wildcard_host_filters_handle_non_ascii_names() {
+    let monitor = replay("\
+open a pid=1 process=app remote=10.0.0.1:443 host=café
+open b pid=1 process=app remote=10.0.0.2:443 host=é
+tick
+");
+    let hosts = |pattern: &str| {
+        let filter = ConnectionFilter::default().with_remote_host(pattern.to_string());
+        let mut hosts: Vec<String> = monitor.get_host_metrics(&filter).into_iter().map(|m| m.host).collect();
+        hosts.sort();
+        hosts
+    };
+
+    // A suffix shorter in bytes than the name's last character must not split it
+    assert!(hosts("*a").is_empty());
+    assert_eq!(hosts("caf*"), vec!["café"]);
+    assert_eq!(hosts("*é"), vec!["café", "é"]);
+    assert!(!wildcard_match("*a", "é"));
+}
+
+#[test]
+fn shareable_filters_round_trip() {
+    let filter = ConnectionFilter::new()
+        .with_pid(42)
+        .with_remote_host("*.s3.amazonaws.com".to_string())
+        .with_remote_host("a,b&c".to_string())
+        .with_remote_port(443)
+        .with_query(FilterExpr::parse("proc~\"my app\" and not port=80").unwrap())
+        .with_matching(MatchOptions { ignore_case: true, fuzzy: false });
+
+    let shared = filter.shareable();
+    assert!(shared.starts_with("pid=42&host=*.s3.amazonaws.com,a%2Cb%26c&port=443&query="), "{}", shared);
+    assert!(shared.ends_with("&match=ignore_case"), "{}", shared);
+    assert_eq!(ConnectionFilter::parse_shareable(&shared), Ok(filter));
+    assert_eq!(ConnectionFilter::parse_shareable(""), Ok(ConnectionFilter::default()));
+    assert!(ConnectionFilter::parse_shareable("pid=abc").is_err());
+    assert!(ConnectionFilter::parse_shareable("user=root").is_err());
+}
+
+#[test]
+fn groups_count_matching_connections() {
+    let monitor = replay_with(POOL, |monitor| {
+        let internal = EndpointGroup::new("internal".to_string())
+            .with_cidrs(vec!["10.0.0.0/8".parse().unwrap()]);
+        monitor.set_groups(vec![internal]);
+    });
+    let groups = monitor.get_group_metrics(&ConnectionFilter::default());
+
+    assert_eq!(groups.len(), 1);
+    assert_eq!(groups[0].group, "internal");
+    assert_eq!((groups[0].current_connections, groups[0].total_connections), (1, 1));
+}
+
+#[test]
+fn state_changes_are_recorded() {
+    let monitor = replay(STATES);
+    let closed = monitor.get_historical_connections();
+
+    assert_eq!(closed.len(), 1);
+    let states: Vec<TcpState> = closed[0].transitions.iter().map(|change| change.state).collect();
+    assert_eq!(states, vec![TcpState::SynSent, TcpState::Established, TcpState::FinWait1]);
+    assert!(closed[0].closed);
+}
+
+#[test]
+fn events_record_opens_and_closes_in_order() {
+    let monitor = replay(STATES);
+    let kinds: Vec<ConnectionEventKind> = monitor.get_events_since(0).iter().map(|event| event.kind).collect();
+
+    assert_eq!(kinds, vec![ConnectionEventKind::Opened, ConnectionEventKind::Closed]);
+    assert_eq!(monitor.get_events_since(0)[0].process_name.as_deref(), Some("wget"));
+}
+
+#[test]
+fn reused_endpoints_count_as_new_connections() {
+    let monitor = replay(REOPEN);
+    let summary = monitor.get_summary(&ConnectionFilter::default());
+
+    assert_eq!((summary.active_connections, summary.total_connections, summary.max_concurrent), (1, 2, 1));
+}
+
+#[test]
+fn script_errors_name_the_line() {
+    let err = ScriptedSockets::parse("tick\nopen a pid=1\n").err().expect("missing remote");
+    assert_eq!(err, "line 2: open needs remote=<ip:port>");
+
+    let err = ScriptedSockets::parse("launch a\n").err().expect("unknown command");
+    assert_eq!(err, "line 1: unknown command 'launch a'");
+}
+
+#[test]
+fn history_counts_connections_in_the_samples_that_saw_them() {
+    let monitor = replay("\
+open a pid=1 remote=10.0.0.1:80
+open b pid=1 remote=10.0.0.1:80
+tick
+close a
+tick
+close b
+tick
+");
+    let filter = ConnectionFilter::default();
+    let counts: Vec<usize> = monitor.get_connection_history_filtered(&filter, None, None)
+        .into_iter()
+        .map(|(_, count)| count)
+        .collect();
+
+    assert_eq!(counts, vec![2, 1, 0]);
+    assert_eq!(monitor.get_summary(&filter).max_concurrent, 2);
+}
+
+#[test]
+fn counters_agree_with_open_connections() {
+    let monitor = replay_with(POOL, |monitor| {
+        monitor.set_groups(vec![EndpointGroup::new("api".to_string()).with_hosts(vec!["api.example.com".to_string()])]);
+    });
+
+    for check in monitor.counter_checks() {
+        assert!(check.is_consistent(), "{} counter: {:?}", check.name, check);
+    }
+}
+
+#[test]
+fn samples_count_refreshes_until_reset() {
+    let mut monitor = replay(POOL);
+    assert_eq!(monitor.samples(), 4);
+
+    monitor.reset();
+    assert_eq!(monitor.samples(), 0);
+    monitor.refresh().expect("scripted refresh cannot fail");
+    assert_eq!(monitor.samples(), 1);
+}
+
+#[test]
+fn clearing_closed_connections_keeps_open_ones() {
+    let mut monitor = replay(POOL);
+    monitor.clear_closed();
+    let filter = ConnectionFilter::default();
+
+    let summary = monitor.get_summary(&filter);
+    assert_eq!((summary.active_connections, summary.total_connections), (3, 3));
+    let api = monitor.get_host_metrics(&filter).into_iter()
+        .find(|h| h.host == "api.example.com").expect("api host");
+    assert_eq!((api.current_connections, api.total_connections, api.max_concurrent), (2, 2, 2));
+    for check in monitor.counter_checks() {
+        assert!(check.is_consistent(), "{} counter: {:?}", check.name, check);
+    }
+}
+
+#[test]
+fn inbound_connections_count_per_listening_port() {
+    let monitor = replay(SERVER);
+    let ports = monitor.get_listen_port_metrics(&ConnectionFilter::default());
+
+    assert_eq!(ports.len(), 1, "outbound connections have no listening port row");
+    let https = &ports[0];
+    assert_eq!(https.port, 443);
+    assert_eq!(https.processes, vec!["nginx".to_string()]);
+    assert_eq!((https.current_connections, https.total_connections, https.max_concurrent), (3, 4, 3));
+    assert_eq!(https.top_clients[0], ("203.0.113.7".parse().unwrap(), 2));
+    assert_eq!(https.top_clients.len(), 3);
+}
+
+#[test]
+fn saturated_accept_queues_alert_once_until_drained() {
+    let mut monitor = replay_with("\
+listen port=443 pid=300 process=nginx queued=2 backlog=128
+listen port=8080 pid=400 process=slow queued=110 backlog=128
+tick
+listen port=8080 pid=400 process=slow queued=120 backlog=128
+tick
+listen port=8080 pid=400 process=slow queued=10 backlog=128
+tick
+listen port=8080 pid=400 process=slow queued=128 backlog=128
+tick
+", |monitor| monitor.set_backlog_options(Some(BacklogOptions::default())));
+
+    let alerts = monitor.take_backlog_alerts();
+    assert_eq!(alerts.iter().map(|alert| alert.queue.queued).collect::<Vec<_>>(), vec![110, 128]);
+    assert!(alerts[0].to_string().starts_with("Accept queue saturated: slow(400) on port 8080 has 110 of 128"));
+
+    // Waiting connections have no owner yet, so the ports show without any
+    let mut ports = monitor.get_listen_port_metrics(&ConnectionFilter::default());
+    ports.sort_by_key(|port| port.port);
+    let queues: Vec<(u16, Option<u32>, bool)> = ports.iter()
+        .map(|port| (port.port, port.accept_queue.map(|queue| queue.queued), port.queue_saturated))
+        .collect();
+    assert_eq!(queues, vec![(443, Some(2), false), (8080, Some(128), true)]);
+    assert_eq!(ports[1].processes, vec!["slow".to_string()]);
+}
+
+#[test]
+fn high_retransmission_rates_alert_per_host() {
+    let mut monitor = replay_with("\
+open a pid=1 process=app remote=10.0.0.1:443 host=lossy segs=500 retrans=20
+open b pid=1 process=app remote=10.0.0.2:443 host=clean segs=5000 retrans=1
+tick
+stats a segs=1500 retrans=60
+tick
+close a
+tick
+", |monitor| monitor.set_retransmit_options(Some(RetransmitOptions::default())));
+
+    // Too few segments to judge on the first sample; closed connections keep counting
+    let alerts = monitor.take_retransmit_alerts();
+    assert_eq!(alerts.iter().map(|alert| alert.host.as_str()).collect::<Vec<_>>(), vec!["lossy:443"]);
+    assert!(alerts[0].to_string().starts_with("High retransmission rate to lossy:443: 4.0% of 1500 segments"));
+
+    let mut hosts = monitor.get_host_metrics(&ConnectionFilter::default());
+    hosts.sort_by(|a, b| a.host.cmp(&b.host));
+    let rows: Vec<(&str, Option<Retransmits>, bool)> = hosts.iter()
+        .map(|m| (m.host.as_str(), m.retransmits, m.high_retransmits))
+        .collect();
+    assert_eq!(rows, vec![
+        ("clean", Some(Retransmits { retransmits: 1, segments_out: 5000 }), false),
+        ("lossy", Some(Retransmits { retransmits: 60, segments_out: 1500 }), true),
+    ]);
+}
+
+#[test]
+fn dns_mode_counts_lookups_per_resolver() {
+    let monitor = replay("\
+open q1 pid=10 process=app remote=10.0.0.53:53 udp=true
+open web pid=10 process=app remote=93.184.216.34:443
+tick
+close q1
+open q2 pid=10 process=app remote=10.0.0.53:53 udp=true
+tick
+close q2
+open dot pid=10 process=app remote=1.1.1.1:853
+tick
+");
+    let filter = ConnectionFilter { remote_ports: vec![DNS_PORT, DOT_PORT], ..Default::default() };
+
+    // Each lookup's socket counts once, TLS lookups next to plain ones
+    let mut rows: Vec<(String, u16, usize)> = monitor.get_process_host_metrics(&filter).into_iter()
+        .map(|m| (m.host, m.port, m.total_connections))
+        .collect();
+    rows.sort();
+    assert_eq!(rows, vec![("1.1.1.1".to_string(), 853, 1), ("10.0.0.53".to_string(), 53, 2)]);
+}
+
+#[test]
+fn inbound_connections_aggregate_per_client() {
+    let monitor = replay(SERVER);
+    let mut clients = monitor.get_client_metrics(&ConnectionFilter::default());
+    clients.sort_by_key(|client| client.addr);
+
+    let counts: Vec<(String, usize, usize, usize)> = clients.iter()
+        .map(|c| (c.addr.to_string(), c.current_connections, c.total_connections, c.max_concurrent))
+        .collect();
+    assert_eq!(counts, vec![
+        ("192.0.2.50".to_string(), 1, 1, 1),
+        ("198.51.100.2".to_string(), 1, 1, 1),
+        ("203.0.113.7".to_string(), 1, 2, 2),
+    ]);
+    assert!(clients.iter().all(|c| c.ports == vec![443] && c.first_seen <= c.last_seen));
+}
+
+#[test]
+fn net_queries_match_client_networks() {
+    let monitor = replay(SERVER);
+    let filter = ConnectionFilter::new()
+        .with_query(FilterExpr::parse("net=203.0.113.0/24 or net=192.0.2.50").unwrap());
+    let mut clients: Vec<String> = monitor.get_client_metrics(&filter).iter().map(|c| c.addr.to_string()).collect();
+    clients.sort();
+
+    assert_eq!(clients, vec!["192.0.2.50", "203.0.113.7"]);
+    assert_eq!(FilterExpr::parse("net~10.0").unwrap_err(), "'net' only works with = and !=");
+    assert!(FilterExpr::parse("net=10.0.0.0/33").is_err());
+}
+
+#[test]
+fn blocklisted_endpoints_are_flagged_counted_and_alerted_once() {
+    let list = Blocklist::parse("abuse.txt".to_string(), "\
+# hosts-file and plain entries
+0.0.0.0 example.com   # matches api.example.com too
+10.0.0.0/24
+");
+    let mut monitor = replay_with(POOL, |monitor| {
+        monitor.set_blocklist_alerts(true);
+        monitor.set_blocklists(Blocklists::new(vec![list]));
+    });
+    let filter = ConnectionFilter::default();
+
+    // All five connections in the fixture are to listed endpoints
+    assert_eq!(monitor.get_summary(&filter).blocklisted, 5);
+    let api = monitor.get_host_metrics(&filter).into_iter()
+        .find(|h| h.host == "api.example.com").expect("api host");
+    assert_eq!(api.blocklist.as_deref(), Some("abuse.txt"));
+    let alerts: Vec<String> = monitor.take_blocklist_alerts().iter().map(|a| a.host.clone()).collect();
+    assert_eq!(alerts, vec!["api.example.com", "db.internal"]);
+
+    monitor.set_blocklists(Blocklists::default());
+    assert_eq!(monitor.get_summary(&filter).blocklisted, 0);
+}
+
+#[test]
+fn connections_split_by_address_scope() {
+    let monitor = replay("\
+open lo pid=1 remote=127.0.0.1:5432
+open ll pid=1 remote=169.254.169.254:80
+open v6ll pid=1 remote=[fe80::1]:80
+open lan pid=1 remote=192.168.1.20:22
+open ula pid=1 remote=[fd00::7]:443
+open web pid=1 remote=93.184.216.34:443
+open mapped pid=1 remote=[::ffff:10.1.2.3]:443
+tick
+");
+    let scopes = monitor.get_summary(&ConnectionFilter::default()).active_by_scope;
+    assert_eq!((scopes.loopback, scopes.link_local, scopes.private, scopes.public), (1, 2, 3, 1));
+
+    let leaving = ConnectionFilter::new().with_query(FilterExpr::parse("scope=public").unwrap());
+    let hosts: Vec<String> = monitor.get_host_metrics(&leaving).into_iter().map(|h| h.host).collect();
+    assert_eq!(hosts, vec!["93.184.216.34"]);
+    let local = ConnectionFilter::new().with_query(FilterExpr::parse("not scope=public and scope!=lo").unwrap());
+    assert_eq!(monitor.get_summary(&local).active_connections, 5);
+    assert!(FilterExpr::parse("scope=internet").is_err());
+}
+
+#[test]
+fn tunneled_connections_are_tagged_and_filterable() {
+    let monitor = replay("\
+open vpn pid=1 remote=10.8.0.1:443 host=intranet tunnel=wg0
+open vpn2 pid=1 remote=10.8.0.9:22 host=bastion tunnel=tun0
+open web pid=1 remote=93.184.216.34:443 host=example.com
+tick
+");
+    let all = ConnectionFilter::default();
+    assert_eq!(monitor.get_summary(&all).active_tunneled, 2);
+
+    let tunnel_of = |query: &str| -> Vec<(String, Option<String>)> {
+        let filter = ConnectionFilter::new().with_query(FilterExpr::parse(query).unwrap());
+        let mut hosts: Vec<_> = monitor.get_host_metrics(&filter).into_iter().map(|h| (h.host, h.tunnel)).collect();
+        hosts.sort();
+        hosts
+    };
+    assert_eq!(tunnel_of("tunnel=no"), vec![("example.com".to_string(), None)]);
+    assert_eq!(tunnel_of("tunnel=yes").len(), 2);
+    assert_eq!(tunnel_of("tunnel=wg*"), vec![("intranet".to_string(), Some("wg0".to_string()))]);
+    assert_eq!(monitor.get_summary(&ConnectionFilter::new().with_query(FilterExpr::parse("tunnel!=yes").unwrap())).active_connections, 1);
+}
+
+#[test]
+fn aliases_name_hosts_before_they_are_counted() {
+    let aliases = HostAliases::parse("\
+# internal services without reverse DNS
+10.0.3.7 = orders-db
+10.0.4.0/24 -> cache-pool
+*.internal.corp → *
+").unwrap();
+    let monitor = replay_with("\
+open db1 pid=1 remote=10.0.3.7:5432
+open db2 pid=1 remote=10.0.3.7:5432
+open c1 pid=1 remote=10.0.4.11:6379
+open c2 pid=1 remote=10.0.4.12:6379
+open api pid=1 remote=10.0.9.1:443 host=billing.eu.internal.corp
+open web pid=1 remote=93.184.216.34:443 host=example.com
+tick
+", |monitor| monitor.set_aliases(aliases));
+
+    let mut hosts: Vec<(String, usize)> = monitor.get_host_metrics(&ConnectionFilter::default()).into_iter()
+        .map(|h| (h.host, h.current_connections))
+        .collect();
+    hosts.sort();
+    assert_eq!(hosts, vec![
+        ("billing.eu".to_string(), 1),
+        ("cache-pool".to_string(), 2),
+        ("example.com".to_string(), 1),
+        ("orders-db".to_string(), 2),
+    ]);
+    let by_alias = ConnectionFilter::new().with_remote_host("orders-db".to_string());
+    assert_eq!(monitor.get_summary(&by_alias).active_connections, 2);
+    assert!(HostAliases::parse("10.0.3.7").is_err());
+}
+
+#[test]
+fn renamed_hosts_move_their_open_connections() {
+    let monitor = replay("\
+open a pid=1 remote=10.0.0.5:443 host=old.example.com
+open b pid=1 remote=10.0.0.5:443 host=old.example.com
+open c pid=1 remote=10.0.0.6:443
+tick
+close b
+tick
+host a new.example.com
+host c late.example.com
+tick
+");
+    let hosts: Vec<(String, usize, usize)> = {
+        let mut hosts: Vec<_> = monitor.get_host_metrics(&ConnectionFilter::default()).into_iter()
+            .map(|h| (h.host, h.current_connections, h.total_connections))
+            .collect();
+        hosts.sort();
+        hosts
+    };
+    // The closed connection keeps the name it had
+    assert_eq!(hosts, vec![
+        ("late.example.com".to_string(), 1, 1),
+        ("new.example.com".to_string(), 1, 1),
+        ("old.example.com".to_string(), 0, 1),
+    ]);
+    assert!(monitor.counter_checks().iter().all(|check| check.is_consistent()), "{:?}", monitor.counter_checks());
+}
+
+#[test]
+fn unresolved_hosts_keep_their_max() {
+    let monitor = replay("\
+open a pid=1 remote=203.0.113.9:443
+open b pid=1 remote=203.0.113.9:443
+open c pid=2 remote=203.0.113.9:443
+tick
+close a
+close c
+tick
+");
+    let all = ConnectionFilter::default();
+    let hosts = monitor.get_host_metrics(&all);
+    assert_eq!(hosts.len(), 1);
+    assert_eq!((hosts[0].host.as_str(), hosts[0].current_connections, hosts[0].max_concurrent), ("203.0.113.9", 1, 3));
+
+    let mut rows: Vec<(u32, usize)> = monitor.get_process_host_metrics(&all).into_iter()
+        .map(|m| (m.pid, m.max_concurrent))
+        .collect();
+    rows.sort();
+    assert_eq!(rows, vec![(1, 2), (2, 1)]);
+    assert!(monitor.counter_checks().iter().all(|check| check.is_consistent()));
+}
+
+#[test]
+fn summary_counts_distinct_hosts_and_processes() {
+    let monitor = replay("\
+open a pid=1 remote=10.0.0.1:80
+open b pid=1 remote=10.0.0.1:80
+open c pid=2 remote=10.0.0.2:443
+tick
+close c
+tick
+");
+    let summary = monitor.get_summary(&ConnectionFilter::default());
+
+    assert_eq!(summary.unique_hosts, 2);
+    assert_eq!(summary.unique_processes, 2);
+    assert!(summary.avg_duration_secs.is_some());
+    // Everything was already open at the first sample
+    assert_eq!(summary.opened_per_minute, 0.0);
+}
+
+#[test]
+fn followed_process_restarts_are_reported_and_annotated() {
+    let mut monitor = replay_with("\
+open a pid=1 process=worker remote=10.0.0.1:80
+open b pid=3 process=other remote=10.0.0.2:80
+tick
+close a
+tick
+open c pid=2 process=worker remote=10.0.0.1:80
+open d pid=4 process=other remote=10.0.0.2:80
+tick
+", |monitor| monitor.set_followed_processes(&["Worker".to_string()]));
+
+    let restarts: Vec<(u32, Vec<u32>)> = monitor.take_restart_alerts().into_iter()
+        .map(|restart| (restart.pid, restart.replaced))
+        .collect();
+    assert_eq!(restarts, vec![(2, vec![1])]);
+    assert!(monitor.take_restart_alerts().is_empty());
+
+    let annotations: Vec<AnnotationKind> = monitor.get_annotations().map(|a| a.kind).collect();
+    assert_eq!(annotations, vec![AnnotationKind::Restart]);
+}
+
+#[test]
+fn restarted_processes_are_linked_and_optionally_carried() {
+    let script = "\
+open a pid=1 process=worker exe=/usr/bin/worker remote=10.0.0.1:80
+open b pid=1 process=worker exe=/usr/bin/worker remote=10.0.0.1:80
+tick
+close a
+close b
+tick
+open c pid=2 process=worker exe=/usr/bin/worker remote=10.0.0.1:80
+tick
+";
+    let mut rows: Vec<(u32, usize, usize, usize)> = replay(script)
+        .get_process_metrics(&ConnectionFilter::default()).into_iter()
+        .map(|m| (m.pid, m.total_connections, m.max_concurrent, m.restarts))
+        .collect();
+    rows.sort();
+    assert_eq!(rows, vec![(1, 2, 2, 0), (2, 1, 1, 1)]);
+
+    let monitor = replay_with(script, |monitor| monitor.set_carry_restarts(true));
+    let rows: Vec<(u32, usize, usize, usize)> = monitor.get_process_metrics(&ConnectionFilter::default()).into_iter()
+        .map(|m| (m.pid, m.total_connections, m.max_concurrent, m.restarts))
+        .collect();
+    assert_eq!(rows, vec![(2, 3, 2, 1)]);
+}
+
+#[test]
+fn exited_processes_are_labelled_and_can_be_hidden() {
+    let mut monitor = replay("\
+open a pid=1 process=job remote=10.0.0.1:80
+open b pid=2 process=web remote=10.0.0.1:80
+tick
+close a
+tick
+");
+    let filter = ConnectionFilter::default();
+    let mut rows: Vec<(u32, bool)> = monitor.get_process_metrics(&filter).into_iter()
+        .map(|m| (m.pid, m.exited_at.is_some()))
+        .collect();
+    rows.sort();
+    assert_eq!(rows, vec![(1, true), (2, false)]);
+
+    monitor.set_exited_retention(Some(Duration::ZERO));
+    let pids: Vec<u32> = monitor.get_process_host_metrics(&filter).into_iter().map(|m| m.pid).collect();
+    assert_eq!(pids, vec![2]);
+}
+
+#[test]
+fn inactive_rows_are_hidden_but_still_counted() {
+    let mut monitor = replay("\
+open a pid=1 process=job remote=10.0.0.1:80
+open b pid=2 process=web remote=10.0.0.2:80
+open c pid=2 process=web remote=10.0.0.1:80
+tick
+close a
+close c
+tick
+");
+    let filter = ConnectionFilter::default();
+    monitor.set_inactive_retention(Some(Duration::from_secs(3600)));
+    assert_eq!(monitor.get_host_metrics(&filter).len(), 2);
+
+    monitor.set_inactive_retention(Some(Duration::ZERO));
+    let hosts: Vec<String> = monitor.get_host_metrics(&filter).into_iter().map(|m| m.host).collect();
+    assert_eq!(hosts, vec!["10.0.0.2"]);
+    let pids: Vec<u32> = monitor.get_process_metrics(&filter).into_iter().map(|m| m.pid).collect();
+    assert_eq!(pids, vec![2]);
+    let pairs: Vec<(u32, String)> = monitor.get_process_host_metrics(&filter).into_iter().map(|m| (m.pid, m.host)).collect();
+    assert_eq!(pairs, vec![(2, "10.0.0.2".to_string())]);
+    assert_eq!(monitor.get_summary(&filter).total_connections, 3);
+}
+
+#[test]
+fn translated_connections_count_under_their_real_destination() {
+    let monitor = replay("\
+open a pid=1 remote=10.244.1.7:8443 nat=10.96.0.10:443
+open b pid=1 remote=93.184.216.34:443
+tick
+");
+    let mut hosts: Vec<(String, u16, Option<String>)> = monitor.get_host_metrics(&ConnectionFilter::default()).into_iter()
+        .map(|m| (m.host, m.port, m.nat))
+        .collect();
+    hosts.sort();
+
+    assert_eq!(hosts, vec![
+        ("10.244.1.7".to_string(), 8443, Some("10.96.0.10:443".to_string())),
+        ("93.184.216.34".to_string(), 443, None),
+    ]);
+}
@@ -0,0 +1,47 @@
+use crate::core::filters::ConnectionFilter;
+use crate::core::snapshot::MetricsSnapshot;
+use crate::core::watchlist::{PinRule, Watchlist};
+use crate::output::mqtt::{MqttTarget, connect_packet, messages, publish_packet};
+
+use super::replay;
+
+#[test]
+fn targets_give_broker_credentials_and_topic_prefix() {
+    assert_eq!(MqttTarget::parse("ha:secret@broker.lan:1884/home/tcpcount/"), MqttTarget {
+        host: "broker.lan".to_string(),
+        port: 1884,
+        username: Some("ha".to_string()),
+        password: Some("secret".to_string()),
+        prefix: "home/tcpcount".to_string(),
+    });
+    let plain = MqttTarget::parse("broker.lan");
+    assert_eq!((plain.host.as_str(), plain.port, plain.username, plain.prefix.as_str()), ("broker.lan", 1883, None, "tcpcount"));
+}
+
+#[test]
+fn packets_follow_mqtt_3_1_1() {
+    let connect = connect_packet("tc", Some("u"), None);
+    assert_eq!(connect, [0x10, 17, 0, 4, b'M', b'Q', b'T', b'T', 4, 0x82, 0, 0, 0, 2, b't', b'c', 0, 1, b'u']);
+
+    // Remaining lengths past 127 take a continuation byte
+    let publish = publish_packet("t", &[b'x'; 200]);
+    assert_eq!(&publish[..6], &[0x31, 0xCB, 0x01, 0, 1, b't']);
+    assert_eq!(publish.len(), 3 + 203);
+}
+
+#[test]
+fn summary_and_watched_hosts_are_published() {
+    let monitor = replay("\
+open a pid=1 remote=10.0.0.5:5432 host=db.lan
+open b pid=1 remote=10.0.0.5:5432 host=db.lan
+open c pid=1 remote=10.0.0.9:443 host=api.lan
+tick
+");
+    let snapshot = MetricsSnapshot::capture(&monitor, &ConnectionFilter::default());
+    let watchlist = Watchlist::new(vec![PinRule::for_host("db.lan".to_string(), 5432)]);
+
+    assert_eq!(messages(&snapshot, "home", &watchlist), vec![
+        ("home/summary".to_string(), r#"{"active":3,"max":3,"total":3}"#.to_string()),
+        ("home/host/db.lan/5432".to_string(), r#"{"active":2,"max":2,"total":2}"#.to_string()),
+    ]);
+}
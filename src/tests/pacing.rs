@@ -0,0 +1,33 @@
+use std::time::{Duration, Instant};
+
+use crate::core::pacing::{BurstOptions, PaceChange, SamplePacer};
+
+#[test]
+fn churn_speeds_sampling_up_until_it_settles() {
+    let base = Duration::from_millis(250);
+    let burst = BurstOptions::default();
+    let mut pacer = SamplePacer::new(base).with_burst(Some(burst));
+    let start = Instant::now();
+    pacer.record(Duration::from_millis(5));
+
+    assert_eq!(pacer.record_churn(burst.trigger - 1, start), None);
+    assert_eq!(pacer.interval(), base);
+
+    assert_eq!(pacer.record_churn(burst.trigger, start), Some(PaceChange::Burst { interval: burst.interval, changes: burst.trigger }));
+    assert_eq!(pacer.interval(), burst.interval);
+    // More churn while bursting extends it without another change
+    assert_eq!(pacer.record_churn(burst.trigger, start + burst.hold / 2), None);
+    assert_eq!(pacer.record_churn(0, start + burst.hold), None);
+
+    assert_eq!(pacer.record_churn(0, start + burst.hold * 2), Some(PaceChange::Steady { interval: base }));
+    assert_eq!(pacer.interval(), base);
+}
+
+#[test]
+fn slow_refreshes_limit_burst_sampling() {
+    let mut pacer = SamplePacer::new(Duration::from_millis(250)).with_burst(Some(BurstOptions::default()));
+    pacer.record(Duration::from_millis(40));
+    pacer.record_churn(10, Instant::now());
+
+    assert_eq!(pacer.interval(), Duration::from_millis(80));
+}
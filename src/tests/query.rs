@@ -0,0 +1,111 @@
+use super::{replay, replay_with};
+use crate::core::filters::{ConnectionFilter, FilterExpr, MatchOptions, QueryField, QueryOp, RowAttributes};
+use crate::core::groups::EndpointGroup;
+
+fn term(field: QueryField, op: QueryOp, value: &str) -> FilterExpr {
+    FilterExpr::Compare { field, op, value: value.to_string() }
+}
+
+fn and(left: FilterExpr, right: FilterExpr) -> FilterExpr {
+    FilterExpr::And(Box::new(left), Box::new(right))
+}
+
+fn or(left: FilterExpr, right: FilterExpr) -> FilterExpr {
+    FilterExpr::Or(Box::new(left), Box::new(right))
+}
+
+#[test]
+fn and_binds_tighter_than_or() {
+    let pid = term(QueryField::Pid, QueryOp::Eq, "1");
+    let port = term(QueryField::Port, QueryOp::Eq, "443");
+    let host = term(QueryField::Host, QueryOp::Contains, "api");
+
+    assert_eq!(FilterExpr::parse("pid=1 or port=443 and host~api").unwrap(), or(pid.clone(), and(port.clone(), host.clone())));
+    assert_eq!(FilterExpr::parse("pid=1 and port=443 or host~api").unwrap(), or(and(pid.clone(), port.clone()), host.clone()));
+    // Adjacent terms are and-ed
+    assert_eq!(FilterExpr::parse("pid=1 port=443").unwrap(), and(pid.clone(), port.clone()));
+    assert_eq!(FilterExpr::parse("(pid=1 or port=443) and host~api").unwrap(), and(or(pid, port), host));
+}
+
+#[test]
+fn not_applies_to_the_next_term_only() {
+    let port = term(QueryField::Port, QueryOp::Eq, "80");
+    let pid = term(QueryField::Pid, QueryOp::Eq, "1");
+
+    assert_eq!(FilterExpr::parse("not port=80 and pid=1").unwrap(), and(FilterExpr::Not(Box::new(port.clone())), pid.clone()));
+    assert_eq!(FilterExpr::parse("NOT (port=80 or pid=1)").unwrap(), FilterExpr::Not(Box::new(or(port, pid))));
+}
+
+#[test]
+fn quoted_values_keep_spaces_and_escapes() {
+    assert_eq!(FilterExpr::parse("proc~\"my app\"").unwrap(), term(QueryField::Process, QueryOp::Contains, "my app"));
+    assert_eq!(FilterExpr::parse(r#"host="a \"b\" \\c""#).unwrap(), term(QueryField::Host, QueryOp::Eq, r#"a "b" \c"#));
+    assert_eq!(FilterExpr::parse("proc=\"and\"").unwrap(), term(QueryField::Process, QueryOp::Eq, "and"));
+}
+
+#[test]
+fn malformed_queries_are_errors() {
+    assert_eq!(FilterExpr::parse("proc~\"my app").unwrap_err(), "Unterminated string");
+    assert!(FilterExpr::parse("color=red").is_err());
+    assert_eq!(FilterExpr::parse("(pid=1 or port=2").unwrap_err(), "Missing ')'");
+    assert!(FilterExpr::parse("pid=1 and").is_err());
+    assert!(FilterExpr::parse("pid=1)").is_err());
+    assert!(FilterExpr::parse("").is_err());
+}
+
+#[test]
+fn queries_print_back_to_the_same_expression() {
+    for query in [
+        "pid=1 or port=443 and host~api",
+        "(pid=1 or port=443) and not (host~api or proc=\"my app\")",
+        "not not state=established",
+        "proc=\"and\" or host=\"a \\\"b\\\"\"",
+        "port>=1024 and lport<2000 and group!=db and host!~corp",
+    ] {
+        let expr = FilterExpr::parse(query).unwrap();
+        assert_eq!(FilterExpr::parse(&expr.to_string()).unwrap(), expr, "{}", expr);
+    }
+}
+
+#[test]
+fn negated_terms_keep_rows_without_the_field() {
+    let monitor = replay_with("\
+open a pid=1 process=app remote=10.0.0.1:5432
+open b pid=1 process=app remote=203.0.113.1:443
+tick
+", |monitor| monitor.set_groups(vec![EndpointGroup::new("db".to_string()).with_cidrs(vec!["10.0.0.0/8".parse().unwrap()])]));
+    let active = |query: &str| monitor.get_summary(&ConnectionFilter::new().with_query(FilterExpr::parse(query).unwrap())).active_connections;
+
+    assert_eq!(active("group!=db"), 1);
+    assert_eq!(active("not group=db"), 1);
+    assert_eq!(active("group=db"), 1);
+
+    // Process rows carry no host
+    let row = RowAttributes { process_name: Some("app"), ..Default::default() };
+    assert!(FilterExpr::parse("host!=x").unwrap().matches_row(&row, MatchOptions::default()));
+    assert!(FilterExpr::parse("host!~x").unwrap().matches_row(&row, MatchOptions::default()));
+    assert!(!FilterExpr::parse("host=x").unwrap().matches_row(&row, MatchOptions::default()));
+    assert!(!FilterExpr::parse("port<10").unwrap().matches_row(&row, MatchOptions::default()));
+}
+
+#[test]
+fn process_and_host_terms_follow_the_matching_options() {
+    let monitor = replay("\
+open a pid=1 process=chrome remote=10.0.0.1:443 host=API.example.com
+tick
+");
+    let active = |query: &str, matching: MatchOptions| {
+        let filter = ConnectionFilter::new().with_query(FilterExpr::parse(query).unwrap()).with_matching(matching);
+        monitor.get_summary(&filter).active_connections
+    };
+    let ignore_case = MatchOptions { ignore_case: true, fuzzy: false };
+    let fuzzy = MatchOptions { ignore_case: false, fuzzy: true };
+
+    assert_eq!(active("proc~Chrome", MatchOptions::default()), 0);
+    assert_eq!(active("proc~Chrome", ignore_case), 1);
+    assert_eq!(active("host=*.example.COM", ignore_case), 1);
+    assert_eq!(active("host!=api.example.com", ignore_case), 0);
+    assert_eq!(active("proc~chrm", fuzzy), 1);
+    // Exact matches stay exact when fuzzy
+    assert_eq!(active("proc=chrm", fuzzy), 0);
+}
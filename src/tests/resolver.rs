@@ -0,0 +1,21 @@
+use std::net::IpAddr;
+
+use crate::core::resolver::HostsFile;
+
+#[test]
+fn hosts_file_names_the_first_entry_per_address() {
+    let hosts = HostsFile::parse("\
+127.0.0.1   localhost
+# lab machines
+10.0.0.5    build-01 build-01.lab   # primary name first
+10.0.0.5    old-build
+fe80::1%eth0 router
+10.0.0.6
+");
+    let name = |addr: &str| hosts.name(&addr.parse::<IpAddr>().unwrap()).map(str::to_string);
+
+    assert_eq!(name("10.0.0.5").as_deref(), Some("build-01"));
+    assert_eq!(name("::ffff:10.0.0.5").as_deref(), Some("build-01"));
+    assert_eq!(name("fe80::1").as_deref(), Some("router"));
+    assert_eq!(name("10.0.0.6"), None);
+}
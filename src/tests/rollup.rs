@@ -0,0 +1,60 @@
+use std::time::{Duration, SystemTime};
+
+use chrono::{Local, TimeZone};
+
+use crate::core::heatmap::{Heatmap, HeatmapLayout};
+use crate::core::rollup::{RollupOptions, RollupSeries};
+
+#[test]
+fn old_samples_roll_up_into_minutes_then_hours_then_drop() {
+    let start = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000 * 3600);
+    let at = |secs: u64| start + Duration::from_secs(secs);
+    let options = RollupOptions {
+        raw_age: Duration::from_secs(120),
+        minute_age: Duration::from_secs(3600),
+        max_age: Duration::from_secs(3 * 3600),
+    };
+    let mut series = RollupSeries::default();
+
+    for (secs, value) in [(0, 4), (20, 1), (40, 7), (60, 2)] {
+        series.push(at(secs), value);
+    }
+    series.compact(at(170), &options);
+    let minutes: Vec<_> = series.rollups().map(|r| (r.samples, r.min, r.max, r.average())).collect();
+    assert_eq!(minutes, vec![(3, 1, 7, 4.0)]);
+    assert_eq!(series.raw().count(), 1);
+
+    // Both minutes merge into the hour once past the minute age
+    series.compact(at(3700), &options);
+    let hours: Vec<_> = series.rollups().map(|r| (r.width.as_secs(), r.samples, r.min, r.max)).collect();
+    assert_eq!(hours, vec![(3600, 4, 1, 7)]);
+    assert_eq!(series.history(), vec![(at(0), 4)]);
+
+    series.compact(at(4 * 3600), &options);
+    assert!(series.is_empty());
+}
+
+#[test]
+fn heatmap_lines_up_periodic_opens_in_the_same_minute_columns() {
+    let now = Local.with_ymd_and_hms(2024, 1, 10, 12, 30, 0).unwrap();
+    let mut series = RollupSeries::default();
+    // A burst every 5 minutes over the last two hours, nothing in between
+    for minutes_ago in (0..120).step_by(5) {
+        let time = now - chrono::Duration::minutes(minutes_ago);
+        series.push(time.into(), 3);
+        series.push((time + chrono::Duration::seconds(1)).into(), 0);
+    }
+
+    let heatmap = Heatmap::build(&series, HeatmapLayout::HoursByMinutes, now, 3);
+    assert_eq!(heatmap.max, 3);
+    assert_eq!(heatmap.rows[2].start, Local.with_ymd_and_hms(2024, 1, 10, 12, 0, 0).unwrap());
+    let busy: Vec<usize> = heatmap.rows[1].cells.iter().enumerate()
+        .filter(|(_, opens)| **opens > 0)
+        .map(|(minute, _)| minute)
+        .collect();
+    assert_eq!(busy, (0..60).step_by(5).collect::<Vec<_>>());
+
+    let days = Heatmap::build(&series, HeatmapLayout::DaysByHours, now, 2);
+    assert_eq!(days.rows[0].cells.iter().sum::<u64>(), 0);
+    assert_eq!(days.rows[1].cells[11], 36);
+}
@@ -0,0 +1,63 @@
+use std::fs;
+
+use crate::core::filters::ConnectionFilter;
+use crate::core::script::ScriptHooks;
+use super::replay_with;
+
+const POOL: &str = include_str!("../../tests/fixtures/pool.script");
+
+const HOOKS: &str = r#"
+fn group(conn) {
+    if conn.remote_port == 5432 { "database" }
+}
+
+fn host_column(row) {
+    row.max_concurrent * 10
+}
+
+fn process_column(row) {
+    if row.name == "curl" { "web" } else { "other" }
+}
+
+fn alert(summary) {
+    if summary.max_concurrent >= 3 { "pool grew to " + summary.max_concurrent }
+}
+"#;
+
+fn load(name: &str, source: &str) -> ScriptHooks {
+    let path = std::env::temp_dir().join(format!("tcpcount-{}-{}.rhai", name, std::process::id()));
+    fs::write(&path, source).unwrap();
+    let hooks = ScriptHooks::load(&path).expect("script should compile");
+    fs::remove_file(&path).ok();
+    hooks
+}
+
+#[test]
+fn script_hooks_group_connections_fill_columns_and_raise_alerts() {
+    let mut monitor = replay_with(POOL, |monitor| monitor.set_script(Some(load("hooks", HOOKS))));
+    let filter = ConnectionFilter::default();
+
+    let groups = monitor.get_group_metrics(&filter);
+    assert_eq!(groups.len(), 1);
+    assert_eq!((groups[0].group.as_str(), groups[0].total_connections), ("database", 1));
+
+    let hosts = monitor.get_host_metrics(&filter);
+    let api = hosts.iter().find(|m| m.port == 443).unwrap();
+    assert_eq!(api.script.as_deref(), Some("30"));
+    let processes = monitor.get_process_metrics(&filter);
+    let psql = processes.iter().find(|m| m.name == "psql").unwrap();
+    assert_eq!(psql.script.as_deref(), Some("other"));
+
+    // Raised once when four connections are open at a time, not again while the message stays the same
+    assert_eq!(monitor.take_script_alerts(), vec!["pool grew to 4".to_string()]);
+}
+
+#[test]
+fn scripts_that_fail_to_compile_are_rejected() {
+    let path = std::env::temp_dir().join(format!("tcpcount-broken-{}.rhai", std::process::id()));
+    fs::write(&path, "fn group(conn) {").unwrap();
+    let result = ScriptHooks::load(&path);
+    fs::remove_file(&path).ok();
+
+    assert!(result.is_err());
+}
@@ -0,0 +1,38 @@
+use crate::core::threads::{SocketThreads, ThreadInfo, ThreadSockets};
+
+fn thread(tid: u32, name: &str) -> ThreadInfo {
+    ThreadInfo { tid, name: name.to_string() }
+}
+
+#[test]
+fn sockets_are_attributed_to_the_threads_holding_them() {
+    // A shared descriptor table shows every socket in every thread; the worker below was
+    // created with its own table and holds one socket the others don't
+    let threads = ThreadSockets::from_threads(vec![
+        (thread(102, "tokio-runtime-w"), vec![7001, 7002]),
+        (thread(100, "server"), vec![7001]),
+        (thread(101, "tokio-runtime-w"), vec![7001]),
+    ]);
+
+    assert_eq!(threads.owners(7001), Some(SocketThreads::All(3)));
+    assert_eq!(threads.owners(7002), Some(SocketThreads::Some(vec![thread(102, "tokio-runtime-w")])));
+    assert_eq!(threads.owners(7003), None);
+    assert_eq!(SocketThreads::All(3).to_string(), "all 3 threads (shared fd table)");
+
+    // A single-threaded process owns its sockets outright
+    let single = ThreadSockets::from_threads(vec![(thread(200, "curl"), vec![8001])]);
+    assert_eq!(single.owners(8001), Some(SocketThreads::Some(vec![thread(200, "curl")])));
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn socket_descriptors_are_read_from_proc() {
+    use std::os::fd::AsRawFd;
+
+    use crate::core::threads::process_socket_fds;
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let fds = process_socket_fds(std::process::id());
+
+    assert!(fds.values().any(|&fd| fd as i32 == listener.as_raw_fd()));
+}
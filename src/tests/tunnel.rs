@@ -0,0 +1,37 @@
+use std::net::IpAddr;
+
+use crate::core::tunnel::RoutingTable;
+
+fn ip(addr: &str) -> IpAddr {
+    addr.parse().unwrap()
+}
+
+// Host byte order of a little-endian machine, as the kernel writes it
+const IPV4_ROUTES: &str = "\
+Iface\tDestination\tGateway \tFlags\tRefCnt\tUse\tMetric\tMask\t\tMTU\tWindow\tIRTT
+eth0\t00000000\t0101A8C0\t0003\t0\t0\t100\t00000000\t0\t0\t0
+eth0\t0001A8C0\t00000000\t0001\t0\t0\t100\t00FFFFFF\t0\t0\t0
+wg0\t0000080A\t00000000\t0001\t0\t0\t0\t0000FFFF\t0\t0\t0
+";
+
+const IPV6_ROUTES: &str = "\
+fd7a115ca1e0ab120000000000000000 30 00000000000000000000000000000000 00 00000000000000000000000000000000 00000000 00000001 00000000 00000001 tailscale0
+00000000000000000000000000000000 00 00000000000000000000000000000000 00 fe800000000000000000000000000001 00000400 00000001 00000000 00000003     eth0
+";
+
+#[test]
+fn routes_pick_the_tunnel_interface_by_local_or_remote_address() {
+    let routes = RoutingTable::parse(IPV4_ROUTES, IPV6_ROUTES);
+
+    assert_eq!(routes.interface_for(&ip("10.8.3.4")), Some("wg0"));
+    assert_eq!(routes.interface_for(&ip("192.168.1.7")), Some("eth0"));
+    assert_eq!(routes.interface_for(&ip("::ffff:10.8.0.1")), Some("wg0"));
+    assert_eq!(routes.interface_for(&ip("fd7a:115c:a1e0:ab12::5")), Some("tailscale0"));
+
+    // Bound to the tunnel address, even when talking to a public host
+    assert_eq!(routes.tunnel_for(&ip("10.8.0.2"), &ip("93.184.216.34")).as_deref(), Some("wg0"));
+    // Routed into the tunnel's network from a LAN address
+    assert_eq!(routes.tunnel_for(&ip("192.168.1.7"), &ip("10.8.0.1")).as_deref(), Some("wg0"));
+    assert_eq!(routes.tunnel_for(&ip("192.168.1.7"), &ip("93.184.216.34")), None);
+    assert_eq!(routes.tunnel_for(&ip("127.0.0.1"), &ip("127.0.0.1")), None);
+}
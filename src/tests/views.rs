@@ -0,0 +1,53 @@
+use crate::core::connection::Connection;
+use crate::core::filters::ConnectionFilter;
+use crate::core::monitor::GroupMetrics;
+use crate::core::process::Process;
+use crate::core::views::AggregationView;
+use super::{replay, replay_with};
+
+const POOL: &str = include_str!("../../tests/fixtures/pool.script");
+
+/// Connections by the name of their process, as a view plugged in from outside.
+struct ProcessNameView;
+
+impl AggregationView for ProcessNameView {
+    fn name(&self) -> &str {
+        "process"
+    }
+
+    fn column(&self) -> &str {
+        "Process"
+    }
+
+    fn key(&self, _conn: &Connection, process: Option<&Process>) -> Option<String> {
+        process.and_then(|process| process.name.clone())
+    }
+}
+
+fn row(rows: &[GroupMetrics], key: &str) -> (usize, usize, usize) {
+    let row = rows.iter().find(|row| row.group == key).expect("view should have the row");
+    (row.current_connections, row.total_connections, row.max_concurrent)
+}
+
+#[test]
+fn builtin_views_group_connections_by_their_key() {
+    let monitor = replay(POOL);
+    let scopes = monitor.get_view_metrics("scope", &ConnectionFilter::default());
+
+    assert_eq!(scopes.len(), 2);
+    assert_eq!(row(&scopes, "public"), (2, 4, 3));
+    assert_eq!(row(&scopes, "private"), (1, 1, 1));
+    assert!(monitor.get_view_metrics("no-such-view", &ConnectionFilter::default()).is_empty());
+}
+
+#[test]
+fn added_views_are_tracked_and_filtered() {
+    let monitor = replay_with(POOL, |monitor| monitor.add_view(Box::new(ProcessNameView)));
+    let filter = ConnectionFilter::default().with_process_name("curl".to_string());
+    let processes = monitor.get_view_metrics("process", &filter);
+
+    assert_eq!(monitor.view_names(), vec!["group", "scope", "tunnel", "exe", "process"]);
+    assert_eq!(monitor.view_column("process"), Some("Process"));
+    assert_eq!(processes.len(), 1);
+    assert_eq!(row(&processes, "curl"), (2, 4, 3));
+}
@@ -1,26 +1,32 @@
 use std::sync::{Arc, Mutex};
 use std::time::{SystemTime, Duration};
 use std::cmp;
+use chrono::{DateTime, Local};
 use ratatui::{
     buffer::Buffer,
     layout::Rect,
     style::{Stylize, Style, Color},
     widgets::{Block, Widget, Sparkline, BorderType},
     text::Span,
-    symbols,
 };
 
+use crate::core::annotations::{Annotation, AnnotationKind};
 use crate::core::monitor::ConnectionMonitor;
 use crate::core::filters::ConnectionFilter;
+use super::glyphs;
 
 pub struct ActiveConnectionsGraphWidget {
     monitor: Arc<Mutex<ConnectionMonitor>>,
     filter: ConnectionFilter,
     max_points: usize,
     history_data: Vec<u64>,
+    history_times: Vec<SystemTime>, // When each point of history_data was sampled
+    annotations: Vec<Annotation>, // Marked above the bars they fall in
     last_sample_time: SystemTime,
     sample_interval: Duration,
     last_filter_hash: u64, // To detect filter changes
+    cleared_at: Option<SystemTime>, // History before this is no longer shown
+    label: Option<String>, // Shown before the title, e.g. to name a split view's side
 }
 
 impl ActiveConnectionsGraphWidget {
@@ -33,9 +39,13 @@ impl ActiveConnectionsGraphWidget {
             filter,
             max_points: 100, // Default to 100 data points
             history_data: Vec::new(),
+            history_times: Vec::new(),
+            annotations: Vec::new(),
             last_sample_time: SystemTime::now(),
             sample_interval: Duration::from_secs(1), // 1 second per bar
             last_filter_hash: filter_hash,
+            cleared_at: None,
+            label: None,
         }
     }
 
@@ -45,20 +55,14 @@ impl ActiveConnectionsGraphWidget {
         
         let mut hasher = DefaultHasher::new();
         
-        if let Some(pid) = filter.pid {
-            pid.hash(&mut hasher);
-        }
-        
-        if let Some(ref name) = filter.process_name {
-            name.hash(&mut hasher);
-        }
-        
-        if let Some(ref host) = filter.remote_host {
-            host.hash(&mut hasher);
-        }
-        
-        if let Some(port) = filter.remote_port {
-            port.hash(&mut hasher);
+        filter.pids.hash(&mut hasher);
+        filter.process_names.hash(&mut hasher);
+        filter.remote_hosts.hash(&mut hasher);
+        filter.remote_ports.hash(&mut hasher);
+        filter.groups.hash(&mut hasher);
+
+        if let Some(ref query) = filter.query {
+            query.to_string().hash(&mut hasher);
         }
         
         hasher.finish()
@@ -71,38 +75,63 @@ impl ActiveConnectionsGraphWidget {
         self.rebuild_history_data();
     }
     
+    /// Active connections at each sample shown, oldest first.
+    pub fn history(&self) -> &[u64] {
+        &self.history_data
+    }
+
+    pub fn set_label(&mut self, label: Option<String>) {
+        self.label = label;
+    }
+
     pub fn with_max_points(mut self, points: usize) -> Self {
         self.max_points = points;
         self
     }
     
+    pub fn set_max_points(&mut self, points: usize) {
+        self.max_points = points;
+        self.rebuild_history_data();
+    }
+
+    /// Empties the graph without touching the collected metrics. Earlier samples stay
+    /// hidden when the history is rebuilt for another filter.
+    pub fn clear(&mut self) {
+        self.history_data.clear();
+        self.history_times.clear();
+        self.cleared_at = Some(SystemTime::now());
+    }
+
     fn rebuild_history_data(&mut self) {
         if let Ok(monitor_guard) = self.monitor.lock() {
             let history = monitor_guard.get_connection_history_filtered(
                 &self.filter,
-                None,
+                self.cleared_at,
                 None  // No end time limit
             );
             
+            let skip = history.len().saturating_sub(self.max_points);
             self.history_data = history.iter()
+                .skip(skip)
                 .map(|(_, count)| *count as u64)
                 .collect();
-            
-            if self.history_data.len() > self.max_points {
-                let skip = self.history_data.len() - self.max_points;
-                self.history_data = self.history_data.iter().skip(skip).cloned().collect();
-            }
+            self.history_times = history.iter()
+                .skip(skip)
+                .map(|(time, _)| *time)
+                .collect();
+            self.annotations = monitor_guard.get_annotations().cloned().collect();
         }
     }
 
-    pub fn update(&mut self) {
+    /// Samples the active connection count once per interval. Returns whether the graph changed.
+    pub fn update(&mut self) -> bool {
         let now = SystemTime::now();
         
         let current_hash = Self::hash_filter(&self.filter);
         if current_hash != self.last_filter_hash {
             self.last_filter_hash = current_hash;
             self.rebuild_history_data();
-            return;
+            return true;
         }
         
         if let Ok(elapsed) = now.duration_since(self.last_sample_time) {
@@ -111,17 +140,46 @@ impl ActiveConnectionsGraphWidget {
                     let active_connections = monitor_guard.get_filtered_active_connections(&self.filter).len() as u64;
                     
                     self.history_data.push(active_connections);
+                    self.history_times.push(now);
                     
                     if self.history_data.len() > self.max_points {
                         self.history_data.remove(0);
+                        self.history_times.remove(0);
                     }
+                    self.annotations = monitor_guard.get_annotations().cloned().collect();
                     
                     self.last_sample_time = now;
+                    return true;
                 }
             }
         }
+        false
     }
     
+    fn title(&self) -> String {
+        let title = match self.cleared_at {
+            Some(at) => format!("Active Connections (1s interval, since {})", DateTime::<Local>::from(at).format("%H:%M:%S")),
+            None => "Active Connections (1s interval)".to_string(),
+        };
+        match &self.label {
+            Some(label) => format!("{} - {}", label, title),
+            None => title,
+        }
+    }
+
+    /// The latest annotation made during the bar at `idx` of the history.
+    fn annotation_at(&self, idx: usize) -> Option<&Annotation> {
+        let end = *self.history_times.get(idx)?;
+        let start = match idx.checked_sub(1).and_then(|prev| self.history_times.get(prev)) {
+            Some(&prev) => prev,
+            None => end.checked_sub(self.sample_interval).unwrap_or(end),
+        };
+        self.annotations.iter().rev().find(|annotation| {
+            let at = SystemTime::from(annotation.time);
+            at > start && at <= end
+        })
+    }
+
     /// Find the maximum value in the history data
     fn get_max_value(&self) -> u64 {
         self.history_data.iter().fold(0, |max, &val| cmp::max(max, val))
@@ -132,9 +190,9 @@ impl Widget for &ActiveConnectionsGraphWidget {
     fn render(self, area: Rect, buf: &mut Buffer) {
         if self.history_data.is_empty() {
             let block = Block::bordered()
-                .title("Active Connections (1s interval)")
+                .title(self.title())
                 .title_style(Style::new().bold().fg(Color::Cyan))
-                .border_type(BorderType::Rounded)
+                .border_set(glyphs::border_set(BorderType::Rounded))
                 .border_style(Style::new().fg(Color::Blue));
             
             block.render(area, buf);
@@ -151,9 +209,9 @@ impl Widget for &ActiveConnectionsGraphWidget {
         };
         
         let block = Block::bordered()
-            .title("Active Connections (1s interval)")
+            .title(self.title())
             .title_style(Style::new().bold().fg(Color::Cyan))
-            .border_type(BorderType::Plain)
+            .border_set(glyphs::border_set(BorderType::Plain))
             .border_style(Style::new().fg(Color::Blue));
         
         let inner_area = block.inner(area);
@@ -209,8 +267,37 @@ impl Widget for &ActiveConnectionsGraphWidget {
             .data(&data_slice)
             .max(max_value_rounded)
             .style(Style::default().fg(Color::Cyan))
-            .bar_set(symbols::bar::NINE_LEVELS);
+            .bar_set(glyphs::bar_set());
             
         sparkline.render(sparkline_area, buf);
+
+        // Annotation markers on the top row, over the bar they happened in; notes also
+        // get their text when there is room
+        if self.annotations.is_empty() {
+            return;
+        }
+        let visible = self.history_data.len().min(available_points);
+        let first_idx = self.history_data.len() - visible;
+        let first_x = sparkline_area.x + (available_points - visible) as u16;
+        for offset in 0..visible {
+            let Some(annotation) = self.annotation_at(first_idx + offset) else {
+                continue;
+            };
+            let x = first_x + offset as u16;
+            let color = annotation_color(annotation.kind);
+            buf.set_span(x, sparkline_area.y, &Span::styled(glyphs::glyph("▼", "v"), Style::new().bold().fg(color)), 1);
+            if annotation.kind == AnnotationKind::Manual && sparkline_area.height > 1 {
+                let width = sparkline_area.right().saturating_sub(x + 1);
+                buf.set_span(x + 1, sparkline_area.y, &Span::styled(annotation.text.as_str(), Style::new().fg(color)), width);
+            }
+        }
+    }
+}
+fn annotation_color(kind: AnnotationKind) -> Color {
+    match kind {
+        AnnotationKind::Manual => Color::Magenta,
+        AnnotationKind::Restart => Color::Yellow,
+        AnnotationKind::Alert => Color::Red,
+        AnnotationKind::Filter => Color::Gray,
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,130 @@
+use std::cmp::Ordering;
+use std::ops::{Deref, DerefMut};
+use std::sync::{Arc, Mutex};
+use chrono::{DateTime, Local};
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::Color,
+    widgets::Widget,
+};
+
+use crate::core::monitor::{ClientMetrics, ConnectionMonitor};
+use crate::core::scope::AddressScope;
+use crate::core::filters::RowAttributes;
+use super::metrics_table::{MetricsTable, pin_first};
+use super::table::{Column, Highlight, TableRow, highlight_color};
+
+/// Columns this table can show, in picker order.
+pub const AVAILABLE_COLUMNS: [Column; 8] = [Column::Host, Column::Port, Column::Active, Column::Total, Column::Max, Column::Scope, Column::FirstSeen, Column::LastSeen];
+
+const DEFAULT_COLUMNS: [Column; 7] = [Column::Host, Column::Port, Column::Active, Column::Total, Column::Max, Column::FirstSeen, Column::LastSeen];
+
+impl ClientMetrics {
+    /// The client's hostname with its address, or just the address when unresolved.
+    fn label(&self) -> String {
+        match &self.hostname {
+            Some(hostname) if *hostname != self.addr.to_string() => format!("{} ({})", hostname, self.addr),
+            _ => self.addr.to_string(),
+        }
+    }
+}
+
+impl TableRow for ClientMetrics {
+    const KEY_COLUMNS: &'static [Column] = &[Column::Host];
+
+    fn cell(&self, column: Column) -> String {
+        match column {
+            Column::Host => self.label(),
+            Column::Port => self.ports.iter().map(u16::to_string).collect::<Vec<_>>().join(", "),
+            Column::Active => self.current_connections.to_string(),
+            Column::Total => self.total_connections.to_string(),
+            Column::Max => self.max_concurrent.to_string(),
+            Column::Scope => AddressScope::of(&self.addr).to_string(),
+            Column::FirstSeen => DateTime::<Local>::from(self.first_seen).format("%H:%M:%S").to_string(),
+            Column::LastSeen if self.current_connections > 0 => "now".to_string(),
+            Column::LastSeen => DateTime::<Local>::from(self.last_seen).format("%H:%M:%S").to_string(),
+            _ => String::new(),
+        }
+    }
+
+    fn compare(&self, other: &Self, column: Column) -> Ordering {
+        match column {
+            Column::Host => self.addr.cmp(&other.addr),
+            Column::Port => self.ports.cmp(&other.ports),
+            Column::Active => self.current_connections.cmp(&other.current_connections),
+            Column::Total => self.total_connections.cmp(&other.total_connections),
+            Column::Max => self.max_concurrent.cmp(&other.max_concurrent),
+            Column::Scope => AddressScope::of(&self.addr).cmp(&AddressScope::of(&other.addr)),
+            Column::FirstSeen => self.first_seen.cmp(&other.first_seen),
+            Column::LastSeen => (self.current_connections > 0, self.last_seen)
+                .cmp(&(other.current_connections > 0, other.last_seen)),
+            _ => Ordering::Equal,
+        }
+    }
+
+    fn color(&self, highlights: &[Highlight]) -> Option<Color> {
+        let addr = self.addr.to_string();
+        let row = RowAttributes {
+            host: Some(self.hostname.as_deref().unwrap_or(&addr)),
+            scope: Some(AddressScope::of(&self.addr)),
+            ..Default::default()
+        };
+        highlight_color(highlights, &row)
+    }
+}
+
+/// Inbound connections per client address, shown in place of the listening port table.
+pub struct ClientTableWidget {
+    monitor: Arc<Mutex<ConnectionMonitor>>,
+    table: MetricsTable<ClientMetrics>,
+}
+
+impl ClientTableWidget {
+    pub fn new(monitor: Arc<Mutex<ConnectionMonitor>>) -> Self {
+        Self {
+            monitor,
+            table: MetricsTable::new("Inbound by Client", &AVAILABLE_COLUMNS, &DEFAULT_COLUMNS),
+        }
+    }
+
+    /// Sorted rows, paired with their pinned flag.
+    pub fn sorted_metrics(&self, monitor: &ConnectionMonitor) -> Vec<(ClientMetrics, bool)> {
+        let mut rows = monitor.get_client_metrics(self.filter());
+        self.table.sort(&mut rows);
+        pin_first(rows, |_| false)
+    }
+
+    pub fn selected_metrics(&self) -> Option<ClientMetrics> {
+        let monitor = self.monitor.lock().ok()?;
+        self.table.selected_row(self.sorted_metrics(&monitor))
+    }
+
+    /// Text of the selected row's visible columns.
+    pub fn selected_cells(&self) -> Option<Vec<String>> {
+        Some(self.table.cells(&self.selected_metrics()?))
+    }
+}
+
+impl Deref for ClientTableWidget {
+    type Target = MetricsTable<ClientMetrics>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.table
+    }
+}
+
+impl DerefMut for ClientTableWidget {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.table
+    }
+}
+
+impl Widget for &ClientTableWidget {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let Ok(monitor_guard) = self.monitor.lock() else {
+            return;
+        };
+        self.table.render(&self.sorted_metrics(&monitor_guard), area, buf);
+    }
+}
@@ -0,0 +1,137 @@
+use ratatui::{
+    buffer::Buffer,
+    layout::{Rect, Layout, Direction, Constraint},
+    style::{Stylize, Style, Color, Modifier},
+    text::Line,
+    widgets::{Block, BorderType, Paragraph, Widget, Clear},
+};
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind};
+
+use super::table::Column;
+use super::glyphs;
+
+/// Popup for choosing which columns a table shows.
+pub struct ColumnPickerWidget {
+    title: String,
+    columns: Vec<(Column, bool)>,
+    selected: usize,
+    active: bool,
+}
+
+impl ColumnPickerWidget {
+    pub fn new() -> Self {
+        Self {
+            title: String::new(),
+            columns: Vec::new(),
+            selected: 0,
+            active: false,
+        }
+    }
+
+    /// Opens the picker for a table, listing its shown columns in order followed by the hidden ones.
+    pub fn show(&mut self, title: &str, available: &[Column], shown: &[Column]) {
+        self.title = title.to_string();
+        self.columns = shown.iter().map(|column| (*column, true))
+            .chain(available.iter()
+                .filter(|column| !shown.contains(column))
+                .map(|column| (*column, false)))
+            .collect();
+        self.selected = 0;
+        self.active = true;
+    }
+
+    pub fn hide(&mut self) {
+        self.active = false;
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// Returns the checked columns when the choice is confirmed with Enter.
+    pub fn handle_key_event(&mut self, key_event: KeyEvent) -> Option<Vec<Column>> {
+        if !self.active || key_event.kind != KeyEventKind::Press {
+            return None;
+        }
+
+        match key_event.code {
+            KeyCode::Esc => self.hide(),
+            KeyCode::Up => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down if self.selected + 1 < self.columns.len() => self.selected += 1,
+            KeyCode::Char(' ') => {
+                // A table always keeps at least one column
+                let checked = self.columns.iter().filter(|(_, shown)| *shown).count();
+                if let Some((_, shown)) = self.columns.get_mut(self.selected) {
+                    if !*shown || checked > 1 {
+                        *shown = !*shown;
+                    }
+                }
+            }
+            KeyCode::Enter => {
+                self.hide();
+                return Some(self.columns.iter()
+                    .filter(|(_, shown)| *shown)
+                    .map(|(column, _)| *column)
+                    .collect());
+            }
+            _ => {}
+        }
+        None
+    }
+}
+
+impl Widget for &ColumnPickerWidget {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        if !self.active {
+            return;
+        }
+
+        let popup_width = area.width.min(50);
+        let popup_height = (self.columns.len() as u16 + 4).min(area.height);
+
+        let popup_area = Rect {
+            x: area.x + (area.width.saturating_sub(popup_width)) / 2,
+            y: area.y + (area.height.saturating_sub(popup_height)) / 2,
+            width: popup_width,
+            height: popup_height,
+        };
+
+        Clear.render(popup_area, buf);
+
+        let block = Block::bordered()
+            .title(format!("Columns: {}", self.title))
+            .title_style(Style::new().bold().fg(Color::Yellow))
+            .border_set(glyphs::border_set(BorderType::Plain))
+            .border_style(Style::new().fg(Color::Yellow));
+
+        let inner_area = block.inner(popup_area);
+        block.render(popup_area, buf);
+
+        let layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(1), Constraint::Length(1)])
+            .split(inner_area);
+
+        let lines: Vec<Line> = self.columns.iter().enumerate()
+            .map(|(idx, (column, shown))| {
+                let style = if idx == self.selected {
+                    Style::new().add_modifier(Modifier::REVERSED)
+                } else {
+                    Style::new()
+                };
+                let check = if *shown { "[x]" } else { "[ ]" };
+                Line::styled(format!("{} {}", check, column.label()), style)
+            })
+            .collect();
+
+        let visible = layout[0].height as usize;
+        let offset = (self.selected + 1).saturating_sub(visible);
+        Paragraph::new(lines)
+            .scroll((offset as u16, 0))
+            .render(layout[0], buf);
+
+        Paragraph::new(glyphs::glyph("↑↓", "Up/Down").to_string() + ": Select  |  Space: Toggle  |  Enter: Apply  |  Esc: Cancel")
+            .style(Style::new().fg(Color::Gray))
+            .render(layout[1], buf);
+    }
+}
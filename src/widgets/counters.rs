@@ -0,0 +1,80 @@
+use std::sync::{Arc, Mutex};
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Stylize, Style, Color},
+    text::{Line, Span, Text},
+    widgets::{Block, BorderType, Clear, Paragraph, Widget},
+};
+
+use crate::core::monitor::ConnectionMonitor;
+use super::glyphs;
+
+/// Debug overlay comparing the concurrency counters with a recount of the open
+/// connections, toggled with `D`.
+pub struct CounterOverlayWidget {
+    monitor: Arc<Mutex<ConnectionMonitor>>,
+}
+
+impl CounterOverlayWidget {
+    pub fn new(monitor: Arc<Mutex<ConnectionMonitor>>) -> Self {
+        Self { monitor }
+    }
+}
+
+impl Widget for &CounterOverlayWidget {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let checks = match self.monitor.lock() {
+            Ok(guard) => guard.counter_checks(),
+            Err(_) => return,
+        };
+
+        let mut lines = vec![Line::styled(
+            format!("{:<14}{:>6}{:>8}{:>9}{:>12}", "Counter", "Keys", "Open", "Recount", "Mismatches"),
+            Style::new().bold(),
+        )];
+        for check in &checks {
+            let style = if check.is_consistent() {
+                Style::new()
+            } else {
+                Style::new().fg(Color::LightRed).bold()
+            };
+            lines.push(Line::styled(
+                format!("{:<14}{:>6}{:>8}{:>9}{:>12}", check.name, check.keys, check.counted, check.actual, check.mismatches),
+                style,
+            ));
+        }
+        lines.push(Line::raw(""));
+        let bad = checks.iter().filter(|check| !check.is_consistent()).count();
+        lines.push(if checks.is_empty() {
+            Line::styled("No live counters while analyzing a file", Style::new().fg(Color::DarkGray))
+        } else if bad == 0 {
+            Line::styled("Counters agree with the open connections", Style::new().fg(Color::Green))
+        } else {
+            Line::from(Span::styled(
+                format!("{} counter{} disagree with the open connections", bad, if bad == 1 { "" } else { "s" }),
+                Style::new().fg(Color::LightRed).bold(),
+            ))
+        });
+
+        let width = 53.min(area.width);
+        let height = (lines.len() as u16 + 2).min(area.height);
+        let popup_area = Rect {
+            x: area.x + area.width.saturating_sub(width) / 2,
+            y: area.y + area.height.saturating_sub(height) / 2,
+            width,
+            height,
+        };
+        Clear.render(popup_area, buf);
+
+        Paragraph::new(Text::from(lines))
+            .block(
+                Block::bordered()
+                    .title("Counters (D to close)")
+                    .title_style(Style::new().bold().fg(Color::Cyan))
+                    .border_set(glyphs::border_set(BorderType::Plain))
+                    .border_style(Style::new().fg(Color::Blue))
+            )
+            .render(popup_area, buf);
+    }
+}
@@ -0,0 +1,105 @@
+use std::cell::Cell;
+use std::sync::{Arc, Mutex};
+use chrono::{DateTime, Local};
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Stylize, Style, Color},
+    text::{Line, Span},
+    widgets::{Block, Paragraph, Widget, BorderType},
+};
+
+use crate::core::events::ConnectionEventKind;
+use crate::core::monitor::ConnectionMonitor;
+use crate::core::filters::ConnectionFilter;
+use super::glyphs;
+
+pub struct EventLogWidget {
+    monitor: Arc<Mutex<ConnectionMonitor>>,
+    filter: ConnectionFilter,
+    /// Lines scrolled back from the newest event; 0 follows new events.
+    scroll: usize,
+    /// Event lines that fit at the last render.
+    visible_rows: Cell<usize>,
+}
+
+impl EventLogWidget {
+    pub fn new(monitor: Arc<Mutex<ConnectionMonitor>>) -> Self {
+        Self {
+            monitor,
+            filter: ConnectionFilter::default(),
+            scroll: 0,
+            visible_rows: Cell::new(0),
+        }
+    }
+
+    pub fn set_filter(&mut self, filter: ConnectionFilter) {
+        self.filter = filter;
+    }
+
+    /// Scrolls back towards older events, stopping at the oldest.
+    pub fn scroll_up(&mut self, lines: usize) {
+        let events = self.monitor.lock().map(|monitor| monitor.get_filtered_events(&self.filter).len()).unwrap_or(0);
+        let oldest = events.saturating_sub(self.visible_rows.get());
+        self.scroll = self.scroll.saturating_add(lines).min(oldest);
+    }
+
+    pub fn scroll_down(&mut self, lines: usize) {
+        self.scroll = self.scroll.saturating_sub(lines);
+    }
+
+    pub fn scroll_to_top(&mut self) {
+        self.scroll_up(usize::MAX);
+    }
+
+    /// Goes back to following new events.
+    pub fn scroll_to_bottom(&mut self) {
+        self.scroll = 0;
+    }
+}
+
+impl Widget for &EventLogWidget {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let monitor_guard = match self.monitor.lock() {
+            Ok(guard) => guard,
+            Err(_) => return,
+        };
+
+        let events = monitor_guard.get_filtered_events(&self.filter);
+
+        // Newest events at the bottom, showing as many as fit above the scroll position
+        let visible_rows = area.height.saturating_sub(2) as usize;
+        self.visible_rows.set(visible_rows);
+        let scroll = self.scroll.min(events.len().saturating_sub(visible_rows));
+        let skip = events.len().saturating_sub(visible_rows + scroll);
+
+        let lines: Vec<Line> = events.iter().skip(skip).take(visible_rows).map(|event| {
+            let conn = &event.connection;
+            let time: DateTime<Local> = event.time.into();
+            let host = conn.host();
+            let process = event.process_name.as_deref().unwrap_or("Unknown");
+
+            let kind_style = match event.kind {
+                ConnectionEventKind::Opened => Style::new().fg(Color::Green),
+                ConnectionEventKind::Closed => Style::new().fg(Color::Red),
+            };
+
+            Line::from(vec![
+                Span::styled(time.format("%H:%M:%S ").to_string(), Style::new().fg(Color::Gray)),
+                Span::raw(format!("{}({}) {} {}:{} ", process, conn.pid, glyphs::glyph("→", "->"), host, conn.remote_port)),
+                Span::styled(event.kind.as_str(), kind_style),
+            ])
+        }).collect();
+
+        let paragraph = Paragraph::new(lines)
+            .block(
+                Block::bordered()
+                    .title(if scroll > 0 { format!("Events ({} newer)", scroll) } else { "Events".to_string() })
+                    .title_style(Style::new().bold().fg(Color::Cyan))
+                    .border_set(glyphs::border_set(BorderType::Plain))
+                    .border_style(Style::new().fg(Color::Blue))
+            );
+
+        paragraph.render(area, buf);
+    }
+}
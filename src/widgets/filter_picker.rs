@@ -0,0 +1,139 @@
+use ratatui::{
+    buffer::Buffer,
+    layout::{Rect, Layout, Direction, Constraint},
+    style::{Stylize, Style, Color, Modifier},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Paragraph, Widget, Clear},
+};
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind};
+
+use crate::core::filters::SavedFilter;
+use super::glyphs;
+
+/// Number of saved filters reachable with F1..F5.
+pub const QUICK_SLOTS: usize = 5;
+
+/// Popup listing saved filters to pick one from.
+pub struct FilterPickerWidget {
+    filters: Vec<SavedFilter>,
+    selected: usize,
+    active: bool,
+}
+
+impl FilterPickerWidget {
+    pub fn new() -> Self {
+        Self {
+            filters: Vec::new(),
+            selected: 0,
+            active: false,
+        }
+    }
+
+    pub fn show(&mut self, filters: &[SavedFilter]) {
+        self.filters = filters.to_vec();
+        self.selected = 0;
+        self.active = true;
+    }
+
+    pub fn hide(&mut self) {
+        self.active = false;
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// Returns the index of the saved filter chosen with Enter.
+    pub fn handle_key_event(&mut self, key_event: KeyEvent) -> Option<usize> {
+        if !self.active || key_event.kind != KeyEventKind::Press {
+            return None;
+        }
+
+        match key_event.code {
+            KeyCode::Esc => {
+                self.hide();
+                None
+            }
+            KeyCode::Up => {
+                self.selected = self.selected.saturating_sub(1);
+                None
+            }
+            KeyCode::Down => {
+                if self.selected + 1 < self.filters.len() {
+                    self.selected += 1;
+                }
+                None
+            }
+            KeyCode::Enter if !self.filters.is_empty() => {
+                self.hide();
+                Some(self.selected)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Widget for &FilterPickerWidget {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        if !self.active {
+            return;
+        }
+
+        let popup_width = area.width.min(70);
+        let popup_height = (self.filters.len().max(1) as u16 + 4).min(area.height);
+
+        let popup_area = Rect {
+            x: area.x + (area.width.saturating_sub(popup_width)) / 2,
+            y: area.y + (area.height.saturating_sub(popup_height)) / 2,
+            width: popup_width,
+            height: popup_height,
+        };
+
+        Clear.render(popup_area, buf);
+
+        let block = Block::bordered()
+            .title("Saved Filters")
+            .title_style(Style::new().bold().fg(Color::Yellow))
+            .border_set(glyphs::border_set(BorderType::Plain))
+            .border_style(Style::new().fg(Color::Yellow));
+
+        let inner_area = block.inner(popup_area);
+        block.render(popup_area, buf);
+
+        let layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(1), Constraint::Length(1)])
+            .split(inner_area);
+
+        let lines: Vec<Line> = if self.filters.is_empty() {
+            vec![Line::styled("No saved filters yet; press s to save the current one", Style::new().fg(Color::Gray))]
+        } else {
+            self.filters.iter().enumerate()
+                .map(|(idx, saved)| {
+                    let slot = if idx < QUICK_SLOTS { format!("F{} ", idx + 1) } else { "   ".to_string() };
+                    let style = if idx == self.selected {
+                        Style::new().add_modifier(Modifier::REVERSED)
+                    } else {
+                        Style::new()
+                    };
+                    Line::from(vec![
+                        Span::styled(slot, Style::new().fg(Color::Green)),
+                        Span::styled(saved.name.clone(), style.bold()),
+                        Span::styled(format!("  {}", saved.filter), Style::new().fg(Color::Gray)),
+                    ])
+                })
+                .collect()
+        };
+
+        // Keep the selection visible in long lists
+        let visible = layout[0].height as usize;
+        let offset = (self.selected + 1).saturating_sub(visible);
+        Paragraph::new(lines)
+            .scroll((offset as u16, 0))
+            .render(layout[0], buf);
+
+        Paragraph::new(glyphs::glyph("↑↓", "Up/Down").to_string() + ": Select  |  Enter: Apply  |  Esc: Cancel")
+            .style(Style::new().fg(Color::Gray))
+            .render(layout[1], buf);
+    }
+}
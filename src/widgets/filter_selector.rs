@@ -5,9 +5,14 @@ use ratatui::{
     text::{Line, Span, Text},
     widgets::{Block, BorderType, Paragraph, Widget, Clear},
 };
-use crossterm::event::{KeyCode, KeyEvent, KeyEventKind};
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
 
-use crate::core::filters::ConnectionFilter;
+use crate::core::filters::{ConnectionFilter, FilterExpr, MatchOptions, join_values, parse_list, split_list};
+use super::text_input::TextInput;
+use super::glyphs;
+
+/// Maximum number of completion suggestions shown under a field.
+const MAX_SUGGESTIONS: usize = 5;
 
 #[derive(PartialEq)]
 pub enum FilterField {
@@ -15,6 +20,7 @@ pub enum FilterField {
     ProcessName,
     RemoteHost,
     RemotePort,
+    Group,
 }
 
 impl FilterField {
@@ -24,6 +30,7 @@ impl FilterField {
             FilterField::ProcessName => "Process Name",
             FilterField::RemoteHost => "Remote Host",
             FilterField::RemotePort => "Remote Port",
+            FilterField::Group => "Group",
         }
     }
     
@@ -32,27 +39,36 @@ impl FilterField {
             FilterField::Pid => FilterField::ProcessName,
             FilterField::ProcessName => FilterField::RemoteHost,
             FilterField::RemoteHost => FilterField::RemotePort,
-            FilterField::RemotePort => FilterField::Pid,
+            FilterField::RemotePort => FilterField::Group,
+            FilterField::Group => FilterField::Pid,
         }
     }
     
     pub fn prev(&self) -> Self {
         match self {
-            FilterField::Pid => FilterField::RemotePort,
+            FilterField::Pid => FilterField::Group,
             FilterField::ProcessName => FilterField::Pid,
             FilterField::RemoteHost => FilterField::ProcessName,
             FilterField::RemotePort => FilterField::RemoteHost,
+            FilterField::Group => FilterField::RemotePort,
         }
     }
 }
 
 pub struct FilterWidget {
     current_field: FilterField,
-    pid_input: String,
-    process_name_input: String,
-    remote_host_input: String,
-    remote_port_input: String,
+    pid_input: TextInput,
+    process_name_input: TextInput,
+    remote_host_input: TextInput,
+    remote_port_input: TextInput,
+    group_input: TextInput,
+    query: Option<FilterExpr>, // Kept as-is; edited with the query prompt
+    matching: MatchOptions,
+    known_processes: Vec<String>,
+    known_hosts: Vec<String>,
+    suggestion_idx: usize,
     active: bool,
+    title: &'static str,
     error: Option<String>,
 }
 
@@ -60,44 +76,99 @@ impl FilterWidget {
     pub fn new() -> Self {
         Self {
             current_field: FilterField::Pid,
-            pid_input: String::new(),
-            process_name_input: String::new(),
-            remote_host_input: String::new(),
-            remote_port_input: String::new(),
+            pid_input: TextInput::new(),
+            process_name_input: TextInput::new(),
+            remote_host_input: TextInput::new(),
+            remote_port_input: TextInput::new(),
+            group_input: TextInput::new(),
+            query: None,
+            matching: MatchOptions::default(),
+            known_processes: Vec::new(),
+            known_hosts: Vec::new(),
+            suggestion_idx: 0,
             active: false,
+            title: "Filter Connections",
             error: None,
         }
     }
     
-    pub fn show(&mut self, current_filter: &ConnectionFilter) {
+    pub fn show(&mut self, current_filter: &ConnectionFilter, title: &'static str) {
         self.active = true;
+        self.title = title;
         self.error = None;
         
-        if let Some(pid) = current_filter.pid {
-            self.pid_input = pid.to_string();
-        } else {
-            self.pid_input = String::new();
-        }
+        self.pid_input.set(join_values(&current_filter.pids));
+        self.process_name_input.set(current_filter.process_names.join(","));
+        self.remote_host_input.set(current_filter.remote_hosts.join(","));
+        self.remote_port_input.set(join_values(&current_filter.remote_ports));
+        self.group_input.set(current_filter.groups.join(","));
         
-        if let Some(ref name) = current_filter.process_name {
-            self.process_name_input = name.clone();
-        } else {
-            self.process_name_input = String::new();
-        }
+        self.query = current_filter.query.clone();
+        self.matching = current_filter.matching;
+        self.current_field = FilterField::Pid;
+    }
+    
+    /// Sets the process names and hosts offered as completions.
+    pub fn set_completions(&mut self, mut processes: Vec<String>, mut hosts: Vec<String>) {
+        processes.sort();
+        processes.dedup();
+        hosts.sort();
+        hosts.dedup();
+        self.known_processes = processes;
+        self.known_hosts = hosts;
+    }
+    
+    /// Completions for the value being typed (the last comma-separated entry), prefix matches first.
+    /// Only offered while the cursor is at the end of the field.
+    fn suggestions(&self) -> Vec<&str> {
+        let candidates = match self.current_field {
+            FilterField::ProcessName => &self.known_processes,
+            FilterField::RemoteHost => &self.known_hosts,
+            _ => return Vec::new(),
+        };
         
-        if let Some(ref host) = current_filter.remote_host {
-            self.remote_host_input = host.clone();
-        } else {
-            self.remote_host_input = String::new();
+        let input = self.current_input();
+        if !input.cursor_at_end() {
+            return Vec::new();
         }
-        
-        if let Some(port) = current_filter.remote_port {
-            self.remote_port_input = port.to_string();
-        } else {
-            self.remote_port_input = String::new();
+        let partial = input.value().rsplit(',').next().unwrap_or("").trim();
+        if partial.is_empty() {
+            return Vec::new();
         }
         
-        self.current_field = FilterField::Pid;
+        let partial_lower = partial.to_lowercase();
+        let mut matches: Vec<&str> = candidates.iter()
+            .map(String::as_str)
+            .filter(|candidate| *candidate != partial && candidate.to_lowercase().contains(&partial_lower))
+            .collect();
+        matches.sort_by_key(|candidate| !candidate.to_lowercase().starts_with(&partial_lower));
+        matches.truncate(MAX_SUGGESTIONS);
+        matches
+    }
+    
+    /// Replaces the entry being typed with the highlighted suggestion, if any.
+    fn accept_suggestion(&mut self) -> bool {
+        let suggestion = match self.suggestions().get(self.suggestion_idx) {
+            Some(suggestion) => suggestion.to_string(),
+            None => return false,
+        };
+        
+        let input = self.current_input_mut();
+        let kept = match input.value().rfind(',') {
+            Some(idx) => input.value()[..=idx].to_string(),
+            None => String::new(),
+        };
+        input.set(kept + suggestion.as_str());
+        self.suggestion_idx = 0;
+        true
+    }
+    
+    /// Inserts pasted text into the current field at the cursor.
+    pub fn handle_paste(&mut self, text: &str) {
+        if self.active {
+            self.current_input_mut().insert_str(text);
+            self.suggestion_idx = 0;
+        }
     }
     
     pub fn hide(&mut self) {
@@ -135,69 +206,87 @@ impl FilterWidget {
                 }
             },
             KeyCode::Tab => {
-                self.current_field = self.current_field.next();
+                if !self.accept_suggestion() {
+                    self.current_field = self.current_field.next();
+                    self.suggestion_idx = 0;
+                }
+                None
+            },
+            KeyCode::Right if self.current_input().cursor_at_end() => {
+                self.accept_suggestion();
                 None
             },
             KeyCode::BackTab => {
                 self.current_field = self.current_field.prev();
+                self.suggestion_idx = 0;
                 None
             },
-            KeyCode::Char(c) => {
-                match self.current_field {
-                    FilterField::Pid => self.pid_input.push(c),
-                    FilterField::ProcessName => self.process_name_input.push(c),
-                    FilterField::RemoteHost => self.remote_host_input.push(c),
-                    FilterField::RemotePort => self.remote_port_input.push(c),
+            KeyCode::Down => {
+                if self.suggestion_idx + 1 < self.suggestions().len() {
+                    self.suggestion_idx += 1;
                 }
                 None
             },
-            KeyCode::Backspace => {
-                match self.current_field {
-                    FilterField::Pid => { self.pid_input.pop(); },
-                    FilterField::ProcessName => { self.process_name_input.pop(); },
-                    FilterField::RemoteHost => { self.remote_host_input.pop(); },
-                    FilterField::RemotePort => { self.remote_port_input.pop(); },
+            KeyCode::Up => {
+                self.suggestion_idx = self.suggestion_idx.saturating_sub(1);
+                None
+            },
+            KeyCode::Char('c') if key_event.modifiers.contains(KeyModifiers::ALT) => {
+                self.matching.ignore_case = !self.matching.ignore_case;
+                None
+            },
+            KeyCode::Char('f') if key_event.modifiers.contains(KeyModifiers::ALT) => {
+                self.matching.fuzzy = !self.matching.fuzzy;
+                None
+            },
+            _ => {
+                if self.current_input_mut().handle_key(&key_event) {
+                    self.suggestion_idx = 0;
                 }
                 None
             },
-            _ => None,
         }
     }
     
     fn build_filter(&self) -> Result<ConnectionFilter, String> {
-        let mut filter = ConnectionFilter::default();
-        
-        if !self.pid_input.is_empty() {
-            match self.pid_input.parse::<u32>() {
-                Ok(pid) => filter.pid = Some(pid),
-                Err(_) => return Err(format!("Invalid PID: {}", self.pid_input)),
-            }
-        }
-        
-        if !self.process_name_input.is_empty() {
-            filter.process_name = Some(self.process_name_input.clone());
-        }
-        
-        if !self.remote_host_input.is_empty() {
-            filter.remote_host = Some(self.remote_host_input.clone());
-        }
+        let mut filter = ConnectionFilter {
+            query: self.query.clone(),
+            matching: self.matching,
+            ..ConnectionFilter::default()
+        };
         
-        if !self.remote_port_input.is_empty() {
-            match self.remote_port_input.parse::<u16>() {
-                Ok(port) => filter.remote_port = Some(port),
-                Err(_) => return Err(format!("Invalid port: {}", self.remote_port_input)),
-            }
-        }
+        filter.pids = parse_list(self.pid_input.value())
+            .map_err(|pid| format!("Invalid PID: {}", pid))?;
+        filter.process_names = split_list(self.process_name_input.value()).map(str::to_string).collect();
+        filter.remote_hosts = split_list(self.remote_host_input.value()).map(str::to_string).collect();
+        filter.remote_ports = parse_list(self.remote_port_input.value())
+            .map_err(|port| format!("Invalid port: {}", port))?;
+        filter.groups = split_list(self.group_input.value()).map(str::to_string).collect();
         
         Ok(filter)
     }
     
     pub fn get_input_for_current_field(&self) -> &str {
+        self.current_input().value()
+    }
+    
+    fn current_input(&self) -> &TextInput {
         match self.current_field {
             FilterField::Pid => &self.pid_input,
             FilterField::ProcessName => &self.process_name_input,
             FilterField::RemoteHost => &self.remote_host_input,
             FilterField::RemotePort => &self.remote_port_input,
+            FilterField::Group => &self.group_input,
+        }
+    }
+    
+    fn current_input_mut(&mut self) -> &mut TextInput {
+        match self.current_field {
+            FilterField::Pid => &mut self.pid_input,
+            FilterField::ProcessName => &mut self.process_name_input,
+            FilterField::RemoteHost => &mut self.remote_host_input,
+            FilterField::RemotePort => &mut self.remote_port_input,
+            FilterField::Group => &mut self.group_input,
         }
     }
 }
@@ -208,8 +297,10 @@ impl Widget for &FilterWidget {
             return;
         }
         
+        let suggestions = self.suggestions();
+        
         let popup_width = area.width.min(60);
-        let popup_height = 12;
+        let popup_height = (14 + suggestions.len() as u16).min(area.height);
         
         let hmargin = (area.width.saturating_sub(popup_width)) / 2;
         let vmargin = (area.height.saturating_sub(popup_height)) / 2;
@@ -224,9 +315,9 @@ impl Widget for &FilterWidget {
         Clear.render(popup_area, buf);
         
         let block = Block::bordered()
-            .title("Filter Connections")
+            .title(self.title)
             .title_style(Style::new().bold().fg(Color::Yellow))
-            .border_type(BorderType::Plain)
+            .border_set(glyphs::border_set(BorderType::Plain))
             .border_style(Style::new().fg(Color::Yellow));
             
         let inner_area = block.inner(popup_area);
@@ -241,7 +332,9 @@ impl Widget for &FilterWidget {
                 Constraint::Length(1),  // Process Name
                 Constraint::Length(1),  // Remote Host
                 Constraint::Length(1),  // Remote Port
-                Constraint::Length(1),  // Empty space
+                Constraint::Length(1),  // Group
+                Constraint::Length(1),  // Matching flags
+                Constraint::Length(1 + suggestions.len() as u16),  // Suggestions and empty space
                 Constraint::Length(1),  // Instructions
                 Constraint::Length(2),  // Error message (2 lines for wrapping)
             ])
@@ -251,23 +344,50 @@ impl Widget for &FilterWidget {
         self.render_field(buf, field_layout[1], FilterField::ProcessName, &self.process_name_input);
         self.render_field(buf, field_layout[2], FilterField::RemoteHost, &self.remote_host_input);
         self.render_field(buf, field_layout[3], FilterField::RemotePort, &self.remote_port_input);
+        self.render_field(buf, field_layout[4], FilterField::Group, &self.group_input);
+        
+        let checkbox = |enabled: bool| if enabled { "[x]" } else { "[ ]" };
+        Paragraph::new(Line::from(vec![
+            Span::styled("Match: ", Style::new().fg(Color::White)),
+            Span::styled(
+                format!("{} Ignore case (Alt+C)  {} Fuzzy (Alt+F)", checkbox(self.matching.ignore_case), checkbox(self.matching.fuzzy)),
+                Style::new().fg(Color::Gray),
+            ),
+        ])).render(field_layout[5], buf);
+        
+        let suggestion_lines: Vec<Line> = suggestions.iter().enumerate()
+            .map(|(idx, suggestion)| {
+                let style = if idx == self.suggestion_idx {
+                    Style::new().fg(Color::Yellow).reversed()
+                } else {
+                    Style::new().fg(Color::Gray)
+                };
+                Line::from(vec![Span::raw(glyphs::glyph("  ↳ ", "  > ")), Span::styled(*suggestion, style)])
+            })
+            .collect();
+        Paragraph::new(suggestion_lines).render(field_layout[6], buf);
         
-        let instructions = Paragraph::new("Tab: Next field  |  Shift+Tab: Previous field  |  Enter: Apply  |  Esc: Cancel")
+        let instructions = Paragraph::new(if suggestions.is_empty() {
+            "Tab/Shift+Tab: Switch field  |  a,b: Either value  |  Enter: Apply  |  Esc: Cancel"
+        } else {
+            glyphs::glyph("↑↓: Choose  |  Tab/→: Complete  |  Enter: Apply  |  Esc: Cancel",
+                "Up/Down: Choose  |  Tab/Right: Complete  |  Enter: Apply  |  Esc: Cancel")
+        })
             .style(Style::new().fg(Color::Gray))
             .alignment(Alignment::Center);
-        instructions.render(field_layout[5], buf);
+        instructions.render(field_layout[7], buf);
         
         if let Some(ref error) = self.error {
             let error_msg = Paragraph::new(error.as_str())
                 .style(Style::new().fg(Color::Red))
                 .alignment(Alignment::Left);
-            error_msg.render(field_layout[6], buf);
+            error_msg.render(field_layout[8], buf);
         }
     }
 }
 
 impl FilterWidget {
-    fn render_field(&self, buf: &mut Buffer, area: Rect, field: FilterField, value: &str) {
+    fn render_field(&self, buf: &mut Buffer, area: Rect, field: FilterField, input: &TextInput) {
         let is_active = self.current_field == field;
         
         let label_style = Style::new().fg(Color::White);
@@ -277,18 +397,10 @@ impl FilterWidget {
             Style::new().fg(Color::Gray)
         };
         
-        let value_text = if is_active {
-            format!("{}_", value)
-        } else {
-            value.to_string()
-        };
+        let mut spans = vec![Span::styled(format!("{}: ", field.as_str()), label_style)];
+        spans.extend(input.spans(value_style, is_active));
         
-        let text = Text::from(vec![
-            Line::from(vec![
-                Span::styled(format!("{}: ", field.as_str()), label_style),
-                Span::styled(value_text, value_style),
-            ]),
-        ]);
+        let text = Text::from(vec![Line::from(spans)]);
         
         let paragraph = Paragraph::new(text);
         paragraph.render(area, buf);
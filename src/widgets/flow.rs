@@ -0,0 +1,226 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Rect, Layout, Direction, Constraint},
+    style::{Stylize, Style, Color},
+    text::Line,
+    widgets::{Block, BorderType, Paragraph, Widget, Clear},
+    widgets::canvas::{Canvas, Line as CanvasLine},
+    symbols::Marker,
+};
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind};
+
+use crate::core::filters::ConnectionFilter;
+use crate::core::monitor::{ConnectionMonitor, ProcessHostMetrics};
+use super::glyphs;
+
+/// Widest process or host label before it is cut.
+const LABEL_WIDTH: u16 = 28;
+/// Link thickness in canvas dots for the busiest link.
+const MAX_THICKNESS: usize = 4;
+/// Colors cycled through by process, so a process's links can be told apart.
+const PALETTE: [Color; 6] = [Color::Cyan, Color::Green, Color::Magenta, Color::Yellow, Color::Blue, Color::Red];
+
+/// A process or host with its active connections.
+struct FlowNode {
+    label: String,
+    active: usize,
+}
+
+/// Processes and hosts with the most active connections, and the links between them.
+struct FlowGraph {
+    processes: Vec<FlowNode>,
+    hosts: Vec<FlowNode>,
+    /// Process index, host index and active connections.
+    links: Vec<(usize, usize, usize)>,
+    hidden_processes: usize,
+    hidden_hosts: usize,
+}
+
+impl FlowGraph {
+    /// Keeps the `max_nodes` busiest processes and hosts on each side, busiest first.
+    fn build(metrics: &[ProcessHostMetrics], max_nodes: usize) -> Self {
+        let active: Vec<&ProcessHostMetrics> = metrics.iter()
+            .filter(|m| m.current_connections > 0)
+            .collect();
+
+        let mut processes: HashMap<u32, FlowNode> = HashMap::new();
+        let mut hosts: HashMap<String, FlowNode> = HashMap::new();
+        for m in &active {
+            processes.entry(m.pid)
+                .or_insert_with(|| FlowNode { label: format!("{} ({})", m.process_name, m.pid), active: 0 })
+                .active += m.current_connections;
+            let host = format!("{}:{}", m.host, m.port);
+            hosts.entry(host.clone())
+                .or_insert_with(|| FlowNode { label: host, active: 0 })
+                .active += m.current_connections;
+        }
+
+        let mut processes: Vec<(u32, FlowNode)> = processes.into_iter().collect();
+        processes.sort_by(|a, b| b.1.active.cmp(&a.1.active).then_with(|| a.1.label.cmp(&b.1.label)));
+        let hidden_processes = processes.len().saturating_sub(max_nodes);
+        processes.truncate(max_nodes);
+
+        let mut hosts: Vec<(String, FlowNode)> = hosts.into_iter().collect();
+        hosts.sort_by(|a, b| b.1.active.cmp(&a.1.active).then_with(|| a.0.cmp(&b.0)));
+        let hidden_hosts = hosts.len().saturating_sub(max_nodes);
+        hosts.truncate(max_nodes);
+
+        let links = active.iter()
+            .filter_map(|m| {
+                let process = processes.iter().position(|(pid, _)| *pid == m.pid)?;
+                let host = format!("{}:{}", m.host, m.port);
+                let host = hosts.iter().position(|(key, _)| *key == host)?;
+                Some((process, host, m.current_connections))
+            })
+            .collect();
+
+        Self {
+            processes: processes.into_iter().map(|(_, node)| node).collect(),
+            hosts: hosts.into_iter().map(|(_, node)| node).collect(),
+            links,
+            hidden_processes,
+            hidden_hosts,
+        }
+    }
+}
+
+/// Experimental popup drawing processes on the left, hosts on the right, and links
+/// between them as thick as their active connections.
+pub struct FlowWidget {
+    monitor: Arc<Mutex<ConnectionMonitor>>,
+    filter: Option<ConnectionFilter>,
+}
+
+impl FlowWidget {
+    pub fn new(monitor: Arc<Mutex<ConnectionMonitor>>) -> Self {
+        Self { monitor, filter: None }
+    }
+
+    /// Opens the view for the connections matching `filter`.
+    pub fn show(&mut self, filter: ConnectionFilter) {
+        self.filter = Some(filter);
+    }
+
+    pub fn hide(&mut self) {
+        self.filter = None;
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.filter.is_some()
+    }
+
+    pub fn handle_key_event(&mut self, key_event: KeyEvent) {
+        if key_event.kind != KeyEventKind::Press {
+            return;
+        }
+        if matches!(key_event.code, KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('F')) {
+            self.hide();
+        }
+    }
+}
+
+/// Labels one per row, counts right-aligned, and a "+N more" line if nodes were left out.
+fn node_lines(nodes: &[FlowNode], hidden: usize, width: usize) -> Vec<Line<'static>> {
+    let mut lines: Vec<Line> = nodes.iter()
+        .map(|node| {
+            let count = node.active.to_string();
+            let label_width = width.saturating_sub(count.len() + 1);
+            let label: String = node.label.chars().take(label_width).collect();
+            Line::raw(format!("{:<label_width$} {}", label, count))
+        })
+        .collect();
+    if hidden > 0 {
+        lines.push(Line::styled(format!("+{} more", hidden), Style::new().fg(Color::Gray)));
+    }
+    lines
+}
+
+impl Widget for &FlowWidget {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let Some(filter) = &self.filter else {
+            return;
+        };
+
+        let popup_width = area.width.saturating_sub(4).min(120);
+        let popup_height = area.height.saturating_sub(4).min(40);
+        let popup_area = Rect {
+            x: area.x + (area.width.saturating_sub(popup_width)) / 2,
+            y: area.y + (area.height.saturating_sub(popup_height)) / 2,
+            width: popup_width,
+            height: popup_height,
+        };
+
+        Clear.render(popup_area, buf);
+
+        let block = Block::bordered()
+            .title(format!("Process {} Host Flow (experimental)", glyphs::glyph("→", "->")))
+            .title_style(Style::new().bold().fg(Color::Yellow))
+            .border_set(glyphs::border_set(BorderType::Plain))
+            .border_style(Style::new().fg(Color::Yellow));
+        let inner_area = block.inner(popup_area);
+        block.render(popup_area, buf);
+
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(1), Constraint::Length(1)])
+            .split(inner_area);
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([
+                Constraint::Length(LABEL_WIDTH), // Processes
+                Constraint::Min(4),              // Links
+                Constraint::Length(LABEL_WIDTH), // Hosts
+            ])
+            .split(rows[0]);
+
+        Paragraph::new("Esc/F: Close")
+            .style(Style::new().fg(Color::Gray))
+            .render(rows[1], buf);
+
+        let metrics = match self.monitor.lock() {
+            Ok(monitor) => monitor.get_process_host_metrics(filter),
+            Err(_) => return,
+        };
+        // One row per node, keeping the last row for "+N more"
+        let graph = FlowGraph::build(&metrics, (rows[0].height as usize).saturating_sub(1).max(1));
+        if graph.links.is_empty() {
+            Paragraph::new(Line::styled("No active connections match the current filter", Style::new().fg(Color::Gray)))
+                .render(rows[0], buf);
+            return;
+        }
+
+        Paragraph::new(node_lines(&graph.processes, graph.hidden_processes, LABEL_WIDTH as usize - 1))
+            .render(columns[0], buf);
+        Paragraph::new(node_lines(&graph.hosts, graph.hidden_hosts, LABEL_WIDTH as usize - 1))
+            .render(Rect { x: columns[2].x + 1, width: columns[2].width.saturating_sub(1), ..columns[2] }, buf);
+
+        // Canvas y grows upwards; row `i` is centred at `height - i - 0.5`, and a dot is
+        // a quarter of a row high
+        let height = columns[1].height as f64;
+        let max_link = graph.links.iter().map(|(_, _, active)| *active).max().unwrap_or(1);
+        Canvas::default()
+            .marker(if glyphs::is_ascii() { Marker::Dot } else { Marker::Braille })
+            .x_bounds([0.0, 1.0])
+            .y_bounds([0.0, height])
+            .paint(|ctx| {
+                for (process, host, active) in &graph.links {
+                    let thickness = (MAX_THICKNESS * active).div_ceil(max_link).max(1);
+                    let from = height - *process as f64 - 0.5;
+                    let to = height - *host as f64 - 0.5;
+                    for dot in 0..thickness {
+                        let offset = (dot as f64 - (thickness - 1) as f64 / 2.0) * 0.25;
+                        ctx.draw(&CanvasLine {
+                            x1: 0.0,
+                            y1: from + offset,
+                            x2: 1.0,
+                            y2: to + offset,
+                            color: PALETTE[process % PALETTE.len()],
+                        });
+                    }
+                }
+            })
+            .render(columns[1], buf);
+    }
+}
@@ -0,0 +1,53 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use ratatui::symbols::{bar, border};
+use ratatui::widgets::BorderType;
+
+/// Set once at startup by `--ascii`; read by every widget while drawing.
+static ASCII: AtomicBool = AtomicBool::new(false);
+
+const ASCII_BORDER: border::Set = border::Set {
+    top_left: "+",
+    top_right: "+",
+    bottom_left: "+",
+    bottom_right: "+",
+    vertical_left: "|",
+    vertical_right: "|",
+    horizontal_top: "-",
+    horizontal_bottom: "-",
+};
+
+const ASCII_BARS: bar::Set = bar::Set {
+    full: "#",
+    seven_eighths: "#",
+    three_quarters: "=",
+    five_eighths: "=",
+    half: "-",
+    three_eighths: "-",
+    one_quarter: "_",
+    one_eighth: "_",
+    empty: " ",
+};
+
+/// Draws borders, bars and arrows with plain ASCII for terminals without good Unicode support.
+pub fn set_ascii(ascii: bool) {
+    ASCII.store(ascii, Ordering::Relaxed);
+}
+
+pub fn is_ascii() -> bool {
+    ASCII.load(Ordering::Relaxed)
+}
+
+/// Border symbols for `border_type`, or `+`, `-` and `|` in ASCII mode.
+pub fn border_set(border_type: BorderType) -> border::Set {
+    if is_ascii() { ASCII_BORDER } else { border_type.to_border_set() }
+}
+
+pub fn bar_set() -> bar::Set {
+    if is_ascii() { ASCII_BARS } else { bar::NINE_LEVELS }
+}
+
+/// `unicode`, or `ascii` in ASCII mode; for arrows and other glyphs in text.
+pub fn glyph(unicode: &'static str, ascii: &'static str) -> &'static str {
+    if is_ascii() { ascii } else { unicode }
+}
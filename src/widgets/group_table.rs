@@ -0,0 +1,132 @@
+use std::cmp::Ordering;
+use std::ops::{Deref, DerefMut};
+use std::sync::{Arc, Mutex};
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::Color,
+    widgets::Widget,
+};
+
+use crate::core::monitor::{ConnectionMonitor, GroupMetrics};
+use crate::core::filters::RowAttributes;
+use crate::core::views::GROUP_VIEW;
+use super::metrics_table::{MetricsTable, pin_first};
+use super::table::{Column, Highlight, TableRow, format_age, highlight_color};
+
+/// Columns this table can show, in picker order.
+pub const AVAILABLE_COLUMNS: [Column; 5] = [Column::Group, Column::Active, Column::Total, Column::Max, Column::Duration];
+
+const DEFAULT_COLUMNS: [Column; 4] = [Column::Group, Column::Active, Column::Total, Column::Max];
+
+impl TableRow for GroupMetrics {
+    const KEY_COLUMNS: &'static [Column] = &[Column::Group];
+
+    fn cell(&self, column: Column) -> String {
+        match column {
+            Column::Group => self.group.clone(),
+            Column::Active => self.current_connections.to_string(),
+            Column::Total => self.total_connections.to_string(),
+            Column::Max => self.max_concurrent.to_string(),
+            Column::Duration => format_age(self.first_seen),
+            _ => String::new(),
+        }
+    }
+
+    fn compare(&self, other: &Self, column: Column) -> Ordering {
+        match column {
+            Column::Group => self.group.cmp(&other.group),
+            Column::Active => self.current_connections.cmp(&other.current_connections),
+            Column::Total => self.total_connections.cmp(&other.total_connections),
+            Column::Max => self.max_concurrent.cmp(&other.max_concurrent),
+            // Oldest first is the longest duration
+            Column::Duration => other.first_seen.cmp(&self.first_seen),
+            _ => Ordering::Equal,
+        }
+    }
+
+    fn color(&self, highlights: &[Highlight]) -> Option<Color> {
+        let row = RowAttributes {
+            group: Some(&self.group),
+            ..Default::default()
+        };
+        highlight_color(highlights, &row)
+    }
+}
+
+pub struct GroupTableWidget {
+    monitor: Arc<Mutex<ConnectionMonitor>>,
+    table: MetricsTable<GroupMetrics>,
+    /// Name of the aggregation view shown.
+    view: String,
+}
+
+impl GroupTableWidget {
+    pub fn new(monitor: Arc<Mutex<ConnectionMonitor>>) -> Self {
+        Self {
+            monitor,
+            table: MetricsTable::new("Connections by Group", &AVAILABLE_COLUMNS, &DEFAULT_COLUMNS),
+            view: GROUP_VIEW.to_string(),
+        }
+    }
+
+    /// Shows the rows of another aggregation view, titled and headed by its key column.
+    pub fn set_view(&mut self, view: String) {
+        let column = self.monitor.lock().ok()
+            .and_then(|monitor| monitor.view_column(&view).map(str::to_string))
+            .unwrap_or_else(|| Column::Group.label().to_string());
+        self.table.set_title(format!("Connections by {}", column));
+        self.table.set_label(Column::Group, column);
+        self.table.scroll_to_top();
+        self.view = view;
+    }
+
+    pub fn view(&self) -> &str {
+        &self.view
+    }
+
+    pub fn sorted_metrics(&self, monitor: &ConnectionMonitor) -> Vec<(GroupMetrics, bool)> {
+        let mut rows = monitor.get_view_metrics(&self.view, self.filter());
+        self.table.sort(&mut rows);
+        pin_first(rows, |_| false)
+    }
+
+    pub fn selected_metrics(&self) -> Option<GroupMetrics> {
+        let monitor = self.monitor.lock().ok()?;
+        self.table.selected_row(self.sorted_metrics(&monitor))
+    }
+
+    /// Text of the selected row's visible columns.
+    pub fn selected_cells(&self) -> Option<Vec<String>> {
+        Some(self.table.cells(&self.selected_metrics()?))
+    }
+}
+
+impl Deref for GroupTableWidget {
+    type Target = MetricsTable<GroupMetrics>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.table
+    }
+}
+
+impl DerefMut for GroupTableWidget {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.table
+    }
+}
+
+impl Widget for &GroupTableWidget {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let Ok(monitor_guard) = self.monitor.lock() else {
+            return;
+        };
+        let rows = self.sorted_metrics(&monitor_guard);
+        if self.view == GROUP_VIEW {
+            self.table.render(&rows, area, buf);
+        } else {
+            // Highlight rules match endpoint groups, not the keys of other views
+            self.table.render_with(&rows, &[], area, buf);
+        }
+    }
+}
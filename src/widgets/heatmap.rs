@@ -0,0 +1,173 @@
+use std::sync::{Arc, Mutex};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Rect, Layout, Direction, Constraint},
+    style::{Stylize, Style, Color},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Paragraph, Widget, Clear},
+};
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind};
+
+use crate::core::heatmap::{Heatmap, HeatmapLayout};
+use crate::core::monitor::ConnectionMonitor;
+use super::glyphs;
+
+/// Rows shown at most: a day of hours, or a week of days.
+fn max_rows(layout: HeatmapLayout) -> usize {
+    match layout {
+        HeatmapLayout::HoursByMinutes => 24,
+        HeatmapLayout::DaysByHours => 7,
+    }
+}
+
+/// Glyph for a cell, darker for more opens; empty cells stay blank.
+fn shade(value: u64, max: u64) -> &'static str {
+    const UNICODE: [&str; 4] = ["░", "▒", "▓", "█"];
+    const ASCII: [&str; 4] = [".", ":", "*", "#"];
+    if value == 0 || max == 0 {
+        return " ";
+    }
+    let level = ((value * 4).div_ceil(max) as usize).clamp(1, 4) - 1;
+    glyphs::glyph(UNICODE[level], ASCII[level])
+}
+
+/// Popup with connections opened per minute of each hour, or per hour of each day.
+pub struct HeatmapWidget {
+    monitor: Arc<Mutex<ConnectionMonitor>>,
+    layout: Option<HeatmapLayout>,
+}
+
+impl HeatmapWidget {
+    pub fn new(monitor: Arc<Mutex<ConnectionMonitor>>) -> Self {
+        Self { monitor, layout: None }
+    }
+
+    pub fn show(&mut self) {
+        self.layout = Some(HeatmapLayout::HoursByMinutes);
+    }
+
+    pub fn hide(&mut self) {
+        self.layout = None;
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.layout.is_some()
+    }
+
+    pub fn handle_key_event(&mut self, key_event: KeyEvent) {
+        if key_event.kind != KeyEventKind::Press {
+            return;
+        }
+        match key_event.code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('w') => self.hide(),
+            KeyCode::Tab => {
+                self.layout = self.layout.map(|layout| match layout {
+                    HeatmapLayout::HoursByMinutes => HeatmapLayout::DaysByHours,
+                    HeatmapLayout::DaysByHours => HeatmapLayout::HoursByMinutes,
+                });
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Widget for &HeatmapWidget {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let Some(layout) = self.layout else {
+            return;
+        };
+
+        let popup_width = area.width.saturating_sub(4).min(80);
+        let popup_height = area.height.saturating_sub(4).min(max_rows(layout) as u16 + 6);
+        let popup_area = Rect {
+            x: area.x + (area.width.saturating_sub(popup_width)) / 2,
+            y: area.y + (area.height.saturating_sub(popup_height)) / 2,
+            width: popup_width,
+            height: popup_height,
+        };
+
+        Clear.render(popup_area, buf);
+
+        let title = match layout {
+            HeatmapLayout::HoursByMinutes => "Connections Opened per Minute",
+            HeatmapLayout::DaysByHours => "Connections Opened per Hour",
+        };
+        let block = Block::bordered()
+            .title(title)
+            .title_style(Style::new().bold().fg(Color::Yellow))
+            .border_set(glyphs::border_set(BorderType::Plain))
+            .border_style(Style::new().fg(Color::Yellow));
+        let inner_area = block.inner(popup_area);
+        block.render(popup_area, buf);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(1), // Column scale
+                Constraint::Min(1),    // Rows
+                Constraint::Length(1), // Legend
+                Constraint::Length(1), // Instructions
+            ])
+            .split(inner_area);
+
+        let rows = max_rows(layout).min(chunks[1].height as usize).max(1);
+        let heatmap = match self.monitor.lock() {
+            Ok(monitor) => monitor.get_activity_heatmap(layout, rows),
+            Err(_) => return,
+        };
+
+        // Hour columns are two cells wide so the day fills the width like the minutes do
+        let (label_format, cell_width) = match layout {
+            HeatmapLayout::HoursByMinutes => ("%a %H:00", 1),
+            HeatmapLayout::DaysByHours => ("%a %d %b", 2),
+        };
+        let gray = Style::new().fg(Color::Gray);
+        let scale: String = (0..layout.columns())
+            .map(|column| {
+                let step = match layout {
+                    HeatmapLayout::HoursByMinutes => 10,
+                    HeatmapLayout::DaysByHours => 3,
+                };
+                if column % step == 0 {
+                    format!("{:<width$}", column, width = cell_width)
+                } else {
+                    " ".repeat(cell_width)
+                }
+            })
+            .collect();
+        Paragraph::new(Line::styled(format!("{:<10} {}", "", scale), gray))
+            .render(chunks[0], buf);
+
+        let lines: Vec<Line> = heatmap.rows.iter()
+            .map(|row| {
+                let cells: String = row.cells.iter()
+                    .map(|value| shade(*value, heatmap.max).repeat(cell_width))
+                    .collect();
+                Line::from(vec![
+                    Span::styled(format!("{:<10} ", row.start.format(label_format)), gray),
+                    Span::styled(cells, Style::new().fg(Color::Cyan)),
+                ])
+            })
+            .collect();
+        Paragraph::new(lines).render(chunks[1], buf);
+
+        Paragraph::new(legend(&heatmap)).render(chunks[2], buf);
+        Paragraph::new("Tab: Hours/Days  Esc/w: Close")
+            .style(gray)
+            .render(chunks[3], buf);
+    }
+}
+
+fn legend(heatmap: &Heatmap) -> Line<'static> {
+    let mut spans = vec![Span::styled("Opens: ", Style::new().fg(Color::Gray))];
+    if heatmap.max == 0 {
+        spans.push(Span::raw("none yet"));
+        return Line::from(spans);
+    }
+    for level in 1..=4u64 {
+        let upper = (heatmap.max * level).div_ceil(4);
+        spans.push(Span::styled(shade(upper, heatmap.max), Style::new().fg(Color::Cyan)));
+        spans.push(Span::raw(format!(" {}{} ", glyphs::glyph("≤", "<="), upper)));
+    }
+    Line::from(spans)
+}
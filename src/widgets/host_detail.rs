@@ -0,0 +1,248 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+use chrono::{DateTime, Local};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Rect, Layout, Direction, Constraint},
+    style::{Stylize, Style, Color},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Paragraph, Widget, Clear},
+};
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind};
+
+use crate::core::events::ConnectionEventKind;
+use crate::core::filters::ConnectionFilter;
+use crate::core::monitor::ConnectionMonitor;
+use super::glyphs;
+
+/// Recent events listed for the host.
+const MAX_EVENTS: usize = 50;
+
+/// Connections listed with their state timelines.
+const MAX_CONNECTIONS: usize = 8;
+
+/// Formats a time spent in a state, e.g. `12ms`, `34s`, `5m07s`, `2h03m`.
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    match secs {
+        0 => format!("{}ms", duration.as_millis()),
+        1..=59 => format!("{}s", secs),
+        60..=3599 => format!("{}m{:02}s", secs / 60, secs % 60),
+        _ => format!("{}h{:02}m", secs / 3600, secs % 3600 / 60),
+    }
+}
+
+/// Popup with the processes, connections, addresses and recent activity behind one host row.
+pub struct HostDetailWidget {
+    monitor: Arc<Mutex<ConnectionMonitor>>,
+    filter: ConnectionFilter,
+    target: Option<(String, u16)>,
+}
+
+impl HostDetailWidget {
+    pub fn new(monitor: Arc<Mutex<ConnectionMonitor>>) -> Self {
+        Self {
+            monitor,
+            filter: ConnectionFilter::default(),
+            target: None,
+        }
+    }
+
+    /// Opens the view for `host`:`port`, limited to connections matching `filter`.
+    pub fn show(&mut self, host: String, port: u16, filter: ConnectionFilter) {
+        self.target = Some((host, port));
+        self.filter = filter;
+    }
+
+    pub fn hide(&mut self) {
+        self.target = None;
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.target.is_some()
+    }
+
+    pub fn handle_key_event(&mut self, key_event: KeyEvent) {
+        if key_event.kind != KeyEventKind::Press {
+            return;
+        }
+        if matches!(key_event.code, KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q')) {
+            self.hide();
+        }
+    }
+}
+
+impl Widget for &HostDetailWidget {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let Some((host, port)) = &self.target else {
+            return;
+        };
+        let (detail, idle_threshold) = match self.monitor.lock() {
+            Ok(monitor) => (monitor.get_host_detail(&self.filter, host, *port, MAX_EVENTS), monitor.idle_threshold()),
+            Err(_) => return,
+        };
+        let now = SystemTime::now();
+
+        let popup_width = area.width.saturating_sub(4).min(100);
+        let popup_height = area.height.saturating_sub(4).min(40);
+
+        let popup_area = Rect {
+            x: area.x + (area.width.saturating_sub(popup_width)) / 2,
+            y: area.y + (area.height.saturating_sub(popup_height)) / 2,
+            width: popup_width,
+            height: popup_height,
+        };
+
+        Clear.render(popup_area, buf);
+
+        let block = Block::bordered()
+            .title(format!("Host: {}:{}", host, port))
+            .title_style(Style::new().bold().fg(Color::Yellow))
+            .border_set(glyphs::border_set(BorderType::Plain))
+            .border_style(Style::new().fg(Color::Yellow));
+
+        let inner_area = block.inner(popup_area);
+        block.render(popup_area, buf);
+
+        let process_rows = detail.processes.len().max(1) as u16 + 1;
+        let connection_rows = detail.connections.len().clamp(1, MAX_CONNECTIONS) as u16 + 1;
+        let layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(1),               // Addresses
+                Constraint::Length(1),               // Concurrency
+                Constraint::Length(1),
+                Constraint::Length(process_rows),    // Processes
+                Constraint::Length(1),
+                Constraint::Length(connection_rows), // Connections and their state timelines
+                Constraint::Length(1),
+                Constraint::Min(2),                  // Recent activity
+                Constraint::Length(1),               // Instructions
+            ])
+            .split(inner_area);
+
+        let heading = Style::new().bold().fg(Color::White);
+
+        let addresses = if detail.addresses.is_empty() {
+            "none".to_string()
+        } else {
+            detail.addresses.iter().map(|addr| addr.to_string()).collect::<Vec<_>>().join(", ")
+        };
+        Paragraph::new(Line::from(vec![
+            Span::styled("Addresses: ", heading),
+            Span::raw(addresses),
+        ]))
+        .render(layout[0], buf);
+
+        let concurrency = match &detail.metrics {
+            Some(metrics) => format!(
+                "avg {:.1}, p95 {}, max {} (1m {}, 5m {}, 1h {})",
+                metrics.sampled.average, metrics.sampled.p95, metrics.max_concurrent,
+                metrics.recent_max.last_1m, metrics.recent_max.last_5m, metrics.recent_max.last_1h,
+            ),
+            None => "none".to_string(),
+        };
+        Paragraph::new(Line::from(vec![
+            Span::styled("Concurrency: ", heading),
+            Span::raw(concurrency),
+        ]))
+        .render(layout[1], buf);
+
+        let mut process_lines = vec![Line::styled(
+            format!("{:<8} {:<30} {:>7} {:>7}", "PID", "Process", "Active", "Total"),
+            heading,
+        )];
+        if detail.processes.is_empty() {
+            process_lines.push(Line::styled("No processes match the current filter", Style::new().fg(Color::Gray)));
+        }
+        for metrics in &detail.processes {
+            let pid_style = if metrics.is_alive {
+                Style::new().fg(Color::Green)
+            } else {
+                Style::new().fg(Color::Red)
+            };
+            process_lines.push(Line::from(vec![
+                Span::styled(format!("{:<8} ", metrics.pid), pid_style),
+                Span::raw(format!("{:<30} {:>7} {:>7}", metrics.process_name,
+                    metrics.current_connections, metrics.total_connections)),
+            ]));
+        }
+        Paragraph::new(process_lines).render(layout[3], buf);
+
+        let mut connection_lines = vec![Line::styled("Connections", heading)];
+        if detail.connections.is_empty() {
+            connection_lines.push(Line::styled("No connections match the current filter", Style::new().fg(Color::Gray)));
+        }
+        for conn in detail.connections.iter().take(MAX_CONNECTIONS) {
+            // UDP sockets have no states to follow
+            let timeline = if conn.udp {
+                "UDP".to_string()
+            } else {
+                conn.state_durations().iter()
+                    .map(|(state, duration)| format!("{} {}", state, format_duration(*duration)))
+                    .collect::<Vec<_>>()
+                    .join(&format!(" {} ", glyphs::glyph("→", "->")))
+            };
+            let status = if conn.closed {
+                Span::styled(" (closed)", Style::new().fg(Color::Red))
+            } else if let Some(idle) = conn.idle_for(now) {
+                let color = if idle >= idle_threshold { Color::Yellow } else { Color::Gray };
+                Span::styled(format!(" idle for {}", format_duration(idle)), Style::new().fg(color))
+            } else {
+                Span::raw("")
+            };
+            let threads = match &conn.threads {
+                Some(threads) => Span::styled(format!(" [threads: {}]", threads), Style::new().fg(Color::Cyan)),
+                None => Span::raw(""),
+            };
+            let mut socket = Vec::new();
+            if let Some(inode) = conn.inode {
+                socket.push(format!("inode {}", inode));
+            }
+            if let Some(fd) = conn.fd {
+                socket.push(format!("fd {}", fd));
+            }
+            let socket = if socket.is_empty() {
+                Span::raw("")
+            } else {
+                Span::styled(format!(" ({})", socket.join(", ")), Style::new().fg(Color::Gray))
+            };
+            connection_lines.push(Line::from(vec![
+                Span::styled(format!(":{:<6} {:<15} ", conn.local_port, conn.remote_addr), Style::new().fg(Color::Gray)),
+                Span::raw(timeline),
+                status,
+                socket,
+                threads,
+            ]));
+        }
+        Paragraph::new(connection_lines).render(layout[5], buf);
+
+        // Newest events at the bottom, showing as many as fit under the heading
+        let visible_events = layout[7].height.saturating_sub(1) as usize;
+        let skip = detail.events.len().saturating_sub(visible_events);
+        let mut event_lines = vec![Line::styled("Recent Activity", heading)];
+        if detail.events.is_empty() {
+            event_lines.push(Line::styled("No opens or closes recorded yet", Style::new().fg(Color::Gray)));
+        }
+        for event in detail.events.iter().skip(skip) {
+            let conn = &event.connection;
+            let time: DateTime<Local> = event.time.into();
+            let (kind, kind_style) = match event.kind {
+                ConnectionEventKind::Opened => ("opened", Style::new().fg(Color::Green)),
+                ConnectionEventKind::Closed => ("closed", Style::new().fg(Color::Red)),
+            };
+            event_lines.push(Line::from(vec![
+                Span::styled(time.format("%H:%M:%S ").to_string(), Style::new().fg(Color::Gray)),
+                Span::styled(format!("{:<7}", kind), kind_style),
+                Span::raw(format!("{}({}) {} {}:{}",
+                    event.process_name.as_deref().unwrap_or("Unknown"), conn.pid,
+                    glyphs::glyph("→", "->"), conn.remote_addr, conn.remote_port)),
+            ]));
+        }
+        Paragraph::new(event_lines).render(layout[7], buf);
+
+        Paragraph::new("Esc/Enter: Close")
+            .style(Style::new().fg(Color::Gray))
+            .render(layout[8], buf);
+    }
+}
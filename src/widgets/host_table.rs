@@ -1,131 +1,176 @@
+use std::cmp::Ordering;
+use std::ops::{Deref, DerefMut};
 use std::sync::{Arc, Mutex};
 use ratatui::{
     buffer::Buffer,
-    layout::{Rect, Constraint},
+    layout::Rect,
     style::{Stylize, Style, Color},
-    widgets::{Block, Table, Row, Cell, Widget, BorderType},
+    widgets::Widget,
 };
 
-use crate::core::monitor::ConnectionMonitor;
-use crate::core::filters::ConnectionFilter;
-use crate::app::SortBy;
+use crate::core::monitor::{ConnectionMonitor, HostMetrics};
+use crate::core::filters::RowAttributes;
+use crate::core::watchlist::Watchlist;
+use super::metrics_table::{MetricsTable, pin_first};
+use super::table::{ANOMALY_COLOR, Column, Highlight, TableRow, compare_script_values, format_age, highlight_color};
+use super::glyphs;
+
+/// Columns this table can show, in picker order.
+pub const AVAILABLE_COLUMNS: [Column; 20] = [Column::Host, Column::Port, Column::Active, Column::Total, Column::Max, Column::Max1m, Column::Max5m, Column::Max1h, Column::Average, Column::P95, Column::RemoteIps, Column::LocalPorts, Column::Retransmits, Column::Scope, Column::Tunnel, Column::Nat, Column::Duration, Column::FirstSeen, Column::LastSeen, Column::Script];
+
+const DEFAULT_COLUMNS: [Column; 5] = [Column::Host, Column::Port, Column::Active, Column::Total, Column::Max];
+
+impl TableRow for HostMetrics {
+    const KEY_COLUMNS: &'static [Column] = &[Column::Host];
+
+    fn cell(&self, column: Column) -> String {
+        match column {
+            Column::Host => {
+                let mut host = self.host.clone();
+                if self.blocklist.is_some() {
+                    host = format!("{} {}", glyphs::glyph("✖", "X"), host);
+                }
+                if self.proxied {
+                    host = format!("{} (proxy)", host);
+                }
+                host
+            }
+            Column::Port => self.port.to_string(),
+            Column::Active => self.current_connections.to_string(),
+            Column::Total => self.total_connections.to_string(),
+            Column::Max => self.max_concurrent.to_string(),
+            Column::Max1m => self.recent_max.last_1m.to_string(),
+            Column::Max5m => self.recent_max.last_5m.to_string(),
+            Column::Max1h => self.recent_max.last_1h.to_string(),
+            Column::Average => format!("{:.1}", self.sampled.average),
+            Column::P95 => self.sampled.p95.to_string(),
+            Column::Scope => self.scope.to_string(),
+            Column::Tunnel => self.tunnel.clone().unwrap_or_else(|| "-".to_string()),
+            Column::Nat => self.nat.as_ref().map_or_else(|| "-".to_string(), |addr| format!("via {}", addr)),
+            Column::Script => self.script.clone().unwrap_or_else(|| "-".to_string()),
+            // Snapshots taken before these were recorded have them at 0
+            Column::RemoteIps if self.remote_ips == 0 => "-".to_string(),
+            Column::RemoteIps => self.remote_ips.to_string(),
+            Column::LocalPorts if self.local_ports == 0 => "-".to_string(),
+            Column::LocalPorts => self.local_ports.to_string(),
+            Column::Retransmits => retransmit_rate(self).map_or_else(|| "-".to_string(), |rate| format!("{:.1}%", rate * 100.0)),
+            Column::Duration => format_age(self.first_seen),
+            Column::FirstSeen => format!("{} ago", format_age(self.first_seen)),
+            Column::LastSeen if self.current_connections > 0 => "now".to_string(),
+            Column::LastSeen => self.last_seen.map_or_else(|| "-".to_string(), |at| format!("{} ago", format_age(at))),
+            _ => String::new(),
+        }
+    }
+
+    fn compare(&self, other: &Self, column: Column) -> Ordering {
+        match column {
+            Column::Host => self.host.cmp(&other.host),
+            Column::Port => self.port.cmp(&other.port),
+            Column::Active => self.current_connections.cmp(&other.current_connections),
+            Column::Total => self.total_connections.cmp(&other.total_connections),
+            Column::Max => self.max_concurrent.cmp(&other.max_concurrent),
+            Column::Max1m => self.recent_max.last_1m.cmp(&other.recent_max.last_1m),
+            Column::Max5m => self.recent_max.last_5m.cmp(&other.recent_max.last_5m),
+            Column::Max1h => self.recent_max.last_1h.cmp(&other.recent_max.last_1h),
+            Column::Average => self.sampled.average.total_cmp(&other.sampled.average),
+            Column::P95 => self.sampled.p95.cmp(&other.sampled.p95),
+            Column::Scope => self.scope.cmp(&other.scope),
+            Column::Tunnel => self.tunnel.cmp(&other.tunnel),
+            Column::Nat => self.nat.cmp(&other.nat),
+            Column::Script => compare_script_values(&self.script, &other.script),
+            Column::RemoteIps => self.remote_ips.cmp(&other.remote_ips),
+            Column::LocalPorts => self.local_ports.cmp(&other.local_ports),
+            Column::Retransmits => retransmit_rate(self).unwrap_or(-1.0).total_cmp(&retransmit_rate(other).unwrap_or(-1.0)),
+            // Oldest first is the longest duration
+            Column::Duration => other.first_seen.cmp(&self.first_seen),
+            Column::FirstSeen => self.first_seen.cmp(&other.first_seen),
+            Column::LastSeen => (self.current_connections > 0, self.last_seen)
+                .cmp(&(other.current_connections > 0, other.last_seen)),
+            _ => Ordering::Equal,
+        }
+    }
+
+    fn color(&self, highlights: &[Highlight]) -> Option<Color> {
+        let row = RowAttributes {
+            host: Some(&self.host),
+            port: Some(self.port),
+            scope: Some(self.scope),
+            tunnel: Some(self.tunnel.as_deref().unwrap_or("")),
+            ..Default::default()
+        };
+        highlight_color(highlights, &row).or(self.anomaly_score.map(|_| ANOMALY_COLOR))
+    }
+
+    fn cell_style(&self, column: Column) -> Option<Style> {
+        match column {
+            Column::Host if self.blocklist.is_some() => Some(Style::new().fg(Color::Red).bold()),
+            Column::Retransmits if self.high_retransmits => Some(Style::new().fg(Color::LightRed).bold()),
+            _ => None,
+        }
+    }
+}
+
+/// Share of the segments sent to the host that were retransmitted, when known.
+fn retransmit_rate(metrics: &HostMetrics) -> Option<f64> {
+    metrics.retransmits.and_then(|retransmits| retransmits.rate())
+}
 
 pub struct HostTableWidget {
     monitor: Arc<Mutex<ConnectionMonitor>>,
-    filter: ConnectionFilter,
-    sort_by: SortBy,
-    scroll_offset: usize,
+    table: MetricsTable<HostMetrics>,
+    watchlist: Watchlist,
 }
 
 impl HostTableWidget {
     pub fn new(monitor: Arc<Mutex<ConnectionMonitor>>) -> Self {
         Self {
             monitor,
-            filter: ConnectionFilter::default(),
-            sort_by: SortBy::Total,
-            scroll_offset: 0,
+            table: MetricsTable::new("Connections by Host", &AVAILABLE_COLUMNS, &DEFAULT_COLUMNS),
+            watchlist: Watchlist::default(),
         }
     }
 
-    pub fn set_filter(&mut self, filter: ConnectionFilter) {
-        self.filter = filter;
-        self.scroll_offset = 0;
+    pub fn set_watchlist(&mut self, watchlist: Watchlist) {
+        self.watchlist = watchlist;
     }
 
-    pub fn set_sort_by(&mut self, sort_by: SortBy) {
-        self.sort_by = sort_by;
-        self.scroll_offset = 0;
+    /// Sorted rows with pinned hosts first, paired with their pinned flag.
+    pub fn sorted_metrics(&self, monitor: &ConnectionMonitor) -> Vec<(HostMetrics, bool)> {
+        let mut host_metrics = monitor.get_host_metrics(self.filter());
+        self.table.sort(&mut host_metrics);
+        pin_first(host_metrics, |metrics| self.watchlist.is_host_pinned(&metrics.host, metrics.port))
     }
 
-    pub fn scroll_up(&mut self, amount: usize) {
-        self.scroll_offset = self.scroll_offset.saturating_sub(amount);
+    pub fn selected_metrics(&self) -> Option<HostMetrics> {
+        let monitor = self.monitor.lock().ok()?;
+        self.table.selected_row(self.sorted_metrics(&monitor))
     }
 
-    pub fn scroll_down(&mut self, amount: usize, total_rows: usize, visible_rows: usize) {
-        let max_scroll = total_rows.saturating_sub(visible_rows);
-        self.scroll_offset = (self.scroll_offset + amount).min(max_scroll);
+    /// Text of the selected row's visible columns.
+    pub fn selected_cells(&self) -> Option<Vec<String>> {
+        Some(self.table.cells(&self.selected_metrics()?))
     }
+}
 
-    pub fn scroll_to_top(&mut self) {
-        self.scroll_offset = 0;
+impl Deref for HostTableWidget {
+    type Target = MetricsTable<HostMetrics>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.table
     }
+}
 
-    pub fn scroll_to_bottom(&mut self, total_rows: usize, visible_rows: usize) {
-        let max_scroll = total_rows.saturating_sub(visible_rows);
-        self.scroll_offset = max_scroll;
+impl DerefMut for HostTableWidget {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.table
     }
 }
 
 impl Widget for &HostTableWidget {
     fn render(self, area: Rect, buf: &mut Buffer) {
-        let monitor_guard = match self.monitor.lock() {
-            Ok(guard) => guard,
-            Err(_) => return,
+        let Ok(monitor_guard) = self.monitor.lock() else {
+            return;
         };
-
-        let mut host_metrics = monitor_guard.get_host_metrics(&self.filter);
-
-        match self.sort_by {
-            SortBy::Total => {
-                host_metrics.sort_by(|a, b| b.total_connections.cmp(&a.total_connections)
-                    .then_with(|| a.host.cmp(&b.host)));
-            },
-            SortBy::Active => {
-                host_metrics.sort_by(|a, b| b.current_connections.cmp(&a.current_connections)
-                    .then_with(|| a.host.cmp(&b.host)));
-            },
-            SortBy::Max => {
-                host_metrics.sort_by(|a, b| b.max_concurrent.cmp(&a.max_concurrent)
-                    .then_with(|| a.host.cmp(&b.host)));
-            },
-        }
-
-        let content_height = area.height.saturating_sub(3);
-        let visible_rows = content_height as usize;
-        let total_rows = host_metrics.len();
-        
-        let start_idx = self.scroll_offset;
-        let end_idx = (start_idx + visible_rows).min(total_rows);
-        let visible_metrics = &host_metrics[start_idx..end_idx];
-        
-        let rows: Vec<Row> = visible_metrics.iter().map(|metrics| {
-            Row::new(vec![
-                Cell::from(metrics.host.clone()),
-                Cell::from(metrics.port.to_string()),
-                Cell::from(metrics.current_connections.to_string()),
-                Cell::from(metrics.total_connections.to_string()),
-                Cell::from(metrics.max_concurrent.to_string()),
-            ])
-        }).collect();
-        
-        let widths = [
-            Constraint::Percentage(60),
-            Constraint::Percentage(10),
-            Constraint::Percentage(10),
-            Constraint::Percentage(10),
-            Constraint::Percentage(10),
-        ];
-        
-        let table = Table::new(rows, widths)
-            .header(
-                Row::new(vec![
-                    "Remote Host",
-                    "Port",
-                    "Active",
-                    "Total",
-                    "Max",
-                ])
-                .style(Style::new().bold().fg(Color::White))
-                .bottom_margin(1)
-            )
-            .block(
-                Block::bordered()
-                    .title("Connections by Host")
-                    .title_style(Style::new().bold().fg(Color::Cyan))
-                    .border_type(BorderType::Plain)
-                    .border_style(Style::new().fg(Color::Blue))
-            );
-        
-        table.render(area, buf);
+        self.table.render(&self.sorted_metrics(&monitor_guard), area, buf);
     }
-}
\ No newline at end of file
+}
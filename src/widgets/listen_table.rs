@@ -0,0 +1,131 @@
+use std::cmp::Ordering;
+use std::ops::{Deref, DerefMut};
+use std::sync::{Arc, Mutex};
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Style, Stylize},
+    widgets::Widget,
+};
+
+use crate::core::monitor::{ConnectionMonitor, ListenPortMetrics};
+use crate::core::filters::RowAttributes;
+use super::metrics_table::{MetricsTable, pin_first};
+use super::table::{Column, Highlight, TableRow, format_age, highlight_color};
+
+/// Columns this table can show, in picker order.
+pub const AVAILABLE_COLUMNS: [Column; 8] = [Column::Port, Column::Process, Column::Active, Column::Total, Column::Max, Column::AcceptQueue, Column::Clients, Column::Duration];
+
+const DEFAULT_COLUMNS: [Column; 7] = [Column::Port, Column::Process, Column::Active, Column::Total, Column::Max, Column::AcceptQueue, Column::Clients];
+
+impl TableRow for ListenPortMetrics {
+    const KEY_COLUMNS: &'static [Column] = &[Column::Port];
+
+    fn cell(&self, column: Column) -> String {
+        match column {
+            Column::Port => self.port.to_string(),
+            Column::Process => self.processes.join(", "),
+            Column::Active => self.current_connections.to_string(),
+            Column::Total => self.total_connections.to_string(),
+            Column::Max => self.max_concurrent.to_string(),
+            Column::Clients => self.top_clients.iter()
+                .map(|(addr, count)| format!("{} ({})", addr, count))
+                .collect::<Vec<_>>()
+                .join(", "),
+            Column::Duration => format_age(self.first_seen),
+            Column::AcceptQueue => self.accept_queue.map_or_else(|| "-".to_string(), |queue| queue.to_string()),
+            _ => String::new(),
+        }
+    }
+
+    fn compare(&self, other: &Self, column: Column) -> Ordering {
+        match column {
+            Column::Port => self.port.cmp(&other.port),
+            Column::Process => self.processes.cmp(&other.processes),
+            Column::Active => self.current_connections.cmp(&other.current_connections),
+            Column::Total => self.total_connections.cmp(&other.total_connections),
+            Column::Max => self.max_concurrent.cmp(&other.max_concurrent),
+            Column::Clients => self.top_clients.len().cmp(&other.top_clients.len()),
+            // Oldest first is the longest duration
+            Column::Duration => other.first_seen.cmp(&self.first_seen),
+            Column::AcceptQueue => saturation(self).total_cmp(&saturation(other)),
+            _ => Ordering::Equal,
+        }
+    }
+
+    fn color(&self, highlights: &[Highlight]) -> Option<Color> {
+        let row = RowAttributes {
+            process_name: self.processes.first().map(String::as_str),
+            ..Default::default()
+        };
+        highlight_color(highlights, &row)
+    }
+
+    fn cell_style(&self, column: Column) -> Option<Style> {
+        match (column, self.accept_queue) {
+            (Column::AcceptQueue, _) if self.queue_saturated => Some(Style::new().fg(Color::LightRed).bold()),
+            (Column::AcceptQueue, Some(queue)) if queue.queued > 0 => Some(Style::new().fg(Color::Yellow)),
+            _ => None,
+        }
+    }
+}
+
+/// Share of the backlog in use, unknown queues sorting below empty ones.
+fn saturation(metrics: &ListenPortMetrics) -> f64 {
+    metrics.accept_queue.map_or(-1.0, |queue| queue.saturation())
+}
+
+/// Connections accepted per local listening port, toggled with `i`.
+pub struct ListenTableWidget {
+    monitor: Arc<Mutex<ConnectionMonitor>>,
+    table: MetricsTable<ListenPortMetrics>,
+}
+
+impl ListenTableWidget {
+    pub fn new(monitor: Arc<Mutex<ConnectionMonitor>>) -> Self {
+        Self {
+            monitor,
+            table: MetricsTable::new("Inbound by Listening Port", &AVAILABLE_COLUMNS, &DEFAULT_COLUMNS),
+        }
+    }
+
+    /// Sorted rows, paired with their pinned flag.
+    pub fn sorted_metrics(&self, monitor: &ConnectionMonitor) -> Vec<(ListenPortMetrics, bool)> {
+        let mut rows = monitor.get_listen_port_metrics(self.filter());
+        self.table.sort(&mut rows);
+        pin_first(rows, |_| false)
+    }
+
+    pub fn selected_metrics(&self) -> Option<ListenPortMetrics> {
+        let monitor = self.monitor.lock().ok()?;
+        self.table.selected_row(self.sorted_metrics(&monitor))
+    }
+
+    /// Text of the selected row's visible columns.
+    pub fn selected_cells(&self) -> Option<Vec<String>> {
+        Some(self.table.cells(&self.selected_metrics()?))
+    }
+}
+
+impl Deref for ListenTableWidget {
+    type Target = MetricsTable<ListenPortMetrics>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.table
+    }
+}
+
+impl DerefMut for ListenTableWidget {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.table
+    }
+}
+
+impl Widget for &ListenTableWidget {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let Ok(monitor_guard) = self.monitor.lock() else {
+            return;
+        };
+        self.table.render(&self.sorted_metrics(&monitor_guard), area, buf);
+    }
+}
@@ -0,0 +1,239 @@
+use std::cell::RefCell;
+use std::marker::PhantomData;
+use ratatui::{
+    buffer::Buffer,
+    layout::{Rect, Constraint},
+    style::{Stylize, Style, Color},
+    widgets::{Block, Table, Row, Cell, Widget, BorderType},
+};
+
+use crate::core::filters::ConnectionFilter;
+use crate::app::SortBy;
+use super::table::{Column, ColumnSort, Highlight, TableClick, TableCursor, TableRow, fit_widths, header_labels, row_style, select_columns, table_click};
+use super::glyphs;
+
+/// Column choice, sorting, selection and drawing shared by the metrics tables. A table
+/// only says where its rows come from and which are pinned; what a row shows in each
+/// column, and how it sorts by it, comes from its `TableRow` implementation.
+pub struct MetricsTable<T> {
+    title: String,
+    available: &'static [Column],
+    default: &'static [Column],
+    /// Header labels replacing the usual ones of their columns.
+    labels: Vec<(Column, String)>,
+    filter: ConnectionFilter,
+    sort_by: SortBy,
+    highlights: Vec<Highlight>,
+    cursor: TableCursor,
+    columns: Vec<Column>,
+    column_sort: Option<ColumnSort>,
+    widths: RefCell<Vec<Constraint>>, // As last drawn, for mapping clicks to columns
+    focused: bool,
+    rows: PhantomData<fn() -> T>,
+}
+
+impl<T: TableRow> MetricsTable<T> {
+    /// A table titled `title` offering the `available` columns, showing `default` until
+    /// others are picked.
+    pub fn new(title: &str, available: &'static [Column], default: &'static [Column]) -> Self {
+        Self {
+            title: title.to_string(),
+            available,
+            default,
+            labels: Vec::new(),
+            filter: ConnectionFilter::default(),
+            sort_by: SortBy::Total,
+            highlights: Vec::new(),
+            cursor: TableCursor::new(),
+            columns: default.to_vec(),
+            column_sort: None,
+            widths: RefCell::new(Vec::new()),
+            focused: false,
+            rows: PhantomData,
+        }
+    }
+
+    pub fn set_title(&mut self, title: String) {
+        self.title = title;
+    }
+
+    /// Shows `label` as the header of `column` instead of its usual label.
+    pub fn set_label(&mut self, column: Column, label: String) {
+        self.labels.retain(|(labeled, _)| *labeled != column);
+        self.labels.push((column, label));
+    }
+
+    pub fn filter(&self) -> &ConnectionFilter {
+        &self.filter
+    }
+
+    pub fn set_filter(&mut self, filter: ConnectionFilter) {
+        self.filter = filter;
+        self.cursor.reset();
+    }
+
+    pub fn set_sort_by(&mut self, sort_by: SortBy) {
+        self.sort_by = sort_by;
+        self.column_sort = None;
+        self.cursor.reset();
+    }
+
+    pub fn set_highlights(&mut self, highlights: Vec<Highlight>) {
+        self.highlights = highlights;
+    }
+
+    pub fn set_focused(&mut self, focused: bool) {
+        self.focused = focused;
+    }
+
+    /// Shows the given columns in order, ignoring any this table doesn't have.
+    pub fn set_columns(&mut self, columns: &[Column]) {
+        self.columns = select_columns(columns, self.available, self.default);
+        if self.column_sort.is_some_and(|sort| !self.columns.contains(&sort.column)) {
+            self.column_sort = None;
+        }
+    }
+
+    pub fn columns(&self) -> &[Column] {
+        &self.columns
+    }
+
+    /// Sorts by a column, flipping the direction if it is already the sort column.
+    pub fn sort_by_column(&mut self, column: Column) {
+        self.column_sort = Some(ColumnSort::toggle(self.column_sort, column));
+        self.cursor.reset();
+    }
+
+    /// Sorts by a clicked header or selects a clicked row of the table drawn in `area`.
+    pub fn handle_click(&mut self, area: Rect, x: u16, y: u16, total_rows: usize) {
+        let click = table_click(area, &self.widths.borrow(), x, y);
+        match click {
+            Some(TableClick::Header(idx)) => {
+                if let Some(column) = self.columns.get(idx).copied() {
+                    self.sort_by_column(column);
+                }
+            }
+            Some(TableClick::Row(row)) => self.cursor.select_visible(row, total_rows),
+            None => {}
+        }
+    }
+
+    pub fn scroll_up(&mut self, amount: usize) {
+        self.cursor.move_up(amount);
+    }
+
+    pub fn scroll_down(&mut self, amount: usize, total_rows: usize) {
+        self.cursor.move_down(amount, total_rows);
+    }
+
+    pub fn scroll_to_top(&mut self) {
+        self.cursor.move_to_top();
+    }
+
+    pub fn scroll_to_bottom(&mut self, total_rows: usize) {
+        self.cursor.move_to_bottom(total_rows);
+    }
+
+    /// Sorts rows by the global sort, largest first with ties in key column order, then
+    /// by the clicked column, if any.
+    pub fn sort(&self, rows: &mut [T]) {
+        let column = match self.sort_by {
+            SortBy::Total => Column::Total,
+            SortBy::Active => Column::Active,
+            SortBy::Max => Column::Max,
+        };
+        rows.sort_by(|a, b| {
+            T::KEY_COLUMNS.iter().fold(b.compare(a, column), |ordering, key| ordering.then_with(|| a.compare(b, *key)))
+        });
+
+        if let Some(sort) = self.column_sort {
+            sort.apply(rows);
+        }
+    }
+
+    /// Index of the selected row among `total_rows`.
+    pub fn selected_index(&self, total_rows: usize) -> Option<usize> {
+        self.cursor.selected(total_rows)
+    }
+
+    /// The selected one of `rows`, as sorted and pinned for drawing.
+    pub fn selected_row(&self, rows: Vec<(T, bool)>) -> Option<T> {
+        let selected = self.cursor.selected(rows.len())?;
+        rows.into_iter().nth(selected).map(|(row, _)| row)
+    }
+
+    /// Text of the row's visible columns.
+    pub fn cells(&self, row: &T) -> Vec<String> {
+        self.columns.iter().map(|column| row.cell(*column)).collect()
+    }
+
+    /// Draws `rows`, paired with their pinned flag, with the table's highlight rules.
+    pub fn render(&self, rows: &[(T, bool)], area: Rect, buf: &mut Buffer) {
+        self.render_with(rows, &self.highlights, area, buf);
+    }
+
+    /// Draws `rows` coloring them by `highlights` instead of the table's own rules.
+    pub fn render_with(&self, rows: &[(T, bool)], highlights: &[Highlight], area: Rect, buf: &mut Buffer) {
+        let content_height = area.height.saturating_sub(3);
+        let visible_rows = content_height as usize;
+        let total_rows = rows.len();
+
+        let range = self.cursor.visible_range(total_rows, visible_rows);
+        let selected = self.cursor.selected(total_rows);
+        let start_idx = range.start;
+
+        let texts: Vec<Vec<String>> = rows.iter().map(|(row, _)| self.cells(row)).collect();
+        let header: Vec<String> = header_labels(&self.columns, self.column_sort).into_iter()
+            .zip(&self.columns)
+            .map(|(label, column)| match self.labels.iter().find(|(labeled, _)| labeled == column) {
+                Some((_, relabel)) => label.replacen(column.label(), relabel, 1),
+                None => label,
+            })
+            .collect();
+        let widths = fit_widths(&self.columns, &header, &texts, area.width.saturating_sub(2));
+        *self.widths.borrow_mut() = widths.clone();
+
+        let visible_rows = &rows[range.clone()];
+        let visible_texts = &texts[range];
+
+        let table_rows: Vec<Row> = visible_rows.iter().zip(visible_texts).enumerate().map(|(i, ((row, pinned), cells))| {
+            let cells = self.columns.iter().zip(cells).map(|(column, text)| {
+                let cell = Cell::from(text.as_str());
+                match row.cell_style(*column) {
+                    Some(style) => cell.style(style),
+                    None => cell,
+                }
+            });
+            Row::new(cells)
+            .style(row_style(*pinned, row.color(highlights), self.focused && selected == Some(start_idx + i)))
+        }).collect();
+
+        let table = Table::new(table_rows, widths)
+            .header(
+                Row::new(header)
+                .style(Style::new().bold().fg(Color::White))
+                .bottom_margin(1)
+            )
+            .block(
+                Block::bordered()
+                    .title(self.title.as_str())
+                    .title_style(Style::new().bold().fg(Color::Cyan))
+                    .border_set(glyphs::border_set(BorderType::Plain))
+                    .border_style(Style::new().fg(Color::Blue))
+            );
+
+        table.render(area, buf);
+    }
+}
+
+/// Pairs rows with whether they are pinned, pinned rows first, each part keeping its order.
+pub fn pin_first<T>(rows: Vec<T>, pinned: impl Fn(&T) -> bool) -> Vec<(T, bool)> {
+    let mut rows: Vec<(T, bool)> = rows.into_iter()
+        .map(|row| {
+            let pinned = pinned(&row);
+            (row, pinned)
+        })
+        .collect();
+    rows.sort_by_key(|(_, pinned)| !pinned);
+    rows
+}
@@ -4,10 +4,49 @@ pub mod process_table;
 pub mod summary_block;
 pub mod active_connections_graph;
 pub mod filter_selector;
+pub mod group_table;
+pub mod listen_table;
+pub mod client_table;
+pub mod table;
+pub mod metrics_table;
+pub mod event_log;
+pub mod query_prompt;
+pub mod text_prompt;
+pub mod reset_prompt;
+pub mod filter_picker;
+pub mod text_input;
+pub mod column_picker;
+pub mod glyphs;
+pub mod theme;
+pub mod host_detail;
+pub mod overhead;
+pub mod counters;
+pub mod top_bars;
+pub mod heatmap;
+pub mod flow;
+pub mod pinned_graphs;
+pub mod toasts;
 
 pub use self::host_table::HostTableWidget;
 pub use self::process_host_table::ProcessHostTableWidget;
 pub use self::process_table::ProcessTableWidget;
 pub use self::summary_block::SummaryWidget;
 pub use self::active_connections_graph::ActiveConnectionsGraphWidget;
-pub use self::filter_selector::FilterWidget;
\ No newline at end of file
+pub use self::filter_selector::FilterWidget;
+pub use self::group_table::GroupTableWidget;
+pub use self::listen_table::ListenTableWidget;
+pub use self::client_table::ClientTableWidget;
+pub use self::event_log::EventLogWidget;
+pub use self::query_prompt::QueryPromptWidget;
+pub use self::text_prompt::TextPromptWidget;
+pub use self::reset_prompt::ResetPromptWidget;
+pub use self::filter_picker::FilterPickerWidget;
+pub use self::column_picker::ColumnPickerWidget;
+pub use self::host_detail::HostDetailWidget;
+pub use self::overhead::OverheadWidget;
+pub use self::counters::CounterOverlayWidget;
+pub use self::top_bars::TopBarsWidget;
+pub use self::heatmap::HeatmapWidget;
+pub use self::flow::FlowWidget;
+pub use self::pinned_graphs::PinnedGraphsWidget;
+pub use self::toasts::ToastsWidget;
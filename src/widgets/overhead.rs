@@ -0,0 +1,59 @@
+use std::sync::{Arc, Mutex};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Rect, Alignment},
+    style::{Stylize, Style, Color},
+    text::{Line, Span, Text},
+    widgets::{Block, Paragraph, Widget, BorderType},
+};
+
+use crate::core::monitor::ConnectionMonitor;
+use super::glyphs;
+use super::table::format_bytes;
+
+/// tcpcount's own CPU, memory and refresh cost, toggled with `o`.
+pub struct OverheadWidget {
+    monitor: Arc<Mutex<ConnectionMonitor>>,
+}
+
+impl OverheadWidget {
+    pub fn new(monitor: Arc<Mutex<ConnectionMonitor>>) -> Self {
+        Self { monitor }
+    }
+}
+
+fn stat_line(label: &'static str, value: String) -> Line<'static> {
+    Line::from(vec![
+        Span::raw(label),
+        Span::styled(value, Style::default().fg(Color::Green).bold()),
+    ])
+}
+
+impl Widget for &OverheadWidget {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let stats = match self.monitor.lock() {
+            Ok(guard) => guard.overhead().clone(),
+            Err(_) => return,
+        };
+
+        let text = Text::from(vec![
+            stat_line("CPU: ", format!("{:.1}%", stats.cpu_percent)),
+            stat_line("RSS: ", format_bytes(stats.rss_bytes)),
+            stat_line("Refresh: ", format!("{:.1} ms", stats.refresh_ms)),
+            stat_line("Sockets: ", stats.sockets.to_string()),
+            stat_line("DNS: ", format!("{} in {:.1} ms", stats.dns_lookups, stats.dns_ms)),
+        ]);
+
+        let paragraph = Paragraph::new(text)
+            .block(
+                Block::bordered()
+                    .title("tcpcount overhead")
+                    .title_style(Style::new().bold().fg(Color::Cyan))
+                    .border_set(glyphs::border_set(BorderType::Plain))
+                    .border_style(Style::new().fg(Color::Blue))
+            )
+            .alignment(Alignment::Left);
+
+        paragraph.render(area, buf);
+    }
+}
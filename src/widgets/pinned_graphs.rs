@@ -0,0 +1,136 @@
+use std::sync::{Arc, Mutex};
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Stylize, Style, Color},
+    text::Span,
+    widgets::{Block, Widget, Sparkline, BorderType},
+};
+
+use crate::core::monitor::ConnectionMonitor;
+use crate::core::filters::ConnectionFilter;
+use super::active_connections_graph::ActiveConnectionsGraphWidget;
+use super::glyphs;
+
+/// Filters that can be pinned at once.
+pub const MAX_PINNED_GRAPHS: usize = 4;
+/// Width of the filter label in front of each sparkline.
+const LABEL_WIDTH: usize = 24;
+
+/// What pinning the current filter did.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PinOutcome {
+    Pinned,
+    Unpinned,
+    Full,
+}
+
+struct PinnedGraph {
+    filter: ConnectionFilter,
+    graph: ActiveConnectionsGraphWidget,
+}
+
+/// One-row sparklines of active connections, one per pinned filter, stacked under the
+/// main graph.
+pub struct PinnedGraphsWidget {
+    monitor: Arc<Mutex<ConnectionMonitor>>,
+    graphs: Vec<PinnedGraph>,
+    max_points: usize,
+}
+
+impl PinnedGraphsWidget {
+    pub fn new(monitor: Arc<Mutex<ConnectionMonitor>>) -> Self {
+        Self {
+            monitor,
+            graphs: Vec::new(),
+            max_points: 300,
+        }
+    }
+
+    /// Pins `filter`, or unpins it if it already is.
+    pub fn toggle(&mut self, filter: ConnectionFilter) -> PinOutcome {
+        if let Some(idx) = self.graphs.iter().position(|pinned| pinned.filter == filter) {
+            self.graphs.remove(idx);
+            return PinOutcome::Unpinned;
+        }
+        if self.graphs.len() >= MAX_PINNED_GRAPHS {
+            return PinOutcome::Full;
+        }
+        let mut graph = ActiveConnectionsGraphWidget::new(Arc::clone(&self.monitor))
+            .with_max_points(self.max_points);
+        graph.set_filter(filter.clone());
+        self.graphs.push(PinnedGraph { filter, graph });
+        PinOutcome::Pinned
+    }
+
+    pub fn len(&self) -> usize {
+        self.graphs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.graphs.is_empty()
+    }
+
+    pub fn set_max_points(&mut self, points: usize) {
+        self.max_points = points;
+        for pinned in &mut self.graphs {
+            pinned.graph.set_max_points(points);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        for pinned in &mut self.graphs {
+            pinned.graph.clear();
+        }
+    }
+
+    /// Samples every pinned filter. Returns whether any graph changed.
+    pub fn update(&mut self) -> bool {
+        let mut changed = false;
+        for pinned in &mut self.graphs {
+            changed |= pinned.graph.update();
+        }
+        changed
+    }
+}
+
+impl Widget for &PinnedGraphsWidget {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let block = Block::bordered()
+            .title("Pinned Filters")
+            .title_style(Style::new().bold().fg(Color::Cyan))
+            .border_set(glyphs::border_set(BorderType::Plain))
+            .border_style(Style::new().fg(Color::Blue));
+        let inner_area = block.inner(area);
+        block.render(area, buf);
+
+        for (row, pinned) in self.graphs.iter().enumerate().take(inner_area.height as usize) {
+            let y = inner_area.y + row as u16;
+            let history = pinned.graph.history();
+            let current = history.last().copied().unwrap_or(0);
+
+            let label: String = pinned.filter.to_string().chars().take(LABEL_WIDTH).collect();
+            let label = Span::styled(format!("{:<width$} ", label, width = LABEL_WIDTH), Style::new().fg(Color::Gray));
+            buf.set_span(inner_area.x, y, &label, inner_area.width);
+
+            let value = Span::styled(format!(" {:>5}", current), Style::new().bold());
+            let value_x = inner_area.right().saturating_sub(6);
+            buf.set_span(value_x, y, &value, 6);
+
+            let spark_x = inner_area.x + LABEL_WIDTH as u16 + 1;
+            let spark_area = Rect {
+                x: spark_x,
+                y,
+                width: value_x.saturating_sub(spark_x),
+                height: 1,
+            };
+            let skip = history.len().saturating_sub(spark_area.width as usize);
+            Sparkline::default()
+                .data(&history[skip..])
+                .max(history.iter().copied().max().unwrap_or(0).max(1))
+                .style(Style::new().fg(Color::Cyan))
+                .bar_set(glyphs::bar_set())
+                .render(spark_area, buf);
+        }
+    }
+}
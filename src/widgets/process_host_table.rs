@@ -1,146 +1,343 @@
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::ops::{Deref, DerefMut};
 use std::sync::{Arc, Mutex};
 use ratatui::{
     buffer::Buffer,
-    layout::{Rect, Constraint},
+    layout::Rect,
     style::{Stylize, Style, Color},
-    widgets::{Block, Table, Row, Cell, Widget, BorderType},
+    widgets::Widget,
 };
 
-use crate::core::monitor::ConnectionMonitor;
-use crate::core::filters::ConnectionFilter;
-use crate::app::SortBy;
+use crate::core::monitor::{ConnectionMonitor, ProcessHostMetrics};
+use crate::core::churn::REUSED_CHURN;
+use crate::core::filters::RowAttributes;
+use crate::core::watchlist::Watchlist;
+use super::metrics_table::{MetricsTable, pin_first};
+use super::table::{Column, Highlight, TableRow, format_age, format_bytes, highlight_color};
+use super::glyphs;
+
+/// Columns this table can show, in picker order.
+pub const AVAILABLE_COLUMNS: [Column; 13] = [Column::Pid, Column::Process, Column::Host, Column::Port, Column::Active, Column::Total, Column::Max, Column::Churn, Column::Scope, Column::Tunnel, Column::Nat, Column::Memory, Column::Duration];
+
+const DEFAULT_COLUMNS: [Column; 8] = [Column::Pid, Column::Process, Column::Host, Column::Port, Column::Active, Column::Total, Column::Max, Column::Churn];
+
+impl TableRow for ProcessHostMetrics {
+    const KEY_COLUMNS: &'static [Column] = &[Column::Pid, Column::Host];
+
+    fn cell(&self, column: Column) -> String {
+        match column {
+            Column::Pid => self.pid.to_string(),
+            Column::Process => {
+                let mut name = self.process_name.clone();
+                if self.leak_suspected {
+                    name = format!("{} {}", glyphs::glyph("⚠", "!"), name);
+                }
+                if let Some(at) = self.exited_at {
+                    name = format!("{} (exited {} ago)", name, format_age(at));
+                }
+                name
+            }
+            Column::Host => {
+                let mut host = self.host.clone();
+                if self.blocklist.is_some() {
+                    host = format!("{} {}", glyphs::glyph("✖", "X"), host);
+                }
+                if self.proxied {
+                    host = format!("{} (proxy)", host);
+                }
+                host
+            }
+            Column::Port => self.port.to_string(),
+            Column::Active => self.current_connections.to_string(),
+            Column::Total => self.total_connections.to_string(),
+            Column::Max => self.max_concurrent.to_string(),
+            Column::Churn => format!("{:.1}", self.churn),
+            Column::Scope => self.scope.to_string(),
+            Column::Tunnel => self.tunnel.clone().unwrap_or_else(|| "-".to_string()),
+            Column::Nat => self.nat.as_ref().map_or_else(|| "-".to_string(), |addr| format!("via {}", addr)),
+            Column::Memory => format_bytes(self.memory_usage),
+            Column::Duration => format_age(self.first_seen),
+            _ => String::new(),
+        }
+    }
+
+    fn compare(&self, other: &Self, column: Column) -> Ordering {
+        match column {
+            Column::Pid => self.pid.cmp(&other.pid),
+            Column::Process => self.process_name.cmp(&other.process_name),
+            Column::Host => self.host.cmp(&other.host),
+            Column::Port => self.port.cmp(&other.port),
+            Column::Active => self.current_connections.cmp(&other.current_connections),
+            Column::Total => self.total_connections.cmp(&other.total_connections),
+            Column::Max => self.max_concurrent.cmp(&other.max_concurrent),
+            Column::Churn => self.churn.total_cmp(&other.churn),
+            Column::Scope => self.scope.cmp(&other.scope),
+            Column::Tunnel => self.tunnel.cmp(&other.tunnel),
+            Column::Nat => self.nat.cmp(&other.nat),
+            Column::Memory => self.memory_usage.cmp(&other.memory_usage),
+            // Oldest first is the longest duration
+            Column::Duration => other.first_seen.cmp(&self.first_seen),
+            _ => Ordering::Equal,
+        }
+    }
+
+    fn color(&self, highlights: &[Highlight]) -> Option<Color> {
+        let row = RowAttributes {
+            pid: Some(self.pid),
+            process_name: Some(&self.process_name),
+            host: Some(&self.host),
+            port: Some(self.port),
+            scope: Some(self.scope),
+            tunnel: Some(self.tunnel.as_deref().unwrap_or("")),
+            ..Default::default()
+        };
+        highlight_color(highlights, &row)
+    }
+
+    fn cell_style(&self, column: Column) -> Option<Style> {
+        match column {
+            Column::Pid => Some(Style::new().fg(if self.is_alive { Color::Green } else { Color::Red })),
+            // Green reuses its connections, yellow reconnects often, red is flagged as churning
+            Column::Churn if self.high_churn => Some(Style::new().fg(Color::LightRed).bold()),
+            Column::Churn if self.churn <= REUSED_CHURN => Some(Style::new().fg(Color::Green)),
+            Column::Churn => Some(Style::new().fg(Color::Yellow)),
+            Column::Host if self.blocklist.is_some() => Some(Style::new().fg(Color::Red).bold()),
+            _ => None,
+        }
+    }
+}
+
+/// Where a row sits in tree mode; every row of the flat list is a `Pair`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TreeNode {
+    Pair,
+    /// A process with its pairs added up, and how many hosts it talks to.
+    Process { hosts: usize, expanded: bool },
+    /// A pair under its process, `last` of them.
+    Host { last: bool },
+}
+
+/// A row of the table: a process-host pair, or in tree mode a process heading its pairs.
+#[derive(Debug, Clone)]
+pub struct ProcessHostRow {
+    pub metrics: ProcessHostMetrics,
+    pub node: TreeNode,
+}
+
+impl ProcessHostRow {
+    /// The process of `pairs`, which are all its pairs, with their counts added up and the
+    /// process's own max concurrent if known.
+    fn process(pairs: &[ProcessHostMetrics], max_concurrent: Option<usize>, expanded: bool) -> Self {
+        let mut metrics = pairs[0].clone();
+        metrics.current_connections = pairs.iter().map(|pair| pair.current_connections).sum();
+        metrics.total_connections = pairs.iter().map(|pair| pair.total_connections).sum();
+        metrics.max_concurrent = max_concurrent
+            .unwrap_or_else(|| pairs.iter().map(|pair| pair.max_concurrent).max().unwrap_or(0));
+        metrics.first_seen = pairs.iter().map(|pair| pair.first_seen).min().unwrap_or(metrics.first_seen);
+        metrics.churn = pairs.iter().map(|pair| pair.churn).fold(0.0, f64::max);
+        metrics.high_churn = pairs.iter().any(|pair| pair.high_churn);
+        metrics.leak_suspected = pairs.iter().any(|pair| pair.leak_suspected);
+        metrics.blocklist = pairs.iter().find_map(|pair| pair.blocklist.clone());
+        Self { metrics, node: TreeNode::Process { hosts: pairs.len(), expanded } }
+    }
+}
+
+impl TableRow for ProcessHostRow {
+    const KEY_COLUMNS: &'static [Column] = ProcessHostMetrics::KEY_COLUMNS;
+
+    fn cell(&self, column: Column) -> String {
+        match (self.node, column) {
+            (TreeNode::Process { expanded, .. }, Column::Process) => {
+                let marker = if expanded { glyphs::glyph("▾", "-") } else { glyphs::glyph("▸", "+") };
+                format!("{} {}", marker, self.metrics.cell(column))
+            }
+            (TreeNode::Process { hosts, .. }, Column::Host) => {
+                let mut host = format!("{} host{}", hosts, if hosts == 1 { "" } else { "s" });
+                if self.metrics.blocklist.is_some() {
+                    host = format!("{} {}", glyphs::glyph("✖", "X"), host);
+                }
+                host
+            }
+            (TreeNode::Process { .. }, Column::Port | Column::Scope | Column::Tunnel | Column::Nat) => String::new(),
+            (TreeNode::Host { .. }, Column::Pid) => String::new(),
+            (TreeNode::Host { last }, Column::Process) => {
+                format!("  {}", if last { glyphs::glyph("└─", "`-") } else { glyphs::glyph("├─", "|-") })
+            }
+            _ => self.metrics.cell(column),
+        }
+    }
+
+    fn compare(&self, other: &Self, column: Column) -> Ordering {
+        match (self.node, other.node, column) {
+            (TreeNode::Process { hosts, .. }, TreeNode::Process { hosts: other_hosts, .. }, Column::Host) => hosts.cmp(&other_hosts),
+            _ => self.metrics.compare(&other.metrics, column),
+        }
+    }
+
+    fn color(&self, highlights: &[Highlight]) -> Option<Color> {
+        self.metrics.color(highlights)
+    }
+
+    fn cell_style(&self, column: Column) -> Option<Style> {
+        self.metrics.cell_style(column)
+    }
+}
 
 pub struct ProcessHostTableWidget {
     monitor: Arc<Mutex<ConnectionMonitor>>,
-    filter: ConnectionFilter,
-    sort_by: SortBy,
-    scroll_offset: usize,
+    table: MetricsTable<ProcessHostRow>,
+    watchlist: Watchlist,
+    tree: bool,
+    /// Processes whose hosts are shown in tree mode.
+    expanded: HashSet<u32>,
 }
 
 impl ProcessHostTableWidget {
     pub fn new(monitor: Arc<Mutex<ConnectionMonitor>>) -> Self {
         Self {
             monitor,
-            filter: ConnectionFilter::default(),
-            sort_by: SortBy::Total,
-            scroll_offset: 0,
+            table: MetricsTable::new("Connections by Process-Host", &AVAILABLE_COLUMNS, &DEFAULT_COLUMNS),
+            watchlist: Watchlist::default(),
+            tree: false,
+            expanded: HashSet::new(),
+        }
+    }
+
+    pub fn is_tree(&self) -> bool {
+        self.tree
+    }
+
+    /// Shows one row per process with its hosts under it, or the flat list of pairs.
+    pub fn set_tree(&mut self, tree: bool) {
+        self.tree = tree;
+        self.table.set_title(if tree {
+            format!("Connections by Process {} Host", glyphs::glyph("→", "->"))
+        } else {
+            "Connections by Process-Host".to_string()
+        });
+        self.table.scroll_to_top();
+    }
+
+    /// Shows or hides the hosts of the selected process, or with `expand` only one of
+    /// those. Collapsing from a host row selects its process.
+    pub fn toggle_selected(&mut self, expand: Option<bool>) {
+        let Ok(monitor) = self.monitor.lock() else {
+            return;
+        };
+        let rows = self.sorted_metrics(&monitor);
+        drop(monitor);
+        let Some(selected) = self.table.selected_index(rows.len()) else {
+            return;
+        };
+        let row = &rows[selected].0;
+        let pid = row.metrics.pid;
+        let expanded = self.expanded.contains(&pid);
+        match (row.node, expand) {
+            (TreeNode::Process { .. }, None) if expanded => { self.expanded.remove(&pid); }
+            (TreeNode::Process { .. }, None | Some(true)) => { self.expanded.insert(pid); }
+            (TreeNode::Process { .. } | TreeNode::Host { .. }, Some(false)) => {
+                self.expanded.remove(&pid);
+                let process = rows[..selected].iter()
+                    .rposition(|(row, _)| matches!(row.node, TreeNode::Process { .. }))
+                    .unwrap_or(selected);
+                self.table.scroll_up(selected - process);
+            }
+            _ => {}
+        }
+    }
+
+    pub fn set_watchlist(&mut self, watchlist: Watchlist) {
+        self.watchlist = watchlist;
+    }
+
+    /// Sorted rows with pinned entries first, paired with their pinned flag. In tree mode
+    /// a process is pinned if any of its pairs is, and its hosts are pinned first under it.
+    pub fn sorted_metrics(&self, monitor: &ConnectionMonitor) -> Vec<(ProcessHostRow, bool)> {
+        let mut pairs: Vec<ProcessHostRow> = monitor.get_process_host_metrics(self.filter()).into_iter()
+            .map(|metrics| ProcessHostRow { metrics, node: TreeNode::Pair })
+            .collect();
+        self.table.sort(&mut pairs);
+        let pairs = pin_first(pairs, |row| self.is_pinned(&row.metrics));
+        if !self.tree {
+            return pairs;
+        }
+
+        let mut by_pid: HashMap<u32, Vec<(ProcessHostRow, bool)>> = HashMap::new();
+        for (row, pinned) in pairs {
+            by_pid.entry(row.metrics.pid).or_default().push((row, pinned));
         }
+        let max_concurrent: HashMap<u32, usize> = monitor.get_process_metrics(self.filter()).into_iter()
+            .map(|process| (process.pid, process.max_concurrent))
+            .collect();
+        let mut processes: Vec<ProcessHostRow> = by_pid.values()
+            .map(|children| {
+                let metrics: Vec<ProcessHostMetrics> = children.iter().map(|(row, _)| row.metrics.clone()).collect();
+                let pid = metrics[0].pid;
+                ProcessHostRow::process(&metrics, max_concurrent.get(&pid).copied(), self.expanded.contains(&pid))
+            })
+            .collect();
+        self.table.sort(&mut processes);
+        let processes = pin_first(processes, |row| by_pid[&row.metrics.pid].iter().any(|(_, pinned)| *pinned));
+
+        let mut rows = Vec::new();
+        for (process, pinned) in processes {
+            let pid = process.metrics.pid;
+            let expanded = self.expanded.contains(&pid);
+            rows.push((process, pinned));
+            if expanded {
+                let children = by_pid.remove(&pid).unwrap_or_default();
+                let count = children.len();
+                rows.extend(children.into_iter().enumerate().map(|(i, (row, pinned))| {
+                    (ProcessHostRow { node: TreeNode::Host { last: i + 1 == count }, ..row }, pinned)
+                }));
+            }
+        }
+        rows
     }
 
-    pub fn set_filter(&mut self, filter: ConnectionFilter) {
-        self.filter = filter;
-        self.scroll_offset = 0;
+    fn is_pinned(&self, metrics: &ProcessHostMetrics) -> bool {
+        self.watchlist.is_process_host_pinned(&metrics.process_name, &metrics.host, metrics.port)
     }
 
-    pub fn set_sort_by(&mut self, sort_by: SortBy) {
-        self.sort_by = sort_by;
-        self.scroll_offset = 0;
+    /// The selected row, which in tree mode may be a whole process.
+    pub fn selected_row(&self) -> Option<ProcessHostRow> {
+        let monitor = self.monitor.lock().ok()?;
+        self.table.selected_row(self.sorted_metrics(&monitor))
     }
 
-    pub fn scroll_up(&mut self, amount: usize) {
-        self.scroll_offset = self.scroll_offset.saturating_sub(amount);
+    /// The selected process-host pair; `None` when a process row of the tree is selected.
+    pub fn selected_metrics(&self) -> Option<ProcessHostMetrics> {
+        self.selected_row()
+            .filter(|row| !matches!(row.node, TreeNode::Process { .. }))
+            .map(|row| row.metrics)
     }
 
-    pub fn scroll_down(&mut self, amount: usize, total_rows: usize, visible_rows: usize) {
-        let max_scroll = total_rows.saturating_sub(visible_rows);
-        self.scroll_offset = (self.scroll_offset + amount).min(max_scroll);
+    /// Text of the selected row's visible columns.
+    pub fn selected_cells(&self) -> Option<Vec<String>> {
+        Some(self.table.cells(&self.selected_row()?))
     }
+}
 
-    pub fn scroll_to_top(&mut self) {
-        self.scroll_offset = 0;
+impl Deref for ProcessHostTableWidget {
+    type Target = MetricsTable<ProcessHostRow>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.table
     }
+}
 
-    pub fn scroll_to_bottom(&mut self, total_rows: usize, visible_rows: usize) {
-        let max_scroll = total_rows.saturating_sub(visible_rows);
-        self.scroll_offset = max_scroll;
+impl DerefMut for ProcessHostTableWidget {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.table
     }
 }
 
 impl Widget for &ProcessHostTableWidget {
     fn render(self, area: Rect, buf: &mut Buffer) {
-        let monitor_guard = match self.monitor.lock() {
-            Ok(guard) => guard,
-            Err(_) => return,
+        let Ok(monitor_guard) = self.monitor.lock() else {
+            return;
         };
-
-        let mut process_host_metrics = monitor_guard.get_process_host_metrics(&self.filter);
-        
-        match self.sort_by {
-            SortBy::Total => {
-                process_host_metrics.sort_by(|a, b| b.total_connections.cmp(&a.total_connections)
-                    .then_with(|| a.pid.cmp(&b.pid))
-                    .then_with(|| a.host.cmp(&b.host)));
-            }, 
-            SortBy::Active => {
-                process_host_metrics.sort_by(|a, b| b.current_connections.cmp(&a.current_connections)
-                    .then_with(|| a.pid.cmp(&b.pid))
-                    .then_with(|| a.host.cmp(&b.host)));
-            },
-            SortBy::Max => {
-                process_host_metrics.sort_by(|a, b| b.max_concurrent.cmp(&a.max_concurrent)
-                    .then_with(|| a.pid.cmp(&b.pid))
-                    .then_with(|| a.host.cmp(&b.host)));
-            }
-        }
-
-        let content_height = area.height.saturating_sub(3);
-        let visible_rows = content_height as usize;
-        let total_rows = process_host_metrics.len();
-        
-        let start_idx = self.scroll_offset;
-        let end_idx = (start_idx + visible_rows).min(total_rows);
-        let visible_metrics = &process_host_metrics[start_idx..end_idx];
-        
-        let rows: Vec<Row> = visible_metrics.iter().map(|metrics| {
-            let pid_style = if metrics.is_alive {
-                Style::new().fg(Color::Green)
-            } else {
-                Style::new().fg(Color::Red)
-            };
-            
-            Row::new(vec![
-                Cell::from(metrics.pid.to_string()).style(pid_style),
-                Cell::from(metrics.process_name.clone()),
-                Cell::from(metrics.host.clone()),
-                Cell::from(metrics.port.to_string()),
-                Cell::from(metrics.current_connections.to_string()),
-                Cell::from(metrics.total_connections.to_string()),
-                Cell::from(metrics.max_concurrent.to_string()),
-            ])
-        }).collect();
-        
-        let widths = [
-            Constraint::Percentage(5),   // PID
-            Constraint::Percentage(55),  // Process Name
-            Constraint::Percentage(20),  // Remote Host
-            Constraint::Percentage(5),   // Port
-            Constraint::Percentage(5),  // Current Connections
-            Constraint::Percentage(5),  // Total Connections
-            Constraint::Percentage(5),  // Max Concurrent
-        ];
-        
-        let table = Table::new(rows, widths)
-            .header(
-                Row::new(vec![
-                    "PID",
-                    "Process",
-                    "Remote Host",
-                    "Port",
-                    "Active",
-                    "Total",
-                    "Max",
-                ])
-                .style(Style::new().bold().fg(Color::White))
-                .bottom_margin(1)
-            )
-            .block(
-                Block::bordered()
-                    .title("Connections by Process-Host")
-                    .title_style(Style::new().bold().fg(Color::Cyan))
-                    .border_type(BorderType::Plain)
-                    .border_style(Style::new().fg(Color::Blue))
-            );
-        
-        table.render(area, buf);
-    }
-}
\ No newline at end of file
+        self.table.render(&self.sorted_metrics(&monitor_guard), area, buf);
+    }
+}
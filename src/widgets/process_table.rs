@@ -1,137 +1,154 @@
+use std::cmp::Ordering;
+use std::ops::{Deref, DerefMut};
 use std::sync::{Arc, Mutex};
 use ratatui::{
     buffer::Buffer,
-    layout::{Rect, Constraint},
-    style::{Stylize, Style, Color},
-    widgets::{Block, Table, Row, Cell, Widget, BorderType},
+    layout::Rect,
+    style::{Style, Color},
+    widgets::Widget,
 };
 
-use crate::core::monitor::ConnectionMonitor;
-use crate::core::filters::ConnectionFilter;
-use crate::app::SortBy;
+use crate::core::monitor::{ConnectionMonitor, ProcessMetrics};
+use crate::core::filters::RowAttributes;
+use crate::core::watchlist::Watchlist;
+use super::metrics_table::{MetricsTable, pin_first};
+use super::table::{ANOMALY_COLOR, Column, Highlight, TableRow, compare_script_values, format_age, format_bytes, highlight_color};
+
+/// Columns this table can show, in picker order.
+pub const AVAILABLE_COLUMNS: [Column; 18] = [Column::Pid, Column::Process, Column::Active, Column::Total, Column::Max, Column::PerMinute, Column::Hosts, Column::Idle, Column::Max1m, Column::Max5m, Column::Max1h, Column::Average, Column::P95, Column::Memory, Column::Duration, Column::Age, Column::Restarts, Column::Script];
+
+const DEFAULT_COLUMNS: [Column; 5] = [Column::Pid, Column::Process, Column::Active, Column::Total, Column::Max];
+
+impl TableRow for ProcessMetrics {
+    const KEY_COLUMNS: &'static [Column] = &[Column::Pid];
+
+    fn cell(&self, column: Column) -> String {
+        match column {
+            Column::Pid => self.pid.to_string(),
+            Column::Process => match self.exited_at {
+                Some(at) => format!("{} (exited {} ago)", self.name, format_age(at)),
+                None => self.name.clone(),
+            },
+            Column::Active => self.current_connections.to_string(),
+            Column::Total => self.total_connections.to_string(),
+            Column::Max => self.max_concurrent.to_string(),
+            Column::Max1m => self.recent_max.last_1m.to_string(),
+            Column::Max5m => self.recent_max.last_5m.to_string(),
+            Column::Max1h => self.recent_max.last_1h.to_string(),
+            Column::Average => format!("{:.1}", self.sampled.average),
+            Column::P95 => self.sampled.p95.to_string(),
+            Column::Memory => format_bytes(self.memory_usage),
+            Column::Duration => format_age(self.first_seen),
+            Column::Restarts => self.restarts.to_string(),
+            Column::Script => self.script.clone().unwrap_or_else(|| "-".to_string()),
+            Column::Hosts if self.hosts == 0 => "-".to_string(),
+            Column::Hosts => self.hosts.to_string(),
+            Column::Idle => self.idle.to_string(),
+            Column::Age => self.started_at.map_or_else(|| "-".to_string(), format_age),
+            Column::PerMinute => format!("{:.1}", self.per_minute),
+            _ => String::new(),
+        }
+    }
+
+    fn compare(&self, other: &Self, column: Column) -> Ordering {
+        match column {
+            Column::Pid => self.pid.cmp(&other.pid),
+            Column::Process => self.name.cmp(&other.name),
+            Column::Active => self.current_connections.cmp(&other.current_connections),
+            Column::Total => self.total_connections.cmp(&other.total_connections),
+            Column::Max => self.max_concurrent.cmp(&other.max_concurrent),
+            Column::Max1m => self.recent_max.last_1m.cmp(&other.recent_max.last_1m),
+            Column::Max5m => self.recent_max.last_5m.cmp(&other.recent_max.last_5m),
+            Column::Max1h => self.recent_max.last_1h.cmp(&other.recent_max.last_1h),
+            Column::Average => self.sampled.average.total_cmp(&other.sampled.average),
+            Column::P95 => self.sampled.p95.cmp(&other.sampled.p95),
+            Column::Memory => self.memory_usage.cmp(&other.memory_usage),
+            // Oldest first is the longest duration
+            Column::Duration => other.first_seen.cmp(&self.first_seen),
+            Column::Restarts => self.restarts.cmp(&other.restarts),
+            Column::Script => compare_script_values(&self.script, &other.script),
+            Column::Hosts => self.hosts.cmp(&other.hosts),
+            Column::Idle => self.idle.cmp(&other.idle),
+            // Started earliest is the oldest, unknown starts count as the youngest
+            Column::Age => self.started_at.map(|at| -at.timestamp()).cmp(&other.started_at.map(|at| -at.timestamp())),
+            Column::PerMinute => self.per_minute.total_cmp(&other.per_minute),
+            _ => Ordering::Equal,
+        }
+    }
+
+    fn color(&self, highlights: &[Highlight]) -> Option<Color> {
+        let row = RowAttributes {
+            pid: Some(self.pid),
+            process_name: Some(&self.name),
+            ..Default::default()
+        };
+        highlight_color(highlights, &row).or(self.anomaly_score.map(|_| ANOMALY_COLOR))
+    }
+
+    fn cell_style(&self, column: Column) -> Option<Style> {
+        match column {
+            Column::Pid => Some(Style::new().fg(if self.is_alive { Color::Green } else { Color::Red })),
+            _ => None,
+        }
+    }
+}
 
 pub struct ProcessTableWidget {
     monitor: Arc<Mutex<ConnectionMonitor>>,
-    filter: ConnectionFilter,
-    sort_by: SortBy,
-    scroll_offset: usize,
+    table: MetricsTable<ProcessMetrics>,
+    watchlist: Watchlist,
 }
 
 impl ProcessTableWidget {
     pub fn new(monitor: Arc<Mutex<ConnectionMonitor>>) -> Self {
         Self {
             monitor,
-            filter: ConnectionFilter::default(),
-            sort_by: SortBy::Total,
-            scroll_offset: 0,
+            table: MetricsTable::new("Connections by Process", &AVAILABLE_COLUMNS, &DEFAULT_COLUMNS),
+            watchlist: Watchlist::default(),
         }
     }
 
-    pub fn set_filter(&mut self, filter: ConnectionFilter) {
-        self.filter = filter;
-        self.scroll_offset = 0;
+    pub fn set_watchlist(&mut self, watchlist: Watchlist) {
+        self.watchlist = watchlist;
     }
 
-    pub fn set_sort_by(&mut self, sort_by: SortBy) {
-        self.sort_by = sort_by;
-        self.scroll_offset = 0;
+    /// Sorted rows with pinned entries first, paired with their pinned flag.
+    pub fn sorted_metrics(&self, monitor: &ConnectionMonitor) -> Vec<(ProcessMetrics, bool)> {
+        let mut rows = monitor.get_process_metrics(self.filter());
+        self.table.sort(&mut rows);
+        pin_first(rows, |metrics| self.watchlist.is_process_pinned(&metrics.name))
     }
 
-    pub fn scroll_up(&mut self, amount: usize) {
-        self.scroll_offset = self.scroll_offset.saturating_sub(amount);
+    pub fn selected_metrics(&self) -> Option<ProcessMetrics> {
+        let monitor = self.monitor.lock().ok()?;
+        self.table.selected_row(self.sorted_metrics(&monitor))
     }
 
-    pub fn scroll_down(&mut self, amount: usize, total_rows: usize, visible_rows: usize) {
-        let max_scroll = total_rows.saturating_sub(visible_rows);
-        self.scroll_offset = (self.scroll_offset + amount).min(max_scroll);
+    /// Text of the selected row's visible columns.
+    pub fn selected_cells(&self) -> Option<Vec<String>> {
+        Some(self.table.cells(&self.selected_metrics()?))
     }
+}
+
+impl Deref for ProcessTableWidget {
+    type Target = MetricsTable<ProcessMetrics>;
 
-    pub fn scroll_to_top(&mut self) {
-        self.scroll_offset = 0;
+    fn deref(&self) -> &Self::Target {
+        &self.table
     }
+}
 
-    pub fn scroll_to_bottom(&mut self, total_rows: usize, visible_rows: usize) {
-        let max_scroll = total_rows.saturating_sub(visible_rows);
-        self.scroll_offset = max_scroll;
+impl DerefMut for ProcessTableWidget {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.table
     }
 }
 
 impl Widget for &ProcessTableWidget {
     fn render(self, area: Rect, buf: &mut Buffer) {
-        let monitor_guard = match self.monitor.lock() {
-            Ok(guard) => guard,
-            Err(_) => return,
+        let Ok(monitor_guard) = self.monitor.lock() else {
+            return;
         };
-
-        let mut process_metrics = monitor_guard.get_process_metrics(&self.filter);
-        
-        match self.sort_by {
-            SortBy::Total => {
-                process_metrics.sort_by(|a, b| b.total_connections.cmp(&a.total_connections)
-                    .then_with(|| a.pid.cmp(&b.pid)));
-            },
-            SortBy::Active => {
-                process_metrics.sort_by(|a, b| b.current_connections.cmp(&a.current_connections)
-                    .then_with(|| a.pid.cmp(&b.pid)));
-            }, 
-            SortBy::Max => {
-                process_metrics.sort_by(|a, b| b.max_concurrent.cmp(&a.max_concurrent)
-                    .then_with(|| a.pid.cmp(&b.pid)));
-            }
-        }
-
-        let content_height = area.height.saturating_sub(3);
-        let visible_rows = content_height as usize;
-        let total_rows = process_metrics.len();
-        
-        let start_idx = self.scroll_offset;
-        let end_idx = (start_idx + visible_rows).min(total_rows);
-        let visible_metrics = &process_metrics[start_idx..end_idx];
-        
-        let rows: Vec<Row> = visible_metrics.iter().map(|metrics| {
-            let pid_style = if metrics.is_alive {
-                Style::new().fg(Color::Green)
-            } else {
-                Style::new().fg(Color::Red)
-            };
-            
-            Row::new(vec![
-                Cell::from(metrics.pid.to_string()).style(pid_style),
-                Cell::from(metrics.name.clone()),
-                Cell::from(metrics.current_connections.to_string()),
-                Cell::from(metrics.total_connections.to_string()),
-                Cell::from(metrics.max_concurrent.to_string()),
-            ])
-        }).collect();
-        
-        let widths = [
-            Constraint::Percentage(10),  // PID
-            Constraint::Percentage(60),  // Name
-            Constraint::Percentage(10),  // Current Connections
-            Constraint::Percentage(10),  // Total Connections
-            Constraint::Percentage(10),  // Max Connections
-        ];
-        
-        let table = Table::new(rows, widths)
-            .header(
-                Row::new(vec![
-                    "PID",
-                    "Process Name",
-                    "Active",
-                    "Total",
-                    "Max",
-                ])
-                .style(Style::new().bold().fg(Color::White))
-                .bottom_margin(1)
-            )
-            .block(
-                Block::bordered()
-                    .title("Connections by Process")
-                    .title_style(Style::new().bold().fg(Color::Cyan))
-                    .border_type(BorderType::Plain)
-                    .border_style(Style::new().fg(Color::Blue))
-            );
-        
-        table.render(area, buf);
+        self.table.render(&self.sorted_metrics(&monitor_guard), area, buf);
     }
-}
\ No newline at end of file
+}
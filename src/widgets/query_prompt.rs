@@ -0,0 +1,148 @@
+use ratatui::{
+    buffer::Buffer,
+    layout::{Rect, Layout, Direction, Constraint},
+    style::{Stylize, Style, Color},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Paragraph, Widget, Clear},
+};
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind};
+
+use crate::core::filters::FilterExpr;
+use super::text_input::TextInput;
+use super::glyphs;
+
+/// What the query prompt produced when closed with Enter.
+pub enum QueryInput {
+    Set(FilterExpr),
+    Cleared,
+}
+
+/// Single-line prompt for typing a filter query.
+pub struct QueryPromptWidget {
+    input: TextInput,
+    active: bool,
+    error: Option<String>,
+}
+
+impl QueryPromptWidget {
+    pub fn new() -> Self {
+        Self {
+            input: TextInput::new(),
+            active: false,
+            error: None,
+        }
+    }
+
+    pub fn show(&mut self, current_query: Option<&FilterExpr>) {
+        self.active = true;
+        self.error = None;
+        self.input.set(current_query.map(|query| query.to_string()).unwrap_or_default());
+    }
+
+    /// Inserts pasted text at the cursor.
+    pub fn handle_paste(&mut self, text: &str) {
+        if self.active {
+            self.input.insert_str(text);
+            self.error = None;
+        }
+    }
+
+    pub fn hide(&mut self) {
+        self.active = false;
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    pub fn handle_key_event(&mut self, key_event: KeyEvent) -> Option<QueryInput> {
+        if !self.active || key_event.kind != KeyEventKind::Press {
+            return None;
+        }
+
+        match key_event.code {
+            KeyCode::Esc => {
+                self.hide();
+                None
+            }
+            KeyCode::Enter => {
+                if self.input.value().trim().is_empty() {
+                    self.hide();
+                    return Some(QueryInput::Cleared);
+                }
+
+                match FilterExpr::parse(self.input.value()) {
+                    Ok(query) => {
+                        self.hide();
+                        Some(QueryInput::Set(query))
+                    }
+                    Err(msg) => {
+                        self.error = Some(msg);
+                        None
+                    }
+                }
+            }
+            _ => {
+                if self.input.handle_key(&key_event) {
+                    self.error = None;
+                }
+                None
+            }
+        }
+    }
+}
+
+impl Widget for &QueryPromptWidget {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        if !self.active {
+            return;
+        }
+
+        let popup_width = area.width.min(80);
+        let popup_height = 6;
+
+        let popup_area = Rect {
+            x: area.x + (area.width.saturating_sub(popup_width)) / 2,
+            y: area.y + (area.height.saturating_sub(popup_height)) / 2,
+            width: popup_width,
+            height: popup_height,
+        };
+
+        Clear.render(popup_area, buf);
+
+        let block = Block::bordered()
+            .title("Filter Query")
+            .title_style(Style::new().bold().fg(Color::Yellow))
+            .border_set(glyphs::border_set(BorderType::Plain))
+            .border_style(Style::new().fg(Color::Yellow));
+
+        let inner_area = block.inner(popup_area);
+        block.render(popup_area, buf);
+
+        let layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(1), // Input
+                Constraint::Length(1), // Hint
+                Constraint::Length(2), // Error or instructions
+            ])
+            .split(inner_area);
+
+        let mut spans = vec![Span::styled("> ", Style::new().fg(Color::White))];
+        spans.extend(self.input.spans(Style::new().fg(Color::Yellow), true));
+        Paragraph::new(Line::from(spans)).render(layout[0], buf);
+
+        Paragraph::new("e.g. proc~chrome and (port=443 or port=80) and not host~googleapis")
+            .style(Style::new().fg(Color::DarkGray))
+            .render(layout[1], buf);
+
+        match &self.error {
+            Some(error) => Paragraph::new(error.as_str())
+                .style(Style::new().fg(Color::Red))
+                .render(layout[2], buf),
+            None => Paragraph::new("Fields: pid proc host port lport group state net scope tunnel  |  Enter: Apply  |  Esc: Cancel")
+                .style(Style::new().fg(Color::Gray))
+                .render(layout[2], buf),
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Stylize, Style, Color},
+    text::{Line, Span, Text},
+    widgets::{Block, BorderType, Paragraph, Widget, Clear},
+};
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind};
+use super::glyphs;
+
+/// What a confirmed reset wipes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ResetScope {
+    /// All metrics, history and events.
+    All,
+    /// Only the connection graph.
+    Graph,
+    /// Only closed connections; open ones and the graph are kept.
+    Closed,
+}
+
+/// Asks before `r` wipes collected data, and what to wipe.
+pub struct ResetPromptWidget {
+    active: bool,
+}
+
+impl ResetPromptWidget {
+    pub fn new() -> Self {
+        Self { active: false }
+    }
+
+    pub fn show(&mut self) {
+        self.active = true;
+    }
+
+    pub fn hide(&mut self) {
+        self.active = false;
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// Returns the scope once a reset is confirmed. Any other key cancels.
+    pub fn handle_key_event(&mut self, key_event: KeyEvent) -> Option<ResetScope> {
+        if !self.active || key_event.kind != KeyEventKind::Press {
+            return None;
+        }
+        self.hide();
+        match key_event.code {
+            KeyCode::Char('y') | KeyCode::Char('Y') => Some(ResetScope::All),
+            KeyCode::Char('g') => Some(ResetScope::Graph),
+            KeyCode::Char('c') => Some(ResetScope::Closed),
+            _ => None,
+        }
+    }
+}
+
+fn choice(key: &'static str, label: &'static str) -> Line<'static> {
+    Line::from(vec![
+        Span::styled(format!("  {:<6}", key), Style::new().fg(Color::Green).bold()),
+        Span::raw(label),
+    ])
+}
+
+impl Widget for &ResetPromptWidget {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        if !self.active {
+            return;
+        }
+
+        let popup_width = area.width.min(50);
+        let popup_height = 8.min(area.height);
+
+        let popup_area = Rect {
+            x: area.x + (area.width.saturating_sub(popup_width)) / 2,
+            y: area.y + (area.height.saturating_sub(popup_height)) / 2,
+            width: popup_width,
+            height: popup_height,
+        };
+
+        Clear.render(popup_area, buf);
+
+        let text = Text::from(vec![
+            Line::styled("Reset all collected metrics?", Style::new().bold()),
+            Line::raw(""),
+            choice("y", "Reset everything"),
+            choice("g", "Clear only the graph"),
+            choice("c", "Forget only closed connections"),
+            choice("n/Esc", "Cancel"),
+        ]);
+
+        Paragraph::new(text)
+            .block(
+                Block::bordered()
+                    .title("Reset")
+                    .title_style(Style::new().bold().fg(Color::Yellow))
+                    .border_set(glyphs::border_set(BorderType::Plain))
+                    .border_style(Style::new().fg(Color::Yellow))
+            )
+            .render(popup_area, buf);
+    }
+}
@@ -1,4 +1,5 @@
 use std::sync::{Arc, Mutex};
+use chrono::{DateTime, Local};
 use ratatui::{
     buffer::Buffer,
     layout::{Rect, Alignment},
@@ -7,12 +8,18 @@ use ratatui::{
     widgets::{Block, Paragraph, Widget, BorderType},
 };
 
+use crate::core::limits::LimitOptions;
 use crate::core::monitor::ConnectionMonitor;
 use crate::core::filters::ConnectionFilter;
+use crate::core::scope::{AddressScope, ScopeCounts};
+use super::glyphs;
+use super::table::{format_secs, format_span};
 
 pub struct SummaryWidget {
     monitor: Arc<Mutex<ConnectionMonitor>>,
     filter: ConnectionFilter,
+    /// Title in place of "Overall connections", e.g. to name a split view's side.
+    title: Option<String>,
 }
 
 impl SummaryWidget {
@@ -20,12 +27,74 @@ impl SummaryWidget {
         Self {
             monitor,
             filter: ConnectionFilter::default(),
+            title: None,
         }
     }
 
     pub fn set_filter(&mut self, filter: ConnectionFilter) {
         self.filter = filter;
     }
+
+    pub fn set_title(&mut self, title: Option<String>) {
+        self.title = title;
+    }
+}
+
+/// Active connections per address scope, leaving out empty scopes, and how many of them
+/// go over a tunnel.
+fn scope_line(counts: &ScopeCounts, tunneled: usize) -> Line<'static> {
+    let mut spans = vec![Span::raw("Scope: ")];
+    let scopes: Vec<AddressScope> = AddressScope::ALL.into_iter()
+        .filter(|scope| counts.get(*scope) > 0)
+        .collect();
+    if scopes.is_empty() {
+        spans.push(Span::styled("-", Style::default().fg(Color::Gray)));
+    }
+    for (idx, scope) in scopes.into_iter().enumerate() {
+        if idx > 0 {
+            spans.push(Span::raw(", "));
+        }
+        let color = if scope == AddressScope::Public { Color::Yellow } else { Color::Gray };
+        spans.push(Span::styled(counts.get(scope).to_string(), Style::default().fg(color).bold()));
+        spans.push(Span::raw(format!(" {}", scope)));
+    }
+    if tunneled > 0 {
+        spans.push(Span::raw(" ("));
+        spans.push(Span::styled(tunneled.to_string(), Style::default().fg(Color::Magenta).bold()));
+        spans.push(Span::raw(" via tunnel)"));
+    }
+    Line::from(spans)
+}
+
+/// Cells in the system-wide gauge bar.
+const GAUGE_WIDTH: usize = 10;
+
+/// Active connections across the system against the configured ceiling, as a bar and
+/// percentage, colored by how close they are to the warning count.
+fn limits_line(active: usize, limits: &LimitOptions) -> Line<'static> {
+    let color = match limits.warn_at {
+        Some(warn_at) if active >= warn_at => Color::LightRed,
+        Some(warn_at) if active as f64 >= warn_at as f64 * 0.9 => Color::Yellow,
+        _ => Color::Green,
+    };
+    let mut spans = vec![Span::raw("System: ")];
+    match (limits.ceiling, limits.percent_of_ceiling(active)) {
+        (Some(ceiling), Some(percent)) => {
+            let filled = ((percent / 100.0 * GAUGE_WIDTH as f64).round() as usize).min(GAUGE_WIDTH);
+            spans.push(Span::styled(glyphs::glyph("█", "#").repeat(filled), Style::default().fg(color)));
+            spans.push(Span::styled(glyphs::glyph("░", "-").repeat(GAUGE_WIDTH - filled), Style::default().fg(Color::DarkGray)));
+            spans.push(Span::raw(" "));
+            spans.push(Span::styled(active.to_string(), Style::default().fg(color).bold()));
+            spans.push(Span::raw(format!("/{} ({:.0}%)", ceiling, percent)));
+        }
+        _ => {
+            spans.push(Span::styled(active.to_string(), Style::default().fg(color).bold()));
+            if let Some(warn_at) = limits.warn_at {
+                spans.push(Span::raw(format!(", warn at {}", warn_at)));
+            }
+        }
+    }
+    Line::from(spans)
 }
 
 impl Widget for &SummaryWidget {
@@ -35,44 +104,88 @@ impl Widget for &SummaryWidget {
             Err(_) => return,
         };
 
-        let current_connections = monitor_guard.get_filtered_active_connections(&self.filter).len();
-        
-        let historical_connections = monitor_guard.get_filtered_historical_connections(&self.filter).len();
-        let total_opened = historical_connections + current_connections;
+        let summary = monitor_guard.get_summary(&self.filter);
+        let started = monitor_guard.started();
+        let as_of = monitor_guard.as_of();
+        let local_as_of: DateTime<Local> = as_of.into();
+        let value = Style::default().fg(Color::Gray);
         
-        let history = monitor_guard.get_connection_history_filtered(&self.filter, None, None);
-        let max_concurrent = history.iter().map(|(_, count)| *count).max().unwrap_or(0);
+        // Totals and max count from the start of the session, so show how long and how
+        // often that was sampled
+        let (session, time) = if monitor_guard.is_offline() {
+            (
+                Line::from(vec![Span::raw("Recorded: "), Span::styled(format_span(started, as_of), value)]),
+                Line::from(vec![Span::raw("At: "), Span::styled(local_as_of.format("%Y-%m-%d %H:%M:%S").to_string(), value)]),
+            )
+        } else {
+            (
+                Line::from(vec![
+                    Span::raw("Uptime: "),
+                    Span::styled(format_span(started, as_of), value),
+                    Span::raw(", "),
+                    Span::styled(monitor_guard.samples().to_string(), value),
+                    Span::raw(" samples"),
+                ]),
+                Line::from(vec![Span::raw("Time: "), Span::styled(local_as_of.format("%H:%M:%S").to_string(), value)]),
+            )
+        };
         
-        let text = Text::from(vec![
+        let mut lines = vec![
             Line::from(vec![
                 Span::raw("Active: "),
                 Span::styled(
-                    format!("{}", current_connections), 
+                    format!("{}", summary.active_connections), 
+                    Style::default().fg(Color::Green).bold()
+                ),
+                Span::raw(", Max: "),
+                Span::styled(
+                    format!("{}", summary.max_concurrent),
                     Style::default().fg(Color::Green).bold()
                 ),
             ]),
             Line::from(vec![
                 Span::raw("Total: "),
                 Span::styled(
-                    format!("{}", total_opened),
+                    format!("{}", summary.total_connections),
                     Style::default().fg(Color::Green).bold()
                 ),
+                Span::raw(", "),
+                Span::styled(format!("{:.1}", summary.opened_per_minute), value),
+                Span::raw("/min"),
+                if summary.blocklisted > 0 {
+                    Span::styled(format!(", {} blocklisted", summary.blocklisted), Style::default().fg(Color::Red).bold())
+                } else {
+                    Span::raw("")
+                },
+            ]),
+            Line::from(vec![
+                Span::raw("Hosts: "),
+                Span::styled(summary.unique_hosts.to_string(), value),
+                Span::raw(", Processes: "),
+                Span::styled(summary.unique_processes.to_string(), value),
             ]),
             Line::from(vec![
-                Span::raw("Max: "),
+                Span::raw("Avg Duration: "),
                 Span::styled(
-                    format!("{}", max_concurrent),
-                    Style::default().fg(Color::Green).bold()
+                    summary.avg_duration_secs.map_or_else(|| "-".to_string(), |secs| format_secs(secs.round() as i64)),
+                    value,
                 ),
             ]),
-        ]);
+            scope_line(&summary.active_by_scope, summary.active_tunneled),
+            session,
+            time,
+        ];
+        if let Some(limits) = monitor_guard.limit_options() {
+            lines.push(limits_line(monitor_guard.system_active_connections(), &limits));
+        }
+        let text = Text::from(lines);
         
         let paragraph = Paragraph::new(text)
             .block(
                 Block::bordered()
-                    .title("Overall connections")
+                    .title(self.title.as_deref().unwrap_or("Overall connections"))
                     .title_style(Style::new().bold().fg(Color::Cyan))
-                    .border_type(BorderType::Plain)
+                    .border_set(glyphs::border_set(BorderType::Plain))
                     .border_style(Style::new().fg(Color::Blue))
             )
             .alignment(Alignment::Left);
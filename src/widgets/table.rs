@@ -0,0 +1,432 @@
+use std::cell::Cell;
+use std::cmp::Ordering;
+use std::ops::Range;
+use chrono::{DateTime, Utc};
+use ratatui::layout::{Constraint, Flex, Layout, Position, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use serde::Deserialize;
+
+use crate::core::filters::{ConnectionFilter, RowAttributes};
+use super::glyphs;
+
+/// Selected row and scroll position of a metrics table.
+///
+/// The viewport height is only known while rendering, so it is recorded in a
+/// `Cell` and used by the next navigation call to keep the selection in view.
+#[derive(Debug, Default)]
+pub struct TableCursor {
+    selected: usize,
+    offset: usize,
+    visible_rows: Cell<usize>,
+    first_visible: Cell<usize>,
+}
+
+impl TableCursor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn reset(&mut self) {
+        self.selected = 0;
+        self.offset = 0;
+    }
+
+    /// Index of the selected row, clamped to the number of rows.
+    pub fn selected(&self, total_rows: usize) -> Option<usize> {
+        if total_rows == 0 {
+            None
+        } else {
+            Some(self.selected.min(total_rows - 1))
+        }
+    }
+
+    pub fn move_up(&mut self, amount: usize) {
+        self.selected = self.selected.saturating_sub(amount);
+        self.keep_selection_visible();
+    }
+
+    pub fn move_down(&mut self, amount: usize, total_rows: usize) {
+        self.selected = (self.selected + amount).min(total_rows.saturating_sub(1));
+        self.keep_selection_visible();
+    }
+
+    pub fn move_to_top(&mut self) {
+        self.selected = 0;
+        self.offset = 0;
+    }
+
+    pub fn move_to_bottom(&mut self, total_rows: usize) {
+        self.selected = total_rows.saturating_sub(1);
+        self.keep_selection_visible();
+    }
+
+    /// Selects the `row`-th row on screen, as last drawn.
+    pub fn select_visible(&mut self, row: usize, total_rows: usize) {
+        let index = self.first_visible.get() + row;
+        if index < total_rows {
+            self.selected = index;
+            self.offset = self.first_visible.get();
+        }
+    }
+
+    pub fn visible_rows(&self) -> usize {
+        self.visible_rows.get().max(1)
+    }
+
+    /// Row range to draw for a viewport of `visible_rows`, recording the height for navigation.
+    pub fn visible_range(&self, total_rows: usize, visible_rows: usize) -> Range<usize> {
+        self.visible_rows.set(visible_rows);
+
+        let mut start = self.offset.min(total_rows.saturating_sub(visible_rows));
+        if let Some(selected) = self.selected(total_rows) {
+            if selected < start {
+                start = selected;
+            } else if visible_rows > 0 && selected >= start + visible_rows {
+                start = selected + 1 - visible_rows;
+            }
+        }
+
+        self.first_visible.set(start);
+        start..(start + visible_rows).min(total_rows)
+    }
+
+    fn keep_selection_visible(&mut self) {
+        let visible_rows = self.visible_rows();
+        if self.selected < self.offset {
+            self.offset = self.selected;
+        } else if self.selected >= self.offset + visible_rows {
+            self.offset = self.selected + 1 - visible_rows;
+        }
+    }
+}
+
+/// A column a metrics table can show. Each table offers the subset that applies to its rows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Column {
+    Pid,
+    Process,
+    Host,
+    Port,
+    Group,
+    Active,
+    Total,
+    Max,
+    Memory,
+    Duration,
+    Churn,
+    Clients,
+    #[serde(rename = "first_seen")]
+    FirstSeen,
+    #[serde(rename = "last_seen")]
+    LastSeen,
+    Scope,
+    Tunnel,
+    #[serde(rename = "max_1m")]
+    Max1m,
+    #[serde(rename = "max_5m")]
+    Max5m,
+    #[serde(rename = "max_1h")]
+    Max1h,
+    #[serde(rename = "avg")]
+    Average,
+    P95,
+    Restarts,
+    Nat,
+    Script,
+    #[serde(rename = "remote_ips")]
+    RemoteIps,
+    #[serde(rename = "local_ports")]
+    LocalPorts,
+    Hosts,
+    Idle,
+    #[serde(rename = "accept_queue")]
+    AcceptQueue,
+    #[serde(rename = "retransmits")]
+    Retransmits,
+    Age,
+    #[serde(rename = "per_minute")]
+    PerMinute,
+}
+
+impl Column {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Column::Pid => "PID",
+            Column::Process => "Process",
+            Column::Host => "Remote Host",
+            Column::Port => "Port",
+            Column::Group => "Group",
+            Column::Active => "Active",
+            Column::Total => "Total",
+            Column::Max => "Max",
+            Column::Memory => "Memory",
+            Column::Duration => "Duration",
+            Column::Churn => "Churn",
+            Column::Clients => "Top Clients",
+            Column::FirstSeen => "First Seen",
+            Column::LastSeen => "Last Seen",
+            Column::Scope => "Scope",
+            Column::Tunnel => "Tunnel",
+            Column::Max1m => "Max 1m",
+            Column::Max5m => "Max 5m",
+            Column::Max1h => "Max 1h",
+            Column::Average => "Avg",
+            Column::P95 => "P95",
+            Column::Restarts => "Restarts",
+            Column::Nat => "NAT",
+            Column::Script => "Script",
+            Column::RemoteIps => "IPs",
+            Column::LocalPorts => "Local Ports",
+            Column::Hosts => "Hosts",
+            Column::Idle => "Idle",
+            Column::AcceptQueue => "Accept Queue",
+            Column::Retransmits => "Retrans",
+            Column::Age => "Age",
+            Column::PerMinute => "Conn/min",
+        }
+    }
+
+    /// Text columns share the spare width; the others are sized to their content.
+    pub fn is_text(&self) -> bool {
+        matches!(self, Column::Process | Column::Host | Column::Group | Column::Clients)
+    }
+
+    /// Counts and sizes sort largest first on the first header click.
+    fn descending_first(&self) -> bool {
+        matches!(self, Column::Active | Column::Total | Column::Max | Column::Max1m | Column::Max5m | Column::Max1h | Column::Average | Column::P95 | Column::Restarts | Column::Memory | Column::Duration | Column::Churn | Column::LastSeen | Column::RemoteIps | Column::LocalPorts | Column::Hosts | Column::Idle | Column::AcceptQueue | Column::Retransmits | Column::Age | Column::PerMinute)
+    }
+}
+
+/// Orders script column values as numbers when both are numeric, otherwise as text.
+pub fn compare_script_values(a: &Option<String>, b: &Option<String>) -> Ordering {
+    let number = |value: &Option<String>| value.as_deref().and_then(|value| value.parse::<f64>().ok());
+    match (number(a), number(b)) {
+        (Some(a), Some(b)) => a.total_cmp(&b),
+        _ => a.cmp(b),
+    }
+}
+
+/// A metrics row that can be shown and sorted by column.
+pub trait TableRow {
+    /// Columns ordering rows the global sort finds equal, compared in turn.
+    const KEY_COLUMNS: &'static [Column];
+
+    fn cell(&self, column: Column) -> String;
+    fn compare(&self, other: &Self, column: Column) -> Ordering;
+
+    /// Color of the row: the first highlight rule it matches, or one of its own.
+    fn color(&self, _highlights: &[Highlight]) -> Option<Color> {
+        None
+    }
+
+    /// Style of one cell, overriding the row's.
+    fn cell_style(&self, _column: Column) -> Option<Style> {
+        None
+    }
+}
+
+/// Sort chosen by clicking a column header, overriding the global sort for one table.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ColumnSort {
+    pub column: Column,
+    pub descending: bool,
+}
+
+impl ColumnSort {
+    /// Sort after clicking `column`: the sorted column flips direction, another column
+    /// starts descending for counts and ascending otherwise.
+    pub fn toggle(current: Option<ColumnSort>, column: Column) -> ColumnSort {
+        match current {
+            Some(sort) if sort.column == column => ColumnSort { column, descending: !sort.descending },
+            _ => ColumnSort { column, descending: column.descending_first() },
+        }
+    }
+
+    /// Stable sort by the column, so ties keep their previous order.
+    pub fn apply<T: TableRow>(&self, rows: &mut [T]) {
+        rows.sort_by(|a, b| {
+            let ordering = a.compare(b, self.column);
+            if self.descending { ordering.reverse() } else { ordering }
+        });
+    }
+}
+
+/// Header labels with an arrow on the column the table is sorted by.
+pub fn header_labels(columns: &[Column], sort: Option<ColumnSort>) -> Vec<String> {
+    columns.iter()
+        .map(|column| match sort {
+            Some(sort) if sort.column == *column => {
+                format!("{}{}", column.label(), if sort.descending { glyphs::glyph(" ▼", " v") } else { glyphs::glyph(" ▲", " ^") })
+            }
+            _ => column.label().to_string(),
+        })
+        .collect()
+}
+
+/// Keeps the requested columns a table supports, in the requested order, falling
+/// back to `default` if none remain.
+pub fn select_columns(requested: &[Column], available: &[Column], default: &[Column]) -> Vec<Column> {
+    let mut columns: Vec<Column> = Vec::new();
+    for column in requested {
+        if available.contains(column) && !columns.contains(column) {
+            columns.push(*column);
+        }
+    }
+    if columns.is_empty() {
+        default.to_vec()
+    } else {
+        columns
+    }
+}
+
+/// Column widths fitted to the content for a table `available` cells wide (inside the border).
+/// Non-text columns take exactly the width of their widest value; text columns share what
+/// is left in proportion to their longest value.
+pub fn fit_widths(columns: &[Column], header: &[String], rows: &[Vec<String>], available: u16) -> Vec<Constraint> {
+    let content: Vec<u16> = (0..columns.len())
+        .map(|column| {
+            rows.iter()
+                .map(|row| row[column].chars().count())
+                .chain(header.get(column).map(|label| label.chars().count()))
+                .max()
+                .unwrap_or(0)
+                .min(u16::MAX as usize) as u16
+        })
+        .collect();
+
+    let spacing = columns.len().saturating_sub(1) as u16;
+    let fixed: u16 = columns.iter().zip(&content)
+        .filter(|(column, _)| !column.is_text())
+        .map(|(_, width)| *width)
+        .fold(spacing, u16::saturating_add);
+    let spare = available.saturating_sub(fixed) as u32;
+    let text_total: u32 = columns.iter().zip(&content)
+        .filter(|(column, _)| column.is_text())
+        .map(|(_, width)| (*width).max(1) as u32)
+        .sum();
+    let last_text = columns.iter().rposition(Column::is_text);
+
+    let mut remaining = spare;
+    columns.iter().enumerate()
+        .map(|(idx, column)| {
+            if !column.is_text() {
+                return Constraint::Length(content[idx]);
+            }
+            // The last text column takes the rounding remainder
+            let width = if last_text == Some(idx) {
+                remaining
+            } else {
+                spare * content[idx].max(1) as u32 / text_total.max(1)
+            };
+            remaining -= width;
+            Constraint::Length(width as u16)
+        })
+        .collect()
+}
+
+/// Formats a byte count with a binary unit, e.g. `12.3M`.
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["K", "M", "G", "T"];
+    if bytes < 1024 {
+        return format!("{}B", bytes);
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1}{}", value, UNITS[unit])
+}
+
+/// Formats the time elapsed since `since` compactly, e.g. `42s`, `5m07s`, `3h12m`, `2d04h`.
+pub fn format_age(since: DateTime<Utc>) -> String {
+    format_span(since, Utc::now())
+}
+
+/// Formats the time from `from` to `to` like `format_age`.
+pub fn format_span(from: DateTime<Utc>, to: DateTime<Utc>) -> String {
+    format_secs((to - from).num_seconds())
+}
+
+/// Formats a number of seconds like `format_age`.
+pub fn format_secs(secs: i64) -> String {
+    let secs = secs.max(0);
+    match secs {
+        0..=59 => format!("{}s", secs),
+        60..=3599 => format!("{}m{:02}s", secs / 60, secs % 60),
+        3600..=86399 => format!("{}h{:02}m", secs / 3600, secs % 3600 / 60),
+        _ => format!("{}d{:02}h", secs / 86400, secs % 86400 / 3600),
+    }
+}
+
+/// Part of a bordered table under the mouse.
+pub enum TableClick {
+    Header(usize),
+    Row(usize),
+}
+
+/// Maps a click at (`x`, `y`) to a header column or a visible row of a table drawn
+/// in `area` with a border, one header line and a one-line gap under the header.
+pub fn table_click(area: Rect, widths: &[Constraint], x: u16, y: u16) -> Option<TableClick> {
+    let inner = Rect {
+        x: area.x + 1,
+        y: area.y + 1,
+        width: area.width.saturating_sub(2),
+        height: area.height.saturating_sub(2),
+    };
+    if !inner.contains(Position { x, y }) {
+        return None;
+    }
+
+    if y == inner.y {
+        // Same column layout as ratatui's Table with its default spacing
+        let columns = Layout::horizontal(widths.iter().copied())
+            .flex(Flex::Start)
+            .spacing(1)
+            .split(inner);
+        columns.iter()
+            .position(|column| x >= column.x && x < column.x + column.width)
+            .map(TableClick::Header)
+    } else if y >= inner.y + 2 {
+        Some(TableClick::Row((y - inner.y - 2) as usize))
+    } else {
+        None
+    }
+}
+
+/// Color of rows whose active connections are unusual for them, unless a highlight rule applies.
+pub const ANOMALY_COLOR: Color = Color::Magenta;
+
+/// Colors rows matching the filter without hiding the rows that don't.
+#[derive(Debug, Clone)]
+pub struct Highlight {
+    pub filter: ConnectionFilter,
+    pub color: Color,
+}
+
+/// Color of the first highlight rule matching the row.
+pub fn highlight_color(highlights: &[Highlight], row: &RowAttributes) -> Option<Color> {
+    highlights.iter()
+        .find(|highlight| !highlight.filter.is_empty() && highlight.filter.matches_row(row))
+        .map(|highlight| highlight.color)
+}
+
+/// Style for a table row: pinned rows are bold, highlighted rows take the rule's
+/// color (pinned rows default to yellow), and the selected row is reversed.
+pub fn row_style(pinned: bool, highlight: Option<Color>, selected: bool) -> Style {
+    let mut style = Style::new();
+    if pinned {
+        style = style.fg(Color::Yellow).add_modifier(Modifier::BOLD);
+    }
+    if let Some(color) = highlight {
+        style = style.fg(color);
+    }
+
+    if selected {
+        style.add_modifier(Modifier::REVERSED)
+    } else {
+        style
+    }
+}
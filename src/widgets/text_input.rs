@@ -0,0 +1,108 @@
+use ratatui::{
+    style::{Style, Stylize},
+    text::Span,
+};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// Editable single-line text with a cursor, shared by the filter inputs.
+#[derive(Debug, Clone, Default)]
+pub struct TextInput {
+    value: String,
+    cursor: usize, // In chars, 0..=value.chars().count()
+}
+
+impl TextInput {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    /// Replaces the text and moves the cursor to the end.
+    pub fn set(&mut self, value: String) {
+        self.cursor = value.chars().count();
+        self.value = value;
+    }
+
+    pub fn cursor_at_end(&self) -> bool {
+        self.cursor == self.value.chars().count()
+    }
+
+    /// Inserts text at the cursor; line breaks are dropped since inputs are single-line.
+    pub fn insert_str(&mut self, text: &str) {
+        for c in text.chars().filter(|c| !c.is_control()) {
+            self.insert(c);
+        }
+    }
+
+    fn insert(&mut self, c: char) {
+        let idx = self.byte_index(self.cursor);
+        self.value.insert(idx, c);
+        self.cursor += 1;
+    }
+
+    fn byte_index(&self, cursor: usize) -> usize {
+        self.value.char_indices().nth(cursor).map_or(self.value.len(), |(idx, _)| idx)
+    }
+
+    /// Removes the chars in `start..end` (char positions) and leaves the cursor at `start`.
+    fn delete_range(&mut self, start: usize, end: usize) {
+        let (start_idx, end_idx) = (self.byte_index(start), self.byte_index(end));
+        self.value.replace_range(start_idx..end_idx, "");
+        self.cursor = start;
+    }
+
+    /// Start of the word before the cursor, skipping separators first. Dots and dashes
+    /// separate words so Ctrl+W removes one hostname label at a time.
+    fn word_start(&self) -> usize {
+        let chars: Vec<char> = self.value.chars().take(self.cursor).collect();
+        let is_word = |c: &char| c.is_alphanumeric() || *c == '_';
+        let mut pos = chars.len();
+        while pos > 0 && !is_word(&chars[pos - 1]) {
+            pos -= 1;
+        }
+        while pos > 0 && is_word(&chars[pos - 1]) {
+            pos -= 1;
+        }
+        pos
+    }
+
+    /// Applies an editing key. Returns false for keys that are not editing keys.
+    pub fn handle_key(&mut self, key_event: &KeyEvent) -> bool {
+        let ctrl = key_event.modifiers.contains(KeyModifiers::CONTROL);
+        match key_event.code {
+            KeyCode::Char('w') if ctrl => self.delete_range(self.word_start(), self.cursor),
+            KeyCode::Char('a') if ctrl => self.cursor = 0,
+            KeyCode::Char('e') if ctrl => self.cursor = self.value.chars().count(),
+            KeyCode::Char(c) if !ctrl => self.insert(c),
+            KeyCode::Backspace if self.cursor > 0 => self.delete_range(self.cursor - 1, self.cursor),
+            KeyCode::Delete if !self.cursor_at_end() => self.delete_range(self.cursor, self.cursor + 1),
+            KeyCode::Left => self.cursor = self.cursor.saturating_sub(1),
+            KeyCode::Right if !self.cursor_at_end() => self.cursor += 1,
+            KeyCode::Home => self.cursor = 0,
+            KeyCode::End => self.cursor = self.value.chars().count(),
+            KeyCode::Backspace | KeyCode::Delete | KeyCode::Right => {}
+            _ => return false,
+        }
+        true
+    }
+
+    /// The text as spans in `style`, with the cursor drawn as a reversed cell when `show_cursor` is set.
+    pub fn spans(&self, style: Style, show_cursor: bool) -> Vec<Span<'_>> {
+        if !show_cursor {
+            return vec![Span::styled(self.value.as_str(), style)];
+        }
+
+        let idx = self.byte_index(self.cursor);
+        let (before, rest) = self.value.split_at(idx);
+        let mut rest_chars = rest.chars();
+        let under_cursor = rest_chars.next().map_or(" ".to_string(), |c| c.to_string());
+        vec![
+            Span::styled(before, style),
+            Span::styled(under_cursor, style.reversed()),
+            Span::styled(rest_chars.as_str(), style),
+        ]
+    }
+}
@@ -0,0 +1,134 @@
+use ratatui::{
+    buffer::Buffer,
+    layout::{Rect, Layout, Direction, Constraint},
+    style::{Stylize, Style, Color},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Paragraph, Widget, Clear},
+};
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind};
+use super::glyphs;
+
+/// Single-line text prompt, e.g. for naming a saved filter.
+pub struct TextPromptWidget {
+    title: &'static str,
+    input: String,
+    active: bool,
+    error: Option<String>,
+}
+
+impl TextPromptWidget {
+    pub fn new() -> Self {
+        Self {
+            title: "",
+            input: String::new(),
+            active: false,
+            error: None,
+        }
+    }
+
+    pub fn show(&mut self, title: &'static str) {
+        self.title = title;
+        self.input.clear();
+        self.error = None;
+        self.active = true;
+    }
+
+    /// Reopens the prompt with the previous input and an error message.
+    pub fn show_error(&mut self, error: String) {
+        self.error = Some(error);
+        self.active = true;
+    }
+
+    pub fn hide(&mut self) {
+        self.active = false;
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// Returns the trimmed input when Enter is pressed on a non-empty line.
+    pub fn handle_key_event(&mut self, key_event: KeyEvent) -> Option<String> {
+        if !self.active || key_event.kind != KeyEventKind::Press {
+            return None;
+        }
+
+        match key_event.code {
+            KeyCode::Esc => {
+                self.hide();
+                None
+            }
+            KeyCode::Enter => {
+                let value = self.input.trim().to_string();
+                if value.is_empty() {
+                    self.error = Some("Enter a name".to_string());
+                    return None;
+                }
+                self.hide();
+                Some(value)
+            }
+            KeyCode::Char(c) => {
+                self.input.push(c);
+                None
+            }
+            KeyCode::Backspace => {
+                self.input.pop();
+                None
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Widget for &TextPromptWidget {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        if !self.active {
+            return;
+        }
+
+        let popup_width = area.width.min(50);
+        let popup_height = 5;
+
+        let popup_area = Rect {
+            x: area.x + (area.width.saturating_sub(popup_width)) / 2,
+            y: area.y + (area.height.saturating_sub(popup_height)) / 2,
+            width: popup_width,
+            height: popup_height,
+        };
+
+        Clear.render(popup_area, buf);
+
+        let block = Block::bordered()
+            .title(self.title)
+            .title_style(Style::new().bold().fg(Color::Yellow))
+            .border_set(glyphs::border_set(BorderType::Plain))
+            .border_style(Style::new().fg(Color::Yellow));
+
+        let inner_area = block.inner(popup_area);
+        block.render(popup_area, buf);
+
+        let layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(1), // Input
+                Constraint::Length(1), // Spacer
+                Constraint::Length(1), // Error or instructions
+            ])
+            .split(inner_area);
+
+        Paragraph::new(Line::from(vec![
+            Span::styled("> ", Style::new().fg(Color::White)),
+            Span::styled(format!("{}_", self.input), Style::new().fg(Color::Yellow)),
+        ]))
+        .render(layout[0], buf);
+
+        match &self.error {
+            Some(error) => Paragraph::new(error.as_str())
+                .style(Style::new().fg(Color::Red))
+                .render(layout[2], buf),
+            None => Paragraph::new("Enter: Save  |  Esc: Cancel")
+                .style(Style::new().fg(Color::Gray))
+                .render(layout[2], buf),
+        }
+    }
+}
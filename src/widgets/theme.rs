@@ -0,0 +1,62 @@
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use ratatui::buffer::Buffer;
+use ratatui::style::Color;
+
+/// Color scheme chosen with `--theme`. Widgets draw with the dark palette; other
+/// themes remap the finished frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Theme {
+    Dark,
+    Light,
+}
+
+impl Theme {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "dark" => Some(Theme::Dark),
+            "light" => Some(Theme::Light),
+            _ => None,
+        }
+    }
+}
+
+/// Set once at startup; read after every frame is drawn.
+static THEME: AtomicU8 = AtomicU8::new(Theme::Dark as u8);
+
+pub fn set_theme(theme: Theme) {
+    THEME.store(theme as u8, Ordering::Relaxed);
+}
+
+pub fn theme() -> Theme {
+    if THEME.load(Ordering::Relaxed) == Theme::Light as u8 {
+        Theme::Light
+    } else {
+        Theme::Dark
+    }
+}
+
+/// Darker counterpart of a color that is hard to read on a light background.
+fn light_color(color: Color) -> Color {
+    match color {
+        Color::White => Color::Black,
+        Color::Gray => Color::DarkGray,
+        Color::Yellow => Color::Indexed(130),
+        Color::Cyan | Color::LightCyan => Color::Blue,
+        Color::LightBlue => Color::Blue,
+        Color::LightGreen => Color::Green,
+        Color::LightRed => Color::Red,
+        Color::LightMagenta => Color::Magenta,
+        other => other,
+    }
+}
+
+/// Adjusts the colors of a drawn frame to the current theme.
+pub fn apply(buf: &mut Buffer) {
+    if theme() == Theme::Dark {
+        return;
+    }
+    for cell in buf.content.iter_mut() {
+        cell.fg = light_color(cell.fg);
+    }
+}
@@ -0,0 +1,106 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Stylize, Style, Color},
+    text::Line,
+    widgets::{Clear, Paragraph, Widget},
+};
+
+use super::glyphs;
+
+/// How long a toast stays up.
+pub const TOAST_DURATION: Duration = Duration::from_secs(4);
+const MAX_TOASTS: usize = 4;
+
+/// What a toast reports, which sets its icon and color.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ToastKind {
+    /// Confirmation of an action, or why it did nothing.
+    Info,
+    Error,
+    Alert,
+}
+
+impl ToastKind {
+    fn icon(&self) -> &'static str {
+        match self {
+            ToastKind::Info => glyphs::glyph("✓", "+"),
+            ToastKind::Error => glyphs::glyph("✗", "x"),
+            ToastKind::Alert => glyphs::glyph("⚠", "!"),
+        }
+    }
+
+    fn color(&self) -> Color {
+        match self {
+            ToastKind::Info => Color::LightGreen,
+            ToastKind::Error => Color::LightRed,
+            ToastKind::Alert => Color::Yellow,
+        }
+    }
+}
+
+struct Toast {
+    kind: ToastKind,
+    message: String,
+    shown_at: Instant,
+    /// Times the same message came again while it was up.
+    repeats: usize,
+}
+
+/// Messages stacked in the bottom-right corner for a few seconds, newest at the bottom,
+/// so confirmations and failures are seen without looking at the status bar.
+pub struct ToastsWidget {
+    toasts: VecDeque<Toast>,
+}
+
+impl ToastsWidget {
+    pub fn new() -> Self {
+        Self { toasts: VecDeque::new() }
+    }
+
+    /// Shows `message`, counting it again instead if it is already the newest toast.
+    pub fn push(&mut self, kind: ToastKind, message: String) {
+        self.toasts.retain(|toast| toast.shown_at.elapsed() < TOAST_DURATION);
+        if let Some(last) = self.toasts.back_mut().filter(|last| last.kind == kind && last.message == message) {
+            last.repeats += 1;
+            last.shown_at = Instant::now();
+            return;
+        }
+        if self.toasts.len() == MAX_TOASTS {
+            self.toasts.pop_front();
+        }
+        self.toasts.push_back(Toast { kind, message, shown_at: Instant::now(), repeats: 0 });
+    }
+}
+
+impl Widget for &ToastsWidget {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let toasts: Vec<&Toast> = self.toasts.iter()
+            .filter(|toast| toast.shown_at.elapsed() < TOAST_DURATION)
+            .collect();
+        let max_width = (area.width / 2).max(20).min(area.width);
+
+        for (i, toast) in toasts.iter().rev().enumerate() {
+            let Some(y) = (area.y + area.height).checked_sub(i as u16 + 1).filter(|y| *y >= area.y) else {
+                break;
+            };
+            let mut text = format!(" {} {} ", toast.kind.icon(), toast.message);
+            if toast.repeats > 0 {
+                text.push_str(&format!("(x{}) ", toast.repeats + 1));
+            }
+            let width = (text.chars().count() as u16).min(max_width);
+            let toast_area = Rect {
+                x: area.x + area.width - width,
+                y,
+                width,
+                height: 1,
+            };
+            Clear.render(toast_area, buf);
+            Paragraph::new(Line::from(text))
+                .style(Style::new().bold().fg(Color::Black).bg(toast.kind.color()))
+                .render(toast_area, buf);
+        }
+    }
+}
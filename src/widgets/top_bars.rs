@@ -0,0 +1,116 @@
+use std::sync::{Arc, Mutex};
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Stylize, Style, Color},
+    text::{Line, Span},
+    widgets::{Block, Paragraph, Widget, BorderType},
+};
+
+use crate::core::monitor::ConnectionMonitor;
+use crate::core::filters::ConnectionFilter;
+use super::glyphs;
+
+/// Rows shown at most; fewer when the area is shorter.
+const MAX_BARS: usize = 10;
+/// Widest label before it is cut.
+const MAX_LABEL_WIDTH: usize = 24;
+
+/// What the bar chart ranks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TopBarsKind {
+    Hosts,
+    Processes,
+}
+
+/// Horizontal bars of the hosts or processes with the most active connections, in
+/// place of the connections graph.
+pub struct TopBarsWidget {
+    monitor: Arc<Mutex<ConnectionMonitor>>,
+    filter: ConnectionFilter,
+    kind: TopBarsKind,
+}
+
+impl TopBarsWidget {
+    pub fn new(monitor: Arc<Mutex<ConnectionMonitor>>) -> Self {
+        Self {
+            monitor,
+            filter: ConnectionFilter::default(),
+            kind: TopBarsKind::Hosts,
+        }
+    }
+
+    pub fn set_filter(&mut self, filter: ConnectionFilter) {
+        self.filter = filter;
+    }
+
+    pub fn set_kind(&mut self, kind: TopBarsKind) {
+        self.kind = kind;
+    }
+
+    /// Labels and active counts, largest first, leaving out rows with nothing open.
+    fn top_rows(&self) -> Vec<(String, usize)> {
+        let Ok(monitor) = self.monitor.lock() else {
+            return Vec::new();
+        };
+        let mut rows: Vec<(String, usize)> = match self.kind {
+            TopBarsKind::Hosts => monitor.get_host_metrics(&self.filter).into_iter()
+                .map(|m| (format!("{}:{}", m.host, m.port), m.current_connections))
+                .collect(),
+            TopBarsKind::Processes => monitor.get_process_metrics(&self.filter).into_iter()
+                .map(|m| (format!("{} ({})", m.name, m.pid), m.current_connections))
+                .collect(),
+        };
+        rows.retain(|(_, active)| *active > 0);
+        rows.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        rows.truncate(MAX_BARS);
+        rows
+    }
+}
+
+impl Widget for &TopBarsWidget {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let title = match self.kind {
+            TopBarsKind::Hosts => "Top Hosts by Active Connections",
+            TopBarsKind::Processes => "Top Processes by Active Connections",
+        };
+        let block = Block::bordered()
+            .title(title)
+            .title_style(Style::new().bold().fg(Color::Cyan))
+            .border_set(glyphs::border_set(BorderType::Plain))
+            .border_style(Style::new().fg(Color::Blue));
+        let inner_area = block.inner(area);
+        block.render(area, buf);
+
+        let rows = self.top_rows();
+        if rows.is_empty() {
+            Paragraph::new(Line::styled("No active connections", Style::new().fg(Color::Gray)))
+                .render(inner_area, buf);
+            return;
+        }
+
+        let label_width = rows.iter()
+            .map(|(label, _)| label.chars().count())
+            .max()
+            .unwrap_or(0)
+            .min(MAX_LABEL_WIDTH);
+        let max_active = rows[0].1;
+        let count_width = max_active.to_string().len();
+        let bar_width = (inner_area.width as usize).saturating_sub(label_width + count_width + 2);
+
+        let lines: Vec<Line> = rows.iter()
+            .take(inner_area.height as usize)
+            .map(|(label, active)| {
+                let label: String = label.chars().take(label_width).collect();
+                // Anything open gets at least one cell so it doesn't look empty
+                let filled = (bar_width * active).div_ceil(max_active).max(1).min(bar_width);
+                Line::from(vec![
+                    Span::raw(format!("{:<width$} ", label, width = label_width)),
+                    Span::styled(glyphs::glyph("█", "#").repeat(filled), Style::new().fg(Color::Cyan)),
+                    Span::styled(format!(" {}", active), Style::new().bold()),
+                ])
+            })
+            .collect();
+        Paragraph::new(lines).render(inner_area, buf);
+    }
+}